@@ -147,6 +147,19 @@ pub fn build_v0_transaction(
   Ok(tx)
 }
 
+/// Leading addresses in the LST registry table before per-LST blocks.
+pub const LST_REGISTRY_PREAMBLE_LEN: usize = 16;
+
+/// Number of LSTs in the registry table, one block of four addresses each.
+#[must_use]
+pub fn lst_registry_size(table: &AddressLookupTableAccount) -> usize {
+  table
+    .addresses
+    .len()
+    .saturating_sub(LST_REGISTRY_PREAMBLE_LEN)
+    / 4
+}
+
 /// Creates `remaining_accounts` array from LST registry table with all
 /// headers writable.
 ///
@@ -156,7 +169,9 @@ pub fn build_v0_transaction(
 pub fn build_lst_registry(
   table: AddressLookupTableAccount,
 ) -> Result<(Vec<AccountMeta>, AddressLookupTableAccount)> {
-  if let Some((preamble, blocks)) = table.addresses.split_at_checked(16) {
+  if let Some((preamble, blocks)) =
+    table.addresses.split_at_checked(LST_REGISTRY_PREAMBLE_LEN)
+  {
     let preamble = preamble
       .iter()
       .map(|key| AccountMeta::new_readonly(*key, false));
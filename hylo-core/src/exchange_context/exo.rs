@@ -2,7 +2,7 @@ use anchor_spl::token::Mint;
 use fix::prelude::*;
 use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 
-use super::{ExchangeContext, ProjectedState};
+use super::{ExchangeContext, ModeCrossing, ProjectedState};
 use crate::conversion::ExoConversion;
 use crate::error::CoreError;
 use crate::error::CoreError::{
@@ -256,6 +256,67 @@ impl<C: SolanaClock> ExoExchangeContext<C> {
     })
   }
 
+  /// Stablecoin mint sizes, in collateral, at which the post-trade mode
+  /// changes. Collateral and supply move by the amount left after the
+  /// mint fee.
+  ///
+  /// # Errors
+  /// * Stablecoin NAV or supply computation failure
+  pub fn stablecoin_mint_crossings(
+    &self,
+  ) -> Result<Vec<ModeCrossing<N9>>, CoreError> {
+    let conversion = self.exo_conversion();
+    let nav = self.stablecoin_nav()?;
+    let supply = self.virtual_stablecoin_supply()?;
+    let cap = UFix64::new(u64::MAX - self.total_collateral.bits);
+    self.trade_crossings(cap, |amount| {
+      let net = self.stablecoin_mint_fee(amount).ok()?.amount_remaining;
+      let minted = conversion.exo_to_token(net, nav).ok()?;
+      Some((
+        self.total_collateral.checked_add(&net)?,
+        supply.checked_add(&minted)?,
+      ))
+    })
+  }
+
+  /// Stablecoin redeem sizes, in stablecoin, at which the post-trade mode
+  /// changes. The redeem fee leaves the pool with the withdrawal, so it
+  /// only bounds where a redeem can execute.
+  ///
+  /// # Errors
+  /// * Stablecoin NAV or supply computation failure
+  pub fn stablecoin_redeem_crossings(
+    &self,
+  ) -> Result<Vec<ModeCrossing<N6>>, CoreError> {
+    let conversion = self.exo_conversion();
+    let nav = self.stablecoin_nav()?;
+    let supply = self.virtual_stablecoin_supply()?;
+    self.trade_crossings(supply, |amount| {
+      let released = conversion.token_to_exo(amount, nav).ok()?;
+      self.stablecoin_redeem_fee(released).ok()?;
+      Some((
+        self.total_collateral.checked_sub(&released)?,
+        supply.checked_sub(&amount)?,
+      ))
+    })
+  }
+
+  /// Levercoin mint sizes, in collateral, at which the post-trade mode
+  /// changes. Collateral moves by the amount left after the mint fee.
+  ///
+  /// # Errors
+  /// * Stablecoin supply computation failure
+  pub fn levercoin_mint_crossings(
+    &self,
+  ) -> Result<Vec<ModeCrossing<N9>>, CoreError> {
+    let supply = self.virtual_stablecoin_supply()?;
+    let cap = UFix64::new(u64::MAX - self.total_collateral.bits);
+    self.trade_crossings(cap, |amount| {
+      let net = self.levercoin_mint_fee(amount).ok()?.amount_remaining;
+      Some((self.total_collateral.checked_add(&net)?, supply))
+    })
+  }
+
   /// Levercoin mint fee based on projected rebalance mode.
   ///
   /// # Errors
@@ -2,7 +2,7 @@ use anchor_spl::token::Mint;
 use fix::prelude::*;
 use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 
-use super::{ExchangeContext, ProjectedState};
+use super::{ExchangeContext, ModeCrossing, ProjectedState};
 use crate::conversion::Conversion;
 use crate::error::CoreError;
 use crate::error::CoreError::{
@@ -264,6 +264,81 @@ impl<C: SolanaClock> LstExchangeContext<C> {
     })
   }
 
+  /// Stablecoin mint sizes, in LST, at which the post-trade mode changes.
+  /// Collateral and supply move by the LST left after the mint fee.
+  ///
+  /// # Errors
+  /// * Token conversion, stablecoin NAV or supply computation failure
+  pub fn stablecoin_mint_crossings(
+    &self,
+    lst_sol_price: &LstSolPrice,
+  ) -> Result<Vec<ModeCrossing<N9>>, CoreError> {
+    let conversion = self.token_conversion(lst_sol_price)?;
+    let nav = self.stablecoin_nav()?;
+    let supply = self.virtual_stablecoin_supply()?;
+    let epoch = self.clock.epoch();
+    let cap = UFix64::new(u64::MAX - self.total_sol.bits);
+    self.trade_crossings(cap, |amount| {
+      let net = self
+        .stablecoin_mint_fee(lst_sol_price, amount)
+        .ok()?
+        .amount_remaining;
+      let new_sol = lst_sol_price.convert_lst_to_sol(net, epoch).ok()?;
+      let minted = conversion.lst_to_token(net, nav).ok()?;
+      Some((
+        self.total_sol.checked_add(&new_sol)?,
+        supply.checked_add(&minted)?,
+      ))
+    })
+  }
+
+  /// Stablecoin redeem sizes, in stablecoin, at which the post-trade mode
+  /// changes. The redeem fee leaves the pool with the withdrawal, so it
+  /// only bounds where a redeem can execute.
+  ///
+  /// # Errors
+  /// * Token conversion, stablecoin NAV or supply computation failure
+  pub fn stablecoin_redeem_crossings(
+    &self,
+    lst_sol_price: &LstSolPrice,
+  ) -> Result<Vec<ModeCrossing<N6>>, CoreError> {
+    let conversion = self.token_conversion(lst_sol_price)?;
+    let nav = self.stablecoin_nav()?;
+    let supply = self.virtual_stablecoin_supply()?;
+    let epoch = self.clock.epoch();
+    self.trade_crossings(supply, |amount| {
+      let lst_out = conversion.token_to_lst(amount, nav).ok()?;
+      self.stablecoin_redeem_fee(lst_sol_price, lst_out).ok()?;
+      let sol_out = lst_sol_price.convert_lst_to_sol(lst_out, epoch).ok()?;
+      Some((
+        self.total_sol.checked_sub(&sol_out)?,
+        supply.checked_sub(&amount)?,
+      ))
+    })
+  }
+
+  /// Levercoin mint sizes, in LST, at which the post-trade mode changes.
+  /// Collateral moves by the LST left after the mint fee.
+  ///
+  /// # Errors
+  /// * Stablecoin supply computation failure
+  pub fn levercoin_mint_crossings(
+    &self,
+    lst_sol_price: &LstSolPrice,
+  ) -> Result<Vec<ModeCrossing<N9>>, CoreError> {
+    let supply = self.virtual_stablecoin_supply()?;
+    let epoch = self.clock.epoch();
+    let cap = UFix64::new(u64::MAX - self.total_sol.bits);
+    self.trade_crossings(cap, |amount| {
+      let net = self
+        .levercoin_mint_fee(lst_sol_price, amount)
+        .ok()?
+        .amount_remaining;
+      let new_sol = lst_sol_price.convert_lst_to_sol(net, epoch).ok()?;
+      Some((self.total_sol.checked_add(&new_sol)?, supply))
+    })
+  }
+
   /// Levercoin mint fee based on projected rebalance mode.
   ///
   /// # Errors
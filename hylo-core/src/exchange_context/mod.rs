@@ -8,12 +8,16 @@ mod exo;
 mod lst;
 #[cfg(feature = "offchain")]
 pub mod marginal;
+mod transition;
 
 use fix::prelude::*;
+use fix::typenum::Integer;
 
 pub use self::exo::ExoExchangeContext;
 pub use self::lst::LstExchangeContext;
-use crate::conversion::SwapConversion;
+use self::transition::mode_crossings;
+pub use self::transition::ModeCrossing;
+use crate::conversion::{ExoConversion, SwapConversion};
 use crate::error::CoreError;
 #[cfg(feature = "offchain")]
 use crate::error::CoreError::{CollateralRatio, DestinationCollateral};
@@ -28,7 +32,7 @@ use crate::exchange_math::{
   max_mintable_stablecoin, max_swappable_stablecoin, next_levercoin_mint_nav,
   next_levercoin_redeem_nav, total_value_locked,
 };
-use crate::fees::controller::{FeeExtract, LevercoinFees};
+use crate::fees::controller::{FeeController, FeeExtract, LevercoinFees};
 use crate::pyth::{OraclePrice, PriceRange};
 use crate::rebalance::math::{
   max_buyable_collateral, max_sellable_collateral, midpoint,
@@ -78,6 +82,15 @@ pub trait ExchangeContext {
     self.rebalance_mode() != RebalanceMode::Depeg
   }
 
+  /// Confirm levercoin redeem capability: the current mode has a redeem
+  /// fee tier.
+  fn levercoin_redeem_enabled(&self) -> bool {
+    self
+      .levercoin_fees()
+      .redeem_fee(self.rebalance_mode())
+      .is_ok()
+  }
+
   /// Sell-side rebalance price curve from oracle spot.
   ///
   /// # Errors
@@ -457,6 +470,103 @@ pub trait ExchangeContext {
     )
   }
 
+  /// Collateral ratio projected from new totals, at the lower collateral
+  /// price used for fee mode selection.
+  ///
+  /// Returns `None` if the totals are not representable.
+  fn projected_collateral_ratio(
+    &self,
+    new_total: Option<UFix64<N9>>,
+    new_stablecoin: Option<UFix64<N6>>,
+  ) -> Option<UFix64<N9>> {
+    collateral_ratio(
+      new_total?,
+      self.collateral_usd_price().lower,
+      new_stablecoin?,
+    )
+    .ok()
+  }
+
+  /// Trade sizes up to `cap` at which the post-trade mode changes.
+  ///
+  /// `totals` maps an input to the post-trade collateral and stablecoin
+  /// totals, net of fees, or `None` where the trade cannot execute.
+  ///
+  /// # Errors
+  /// * Malformed mode boundaries
+  fn trade_crossings<Exp: Integer>(
+    &self,
+    cap: UFix64<Exp>,
+    totals: impl Fn(UFix64<Exp>) -> Option<(UFix64<N9>, UFix64<N6>)>,
+  ) -> Result<Vec<ModeCrossing<Exp>>, CoreError> {
+    mode_crossings(self.collateral_ratio(), cap, |amount| {
+      let (total, supply) = totals(amount)?;
+      self.projected_collateral_ratio(Some(total), Some(supply))
+    })
+  }
+
+  /// Levercoin redeem sizes, in levercoin, at which the post-trade mode
+  /// changes.
+  ///
+  /// # Errors
+  /// * Levercoin NAV or supply computation failure
+  fn levercoin_redeem_crossings(
+    &self,
+  ) -> Result<Vec<ModeCrossing<N6>>, CoreError> {
+    let conversion = ExoConversion::new(self.collateral_usd_price());
+    let nav = self.levercoin_redeem_nav()?;
+    let total = self.total_collateral();
+    let supply = self.virtual_stablecoin_supply()?;
+    mode_crossings(
+      self.collateral_ratio(),
+      self.levercoin_supply()?,
+      |amount| {
+        let released = conversion.token_to_exo(amount, nav).ok()?;
+        self.projected_collateral_ratio(
+          total.checked_sub(&released),
+          Some(supply),
+        )
+      },
+    )
+  }
+
+  /// Stablecoin to levercoin swap sizes, in stablecoin, at which the
+  /// post-trade mode changes.
+  ///
+  /// # Errors
+  /// * Stablecoin supply computation failure
+  fn stablecoin_to_levercoin_crossings(
+    &self,
+  ) -> Result<Vec<ModeCrossing<N6>>, CoreError> {
+    let total = self.total_collateral();
+    let supply = self.virtual_stablecoin_supply()?;
+    mode_crossings(self.collateral_ratio(), supply, |amount| {
+      self.projected_collateral_ratio(Some(total), supply.checked_sub(&amount))
+    })
+  }
+
+  /// Levercoin to stablecoin swap sizes, in levercoin, at which the
+  /// post-trade mode changes.
+  ///
+  /// # Errors
+  /// * NAV or supply computation failure
+  fn levercoin_to_stablecoin_crossings(
+    &self,
+  ) -> Result<Vec<ModeCrossing<N6>>, CoreError> {
+    let conversion = self.swap_conversion()?;
+    let total = self.total_collateral();
+    let supply = self.virtual_stablecoin_supply()?;
+    mode_crossings(
+      self.collateral_ratio(),
+      self.levercoin_supply()?,
+      |amount| {
+        let minted = conversion.lever_to_stable(amount).ok()?;
+        self
+          .projected_collateral_ratio(Some(total), supply.checked_add(&minted))
+      },
+    )
+  }
+
   /// Validates a stablecoin mint amount against the protocol max.
   ///
   /// # Errors
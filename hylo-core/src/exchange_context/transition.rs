@@ -0,0 +1,297 @@
+//! Trade sizes at which the post-trade collateral ratio crosses a
+//! [`RebalanceMode`] boundary.
+//!
+//! Fee tiers follow the projected mode, so each crossing is a point where
+//! the marginal fee of an order changes. Crossings are found against the
+//! same projection the fee selection uses, including its rounding, by
+//! galloping to the first crossed input and bisecting back.
+
+use fix::prelude::*;
+use fix::typenum::Integer;
+
+use crate::error::CoreError;
+use crate::rebalance::mode::RebalanceMode;
+
+/// First input at which a trade lands the protocol in another mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ModeCrossing<Exp: Integer> {
+  /// Mode the post-trade CR falls into at `input`.
+  pub mode: RebalanceMode,
+  /// [`CrRange`](crate::rebalance::mode::CrRange) boundary crossed.
+  pub boundary: UFix64<N9>,
+  /// Smallest input whose post-trade CR lies past `boundary`.
+  pub input: UFix64<Exp>,
+}
+
+/// Crossings of every mode boundary reachable with inputs up to `cap`,
+/// nearest first.
+///
+/// `cr_at` projects the post-trade CR of an input, or `None` where the
+/// trade is not representable.
+///
+/// # Errors
+/// * Malformed mode boundaries
+pub(crate) fn mode_crossings<Exp: Integer>(
+  current_cr: UFix64<N9>,
+  cap: UFix64<Exp>,
+  cr_at: impl Fn(UFix64<Exp>) -> Option<UFix64<N9>>,
+) -> Result<Vec<ModeCrossing<Exp>>, CoreError> {
+  let mut crossings = RebalanceMode::ALL
+    .windows(2)
+    .map(|pair| {
+      let boundary = pair[1].active_range().start()?;
+      let downward = boundary <= current_cr;
+      let mode = if downward { pair[0] } else { pair[1] };
+      let crossed = |cr: UFix64<N9>| {
+        if downward {
+          cr < boundary
+        } else {
+          cr >= boundary
+        }
+      };
+      let input =
+        first_input(cap.bits, |x| cr_at(UFix64::new(x)).is_some_and(crossed));
+      Ok(input.map(|bits| ModeCrossing {
+        mode,
+        boundary,
+        input: UFix64::new(bits),
+      }))
+    })
+    .filter_map(Result::transpose)
+    .collect::<Result<Vec<_>, CoreError>>()?;
+  crossings.sort_by_key(|crossing| crossing.input);
+  Ok(crossings)
+}
+
+/// Smallest input in `(0, cap]` satisfying `crossed`, assuming inputs
+/// past the crossing stay crossed.
+fn first_input(cap: u64, crossed: impl Fn(u64) -> bool) -> Option<u64> {
+  if cap == 0 {
+    return None;
+  }
+  let mut lo = 0;
+  let mut hi = 1;
+  while !crossed(hi) {
+    if hi >= cap {
+      return None;
+    }
+    lo = hi;
+    hi = hi.saturating_mul(2).min(cap);
+  }
+  while hi - lo > 1 {
+    let mid = lo + (hi - lo) / 2;
+    if crossed(mid) {
+      hi = mid;
+    } else {
+      lo = mid;
+    }
+  }
+  Some(hi)
+}
+
+#[cfg(all(test, feature = "offchain"))]
+mod tests {
+  use super::*;
+  use crate::conversion::ExoConversion;
+  use crate::exchange_context::ExchangeContext;
+  use crate::exchange_math::collateral_ratio;
+  use crate::fees::controller::{FeeExtract, FeePair, LevercoinFees};
+  use crate::pyth::OraclePrice;
+  use crate::rebalance::pricing::RebalanceCurveConfig;
+  use crate::stress::{StressContext, StressError};
+
+  const FEE: UFixValue64 = UFixValue64 { bits: 10, exp: -4 };
+
+  /// Flat 1% stablecoin mint fee.
+  const FEE_RATE: UFix64<N4> = UFix64::constant(100);
+
+  const CURVE: RebalanceCurveConfig = RebalanceCurveConfig {
+    floor_pct: UFixValue64 {
+      bits: 5_000_000,
+      exp: -9,
+    },
+    ceil_pct: UFixValue64 {
+      bits: 1_000_000,
+      exp: -9,
+    },
+  };
+
+  /// 1,000 collateral at $100 against 70,000 stablecoin: CR ~1.43.
+  fn context() -> Result<StressContext, StressError> {
+    let pair = FeePair::new(FEE, FEE);
+    StressContext::new(
+      UFix64::new(1_000_000_000_000),
+      OraclePrice {
+        spot: UFix64::new(100_000_000_000),
+        conf: UFix64::new(50_000_000),
+      },
+      UFix64::new(70_000_000_000),
+      UFix64::new(25_000_000_000),
+      UFix64::new(1_300_000_000),
+      LevercoinFees::new(pair, pair, pair),
+      CURVE,
+      CURVE,
+    )
+  }
+
+  fn modes<Exp: Integer>(
+    crossings: &[ModeCrossing<Exp>],
+  ) -> Vec<RebalanceMode> {
+    crossings.iter().map(|crossing| crossing.mode).collect()
+  }
+
+  /// Each crossing is tight: one atom less stays on the near side.
+  fn assert_tight<Exp: Integer>(
+    crossings: &[ModeCrossing<Exp>],
+    cr_at: impl Fn(UFix64<Exp>) -> UFix64<N9>,
+  ) {
+    crossings.iter().for_each(|crossing| {
+      let at = RebalanceMode::from_cr(cr_at(crossing.input));
+      let before =
+        RebalanceMode::from_cr(cr_at(UFix64::new(crossing.input.bits - 1)));
+      assert_eq!(at, crossing.mode);
+      assert_ne!(before, crossing.mode);
+    });
+  }
+
+  #[test]
+  fn first_input_is_tight() {
+    assert_eq!(first_input(1_000, |x| x >= 437), Some(437));
+    assert_eq!(first_input(1_000, |x| x >= 1), Some(1));
+    assert_eq!(first_input(1_000, |x| x >= 1_000), Some(1_000));
+    assert_eq!(first_input(1_000, |x| x > 1_000), None);
+    assert_eq!(first_input(u64::MAX, |x| x == u64::MAX), Some(u64::MAX));
+    assert_eq!(first_input(0, |_| true), None);
+  }
+
+  /// Post-trade totals of a stablecoin mint charging `fee` on the input.
+  fn stablecoin_mint(
+    ctx: &StressContext,
+    fee: UFix64<N4>,
+  ) -> Result<
+    impl Fn(UFix64<N9>) -> Option<(UFix64<N9>, UFix64<N6>)> + '_,
+    StressError,
+  > {
+    let conversion = ExoConversion::new(ctx.collateral_usd_price());
+    let nav = ctx.stablecoin_nav()?;
+    let supply = ctx.virtual_stablecoin_supply()?;
+    Ok(move |amount| {
+      let net = FeeExtract::new(fee, amount).ok()?.amount_remaining;
+      let minted = conversion.exo_to_token(net, nav).ok()?;
+      Some((
+        ctx.total_collateral().checked_add(&net)?,
+        supply.checked_add(&minted)?,
+      ))
+    })
+  }
+
+  fn mint_cap(ctx: &StressContext) -> UFix64<N9> {
+    UFix64::new(u64::MAX - ctx.total_collateral().bits)
+  }
+
+  #[test]
+  fn stablecoin_mint_walks_down() -> Result<(), StressError> {
+    let ctx = context()?;
+    let crossings =
+      ctx.trade_crossings(mint_cap(&ctx), stablecoin_mint(&ctx, FEE_RATE)?)?;
+    assert_eq!(
+      modes(&crossings),
+      vec![RebalanceMode::SellZone1, RebalanceMode::SellZone2]
+    );
+    let price = ctx.collateral_usd_price().lower;
+    let supply = ctx.virtual_stablecoin_supply()?;
+    assert_tight(&crossings, |x: UFix64<N9>| {
+      let net = FeeExtract::new(FEE_RATE, x).unwrap().amount_remaining;
+      let minted = net
+        .mul_div_floor(price, UFix64::one())
+        .and_then(UFix64::checked_convert::<N6>)
+        .unwrap();
+      let total = ctx.total_collateral().checked_add(&net).unwrap();
+      collateral_ratio(total, price, supply.checked_add(&minted).unwrap())
+        .unwrap()
+    });
+    Ok(())
+  }
+
+  #[test]
+  fn mint_fee_pushes_crossings_out() -> Result<(), StressError> {
+    let ctx = context()?;
+    let gross = ctx.trade_crossings(
+      mint_cap(&ctx),
+      stablecoin_mint(&ctx, UFix64::zero())?,
+    )?;
+    let net =
+      ctx.trade_crossings(mint_cap(&ctx), stablecoin_mint(&ctx, FEE_RATE)?)?;
+    assert_eq!(modes(&gross), modes(&net));
+    assert!(gross.iter().zip(&net).all(|(g, n)| g.input < n.input));
+    Ok(())
+  }
+
+  #[test]
+  fn levercoin_mint_walks_up() -> Result<(), StressError> {
+    let ctx = context()?;
+    let supply = ctx.virtual_stablecoin_supply()?;
+    let crossings = ctx.trade_crossings(mint_cap(&ctx), |amount| {
+      Some((ctx.total_collateral().checked_add(&amount)?, supply))
+    })?;
+    assert_eq!(
+      modes(&crossings),
+      vec![RebalanceMode::BuyZone1, RebalanceMode::BuyZone2]
+    );
+    let price = ctx.collateral_usd_price().lower;
+    assert_tight(&crossings, |x: UFix64<N9>| {
+      let total = ctx.total_collateral().checked_add(&x).unwrap();
+      collateral_ratio(total, price, supply).unwrap()
+    });
+    Ok(())
+  }
+
+  #[test]
+  fn stablecoin_to_levercoin_walks_up() -> Result<(), StressError> {
+    let ctx = context()?;
+    let crossings = ctx.stablecoin_to_levercoin_crossings()?;
+    assert_eq!(
+      modes(&crossings),
+      vec![RebalanceMode::BuyZone1, RebalanceMode::BuyZone2]
+    );
+    let price = ctx.collateral_usd_price().lower;
+    let supply = ctx.virtual_stablecoin_supply()?;
+    assert_tight(&crossings, |x: UFix64<N6>| {
+      let remaining = supply.checked_sub(&x).unwrap();
+      collateral_ratio(ctx.total_collateral(), price, remaining).unwrap()
+    });
+    Ok(())
+  }
+
+  #[test]
+  fn levercoin_redeem_capped_by_supply() -> Result<(), StressError> {
+    let ctx = context()?;
+    let crossings = ctx.levercoin_redeem_crossings()?;
+    let supply = ctx.levercoin_supply()?;
+    assert!(crossings.iter().all(|crossing| crossing.input <= supply));
+    assert!(crossings.windows(2).all(|w| w[0].input <= w[1].input));
+    assert_eq!(
+      crossings.first().map(|crossing| crossing.mode),
+      Some(RebalanceMode::SellZone1)
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn every_operation_moves_away_from_current_mode() -> Result<(), StressError> {
+    let ctx = context()?;
+    let current = ctx.rebalance_mode();
+    let all = [
+      modes(
+        &ctx
+          .trade_crossings(mint_cap(&ctx), stablecoin_mint(&ctx, FEE_RATE)?)?,
+      ),
+      modes(&ctx.levercoin_redeem_crossings()?),
+      modes(&ctx.stablecoin_to_levercoin_crossings()?),
+      modes(&ctx.levercoin_to_stablecoin_crossings()?),
+    ];
+    assert_eq!(current, RebalanceMode::Neutral);
+    assert!(all.iter().flatten().all(|mode| *mode != current));
+    Ok(())
+  }
+}
@@ -434,7 +434,7 @@ mod tests {
 mod proofs {
   use fix::prelude::*;
 
-  use crate::fees::interp::{LineSegment, Point};
+  use crate::fees::interp::{FixInterp, LineSegment, Point};
   use crate::kani_generators::{deployed_curve_x, deployed_curve_y};
 
   fn curve_coord() -> IFix64<N5> {
@@ -473,4 +473,20 @@ mod proofs {
     let seg = LineSegment(&p0, &p1);
     assert!(seg.lerp(x).is_some());
   }
+
+  /// `interpolate` stays within `range()` on non-decreasing curves.
+  #[kani::proof]
+  fn interpolate_within_range() {
+    let points = [(); 3].map(|()| Point::<N5> {
+      x: curve_coord(),
+      y: curve_coord(),
+    });
+    kani::assume(points[0].y <= points[1].y && points[1].y <= points[2].y);
+    if let Ok(interp) = FixInterp::from_points(points) {
+      let x = curve_coord();
+      kani::assume(interp.domain().contains(&x));
+      let y = interp.interpolate(x);
+      assert!(y.is_ok_and(|y| interp.range().contains(&y)));
+    }
+  }
 }
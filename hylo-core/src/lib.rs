@@ -20,8 +20,12 @@ pub mod lst;
 pub mod par_tolerance;
 pub mod pyth;
 pub mod rebalance;
+#[cfg(test)]
+mod reference;
 pub mod slippage_config;
 pub mod solana_clock;
+#[cfg(feature = "offchain")]
+pub mod stress;
 pub mod util;
 pub mod virtual_stablecoin;
 pub mod yields;
@@ -156,3 +156,30 @@ mod tests {
     assert_eq!(result.err(), Some(DepositLimitArithmetic));
   }
 }
+
+#[cfg(kani)]
+mod proofs {
+  use fix::prelude::*;
+
+  use crate::kani_generators::any_ufix64;
+  use crate::limiter::deposit::DepositLimiter;
+
+  /// A deposit is accepted exactly when it keeps the pool within the limit.
+  #[kani::proof]
+  fn validate_deposit_respects_limit() {
+    let limit: UFix64<N6> = any_ufix64();
+    let pool_amount: UFix64<N6> = any_ufix64();
+    let deposit: UFix64<N6> = any_ufix64();
+    let limiter = DepositLimiter {
+      limit: limit.into(),
+    };
+    let projected = pool_amount.checked_add(&deposit);
+    match limiter.validate_deposit(pool_amount, deposit) {
+      Ok(accepted) => {
+        assert_eq!(accepted, deposit);
+        assert!(projected.is_some_and(|p| p <= limit));
+      }
+      Err(_) => assert!(projected.is_none_or(|p| p > limit)),
+    }
+  }
+}
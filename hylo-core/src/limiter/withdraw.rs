@@ -270,3 +270,60 @@ mod tests {
     assert_eq!(result.err(), Some(WithdrawalLimitInvalidEpoch));
   }
 }
+
+#[cfg(kani)]
+mod proofs {
+  use fix::prelude::*;
+
+  use crate::kani_generators::any_ufix64;
+  use crate::limiter::withdraw::WithdrawalLimiter;
+  use crate::virtual_stablecoin::VirtualStablecoin;
+
+  /// Registering a withdrawal never takes the epoch ledger over the limit,
+  /// starting from any in-limit ledger and any later epoch.
+  #[kani::proof]
+  fn register_withdrawal_respects_limit() {
+    let limit: UFix64<N6> = any_ufix64();
+    let ledger_total: UFix64<N6> = any_ufix64();
+    kani::assume(ledger_total <= limit);
+    let epoch: u64 = kani::any();
+    let current_epoch: u64 = kani::any();
+    let withdrawal: UFix64<N6> = any_ufix64();
+    let mut limiter = WithdrawalLimiter {
+      limit: limit.into(),
+      withdrawal_ledger: VirtualStablecoin {
+        supply: ledger_total.into(),
+      },
+      epoch,
+    };
+    if limiter
+      .register_withdrawal(withdrawal, current_epoch)
+      .is_ok()
+    {
+      assert_eq!(limiter.epoch, current_epoch);
+      assert!(limiter
+        .withdrawal_ledger
+        .supply()
+        .is_ok_and(|total| total <= limit));
+    }
+  }
+
+  /// Withdrawals for an epoch before the ledger's are always rejected.
+  #[kani::proof]
+  fn register_withdrawal_rejects_past_epoch() {
+    let limit: UFix64<N6> = any_ufix64();
+    let epoch: u64 = kani::any();
+    let current_epoch: u64 = kani::any();
+    kani::assume(current_epoch < epoch);
+    let mut limiter = WithdrawalLimiter {
+      limit: limit.into(),
+      withdrawal_ledger: VirtualStablecoin::new(),
+      epoch,
+    };
+    let before = limiter;
+    assert!(limiter
+      .register_withdrawal(any_ufix64(), current_epoch)
+      .is_err());
+    assert_eq!(limiter, before);
+  }
+}
@@ -137,7 +137,8 @@ pub fn validate_publish_time(
 }
 
 /// Number of Solana slots in configured oracle interval time.
-fn slot_interval(oracle_interval_secs: u64) -> Option<u64> {
+#[must_use]
+pub fn slot_interval(oracle_interval_secs: u64) -> Option<u64> {
   let time: UFix64<N2> = UFix64::<Z0>::new(oracle_interval_secs).convert();
   let slot_time = UFix64::<N2>::new(20); // 200ms slot time
   time.checked_div(&slot_time).map(|i| i.bits)
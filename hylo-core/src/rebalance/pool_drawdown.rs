@@ -67,3 +67,33 @@ impl PoolDrawdown {
     *self == PoolDrawdown::default()
   }
 }
+
+#[cfg(kani)]
+mod proofs {
+  use fix::prelude::*;
+
+  use crate::kani_generators::any_ufix64;
+  use crate::rebalance::pool_drawdown::PoolDrawdown;
+  use crate::virtual_stablecoin::VirtualStablecoin;
+
+  /// `repay` succeeds exactly when the debt covers it, so the outstanding
+  /// balance never goes negative.
+  #[kani::proof]
+  fn repay_never_negative() {
+    let outstanding: UFix64<N6> = any_ufix64();
+    let amount: UFix64<N6> = any_ufix64();
+    let mut drawdown = PoolDrawdown::new(VirtualStablecoin {
+      supply: outstanding.into(),
+    });
+    let repaid = drawdown.repay(amount);
+    assert_eq!(repaid.is_ok(), amount <= outstanding);
+    let remaining = drawdown.outstanding();
+    if repaid.is_ok() {
+      assert!(
+        remaining.is_ok_and(|r| r.checked_add(&amount) == Some(outstanding))
+      );
+    } else {
+      assert!(remaining.is_ok_and(|r| r == outstanding));
+    }
+  }
+}
@@ -0,0 +1,535 @@
+//! Differential tests of fixed-point math against the exact reference.
+
+use fix::prelude::*;
+use fix::typenum::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+use proptest::prelude::*;
+
+use super::Rounding::{Down, Up};
+use super::{error_atoms, q, Rounding};
+use crate::conversion::{Conversion, ExoConversion, SwapConversion};
+use crate::earn_pool_math::{
+  amount_token_to_withdraw, lp_token_nav, lp_token_out,
+};
+use crate::exchange_math::{
+  collateral_ratio, depeg_stablecoin_nav, levercoin_market_cap,
+  max_mintable_stablecoin, max_swappable_stablecoin, next_levercoin_mint_nav,
+  next_levercoin_redeem_nav, total_value_locked,
+};
+use crate::fees::controller::FeeExtract;
+use crate::fees::curve_controller::{
+  narrow_cr, InterpolatedFeeController, InterpolatedMintFees,
+  InterpolatedRedeemFees,
+};
+use crate::fees::curves::{MINT_FEE_INV, REDEEM_FEE_LN};
+use crate::fees::interp::{FixInterp, Point};
+use crate::pyth::{OraclePrice, PriceRange};
+use crate::rebalance::math::{
+  max_buyable_collateral, max_sellable_collateral, midpoint,
+};
+use crate::rebalance::mode::RebalanceMode;
+use crate::rebalance::pnl::RebalancePnl;
+use crate::rebalance::pricing::{
+  BuyPriceCurve, RebalanceCurveConfig, RebalancePriceController, SellPriceCurve,
+};
+use crate::reference;
+use crate::util::proptest::*;
+
+/// Fails unless `actual` rounds `exact` in `rounding` by at most one atom.
+fn assert_rounds<Exp: Integer>(
+  actual: UFix64<Exp>,
+  exact: &BigRational,
+  rounding: Rounding,
+) -> Result<(), TestCaseError> {
+  let error = error_atoms(actual, exact, rounding);
+  prop_assert!(
+    error >= BigRational::zero(),
+    "{actual:?} rounds against the protocol, exact {exact}"
+  );
+  prop_assert!(
+    error <= BigRational::one(),
+    "{actual:?} off by {error} atoms, exact {exact}"
+  );
+  Ok(())
+}
+
+/// Fails unless a chained formula lands within one atom of its `rounded`
+/// twin and on the protocol's side of the `exact` value.
+///
+/// Intermediate steps may each lose an atom that later factors scale, so
+/// distance to `exact` is unbounded in general; the twin pins those steps
+/// to the rounding production documents.
+fn assert_chain_rounds<Exp: Integer>(
+  actual: UFix64<Exp>,
+  rounded: &BigRational,
+  exact: &BigRational,
+  rounding: Rounding,
+) -> Result<(), TestCaseError> {
+  assert_rounds(actual, rounded, rounding)?;
+  prop_assert!(
+    error_atoms(actual, exact, rounding) >= BigRational::zero(),
+    "{actual:?} rounds against the protocol, exact {exact}"
+  );
+  Ok(())
+}
+
+fn price_range() -> BoxedStrategy<PriceRange<N9>> {
+  (usd_sol_price(), 0u64..1_000_000_000)
+    .prop_map(|(spot, conf)| {
+      PriceRange::new(
+        spot.checked_sub(&UFix64::new(conf)).unwrap_or(spot),
+        spot.saturating_add(&UFix64::new(conf)),
+      )
+    })
+    .boxed()
+}
+
+fn collateral() -> BoxedStrategy<UFix64<N9>> {
+  (1_000_000_000u64..10_000_000_000_000_000)
+    .prop_map(UFix64::new)
+    .boxed()
+}
+
+fn target_cr_n2() -> BoxedStrategy<UFix64<N2>> {
+  (101u64..300).prop_map(UFix64::new).boxed()
+}
+
+fn target_cr_n9() -> BoxedStrategy<UFix64<N9>> {
+  (1_010_000_000u64..3_000_000_000)
+    .prop_map(UFix64::new)
+    .boxed()
+}
+
+fn fee() -> BoxedStrategy<UFix64<N4>> {
+  (0u64..=1000).prop_map(UFix64::new).boxed()
+}
+
+/// Deviation within the tightest validated cap, `BUY_CEIL_MAX_PCT`.
+fn curve_pct() -> BoxedStrategy<UFix64<N9>> {
+  (1u64..=2_000_000).prop_map(UFix64::new).boxed()
+}
+
+/// CR anywhere in `mode`, including its excluded end.
+fn cr_in(mode: RebalanceMode) -> BoxedStrategy<UFix64<N9>> {
+  let range = mode.active_range();
+  let bits = |bound: Result<UFix64<N9>, _>| bound.map_or(0, |cr| cr.bits);
+  (bits(range.start())..=bits(range.end()))
+    .prop_map(UFix64::new)
+    .boxed()
+}
+
+fn fee_curve_cr() -> BoxedStrategy<UFix64<N9>> {
+  (0u64..4_000_000_000).prop_map(UFix64::new).boxed()
+}
+
+fn exact_points<Exp: Integer>(
+  points: &[Point<Exp>],
+) -> Vec<(BigRational, BigRational)> {
+  points
+    .iter()
+    .map(|point| (reference::qi(point.x), reference::qi(point.y)))
+    .collect()
+}
+
+/// Checks a rebalance curve against the exact one through `config`.
+///
+/// Endpoints widen away from spot, the floor rounded down and the ceiling
+/// up. Prices between them interpolate those stored endpoints and round up
+/// by at most one atom.
+fn assert_rebalance_curve(
+  curve: &impl RebalancePriceController,
+  spot: UFix64<N9>,
+  config: &RebalanceCurveConfig,
+  mode: RebalanceMode,
+  cr: UFix64<N9>,
+) -> Result<(), TestCaseError> {
+  let range = mode.active_range();
+  let (start, end) = (range.start()?, range.end()?);
+  let (floor, ceil) = reference::rebalance_curve_prices(
+    spot,
+    config.floor_pct()?,
+    config.ceil_pct()?,
+  );
+  let (y0, y1) = (curve.price(start)?, curve.price(end)?);
+  assert_rounds(y0, &floor, Down)?;
+  assert_rounds(y1, &ceil, Up)?;
+  let points = [(q(start), q(y0)), (q(end), q(y1))];
+  let exact = reference::interpolate(&points, &q(cr));
+  assert_rounds(curve.price(cr)?, &exact, Up)?;
+  Ok(())
+}
+
+/// Checks a fee curve's rate at `cr` against exact interpolation at the
+/// `N5` CR the curve is looked up with. Fees are owed to the protocol, so
+/// the rate rounds up by at most one atom.
+fn assert_fee_rate<const RES: usize>(
+  fees: &impl InterpolatedFeeController<RES>,
+  points: &[Point<N5>],
+  cr: UFix64<N9>,
+) -> Result<(), TestCaseError> {
+  if let Ok(rate) = fees.fee_rate(cr) {
+    let x = reference::qi(narrow_cr(cr)?);
+    let exact = reference::interpolate(&exact_points(points), &x);
+    assert_rounds(rate, &exact, Up)?;
+  }
+  Ok(())
+}
+
+proptest! {
+  #[test]
+  fn lst_to_token(
+    amount in lst_amount(),
+    lst_sol in lst_sol_price(),
+    usd_sol in price_range(),
+    nav in stablecoin_nav(),
+  ) {
+    let conversion = Conversion::new(usd_sol, lst_sol);
+    if let Ok(out) = conversion.lst_to_token(amount, nav) {
+      let exact = reference::lst_to_token(amount, lst_sol, usd_sol.lower, nav);
+      let rounded =
+        reference::lst_to_token_rounded(amount, lst_sol, usd_sol.lower, nav);
+      assert_chain_rounds(out, &rounded, &exact, Down)?;
+    }
+  }
+
+  #[test]
+  fn token_to_lst(
+    amount in token_amount(),
+    lst_sol in lst_sol_price(),
+    usd_sol in price_range(),
+    nav in levercoin_nav(),
+  ) {
+    let conversion = Conversion::new(usd_sol, lst_sol);
+    if let Ok(out) = conversion.token_to_lst(amount, nav) {
+      let exact = reference::token_to_lst(amount, nav, usd_sol.upper, lst_sol);
+      let rounded =
+        reference::token_to_lst_rounded(amount, nav, usd_sol.upper, lst_sol);
+      assert_chain_rounds(out, &rounded, &exact, Down)?;
+    }
+  }
+
+  #[test]
+  fn stable_to_lever(
+    amount in token_amount(),
+    stablecoin_nav in stablecoin_nav(),
+    levercoin_nav in dollar_centered_price_range(),
+  ) {
+    let conversion = SwapConversion::new(stablecoin_nav, levercoin_nav);
+    if let Ok(out) = conversion.stable_to_lever(amount) {
+      let exact = reference::stable_to_lever(
+        amount, stablecoin_nav, levercoin_nav.upper,
+      );
+      let rounded = reference::stable_to_lever_rounded(
+        amount, stablecoin_nav, levercoin_nav.upper,
+      );
+      assert_chain_rounds(out, &rounded, &exact, Down)?;
+    }
+  }
+
+  #[test]
+  fn lever_to_stable(
+    amount in token_amount(),
+    stablecoin_nav in stablecoin_nav(),
+    levercoin_nav in dollar_centered_price_range(),
+  ) {
+    let conversion = SwapConversion::new(stablecoin_nav, levercoin_nav);
+    if let Ok(out) = conversion.lever_to_stable(amount) {
+      let exact = reference::lever_to_stable(
+        amount, levercoin_nav.lower, stablecoin_nav,
+      );
+      let rounded = reference::lever_to_stable_rounded(
+        amount, levercoin_nav.lower, stablecoin_nav,
+      );
+      assert_chain_rounds(out, &rounded, &exact, Down)?;
+    }
+  }
+
+  #[test]
+  fn exo_to_token(
+    amount in lst_amount(),
+    price in price_range(),
+    nav in levercoin_nav(),
+  ) {
+    let conversion = ExoConversion::new(price);
+    if let Ok(out) = conversion.exo_to_token(amount, nav) {
+      let exact = reference::exo_to_token(amount, price.lower, nav);
+      assert_rounds(out, &exact, Down)?;
+    }
+  }
+
+  #[test]
+  fn token_to_exo(
+    amount in token_amount(),
+    price in price_range(),
+    nav in levercoin_nav(),
+  ) {
+    let conversion = ExoConversion::new(price);
+    if let Ok(out) = conversion.token_to_exo(amount, nav) {
+      let exact = reference::token_to_exo(amount, nav, price.upper);
+      assert_rounds(out, &exact, Down)?;
+    }
+  }
+
+  #[test]
+  fn collateral_ratio_rounds_down(
+    total in collateral(),
+    price in usd_sol_price(),
+    stablecoin in token_amount(),
+  ) {
+    if let Ok(out) = collateral_ratio(total, price, stablecoin) {
+      let exact = reference::collateral_ratio(total, price, stablecoin);
+      assert_rounds(out, &exact, Down)?;
+    }
+  }
+
+  #[test]
+  fn total_value_locked_rounds_down(
+    total in collateral(),
+    price in usd_sol_price(),
+  ) {
+    if let Ok(out) = total_value_locked(total, price) {
+      let exact = reference::total_value_locked(total, price);
+      assert_rounds(out, &exact, Down)?;
+    }
+  }
+
+  #[test]
+  fn levercoin_market_cap_rounds_up(
+    supply in token_amount(),
+    nav in levercoin_nav(),
+  ) {
+    if let Ok(out) = levercoin_market_cap(supply, nav) {
+      let exact = reference::levercoin_market_cap(supply, nav);
+      assert_rounds(out, &exact, Up)?;
+    }
+  }
+
+  #[test]
+  fn max_mintable_stablecoin_rounds_down(
+    target in target_cr_n2(),
+    total in collateral(),
+    price in usd_sol_price(),
+    stablecoin in token_amount(),
+  ) {
+    if let Ok(out) = max_mintable_stablecoin(target, total, price, stablecoin) {
+      let exact =
+        reference::max_mintable_stablecoin(target, total, price, stablecoin);
+      let rounded = reference::max_mintable_stablecoin_rounded(
+        target, total, price, stablecoin,
+      );
+      assert_chain_rounds(out, &rounded, &exact, Down)?;
+    }
+  }
+
+  #[test]
+  fn max_swappable_stablecoin_rounds_down(
+    target in target_cr_n2(),
+    tvl in collateral(),
+    stablecoin in token_amount(),
+  ) {
+    if let Ok(out) = max_swappable_stablecoin(target, tvl, stablecoin) {
+      let exact = reference::max_swappable_stablecoin(target, tvl, stablecoin);
+      assert_rounds(out, &exact, Down)?;
+    }
+  }
+
+  #[test]
+  fn levercoin_navs_bracket_exact(
+    total in collateral(),
+    price in price_range(),
+    stablecoin in token_amount(),
+    stablecoin_nav in stablecoin_nav(),
+    levercoin in token_amount(),
+  ) {
+    let mint = next_levercoin_mint_nav(
+      total, price, stablecoin, stablecoin_nav, levercoin,
+    );
+    if let Some(out) = mint {
+      let exact = reference::levercoin_nav(
+        total, price.upper, stablecoin, stablecoin_nav, levercoin,
+      );
+      let rounded = reference::levercoin_nav_rounded(
+        total, price.upper, stablecoin, stablecoin_nav, levercoin, Up,
+      );
+      assert_chain_rounds(out, &rounded, &exact, Up)?;
+    }
+    let redeem = next_levercoin_redeem_nav(
+      total, price, stablecoin, stablecoin_nav, levercoin,
+    );
+    if let Some(out) = redeem {
+      let exact = reference::levercoin_nav(
+        total, price.lower, stablecoin, stablecoin_nav, levercoin,
+      );
+      let rounded = reference::levercoin_nav_rounded(
+        total, price.lower, stablecoin, stablecoin_nav, levercoin, Down,
+      );
+      assert_chain_rounds(out, &rounded, &exact, Down)?;
+    }
+    if let (Some(mint), Some(redeem)) = (mint, redeem) {
+      prop_assert!(redeem <= mint);
+    }
+  }
+
+  #[test]
+  fn depeg_stablecoin_nav_rounds_down(
+    total in collateral(),
+    price in usd_sol_price(),
+    stablecoin in token_amount(),
+  ) {
+    if let Ok(out) = depeg_stablecoin_nav(total, price, stablecoin) {
+      let exact = reference::depeg_stablecoin_nav(total, price, stablecoin);
+      assert_rounds(out, &exact, Down)?;
+    }
+  }
+
+  #[test]
+  fn lp_token_nav_rounds_up(
+    pool in token_amount(),
+    supply in token_amount(),
+  ) {
+    let out = lp_token_nav(pool, supply)?;
+    assert_rounds(out, &reference::lp_token_nav(pool, supply), Up)?;
+  }
+
+  #[test]
+  fn lp_token_out_rounds_down(
+    amount in token_amount(),
+    nav in (1u64..10_000_000).prop_map(UFix64::<N6>::new),
+  ) {
+    if let Ok(out) = lp_token_out(amount, nav) {
+      assert_rounds(out, &reference::lp_token_out(amount, nav), Down)?;
+    }
+  }
+
+  #[test]
+  fn amount_token_to_withdraw_rounds_down(
+    user in token_amount(),
+    supply in token_amount(),
+    pool in token_amount(),
+  ) {
+    if let Ok(out) = amount_token_to_withdraw(user, supply, pool) {
+      let exact = reference::amount_token_to_withdraw(user, supply, pool);
+      assert_rounds(out, &exact, Down)?;
+    }
+  }
+
+  #[test]
+  fn fee_extraction_rounds_up(
+    fee in fee(),
+    amount in token_amount(),
+  ) {
+    let extract = FeeExtract::new(fee, amount)?;
+    let exact = reference::fee(fee, amount);
+    assert_rounds(extract.fees_extracted, &exact, Up)?;
+    let remaining = q(amount) - exact;
+    assert_rounds(extract.amount_remaining, &remaining, Down)?;
+  }
+
+  #[test]
+  fn max_sellable_collateral_rounds_down(
+    target in target_cr_n9(),
+    stablecoin in token_amount(),
+    price in usd_sol_price(),
+    total in collateral(),
+  ) {
+    let out = max_sellable_collateral(target, stablecoin, price, total);
+    if let Some(out) = out {
+      let exact =
+        reference::max_sellable_collateral(target, stablecoin, price, total);
+      let rounded = reference::max_sellable_collateral_rounded(
+        target, stablecoin, price, total,
+      );
+      assert_chain_rounds(out, &rounded, &exact, Down)?;
+    }
+  }
+
+  #[test]
+  fn max_buyable_collateral_rounds_down(
+    target in target_cr_n9(),
+    stablecoin in token_amount(),
+    price in usd_sol_price(),
+    total in collateral(),
+  ) {
+    let out = max_buyable_collateral(target, stablecoin, price, total);
+    if let Some(out) = out {
+      let exact =
+        reference::max_buyable_collateral(target, stablecoin, price, total);
+      let rounded = reference::max_buyable_collateral_rounded(
+        target, stablecoin, price, total,
+      );
+      assert_chain_rounds(out, &rounded, &exact, Down)?;
+    }
+  }
+
+  #[test]
+  fn midpoint_rounds_down(
+    a in target_cr_n9(),
+    b in target_cr_n9(),
+  ) {
+    if let Some(out) = midpoint(a, b) {
+      assert_rounds(out, &reference::midpoint(a, b), Down)?;
+    }
+  }
+
+  #[test]
+  fn sell_curve_interpolates_widened_endpoints(
+    spot in usd_sol_price(),
+    floor_pct in curve_pct(),
+    ceil_pct in curve_pct(),
+    cr in cr_in(RebalanceMode::SellZone1),
+  ) {
+    let config = RebalanceCurveConfig::new(floor_pct.into(), ceil_pct.into());
+    let oracle = OraclePrice { spot, conf: UFix64::zero() };
+    let curve = SellPriceCurve::new(oracle, &config)?;
+    assert_rebalance_curve(
+      &curve, spot, &config, RebalanceMode::SellZone1, cr,
+    )?;
+  }
+
+  #[test]
+  fn buy_curve_interpolates_widened_endpoints(
+    spot in usd_sol_price(),
+    floor_pct in curve_pct(),
+    ceil_pct in curve_pct(),
+    cr in cr_in(RebalanceMode::BuyZone1),
+  ) {
+    let config = RebalanceCurveConfig::new(floor_pct.into(), ceil_pct.into());
+    let oracle = OraclePrice { spot, conf: UFix64::zero() };
+    let curve = BuyPriceCurve::new(oracle, &config)?;
+    assert_rebalance_curve(
+      &curve, spot, &config, RebalanceMode::BuyZone1, cr,
+    )?;
+  }
+
+  #[test]
+  fn mint_fee_rate_rounds_up(cr in fee_curve_cr()) {
+    let fees = InterpolatedMintFees::new(
+      FixInterp::from_points_unchecked(*MINT_FEE_INV),
+    );
+    assert_fee_rate(&fees, MINT_FEE_INV, cr)?;
+  }
+
+  #[test]
+  fn redeem_fee_rate_rounds_up(cr in fee_curve_cr()) {
+    let fees = InterpolatedRedeemFees::new(
+      FixInterp::from_points_unchecked(*REDEEM_FEE_LN),
+    );
+    assert_fee_rate(&fees, REDEEM_FEE_LN, cr)?;
+  }
+
+  #[test]
+  fn rebalance_pnl_is_exact(
+    value_in in token_amount(),
+    value_out in token_amount(),
+  ) {
+    let exact = reference::rebalance_pnl(value_in, value_out);
+    let signed = match RebalancePnl::from_stablecoin_flow(value_in, value_out) {
+      Some(RebalancePnl::Profit(profit)) => q(profit),
+      Some(RebalancePnl::Loss(loss)) => -q(loss),
+      Some(RebalancePnl::NoChange) => BigRational::zero(),
+      None => Err(TestCaseError::fail("no PnL for a representable flow"))?,
+    };
+    prop_assert_eq!(signed, exact);
+  }
+}
@@ -0,0 +1,428 @@
+//! Exact rational reference for the fixed-point protocol math.
+//!
+//! Each function mirrors a production formula with unbounded precision and
+//! no intermediate rounding. The [`differential`] suite compares fixed-point
+//! results against it: amounts paid out must round down, amounts owed to the
+//! protocol must round up.
+//!
+//! Formulas that round between steps also have a `_rounded` twin that
+//! applies the same intermediate rounding on exact values and leaves only
+//! the final step unrounded. Production must land within one atom of the
+//! twin, and on the protocol's side of the exact value.
+
+use fix::prelude::*;
+use fix::typenum::Integer;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::One;
+
+mod differential;
+
+/// Direction the protocol must round a result in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+  /// Paid out by the protocol, or a bound limiting user action.
+  Down,
+  /// Owed to the protocol.
+  Up,
+}
+
+/// Exact value `bits * 10^Exp`.
+pub fn q<Exp: Integer>(x: UFix64<Exp>) -> BigRational {
+  BigRational::from_integer(BigInt::from(x.bits)) * atom::<Exp>()
+}
+
+/// Value of one atom at precision `Exp`.
+pub fn atom<Exp: Integer>() -> BigRational {
+  BigRational::from_integer(BigInt::from(10)).pow(Exp::to_i32())
+}
+
+/// Distance in atoms from `exact` to `actual`, signed toward `rounding`.
+///
+/// Non-negative when `actual` rounds the right way.
+pub fn error_atoms<Exp: Integer>(
+  actual: UFix64<Exp>,
+  exact: &BigRational,
+  rounding: Rounding,
+) -> BigRational {
+  let delta = match rounding {
+    Rounding::Down => exact - q(actual),
+    Rounding::Up => q(actual) - exact,
+  };
+  delta / atom::<Exp>()
+}
+
+/// `x` on the `Exp` grid, rounded in `rounding` like an intermediate
+/// fixed-point step.
+pub fn round<Exp: Integer>(x: &BigRational, rounding: Rounding) -> BigRational {
+  let atoms = x / atom::<Exp>();
+  let whole = match rounding {
+    Rounding::Down => atoms.floor(),
+    Rounding::Up => atoms.ceil(),
+  };
+  whole * atom::<Exp>()
+}
+
+/// Exact value of a signed fixed-point number.
+pub fn qi<Exp: Integer>(x: IFix64<Exp>) -> BigRational {
+  BigRational::from_integer(BigInt::from(x.bits)) * atom::<Exp>()
+}
+
+/// Piecewise linear interpolation through `points` at `x`, held flat
+/// outside their domain.
+///
+/// ```txt
+/// y = y_0 + (y_1 - y_0) * (x - x_0) / (x_1 - x_0)
+/// ```
+pub fn interpolate(
+  points: &[(BigRational, BigRational)],
+  x: &BigRational,
+) -> BigRational {
+  let (first, last) = (&points[0], &points[points.len() - 1]);
+  if *x <= first.0 {
+    return first.1.clone();
+  }
+  points
+    .windows(2)
+    .find(|segment| *x <= segment[1].0)
+    .map_or_else(
+      || last.1.clone(),
+      |segment| {
+        let ((x0, y0), (x1, y1)) = (&segment[0], &segment[1]);
+        y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+      },
+    )
+}
+
+// `conversion`
+
+/// `LST * (SOL/LST) * (USD/SOL) / NAV`
+pub fn lst_to_token(
+  amount_lst: UFix64<N9>,
+  lst_sol_price: UFix64<N9>,
+  usd_sol_price_lower: UFix64<N9>,
+  token_nav: UFix64<N9>,
+) -> BigRational {
+  q(amount_lst) * q(lst_sol_price) * q(usd_sol_price_lower) / q(token_nav)
+}
+
+/// [`lst_to_token`] with the SOL value floored to `N9`.
+pub fn lst_to_token_rounded(
+  amount_lst: UFix64<N9>,
+  lst_sol_price: UFix64<N9>,
+  usd_sol_price_lower: UFix64<N9>,
+  token_nav: UFix64<N9>,
+) -> BigRational {
+  let sol = round::<N9>(&(q(amount_lst) * q(lst_sol_price)), Rounding::Down);
+  sol * q(usd_sol_price_lower) / q(token_nav)
+}
+
+/// `TOKEN * NAV / ((USD/SOL) * (SOL/LST))`
+pub fn token_to_lst(
+  amount_token: UFix64<N6>,
+  token_nav: UFix64<N9>,
+  usd_sol_price_upper: UFix64<N9>,
+  lst_sol_price: UFix64<N9>,
+) -> BigRational {
+  q(amount_token) * q(token_nav) / (q(usd_sol_price_upper) * q(lst_sol_price))
+}
+
+/// [`token_to_lst`] with the SOL value floored to `N9`.
+pub fn token_to_lst_rounded(
+  amount_token: UFix64<N6>,
+  token_nav: UFix64<N9>,
+  usd_sol_price_upper: UFix64<N9>,
+  lst_sol_price: UFix64<N9>,
+) -> BigRational {
+  let sol = q(amount_token) * q(token_nav) / q(usd_sol_price_upper);
+  round::<N9>(&sol, Rounding::Down) / q(lst_sol_price)
+}
+
+/// `STABLE * stablecoin_nav / levercoin_nav`
+pub fn stable_to_lever(
+  amount_stable: UFix64<N6>,
+  stablecoin_nav: UFix64<N9>,
+  levercoin_nav_upper: UFix64<N9>,
+) -> BigRational {
+  q(amount_stable) * q(stablecoin_nav) / q(levercoin_nav_upper)
+}
+
+/// [`stable_to_lever`] with the USD value floored to `N6`.
+pub fn stable_to_lever_rounded(
+  amount_stable: UFix64<N6>,
+  stablecoin_nav: UFix64<N9>,
+  levercoin_nav_upper: UFix64<N9>,
+) -> BigRational {
+  let usd = q(amount_stable) * q(stablecoin_nav);
+  round::<N6>(&usd, Rounding::Down) / q(levercoin_nav_upper)
+}
+
+/// `LEVER * levercoin_nav / stablecoin_nav`
+pub fn lever_to_stable(
+  amount_lever: UFix64<N6>,
+  levercoin_nav_lower: UFix64<N9>,
+  stablecoin_nav: UFix64<N9>,
+) -> BigRational {
+  q(amount_lever) * q(levercoin_nav_lower) / q(stablecoin_nav)
+}
+
+/// [`lever_to_stable`] with the USD value floored to `N6`.
+pub fn lever_to_stable_rounded(
+  amount_lever: UFix64<N6>,
+  levercoin_nav_lower: UFix64<N9>,
+  stablecoin_nav: UFix64<N9>,
+) -> BigRational {
+  let usd = q(amount_lever) * q(levercoin_nav_lower);
+  round::<N6>(&usd, Rounding::Down) / q(stablecoin_nav)
+}
+
+/// `COLLATERAL * (USD/COLLATERAL) / NAV`
+pub fn exo_to_token(
+  amount: UFix64<N9>,
+  collateral_usd_price_lower: UFix64<N9>,
+  token_nav: UFix64<N9>,
+) -> BigRational {
+  q(amount) * q(collateral_usd_price_lower) / q(token_nav)
+}
+
+/// `TOKEN * NAV / (USD/COLLATERAL)`
+pub fn token_to_exo(
+  amount: UFix64<N6>,
+  token_nav: UFix64<N9>,
+  collateral_usd_price_upper: UFix64<N9>,
+) -> BigRational {
+  q(amount) * q(token_nav) / q(collateral_usd_price_upper)
+}
+
+// `exchange_math`
+
+/// `total_collateral * price / stablecoin`
+pub fn collateral_ratio(
+  total_collateral: UFix64<N9>,
+  usd_collateral_price: UFix64<N9>,
+  amount_stablecoin: UFix64<N6>,
+) -> BigRational {
+  q(total_collateral) * q(usd_collateral_price) / q(amount_stablecoin)
+}
+
+/// `total_collateral * price`
+pub fn total_value_locked(
+  total_collateral: UFix64<N9>,
+  usd_collateral_price: UFix64<N9>,
+) -> BigRational {
+  q(total_collateral) * q(usd_collateral_price)
+}
+
+/// `supply * nav`
+pub fn levercoin_market_cap(
+  levercoin_supply: UFix64<N6>,
+  levercoin_nav: UFix64<N9>,
+) -> BigRational {
+  q(levercoin_supply) * q(levercoin_nav)
+}
+
+/// `(tvl - target_cr * stablecoin) / (target_cr - 1)`
+pub fn max_mintable_stablecoin(
+  target_collateral_ratio: UFix64<N2>,
+  total_collateral: UFix64<N9>,
+  usd_collateral_price: UFix64<N9>,
+  stablecoin_supply: UFix64<N6>,
+) -> BigRational {
+  let target = q(target_collateral_ratio);
+  let tvl = q(total_collateral) * q(usd_collateral_price);
+  (tvl - &target * q(stablecoin_supply)) / (target - BigRational::one())
+}
+
+/// [`max_mintable_stablecoin`] with TVL floored to `N9` and the target
+/// supply ceiled to `N6`.
+pub fn max_mintable_stablecoin_rounded(
+  target_collateral_ratio: UFix64<N2>,
+  total_collateral: UFix64<N9>,
+  usd_collateral_price: UFix64<N9>,
+  stablecoin_supply: UFix64<N6>,
+) -> BigRational {
+  let target = q(target_collateral_ratio);
+  let tvl = q(total_collateral) * q(usd_collateral_price);
+  let target_supply = &target * q(stablecoin_supply);
+  (round::<N9>(&tvl, Rounding::Down)
+    - round::<N6>(&target_supply, Rounding::Up))
+    / (target - BigRational::one())
+}
+
+/// `tvl / target_cr - stablecoin`
+pub fn max_swappable_stablecoin(
+  target_collateral_ratio: UFix64<N2>,
+  total_value_locked: UFix64<N9>,
+  stablecoin_supply: UFix64<N6>,
+) -> BigRational {
+  q(total_value_locked) / q(target_collateral_ratio) - q(stablecoin_supply)
+}
+
+/// `(collateral * price - stablecoin * stablecoin_nav) / levercoin`
+pub fn levercoin_nav(
+  total_collateral: UFix64<N9>,
+  usd_collateral_price: UFix64<N9>,
+  stablecoin_supply: UFix64<N6>,
+  stablecoin_nav: UFix64<N9>,
+  levercoin_supply: UFix64<N6>,
+) -> BigRational {
+  let collateral_value = q(total_collateral) * q(usd_collateral_price);
+  let stablecoin_value = q(stablecoin_supply) * q(stablecoin_nav);
+  (collateral_value - stablecoin_value) / q(levercoin_supply)
+}
+
+/// [`levercoin_nav`] with collateral value rounded to `N9` in `rounding`
+/// and stablecoin value to `N6` against it, so free collateral leans the
+/// same way.
+pub fn levercoin_nav_rounded(
+  total_collateral: UFix64<N9>,
+  usd_collateral_price: UFix64<N9>,
+  stablecoin_supply: UFix64<N6>,
+  stablecoin_nav: UFix64<N9>,
+  levercoin_supply: UFix64<N6>,
+  rounding: Rounding,
+) -> BigRational {
+  let against = match rounding {
+    Rounding::Down => Rounding::Up,
+    Rounding::Up => Rounding::Down,
+  };
+  let collateral_value = q(total_collateral) * q(usd_collateral_price);
+  let stablecoin_value = q(stablecoin_supply) * q(stablecoin_nav);
+  (round::<N9>(&collateral_value, rounding)
+    - round::<N6>(&stablecoin_value, against))
+    / q(levercoin_supply)
+}
+
+/// `collateral * price / stablecoin`
+pub fn depeg_stablecoin_nav(
+  total_collateral: UFix64<N9>,
+  usd_collateral_price: UFix64<N9>,
+  stablecoin_supply: UFix64<N6>,
+) -> BigRational {
+  q(total_collateral) * q(usd_collateral_price) / q(stablecoin_supply)
+}
+
+// `earn_pool_math`
+
+/// `stablecoin_in_pool / lp_token_supply`
+pub fn lp_token_nav(
+  stablecoin_in_pool: UFix64<N6>,
+  lp_token_supply: UFix64<N6>,
+) -> BigRational {
+  q(stablecoin_in_pool) / q(lp_token_supply)
+}
+
+/// `stablecoin_in / lp_token_nav`
+pub fn lp_token_out(
+  amount_stablecoin_in: UFix64<N6>,
+  lp_token_nav: UFix64<N6>,
+) -> BigRational {
+  q(amount_stablecoin_in) / q(lp_token_nav)
+}
+
+/// `user_lp * pool_amount / lp_supply`
+pub fn amount_token_to_withdraw(
+  user_lp_token_amount: UFix64<N6>,
+  lp_token_supply: UFix64<N6>,
+  pool_amount: UFix64<N6>,
+) -> BigRational {
+  q(user_lp_token_amount) * q(pool_amount) / q(lp_token_supply)
+}
+
+// `fees`
+
+/// `amount * fee`
+pub fn fee<Exp: Integer, FeeExp: Integer>(
+  fee: UFix64<FeeExp>,
+  amount_in: UFix64<Exp>,
+) -> BigRational {
+  q(amount_in) * q(fee)
+}
+
+// `rebalance::math`
+
+/// `(target_cr * stablecoin - price * collateral) / (price * (target_cr - 1))`
+pub fn max_sellable_collateral(
+  target_cr: UFix64<N9>,
+  virtual_stablecoin: UFix64<N6>,
+  collateral_usd_price: UFix64<N9>,
+  total_collateral: UFix64<N9>,
+) -> BigRational {
+  let target = q(target_cr);
+  let price = q(collateral_usd_price);
+  let num = &target * q(virtual_stablecoin) - &price * q(total_collateral);
+  num / (price * (target - BigRational::one()))
+}
+
+/// [`max_sellable_collateral`] with each product rounded to `N9` toward
+/// less headroom.
+pub fn max_sellable_collateral_rounded(
+  target_cr: UFix64<N9>,
+  virtual_stablecoin: UFix64<N6>,
+  collateral_usd_price: UFix64<N9>,
+  total_collateral: UFix64<N9>,
+) -> BigRational {
+  let target = q(target_cr);
+  let price = q(collateral_usd_price);
+  let num = round::<N9>(&(&target * q(virtual_stablecoin)), Rounding::Down)
+    - round::<N9>(&(&price * q(total_collateral)), Rounding::Up);
+  let denom = price * (target - BigRational::one());
+  num / round::<N9>(&denom, Rounding::Up)
+}
+
+/// `(price * collateral - target_cr * stablecoin) / (price * (target_cr - 1))`
+pub fn max_buyable_collateral(
+  target_cr: UFix64<N9>,
+  virtual_stablecoin: UFix64<N6>,
+  collateral_usd_price: UFix64<N9>,
+  total_collateral: UFix64<N9>,
+) -> BigRational {
+  let target = q(target_cr);
+  let price = q(collateral_usd_price);
+  let num = &price * q(total_collateral) - &target * q(virtual_stablecoin);
+  num / (price * (target - BigRational::one()))
+}
+
+/// [`max_buyable_collateral`] with each product rounded to `N9` toward
+/// less headroom.
+pub fn max_buyable_collateral_rounded(
+  target_cr: UFix64<N9>,
+  virtual_stablecoin: UFix64<N6>,
+  collateral_usd_price: UFix64<N9>,
+  total_collateral: UFix64<N9>,
+) -> BigRational {
+  let target = q(target_cr);
+  let price = q(collateral_usd_price);
+  let num = round::<N9>(&(&price * q(total_collateral)), Rounding::Down)
+    - round::<N9>(&(&target * q(virtual_stablecoin)), Rounding::Up);
+  let denom = price * (target - BigRational::one());
+  num / round::<N9>(&denom, Rounding::Up)
+}
+
+/// `(a + b) / 2`
+pub fn midpoint(a: UFix64<N9>, b: UFix64<N9>) -> BigRational {
+  (q(a) + q(b)) / BigRational::from_integer(BigInt::from(2))
+}
+
+// `rebalance::pricing`
+
+/// Rebalance curve endpoints `(spot * (1 - floor_pct), spot * (1 + ceil_pct))`.
+pub fn rebalance_curve_prices(
+  spot: UFix64<N9>,
+  floor_pct: UFix64<N9>,
+  ceil_pct: UFix64<N9>,
+) -> (BigRational, BigRational) {
+  let floor = q(spot) * (BigRational::one() - q(floor_pct));
+  let ceil = q(spot) * (BigRational::one() + q(ceil_pct));
+  (floor, ceil)
+}
+
+// `rebalance::pnl`
+
+/// `stablecoin_in - stablecoin_out`, positive for a profit.
+pub fn rebalance_pnl(
+  stablecoin_value_in: UFix64<N6>,
+  stablecoin_value_out: UFix64<N6>,
+) -> BigRational {
+  q(stablecoin_value_in) - q(stablecoin_value_out)
+}
@@ -67,6 +67,16 @@ impl SlippageConfig {
     }
   }
 
+  /// Lowest token amount [`Self::validate_token_out`] accepts.
+  ///
+  /// # Errors
+  /// * Tolerance above 100% or arithmetic overflow
+  pub fn min_token_out<Exp: Integer>(&self) -> Result<UFix64<Exp>, CoreError> {
+    let expected = self.expected_token_out()?;
+    let tolerance = self.slippage_tolerance()?;
+    Self::tolerable_amount(expected, tolerance).ok_or(SlippageArithmetic)
+  }
+
   /// Checks token amount against the configured lowest tolerable amount
   pub fn validate_token_out<Exp: Integer>(
     &self,
@@ -106,6 +116,14 @@ mod tests {
     assert!(config.validate_token_out(amount).is_ok());
   }
 
+  #[test]
+  fn min_token_out_is_boundary() {
+    let config = SlippageConfig::new(UFix64::<N6>::one(), ONE_PERCENT);
+    let min = config.min_token_out::<N6>();
+    assert_eq!(min, Ok(UFix64::new(990_000)));
+    assert!(min.is_ok_and(|min| config.validate_token_out(min).is_ok()));
+  }
+
   #[test]
   fn slippage_one_below_boundary_neg() {
     // One unit below the boundary
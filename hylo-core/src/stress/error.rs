@@ -0,0 +1,16 @@
+//! Errors from stress scenarios.
+
+use thiserror::Error;
+
+use crate::error::CoreError;
+
+/// Errors from building or running a stress scenario.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+pub enum StressError {
+  #[error(transparent)]
+  Core(#[from] CoreError),
+  #[error("Arithmetic error while repricing a stress scenario.")]
+  PriceShock,
+  #[error("Stress price path parameters are invalid.")]
+  PathParameters,
+}
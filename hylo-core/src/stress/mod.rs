@@ -0,0 +1,464 @@
+//! Collateral ratio stress testing under price shocks.
+//!
+//! [`StressContext`] snapshots a pair's totals from any
+//! [`ExchangeContext`] and reprices them along a [`PricePath`], holding
+//! supplies fixed. Each step reports the CR, rebalance mode, route gating,
+//! NAVs, and rebalance liquidity the protocol would show at that price.
+
+mod error;
+mod path;
+
+use fix::prelude::*;
+
+pub use self::error::StressError;
+pub use self::path::{GbmParams, PricePath, SplitMix64};
+use crate::error::CoreError;
+use crate::exchange_context::ExchangeContext;
+use crate::exchange_math::collateral_ratio;
+use crate::fees::controller::LevercoinFees;
+use crate::pyth::{OraclePrice, PriceRange};
+use crate::rebalance::mode::RebalanceMode;
+use crate::rebalance::pricing::RebalanceCurveConfig;
+
+/// Clock-free [`ExchangeContext`] over fixed totals and an arbitrary
+/// oracle price.
+#[derive(Clone)]
+pub struct StressContext {
+  total_collateral: UFix64<N9>,
+  collateral_oracle: OraclePrice,
+  collateral_usd_price: PriceRange<N9>,
+  virtual_stablecoin_supply: UFix64<N6>,
+  levercoin_supply: UFix64<N6>,
+  collateral_ratio: UFix64<N9>,
+  stablecoin_mint_threshold: UFix64<N9>,
+  rebalance_mode: RebalanceMode,
+  levercoin_fees: LevercoinFees,
+  sell_curve_config: RebalanceCurveConfig,
+  buy_curve_config: RebalanceCurveConfig,
+}
+
+impl ExchangeContext for StressContext {
+  fn total_collateral(&self) -> UFix64<N9> {
+    self.total_collateral
+  }
+
+  fn collateral_usd_price(&self) -> PriceRange<N9> {
+    self.collateral_usd_price
+  }
+
+  fn collateral_oracle_price(&self) -> OraclePrice {
+    self.collateral_oracle
+  }
+
+  fn sell_curve_config(&self) -> &RebalanceCurveConfig {
+    &self.sell_curve_config
+  }
+
+  fn buy_curve_config(&self) -> &RebalanceCurveConfig {
+    &self.buy_curve_config
+  }
+
+  fn stablecoin_mint_threshold(&self) -> UFix64<N9> {
+    self.stablecoin_mint_threshold
+  }
+
+  fn virtual_stablecoin_supply(&self) -> Result<UFix64<N6>, CoreError> {
+    Ok(self.virtual_stablecoin_supply)
+  }
+
+  fn levercoin_supply(&self) -> Result<UFix64<N6>, CoreError> {
+    Ok(self.levercoin_supply)
+  }
+
+  fn rebalance_mode(&self) -> RebalanceMode {
+    self.rebalance_mode
+  }
+
+  fn collateral_ratio(&self) -> UFix64<N9> {
+    self.collateral_ratio
+  }
+
+  fn levercoin_fees(&self) -> &LevercoinFees {
+    &self.levercoin_fees
+  }
+}
+
+impl StressContext {
+  /// Builds a context from raw totals, caching CR and mode like the
+  /// onchain loaders.
+  ///
+  /// # Errors
+  /// * Oracle price range or CR arithmetic
+  #[allow(clippy::too_many_arguments)]
+  pub fn new(
+    total_collateral: UFix64<N9>,
+    collateral_oracle: OraclePrice,
+    virtual_stablecoin_supply: UFix64<N6>,
+    levercoin_supply: UFix64<N6>,
+    stablecoin_mint_threshold: UFix64<N9>,
+    levercoin_fees: LevercoinFees,
+    sell_curve_config: RebalanceCurveConfig,
+    buy_curve_config: RebalanceCurveConfig,
+  ) -> Result<StressContext, StressError> {
+    let collateral_usd_price = collateral_oracle.price_range()?;
+    let collateral_ratio = collateral_ratio(
+      total_collateral,
+      collateral_usd_price.lower,
+      virtual_stablecoin_supply,
+    )?;
+    Ok(StressContext {
+      total_collateral,
+      collateral_oracle,
+      collateral_usd_price,
+      virtual_stablecoin_supply,
+      levercoin_supply,
+      collateral_ratio,
+      stablecoin_mint_threshold,
+      rebalance_mode: RebalanceMode::from_cr(collateral_ratio),
+      levercoin_fees,
+      sell_curve_config,
+      buy_curve_config,
+    })
+  }
+
+  /// Snapshots totals and configuration from a loaded context.
+  ///
+  /// # Errors
+  /// * Stablecoin or levercoin supply not loaded
+  /// * CR arithmetic
+  pub fn from_context<X: ExchangeContext>(
+    context: &X,
+  ) -> Result<StressContext, StressError> {
+    StressContext::new(
+      context.total_collateral(),
+      context.collateral_oracle_price(),
+      context.virtual_stablecoin_supply()?,
+      context.levercoin_supply()?,
+      context.stablecoin_mint_threshold(),
+      *context.levercoin_fees(),
+      *context.sell_curve_config(),
+      *context.buy_curve_config(),
+    )
+  }
+
+  /// Same totals repriced at `spot`, scaling the oracle confidence with
+  /// the price so the relative spread is preserved.
+  ///
+  /// # Errors
+  /// * Zero current spot or overflow while scaling confidence
+  pub fn with_spot(
+    &self,
+    spot: UFix64<N9>,
+  ) -> Result<StressContext, StressError> {
+    let conf = self
+      .collateral_oracle
+      .conf
+      .mul_div_floor(spot, self.collateral_oracle.spot)
+      .ok_or(StressError::PriceShock)?;
+    StressContext::new(
+      self.total_collateral,
+      OraclePrice { spot, conf },
+      self.virtual_stablecoin_supply,
+      self.levercoin_supply,
+      self.stablecoin_mint_threshold,
+      self.levercoin_fees,
+      self.sell_curve_config,
+      self.buy_curve_config,
+    )
+  }
+
+  /// Reprices the snapshot at every step of `path`.
+  ///
+  /// # Errors
+  /// * Repricing or CR arithmetic at any step
+  pub fn run(&self, path: &PricePath) -> Result<Vec<StressStep>, StressError> {
+    path
+      .prices()
+      .iter()
+      .enumerate()
+      .map(|(step, spot)| StressStep::observe(step, &self.with_spot(*spot)?))
+      .collect()
+  }
+}
+
+/// Protocol health observed at one step of a price path. Stablecoin
+/// redemption is open in every mode, so only the gated routes are reported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StressStep {
+  pub step: usize,
+  pub collateral_spot: UFix64<N9>,
+  pub collateral_ratio: UFix64<N9>,
+  pub rebalance_mode: RebalanceMode,
+  pub stablecoin_mint_enabled: bool,
+  pub levercoin_mint_enabled: bool,
+  pub levercoin_redeem_enabled: bool,
+  /// Lower-bound levercoin NAV; `None` once CR is at or below 1 and free
+  /// collateral is exhausted.
+  pub levercoin_nav: Option<UFix64<N9>>,
+  /// Stablecoin NAV under [`RebalanceMode::Depeg`], `None` otherwise.
+  pub depeg_stablecoin_nav: Option<UFix64<N9>>,
+  /// Collateral sellable to reach Neutral; `None` unless CR at spot is
+  /// below it.
+  pub rebalance_sell_liquidity: Option<UFix64<N9>>,
+  /// Collateral buyable to reach `BuyZone1`; `None` unless CR at spot is
+  /// at or above it.
+  pub rebalance_buy_target: Option<UFix64<N9>>,
+}
+
+impl StressStep {
+  /// Reads the reported metrics off a repriced context. Rebalance sizes
+  /// are priced at spot, so they are gated on the CR at spot rather than
+  /// the mode.
+  ///
+  /// # Errors
+  /// * Supply not loaded
+  /// * NAV, CR, or rebalance size arithmetic
+  pub fn observe<X: ExchangeContext>(
+    step: usize,
+    context: &X,
+  ) -> Result<StressStep, StressError> {
+    let rebalance_mode = context.rebalance_mode();
+    let cr = context.collateral_ratio();
+    let spot_cr = collateral_ratio(
+      context.total_collateral(),
+      context.collateral_oracle_price().spot,
+      context.virtual_stablecoin_supply()?,
+    )?;
+    let depeg_stablecoin_nav = (rebalance_mode == RebalanceMode::Depeg)
+      .then(|| context.stablecoin_nav())
+      .transpose()?;
+    let levercoin_nav = (cr > UFix64::one())
+      .then(|| context.levercoin_redeem_nav())
+      .transpose()?;
+    let sell_floor = RebalanceMode::Neutral.active_range().start()?;
+    let rebalance_sell_liquidity = (spot_cr < sell_floor)
+      .then(|| context.rebalance_sell_liquidity())
+      .transpose()?;
+    let buy_floor = RebalanceMode::BuyZone1.active_range().start()?;
+    let rebalance_buy_target = (spot_cr >= buy_floor)
+      .then(|| context.rebalance_buy_target())
+      .transpose()?;
+    Ok(StressStep {
+      step,
+      collateral_spot: context.collateral_oracle_price().spot,
+      collateral_ratio: cr,
+      rebalance_mode,
+      stablecoin_mint_enabled: context.stablecoin_mint_enabled(),
+      levercoin_mint_enabled: context.levercoin_mint_enabled(),
+      levercoin_redeem_enabled: context.levercoin_redeem_enabled(),
+      levercoin_nav,
+      depeg_stablecoin_nav,
+      rebalance_sell_liquidity,
+      rebalance_buy_target,
+    })
+  }
+}
+
+/// Stress results for one pair.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PairStress {
+  pub pair: String,
+  pub steps: Vec<StressStep>,
+}
+
+impl PairStress {
+  /// Lowest CR reached along the path.
+  #[must_use]
+  pub fn min_collateral_ratio(&self) -> Option<UFix64<N9>> {
+    self.steps.iter().map(|s| s.collateral_ratio).min()
+  }
+
+  /// Worst rebalance mode reached along the path.
+  #[must_use]
+  pub fn worst_mode(&self) -> Option<RebalanceMode> {
+    self.steps.iter().map(|s| s.rebalance_mode).min()
+  }
+
+  /// First step at which the pair entered Depeg.
+  #[must_use]
+  pub fn first_depeg(&self) -> Option<&StressStep> {
+    self
+      .steps
+      .iter()
+      .find(|s| s.rebalance_mode == RebalanceMode::Depeg)
+  }
+}
+
+/// Protocol-wide scenario: one snapshot and price path per pair, LST and
+/// each exo collateral stressed independently.
+#[derive(Clone, Default)]
+pub struct StressScenario {
+  pairs: Vec<(String, StressContext, PricePath)>,
+}
+
+impl StressScenario {
+  #[must_use]
+  pub fn new() -> StressScenario {
+    StressScenario::default()
+  }
+
+  /// Adds a pair under `name` with its own collateral price path.
+  #[must_use]
+  pub fn pair(
+    mut self,
+    name: impl Into<String>,
+    context: StressContext,
+    path: PricePath,
+  ) -> StressScenario {
+    self.pairs.push((name.into(), context, path));
+    self
+  }
+
+  /// Runs every pair through its path.
+  ///
+  /// # Errors
+  /// * Any pair fails to reprice
+  pub fn run(&self) -> Result<Vec<PairStress>, StressError> {
+    self
+      .pairs
+      .iter()
+      .map(|(pair, context, path)| {
+        Ok(PairStress {
+          pair: pair.clone(),
+          steps: context.run(path)?,
+        })
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use more_asserts::*;
+
+  use super::*;
+  use crate::fees::controller::FeePair;
+
+  const FEE: UFixValue64 = UFixValue64 { bits: 10, exp: -4 };
+
+  const CURVE: RebalanceCurveConfig = RebalanceCurveConfig {
+    floor_pct: UFixValue64 {
+      bits: 5_000_000,
+      exp: -9,
+    },
+    ceil_pct: UFixValue64 {
+      bits: 1_000_000,
+      exp: -9,
+    },
+  };
+
+  const SPOT: UFix64<N9> = UFix64::constant(100_000_000_000);
+
+  /// 1,000 collateral at $100 against 50,000 stablecoin: CR 2.0.
+  fn context() -> Result<StressContext, StressError> {
+    let pair = FeePair::new(FEE, FEE);
+    StressContext::new(
+      UFix64::new(1_000_000_000_000),
+      OraclePrice {
+        spot: SPOT,
+        conf: UFix64::new(50_000_000),
+      },
+      UFix64::new(50_000_000_000),
+      UFix64::new(25_000_000_000),
+      UFix64::new(1_500_000_000),
+      LevercoinFees::new(pair, pair, pair),
+      CURVE,
+      CURVE,
+    )
+  }
+
+  fn shock(pct: u64) -> UFix64<N9> {
+    UFix64::new(pct * 10_000_000)
+  }
+
+  #[test]
+  fn baseline_step_matches_context() -> Result<(), StressError> {
+    let ctx = context()?;
+    let path = PricePath::replay(vec![SPOT])?;
+    let steps = ctx.run(&path)?;
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].collateral_ratio, ctx.collateral_ratio());
+    assert_eq!(steps[0].rebalance_mode, RebalanceMode::BuyZone2);
+    assert!(steps[0].stablecoin_mint_enabled);
+    assert!(steps[0].rebalance_buy_target.is_some());
+    assert_eq!(steps[0].rebalance_sell_liquidity, None);
+    Ok(())
+  }
+
+  #[test]
+  fn deepening_shocks_walk_modes_down() -> Result<(), StressError> {
+    let ctx = context()?;
+    let path = PricePath::shocks(
+      SPOT,
+      &[shock(100), shock(80), shock(64), shock(55), shock(40)],
+    )?;
+    let steps = ctx.run(&path)?;
+    let modes: Vec<_> = steps.iter().map(|s| s.rebalance_mode).collect();
+    assert_eq!(
+      modes,
+      vec![
+        RebalanceMode::BuyZone2,
+        RebalanceMode::Neutral,
+        RebalanceMode::SellZone1,
+        RebalanceMode::SellZone2,
+        RebalanceMode::Depeg,
+      ]
+    );
+    steps.windows(2).for_each(|w| {
+      assert_gt!(w[0].collateral_ratio, w[1].collateral_ratio);
+    });
+    Ok(())
+  }
+
+  #[test]
+  fn sell_zone_reports_liquidity() -> Result<(), StressError> {
+    let steps = context()?.run(&PricePath::shocks(SPOT, &[shock(64)])?)?;
+    let step = &steps[0];
+    assert!(!step.stablecoin_mint_enabled);
+    assert!(step.levercoin_mint_enabled);
+    assert_gt!(step.rebalance_sell_liquidity, Some(UFix64::zero()));
+    assert_eq!(step.rebalance_buy_target, None);
+    assert_eq!(step.depeg_stablecoin_nav, None);
+    Ok(())
+  }
+
+  #[test]
+  fn depeg_gates_levercoin_and_prices_stablecoin() -> Result<(), StressError> {
+    let steps = context()?.run(&PricePath::shocks(SPOT, &[shock(40)])?)?;
+    let step = &steps[0];
+    assert_eq!(step.rebalance_mode, RebalanceMode::Depeg);
+    assert!(!step.levercoin_mint_enabled);
+    assert!(!step.levercoin_redeem_enabled);
+    assert_eq!(step.levercoin_nav, None);
+    let nav = step.depeg_stablecoin_nav.ok_or(StressError::PriceShock)?;
+    assert_lt!(nav, UFix64::one());
+    Ok(())
+  }
+
+  #[test]
+  fn scenario_reports_each_pair() -> Result<(), StressError> {
+    let ctx = context()?;
+    let gbm = GbmParams {
+      drift: 0.0,
+      volatility: 1.2,
+      dt: 1.0 / 365.0,
+      steps: 30,
+    };
+    let report = StressScenario::new()
+      .pair("lst", ctx.clone(), PricePath::gbm(SPOT, gbm, 42)?)
+      .pair("exo", ctx, PricePath::shocks(SPOT, &[shock(40)])?)
+      .run()?;
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[0].steps.len(), 31);
+    assert_eq!(report[1].worst_mode(), Some(RebalanceMode::Depeg));
+    assert_eq!(report[1].first_depeg().map(|s| s.step), Some(0));
+    Ok(())
+  }
+
+  #[test]
+  fn with_spot_preserves_relative_conf() -> Result<(), StressError> {
+    let ctx = context()?.with_spot(UFix64::new(50_000_000_000))?;
+    assert_eq!(ctx.collateral_oracle_price().conf, UFix64::new(25_000_000));
+    Ok(())
+  }
+}
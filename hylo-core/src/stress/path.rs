@@ -0,0 +1,258 @@
+//! Collateral price paths for stress scenarios.
+
+use fix::prelude::*;
+
+use super::error::StressError;
+use super::error::StressError::{PathParameters, PriceShock};
+
+/// Ordered collateral/USD spot prices, one per stress step.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PricePath {
+  prices: Vec<UFix64<N9>>,
+}
+
+/// Geometric Brownian motion parameters, annualized.
+///
+/// ```txt
+/// S(t + dt) = S(t) * exp((drift - volatility^2 / 2) * dt
+///                        + volatility * sqrt(dt) * Z)
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GbmParams {
+  /// Annualized drift `mu`.
+  pub drift: f64,
+  /// Annualized volatility `sigma`.
+  pub volatility: f64,
+  /// Step length in years.
+  pub dt: f64,
+  /// Number of steps after the starting price.
+  pub steps: usize,
+}
+
+impl PricePath {
+  /// Replays recorded spot prices as-is.
+  ///
+  /// # Errors
+  /// * Empty series
+  pub fn replay(prices: Vec<UFix64<N9>>) -> Result<PricePath, StressError> {
+    (!prices.is_empty())
+      .then_some(PricePath { prices })
+      .ok_or(PathParameters)
+  }
+
+  /// Instantaneous shocks: each factor multiplies `start` independently,
+  /// e.g. `0.7` for a 30% drop.
+  ///
+  /// # Errors
+  /// * No factors or overflow while scaling
+  pub fn shocks(
+    start: UFix64<N9>,
+    factors: &[UFix64<N9>],
+  ) -> Result<PricePath, StressError> {
+    let prices = factors
+      .iter()
+      .map(|factor| {
+        start
+          .mul_div_floor(*factor, UFix64::one())
+          .ok_or(PriceShock)
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    PricePath::replay(prices)
+  }
+
+  /// Seeded GBM path starting at `start`; the same seed always yields the
+  /// same path.
+  ///
+  /// # Errors
+  /// * Non-finite or negative parameters
+  /// * Price overflow along the path
+  pub fn gbm(
+    start: UFix64<N9>,
+    params: GbmParams,
+    seed: u64,
+  ) -> Result<PricePath, StressError> {
+    let GbmParams {
+      drift,
+      volatility,
+      dt,
+      steps,
+    } = params;
+    let valid = drift.is_finite()
+      && volatility.is_finite()
+      && volatility >= 0.0
+      && dt.is_finite()
+      && dt > 0.0;
+    if !valid {
+      return Err(PathParameters);
+    }
+    let mut rng = SplitMix64::new(seed);
+    let drift_term = (drift - volatility * volatility / 2.0) * dt;
+    let diffusion = volatility * dt.sqrt();
+    let mut prices = Vec::with_capacity(steps + 1);
+    prices.push(start);
+    (0..steps).try_fold(start, |price, _| {
+      let factor = (drift_term + diffusion * rng.next_normal()).exp();
+      let next = scale_price(price, factor)?;
+      prices.push(next);
+      Ok::<_, StressError>(next)
+    })?;
+    PricePath::replay(prices)
+  }
+
+  /// Spot prices in step order.
+  #[must_use]
+  pub fn prices(&self) -> &[UFix64<N9>] {
+    &self.prices
+  }
+
+  /// Number of steps in the path.
+  #[must_use]
+  pub fn len(&self) -> usize {
+    self.prices.len()
+  }
+
+  /// Always false; paths are non-empty by construction.
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.prices.is_empty()
+  }
+}
+
+/// Multiplies a price by a float factor through an `N9` multiplier.
+///
+/// # Errors
+/// * Factor is negative, non-finite, or overflows `N9`
+fn scale_price(
+  price: UFix64<N9>,
+  factor: f64,
+) -> Result<UFix64<N9>, StressError> {
+  let scaled = (factor * 1e9).round();
+  // Range checked above the cast, so truncation and sign loss are moot
+  #[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+  )]
+  let multiplier = (scaled.is_finite()
+    && (0.0..u64::MAX as f64).contains(&scaled))
+  .then(|| UFix64::<N9>::new(scaled as u64))
+  .ok_or(PriceShock)?;
+  price
+    .mul_div_floor(multiplier, UFix64::one())
+    .ok_or(PriceShock)
+}
+
+const TWO_POW_53: f64 = 9_007_199_254_740_992.0;
+
+/// Small deterministic PRNG (`SplitMix64`) so paths are reproducible from a
+/// seed without an external randomness dependency.
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+  state: u64,
+}
+
+impl SplitMix64 {
+  #[must_use]
+  pub fn new(seed: u64) -> SplitMix64 {
+    SplitMix64 { state: seed }
+  }
+
+  /// Next raw 64-bit output.
+  pub fn next_u64(&mut self) -> u64 {
+    self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = self.state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+  }
+
+  /// Uniform sample in the open interval `(0, 1)`.
+  pub fn next_open_unit(&mut self) -> f64 {
+    // 53-bit mantissa holds the shifted value exactly
+    #[allow(clippy::cast_precision_loss)]
+    let bits = (self.next_u64() >> 11) as f64;
+    (bits + 0.5) / TWO_POW_53
+  }
+
+  /// Standard normal sample via Box-Muller.
+  pub fn next_normal(&mut self) -> f64 {
+    let u1 = self.next_open_unit();
+    let u2 = self.next_open_unit();
+    (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use proptest::prelude::*;
+
+  use super::*;
+
+  const START: UFix64<N9> = UFix64::constant(150_000_000_000);
+
+  const PARAMS: GbmParams = GbmParams {
+    drift: 0.0,
+    volatility: 0.8,
+    dt: 1.0 / 365.0,
+    steps: 90,
+  };
+
+  #[test]
+  fn shocks_scale_start() -> Result<(), StressError> {
+    let path = PricePath::shocks(
+      START,
+      &[
+        UFix64::constant(700_000_000),
+        UFix64::constant(1_100_000_000),
+      ],
+    )?;
+    assert_eq!(
+      path.prices(),
+      &[UFix64::new(105_000_000_000), UFix64::new(165_000_000_000)]
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn empty_replay_rejected() {
+    assert_eq!(PricePath::replay(vec![]), Err(PathParameters));
+  }
+
+  #[test]
+  fn gbm_rejects_bad_params() {
+    let bad = GbmParams {
+      volatility: f64::NAN,
+      ..PARAMS
+    };
+    assert_eq!(PricePath::gbm(START, bad, 7), Err(PathParameters));
+  }
+
+  #[test]
+  fn gbm_zero_volatility_is_flat() -> Result<(), StressError> {
+    let flat = GbmParams {
+      volatility: 0.0,
+      ..PARAMS
+    };
+    let path = PricePath::gbm(START, flat, 7)?;
+    assert!(path.prices().iter().all(|price| *price == START));
+    Ok(())
+  }
+
+  proptest! {
+    #[test]
+    fn gbm_seed_reproducible(seed in any::<u64>()) {
+      let a = PricePath::gbm(START, PARAMS, seed)?;
+      let b = PricePath::gbm(START, PARAMS, seed)?;
+      prop_assert_eq!(a.len(), PARAMS.steps + 1);
+      prop_assert_eq!(a.prices()[0], START);
+      prop_assert_eq!(a, b);
+    }
+
+    #[test]
+    fn open_unit_in_range(seed in any::<u64>()) {
+      let mut rng = SplitMix64::new(seed);
+      let u = rng.next_open_unit();
+      prop_assert!(u > 0.0 && u < 1.0);
+    }
+  }
+}
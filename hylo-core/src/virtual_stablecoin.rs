@@ -210,3 +210,32 @@ mod tests {
     Ok(())
   }
 }
+
+#[cfg(kani)]
+mod proofs {
+  use fix::prelude::*;
+
+  use crate::kani_generators::any_ufix64;
+  use crate::virtual_stablecoin::VirtualStablecoin;
+
+  /// `burn_limited` either lands at `supply - amount >= limit` or leaves
+  /// the counter untouched; it never underflows.
+  #[kani::proof]
+  fn burn_limited_never_underflows() {
+    let supply: UFix64<N6> = any_ufix64();
+    let amount: UFix64<N6> = any_ufix64();
+    let limit: UFix64<N6> = any_ufix64();
+    let mut stablecoin = VirtualStablecoin {
+      supply: supply.into(),
+    };
+    let burned = stablecoin.burn_limited(amount, limit);
+    let after = stablecoin.supply();
+    if burned.is_ok() {
+      assert!(after.is_ok_and(|s| {
+        s >= limit && s.checked_add(&amount) == Some(supply)
+      }));
+    } else {
+      assert!(after.is_ok_and(|s| s == supply));
+    }
+  }
+}
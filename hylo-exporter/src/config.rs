@@ -0,0 +1,94 @@
+//! Exporter settings, loaded from an optional JSON file. Omitted fields
+//! take their defaults; `RPC_URL` overrides the file's endpoint.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Levels at which `hylo_alert` series fire.
+///
+/// * `min_collateral_ratio` — pair CR floor, default the bottom of
+///   `Neutral`
+/// * `max_oracle_age_secs` — oldest tolerated price feed publish time
+/// * `max_harvest_lag_epochs` — epochs a harvest stream may trail the
+///   current epoch
+/// * `min_market_cap_headroom_usd` — exo levercoin room under its market
+///   cap limit
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertThresholds {
+  pub min_collateral_ratio: f64,
+  pub max_oracle_age_secs: i64,
+  pub max_harvest_lag_epochs: u64,
+  pub min_market_cap_headroom_usd: f64,
+}
+
+impl Default for AlertThresholds {
+  fn default() -> AlertThresholds {
+    AlertThresholds {
+      min_collateral_ratio: 1.35,
+      max_oracle_age_secs: 120,
+      max_harvest_lag_epochs: 1,
+      min_market_cap_headroom_usd: 10_000.0,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExporterConfig {
+  pub rpc_url: String,
+  pub listen_addr: SocketAddr,
+  pub poll_interval_secs: u64,
+  pub thresholds: AlertThresholds,
+}
+
+impl Default for ExporterConfig {
+  fn default() -> ExporterConfig {
+    ExporterConfig {
+      rpc_url: "https://api.mainnet-beta.solana.com".to_string(),
+      listen_addr: SocketAddr::from((Ipv4Addr::UNSPECIFIED, 9464)),
+      poll_interval_secs: 30,
+      thresholds: AlertThresholds::default(),
+    }
+  }
+}
+
+impl ExporterConfig {
+  /// Reads `path` if given, otherwise defaults, then applies `RPC_URL`.
+  ///
+  /// # Errors
+  /// * File read or JSON decode failure
+  pub fn load(path: Option<&Path>) -> Result<ExporterConfig> {
+    let config = match path {
+      Some(path) => serde_json::from_reader(BufReader::new(File::open(path)?))?,
+      None => ExporterConfig::default(),
+    };
+    Ok(match std::env::var("RPC_URL") {
+      Ok(rpc_url) => ExporterConfig { rpc_url, ..config },
+      Err(_) => config,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn partial_config_keeps_defaults() -> Result<()> {
+    let config: ExporterConfig = serde_json::from_str(
+      r#"{ "poll_interval_secs": 10,
+           "thresholds": { "max_oracle_age_secs": 60 } }"#,
+    )?;
+    assert_eq!(config.poll_interval_secs, 10);
+    assert_eq!(config.thresholds.max_oracle_age_secs, 60);
+    assert_eq!(config.thresholds.max_harvest_lag_epochs, 1);
+    assert_eq!(config.listen_addr, ExporterConfig::default().listen_addr);
+    Ok(())
+  }
+}
@@ -0,0 +1,236 @@
+//! Health snapshot the exporter renders. Values are plain numbers so a
+//! snapshot round-trips through JSON for offline tests.
+
+use anchor_lang::prelude::Pubkey;
+use fix::prelude::UFix64;
+use hylo_core::exchange_context::ExchangeContext;
+use hylo_core::limiter::deposit::DepositLimiter;
+use hylo_core::limiter::withdraw::WithdrawalLimiter;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_idl::tokens::{TokenMint, CBBTC, HYPE, ONYC, PST, WETH, ZEC};
+use hylo_idl::with_exo_pairs;
+use hylo_quotes::protocol_state::{EarnPoolState, ExoPairState, ProtocolState};
+use hylo_stats::levercoin_stats::market_cap_headroom;
+use hylo_stats::types::{EarnPoolStats, RealizedHarvest};
+use serde::{Deserialize, Serialize};
+
+/// Pair label for the LST collateral pair.
+pub const LST_PAIR: &str = "LST";
+
+/// One collateral pair's state.
+///
+/// * `rebalance_mode` — `RebalanceMode` display name
+/// * `stablecoin_nav`, `levercoin_nav` — `None` where the pair's state
+///   leaves them undefined, so one pair cannot fail the poll
+/// * `oracle_publish_time` — collateral/USD feed publish time
+/// * `market_cap_headroom_usd` — room under the levercoin market cap
+///   limit, `None` for the LST pair or without a levercoin NAV
+/// * `stablecoin_mint_headroom` — hyUSD mintable before the pair's CR
+///   reaches its mint threshold, zero under it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PairHealth {
+  pub pair: String,
+  pub collateral_ratio: f64,
+  pub rebalance_mode: String,
+  pub stablecoin_nav: Option<f64>,
+  pub levercoin_nav: Option<f64>,
+  pub paused: bool,
+  pub oracle_publish_time: i64,
+  pub market_cap_headroom_usd: Option<f64>,
+  pub stablecoin_mint_headroom: Option<f64>,
+}
+
+impl PairHealth {
+  fn from_exo<C: SolanaClock>(
+    pair: &str,
+    state: &ExoPairState<C>,
+  ) -> PairHealth {
+    let ctx = &state.context;
+    let headroom = ctx
+      .levercoin_market_cap_limiter()
+      .ok()
+      .zip(ctx.levercoin_market_cap().ok())
+      .and_then(|(limiter, market_cap)| {
+        market_cap_headroom(&limiter, market_cap).ok()
+      });
+    PairHealth {
+      pair: pair.to_string(),
+      collateral_ratio: ctx.collateral_ratio().to_f64(),
+      rebalance_mode: ctx.rebalance_mode().to_string(),
+      stablecoin_nav: ctx.stablecoin_nav().ok().map(UFix64::to_f64),
+      levercoin_nav: ctx.levercoin_mint_nav().ok().map(UFix64::to_f64),
+      paused: state.paused,
+      oracle_publish_time: state.oracle_publish_time,
+      market_cap_headroom_usd: headroom
+        .map(|headroom| headroom.remaining_usd.to_f64()),
+      stablecoin_mint_headroom: stablecoin_mint_headroom(ctx),
+    }
+  }
+}
+
+/// hyUSD mintable before `ctx`'s collateral ratio reaches its mint
+/// threshold, zero once under it.
+fn stablecoin_mint_headroom<X: ExchangeContext>(ctx: &X) -> Option<f64> {
+  let headroom = if ctx.collateral_ratio() > ctx.stablecoin_mint_threshold() {
+    ctx.max_mintable_stablecoin().ok()
+  } else {
+    Some(UFix64::zero())
+  };
+  headroom.map(UFix64::to_f64)
+}
+
+/// One harvest stream's last harvested epoch, labeled `LST` or by exo
+/// collateral.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HarvestHealth {
+  pub stream: String,
+  pub epoch: u64,
+  pub is_stale: bool,
+}
+
+impl HarvestHealth {
+  fn new(stream: String, harvest: &RealizedHarvest) -> HarvestHealth {
+    HarvestHealth {
+      stream,
+      epoch: harvest.epoch,
+      is_stale: harvest.is_stale,
+    }
+  }
+}
+
+/// sHYUSD NAV, pool balance and APYs from [`EarnPoolStats`], and hyUSD
+/// headroom under the pool's limiters.
+///
+/// * `deposit_headroom` — hyUSD the pool takes before its deposit limit
+/// * `withdrawal_headroom` — hyUSD left in this epoch's withdrawal window
+///
+/// Headroom is `None` when the state leaves the earn pool out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EarnPoolHealth {
+  pub nav: f64,
+  pub pool_balance: f64,
+  pub outstanding_drawdown: f64,
+  pub naive_apy: f64,
+  pub projected_apy: f64,
+  pub deposit_headroom: Option<f64>,
+  pub withdrawal_headroom: Option<f64>,
+}
+
+impl EarnPoolHealth {
+  fn new(
+    pool: &EarnPoolStats,
+    state: Option<&EarnPoolState>,
+  ) -> EarnPoolHealth {
+    let deposit_headroom = state.and_then(|state| {
+      DepositLimiter::from(state.pool_config.deposit_limiter)
+        .max_deposit(UFix64::new(state.hyusd_pool.amount))
+        .ok()
+    });
+    let withdrawal_headroom = state.and_then(|state| {
+      WithdrawalLimiter::from(state.pool_config.withdrawal_limiter)
+        .max_withdrawal(pool.current_epoch)
+        .ok()
+    });
+    EarnPoolHealth {
+      nav: pool.nav.to_f64(),
+      pool_balance: pool.pool_balance.to_f64(),
+      outstanding_drawdown: pool.outstanding_drawdown.to_f64(),
+      naive_apy: pool.naive_apy,
+      projected_apy: pool.projected_apy,
+      deposit_headroom: deposit_headroom.map(UFix64::to_f64),
+      withdrawal_headroom: withdrawal_headroom.map(UFix64::to_f64),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsdcHealth {
+  pub paused: bool,
+  pub usdc_usd_spot: f64,
+  pub oracle_publish_time: i64,
+}
+
+/// Everything the exporter serves from one poll.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthSnapshot {
+  pub epoch: u64,
+  pub unix_timestamp: i64,
+  pub protocol_paused: bool,
+  pub earn_pool: EarnPoolHealth,
+  pub pairs: Vec<PairHealth>,
+  pub harvests: Vec<HarvestHealth>,
+  pub usdc: Option<UsdcHealth>,
+}
+
+impl HealthSnapshot {
+  /// Combines protocol state with earn pool stats. Exo pairs not loaded
+  /// in `state` are left out.
+  #[must_use]
+  pub fn collect<C: SolanaClock>(
+    state: &ProtocolState<C>,
+    pool: &EarnPoolStats,
+  ) -> HealthSnapshot {
+    let ctx = &state.exchange_context;
+    let lst = PairHealth {
+      pair: LST_PAIR.to_string(),
+      collateral_ratio: ctx.collateral_ratio().to_f64(),
+      rebalance_mode: ctx.rebalance_mode().to_string(),
+      stablecoin_nav: ctx.stablecoin_nav().ok().map(UFix64::to_f64),
+      levercoin_nav: ctx.levercoin_mint_nav().ok().map(UFix64::to_f64),
+      paused: state.lst_pair_paused,
+      oracle_publish_time: state.sol_oracle_publish_time,
+      market_cap_headroom_usd: None,
+      stablecoin_mint_headroom: stablecoin_mint_headroom(ctx),
+    };
+    let mut pairs = vec![lst];
+    pairs.extend(exo_pair_health(state));
+    let harvests = std::iter::once(HarvestHealth::new(
+      LST_PAIR.to_string(),
+      &pool.lst_harvest,
+    ))
+    .chain(pool.exo_stats.iter().map(|exo| {
+      HarvestHealth::new(exo_label(&exo.collateral_mint), &exo.harvest)
+    }))
+    .collect();
+    HealthSnapshot {
+      epoch: pool.current_epoch,
+      unix_timestamp: ctx.clock.unix_timestamp(),
+      protocol_paused: state.protocol_paused,
+      earn_pool: EarnPoolHealth::new(pool, state.earn_pool().ok()),
+      pairs,
+      harvests,
+      usdc: state.usdc_exchange_state.as_ref().map(|usdc| UsdcHealth {
+        paused: usdc.paused,
+        usdc_usd_spot: usdc.usdc_usd_spot.to_f64(),
+        oracle_publish_time: usdc.oracle_publish_time,
+      }),
+    }
+  }
+}
+
+macro_rules! exo_pair_labels {
+  ($(($exo:ident, $lever:ident, $exp:ty)),* $(,)?) => {
+    /// Health of every exo pair loaded in `state`.
+    fn exo_pair_health<C: SolanaClock>(
+      state: &ProtocolState<C>,
+    ) -> Vec<PairHealth> {
+      let mut pairs = Vec::new();
+      $(
+        if let Ok(pair) = state.exo_pair::<$exo>() {
+          pairs.push(PairHealth::from_exo(stringify!($exo), pair));
+        }
+      )*
+      pairs
+    }
+
+    /// Label for an exo collateral mint, base58 if unregistered.
+    fn exo_label(collateral_mint: &Pubkey) -> String {
+      match *collateral_mint {
+        $($exo::MINT => stringify!($exo).to_string(),)*
+        mint => mint.to_string(),
+      }
+    }
+  };
+}
+
+with_exo_pairs!(exo_pair_labels);
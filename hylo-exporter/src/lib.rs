@@ -0,0 +1,46 @@
+//! # Hylo Exporter
+//!
+//! Polls protocol state and earn pool statistics and serves them as
+//! Prometheus gauges.
+//!
+//! - [`config`] — Exporter settings and alert thresholds
+//! - [`health`] — Plain-number health snapshot built from protocol state and
+//!   earn pool stats
+//! - [`metrics`] — Prometheus text rendering and threshold alerts
+//! - [`server`] — Minimal HTTP listener serving `/metrics`
+//! - [`source`] — Health sources: live RPC or a stored JSON snapshot
+
+pub mod config;
+pub mod health;
+pub mod metrics;
+pub mod server;
+pub mod source;
+
+use anyhow::Result;
+use tokio::sync::RwLock;
+
+use crate::config::AlertThresholds;
+use crate::metrics::{render, render_down};
+use crate::source::HealthSource;
+
+/// Polls `source` once and replaces the served exposition. A failed poll
+/// clears every gauge but `hylo_up` rather than serving stale values.
+///
+/// # Errors
+/// * Propagates the source failure after marking the exporter down
+pub async fn refresh(
+  source: &impl HealthSource,
+  thresholds: &AlertThresholds,
+  metrics: &RwLock<String>,
+) -> Result<()> {
+  match source.health().await {
+    Ok(health) => {
+      *metrics.write().await = render(&health, thresholds);
+      Ok(())
+    }
+    Err(err) => {
+      *metrics.write().await = render_down();
+      Err(err)
+    }
+  }
+}
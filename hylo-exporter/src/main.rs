@@ -0,0 +1,47 @@
+//! Serves Hylo health gauges for Prometheus.
+//!
+//! ```txt
+//! hylo-exporter [config.json]
+//! ```
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anyhow::Result;
+use hylo_exporter::config::ExporterConfig;
+use hylo_exporter::metrics::render_down;
+use hylo_exporter::refresh;
+use hylo_exporter::server::serve;
+use hylo_exporter::source::RpcHealthSource;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use tokio::net::TcpListener;
+use tokio::sync::RwLock;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+  env_logger::Builder::from_env(
+    env_logger::Env::default().default_filter_or("info"),
+  )
+  .init();
+  let path = std::env::args().nth(1);
+  let config = ExporterConfig::load(path.as_deref().map(Path::new))?;
+  let rpc = Arc::new(RpcClient::new_with_commitment(
+    config.rpc_url.clone(),
+    CommitmentConfig::confirmed(),
+  ));
+  let source = RpcHealthSource::new(rpc);
+  let metrics = Arc::new(RwLock::new(render_down()));
+  let listener = TcpListener::bind(config.listen_addr).await?;
+  let server = tokio::spawn(serve(listener, metrics.clone()));
+  let mut interval =
+    tokio::time::interval(Duration::from_secs(config.poll_interval_secs));
+  while !server.is_finished() {
+    interval.tick().await;
+    if let Err(err) = refresh(&source, &config.thresholds, &metrics).await {
+      log::warn!("poll failed: {err:#}");
+    }
+  }
+  server.await?
+}
@@ -0,0 +1,367 @@
+//! Prometheus text exposition of a [`HealthSnapshot`].
+//!
+//! Every series is a gauge. Rebalance mode is a state set, one series
+//! per mode with the active one at `1`. `hylo_alert` series are `1` when
+//! their [`AlertThresholds`] level is crossed, `0` otherwise.
+
+use std::fmt::Write;
+
+use hylo_core::rebalance::mode::RebalanceMode;
+
+use crate::config::AlertThresholds;
+use crate::health::HealthSnapshot;
+
+/// Every [`RebalanceMode`] in ascending CR order.
+const REBALANCE_MODES: [RebalanceMode; 6] = [
+  RebalanceMode::Depeg,
+  RebalanceMode::SellZone2,
+  RebalanceMode::SellZone1,
+  RebalanceMode::Neutral,
+  RebalanceMode::BuyZone1,
+  RebalanceMode::BuyZone2,
+];
+
+/// Feed label for the USDC/USD oracle.
+const USDC_FEED: &str = "USDC";
+
+/// Text exposition buffer, one gauge family at a time.
+#[derive(Default)]
+struct Exposition {
+  out: String,
+}
+
+impl Exposition {
+  fn gauge(&mut self, name: &str, help: &str) -> &mut Exposition {
+    let _ = writeln!(self.out, "# HELP {name} {help}");
+    let _ = writeln!(self.out, "# TYPE {name} gauge");
+    self
+  }
+
+  fn sample(
+    &mut self,
+    name: &str,
+    labels: &[(&str, &str)],
+    value: f64,
+  ) -> &mut Exposition {
+    let _ = write!(self.out, "{name}");
+    if !labels.is_empty() {
+      let labels = labels
+        .iter()
+        .map(|(key, value)| format!("{key}=\"{}\"", escape(value)))
+        .collect::<Vec<_>>()
+        .join(",");
+      let _ = write!(self.out, "{{{labels}}}");
+    }
+    let _ = writeln!(self.out, " {}", format_value(value));
+    self
+  }
+}
+
+/// Escapes a label value per the exposition format.
+fn escape(value: &str) -> String {
+  value
+    .replace('\\', "\\\\")
+    .replace('"', "\\\"")
+    .replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+  if value.is_nan() {
+    "NaN".to_string()
+  } else if value.is_infinite() && value.is_sign_positive() {
+    "+Inf".to_string()
+  } else if value.is_infinite() {
+    "-Inf".to_string()
+  } else {
+    value.to_string()
+  }
+}
+
+fn flag(value: bool) -> f64 {
+  f64::from(u8::from(value))
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn count(value: u64) -> f64 {
+  value as f64
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn seconds(value: i64) -> f64 {
+  value as f64
+}
+
+/// Renders every gauge for `health`, with `hylo_up` at `1`.
+#[must_use]
+pub fn render(health: &HealthSnapshot, thresholds: &AlertThresholds) -> String {
+  let mut exp = Exposition::default();
+  exp
+    .gauge("hylo_up", "Whether the last poll succeeded.")
+    .sample("hylo_up", &[], 1.0);
+  exp.gauge("hylo_epoch", "Current Solana epoch.").sample(
+    "hylo_epoch",
+    &[],
+    count(health.epoch),
+  );
+  exp
+    .gauge("hylo_protocol_paused", "Protocol-wide pause flag.")
+    .sample("hylo_protocol_paused", &[], flag(health.protocol_paused));
+  pair_gauges(&mut exp, health);
+  let feeds = oracle_ages(health);
+  oracle_gauges(&mut exp, health, &feeds);
+  earn_pool_gauges(&mut exp, health);
+  alert_gauges(&mut exp, health, thresholds, &feeds);
+  exp.out
+}
+
+/// Per-pair pause, CR, mode, NAV and limiter headroom gauges. NAVs a pair left
+/// undefined are omitted.
+fn pair_gauges(exp: &mut Exposition, health: &HealthSnapshot) {
+  exp.gauge("hylo_pair_paused", "Pair pause flag.");
+  for pair in &health.pairs {
+    exp.sample(
+      "hylo_pair_paused",
+      &[("pair", &pair.pair)],
+      flag(pair.paused),
+    );
+  }
+  exp.gauge("hylo_collateral_ratio", "Pair collateral ratio.");
+  for pair in &health.pairs {
+    exp.sample(
+      "hylo_collateral_ratio",
+      &[("pair", &pair.pair)],
+      pair.collateral_ratio,
+    );
+  }
+  exp.gauge("hylo_rebalance_mode", "Active rebalance mode, one-hot.");
+  for pair in &health.pairs {
+    for mode in REBALANCE_MODES {
+      let mode = mode.to_string();
+      exp.sample(
+        "hylo_rebalance_mode",
+        &[("pair", &pair.pair), ("mode", &mode)],
+        flag(pair.rebalance_mode == mode),
+      );
+    }
+  }
+  exp.gauge("hylo_stablecoin_nav", "hyUSD NAV in USD per pair.");
+  for pair in &health.pairs {
+    if let Some(nav) = pair.stablecoin_nav {
+      exp.sample("hylo_stablecoin_nav", &[("pair", &pair.pair)], nav);
+    }
+  }
+  exp.gauge("hylo_levercoin_nav", "Levercoin mint NAV in USD.");
+  for pair in &health.pairs {
+    if let Some(nav) = pair.levercoin_nav {
+      exp.sample("hylo_levercoin_nav", &[("pair", &pair.pair)], nav);
+    }
+  }
+  exp.gauge(
+    "hylo_levercoin_market_cap_headroom_usd",
+    "USD left under the exo levercoin market cap limit.",
+  );
+  for pair in &health.pairs {
+    if let Some(headroom) = pair.market_cap_headroom_usd {
+      exp.sample(
+        "hylo_levercoin_market_cap_headroom_usd",
+        &[("pair", &pair.pair)],
+        headroom,
+      );
+    }
+  }
+  exp.gauge(
+    "hylo_stablecoin_mint_headroom",
+    "hyUSD mintable before the pair's CR reaches its mint threshold.",
+  );
+  for pair in &health.pairs {
+    if let Some(headroom) = pair.stablecoin_mint_headroom {
+      exp.sample(
+        "hylo_stablecoin_mint_headroom",
+        &[("pair", &pair.pair)],
+        headroom,
+      );
+    }
+  }
+}
+
+/// Seconds since publish for every pair feed, then USDC/USD.
+fn oracle_ages(health: &HealthSnapshot) -> Vec<(&str, i64)> {
+  health
+    .pairs
+    .iter()
+    .map(|pair| (pair.pair.as_str(), pair.oracle_publish_time))
+    .chain(
+      health
+        .usdc
+        .iter()
+        .map(|usdc| (USDC_FEED, usdc.oracle_publish_time)),
+    )
+    .map(|(feed, publish_time)| (feed, health.unix_timestamp - publish_time))
+    .collect()
+}
+
+/// Oracle age and USDC pair gauges.
+fn oracle_gauges(
+  exp: &mut Exposition,
+  health: &HealthSnapshot,
+  feeds: &[(&str, i64)],
+) {
+  exp.gauge(
+    "hylo_oracle_age_seconds",
+    "Seconds since feed publish time.",
+  );
+  for (feed, age) in feeds {
+    exp.sample("hylo_oracle_age_seconds", &[("feed", feed)], seconds(*age));
+  }
+
+  if let Some(usdc) = &health.usdc {
+    exp
+      .gauge("hylo_usdc_paused", "USDC pair pause flag.")
+      .sample("hylo_usdc_paused", &[], flag(usdc.paused));
+    exp
+      .gauge("hylo_usdc_usd_spot", "USDC/USD oracle spot price.")
+      .sample("hylo_usdc_usd_spot", &[], usdc.usdc_usd_spot);
+  }
+}
+
+/// sHYUSD, earn pool balance, limiter and harvest gauges.
+fn earn_pool_gauges(exp: &mut Exposition, health: &HealthSnapshot) {
+  let pool = &health.earn_pool;
+  exp.gauge("hylo_shyusd_nav", "hyUSD per sHYUSD.").sample(
+    "hylo_shyusd_nav",
+    &[],
+    pool.nav,
+  );
+  exp
+    .gauge("hylo_shyusd_apy", "sHYUSD APY by estimation basis.")
+    .sample("hylo_shyusd_apy", &[("basis", "naive")], pool.naive_apy)
+    .sample(
+      "hylo_shyusd_apy",
+      &[("basis", "projected")],
+      pool.projected_apy,
+    );
+  exp
+    .gauge("hylo_earn_pool_balance", "hyUSD held by the earn pool.")
+    .sample("hylo_earn_pool_balance", &[], pool.pool_balance);
+  exp
+    .gauge(
+      "hylo_outstanding_drawdown",
+      "Earn pool hyUSD owed back by rebalance drawdowns.",
+    )
+    .sample("hylo_outstanding_drawdown", &[], pool.outstanding_drawdown);
+  exp.gauge(
+    "hylo_earn_pool_limiter_headroom",
+    "hyUSD left under the earn pool deposit and withdrawal limiters.",
+  );
+  for (limiter, headroom) in [
+    ("deposit", pool.deposit_headroom),
+    ("withdrawal", pool.withdrawal_headroom),
+  ] {
+    if let Some(headroom) = headroom {
+      exp.sample(
+        "hylo_earn_pool_limiter_headroom",
+        &[("limiter", limiter)],
+        headroom,
+      );
+    }
+  }
+
+  exp.gauge(
+    "hylo_harvest_stale",
+    "No harvest yet for the current epoch.",
+  );
+  for harvest in &health.harvests {
+    exp.sample(
+      "hylo_harvest_stale",
+      &[("stream", &harvest.stream)],
+      flag(harvest.is_stale),
+    );
+  }
+  exp.gauge("hylo_harvest_lag_epochs", "Epochs since the last harvest.");
+  for harvest in &health.harvests {
+    exp.sample(
+      "hylo_harvest_lag_epochs",
+      &[("stream", &harvest.stream)],
+      count(health.epoch.saturating_sub(harvest.epoch)),
+    );
+  }
+}
+
+/// One `hylo_alert` series per threshold and subject.
+fn alert_gauges(
+  exp: &mut Exposition,
+  health: &HealthSnapshot,
+  thresholds: &AlertThresholds,
+  feeds: &[(&str, i64)],
+) {
+  exp.gauge("hylo_alert", "Alert threshold crossed.");
+  for pair in &health.pairs {
+    exp.sample(
+      "hylo_alert",
+      &[("alert", "low_collateral_ratio"), ("subject", &pair.pair)],
+      flag(pair.collateral_ratio < thresholds.min_collateral_ratio),
+    );
+    if let Some(headroom) = pair.market_cap_headroom_usd {
+      exp.sample(
+        "hylo_alert",
+        &[
+          ("alert", "low_market_cap_headroom"),
+          ("subject", &pair.pair),
+        ],
+        flag(headroom < thresholds.min_market_cap_headroom_usd),
+      );
+    }
+  }
+  for (feed, age) in feeds {
+    exp.sample(
+      "hylo_alert",
+      &[("alert", "stale_oracle"), ("subject", feed)],
+      flag(*age > thresholds.max_oracle_age_secs),
+    );
+  }
+  for harvest in &health.harvests {
+    exp.sample(
+      "hylo_alert",
+      &[("alert", "harvest_lag"), ("subject", &harvest.stream)],
+      flag(
+        health.epoch.saturating_sub(harvest.epoch)
+          > thresholds.max_harvest_lag_epochs,
+      ),
+    );
+  }
+}
+
+/// Exposition for a failed poll: `hylo_up` at `0` and nothing else.
+#[must_use]
+pub fn render_down() -> String {
+  let mut exp = Exposition::default();
+  exp
+    .gauge("hylo_up", "Whether the last poll succeeded.")
+    .sample("hylo_up", &[], 0.0);
+  exp.out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn samples_escape_labels_and_format_values() {
+    let mut exp = Exposition::default();
+    exp
+      .sample("m", &[("a", "x\"y")], 1.5)
+      .sample("m", &[], f64::INFINITY)
+      .sample("m", &[("a", "1"), ("b", "2")], 0.0);
+    assert_eq!(
+      exp.out,
+      "m{a=\"x\\\"y\"} 1.5\nm +Inf\nm{a=\"1\",b=\"2\"} 0\n"
+    );
+  }
+
+  #[test]
+  fn down_exposes_only_up() {
+    let down = render_down();
+    assert!(down.contains("hylo_up 0\n"));
+    assert_eq!(down.lines().filter(|l| !l.starts_with('#')).count(), 1);
+  }
+}
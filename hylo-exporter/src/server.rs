@@ -0,0 +1,139 @@
+//! Minimal HTTP/1.1 listener: `GET /metrics` returns the current
+//! exposition, anything else 404. One request per connection; the request
+//! head is read up to [`MAX_HEAD_BYTES`] and the connection is dropped after
+//! [`REQUEST_TIMEOUT`].
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::RwLock;
+
+/// Prometheus text format content type.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Cap on the request head read before answering.
+const MAX_HEAD_BYTES: usize = 8 * 1024;
+
+/// Time a connection has to send its request and take the response.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Accepts connections until the listener fails.
+///
+/// # Errors
+/// * Listener accept failure
+pub async fn serve(
+  listener: TcpListener,
+  metrics: Arc<RwLock<String>>,
+) -> Result<()> {
+  loop {
+    let (stream, _) = listener.accept().await?;
+    let metrics = metrics.clone();
+    tokio::spawn(async move {
+      match tokio::time::timeout(REQUEST_TIMEOUT, respond(stream, &metrics))
+        .await
+      {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => log::warn!("metrics request failed: {err:#}"),
+        Err(_) => log::warn!("metrics request timed out"),
+      }
+    });
+  }
+}
+
+/// Reads until the end of the request head, EOF or [`MAX_HEAD_BYTES`].
+async fn read_head<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Vec<u8>> {
+  let mut head = Vec::with_capacity(1024);
+  let mut buf = [0u8; 1024];
+  while head.len() < MAX_HEAD_BYTES
+    && !head.windows(4).any(|window| window == b"\r\n\r\n")
+  {
+    let read = stream.read(&mut buf).await?;
+    if read == 0 {
+      break;
+    }
+    head.extend_from_slice(&buf[..read]);
+  }
+  Ok(head)
+}
+
+async fn respond<S: AsyncRead + AsyncWrite + Unpin>(
+  mut stream: S,
+  metrics: &RwLock<String>,
+) -> Result<()> {
+  let head = read_head(&mut stream).await?;
+  let request = String::from_utf8_lossy(&head);
+  let mut request_line = request.split_whitespace();
+  let (status, body) = match (request_line.next(), request_line.next()) {
+    (Some("GET"), Some(target))
+      if target.split('?').next() == Some("/metrics") =>
+    {
+      ("200 OK", metrics.read().await.clone())
+    }
+    _ => ("404 Not Found", String::new()),
+  };
+  let response = format!(
+    "HTTP/1.1 {status}\r\nContent-Type: {CONTENT_TYPE}\r\nContent-Length: \
+     {}\r\nConnection: close\r\n\r\n{body}",
+    body.len()
+  );
+  stream.write_all(response.as_bytes()).await?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt};
+  use tokio::sync::RwLock;
+
+  use super::respond;
+
+  #[tokio::test]
+  async fn request_head_split_across_reads() -> anyhow::Result<()> {
+    let metrics = RwLock::new("hylo_up 1\n".to_string());
+    let (mut client, server) = duplex(64);
+    let handler = tokio::spawn(async move { respond(server, &metrics).await });
+    client.write_all(b"GET /met").await?;
+    tokio::task::yield_now().await;
+    client
+      .write_all(b"rics HTTP/1.1\r\nHost: x\r\n\r\n")
+      .await?;
+    let mut response = String::new();
+    client.read_to_string(&mut response).await?;
+    handler.await??;
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with("hylo_up 1\n"));
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn query_string_is_ignored() -> anyhow::Result<()> {
+    let metrics = RwLock::new("hylo_up 1\n".to_string());
+    let (mut client, server) = duplex(64);
+    let handler = tokio::spawn(async move { respond(server, &metrics).await });
+    client
+      .write_all(b"GET /metrics?name[]=hylo_up HTTP/1.1\r\n\r\n")
+      .await?;
+    let mut response = String::new();
+    client.read_to_string(&mut response).await?;
+    handler.await??;
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with("hylo_up 1\n"));
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn unknown_path_is_not_found() -> anyhow::Result<()> {
+    let metrics = RwLock::new(String::new());
+    let (mut client, server) = duplex(64);
+    let handler = tokio::spawn(async move { respond(server, &metrics).await });
+    client.write_all(b"GET / HTTP/1.1\r\n\r\n").await?;
+    let mut response = String::new();
+    client.read_to_string(&mut response).await?;
+    handler.await??;
+    assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+    Ok(())
+  }
+}
@@ -0,0 +1,69 @@
+//! Where the exporter gets its [`HealthSnapshot`] each poll.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use hylo_quotes::protocol_state::{RpcStateProvider, StateProvider};
+use hylo_stats::client::StatsClient;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+
+use crate::health::HealthSnapshot;
+
+/// Produces one [`HealthSnapshot`] per poll.
+#[async_trait]
+pub trait HealthSource: Send + Sync {
+  /// # Errors
+  /// * Source fetch or decode failure
+  async fn health(&self) -> Result<HealthSnapshot>;
+}
+
+/// Live source: [`StatsClient::earn_pool_stats`] and
+/// [`RpcStateProvider`] against one RPC endpoint.
+pub struct RpcHealthSource {
+  stats: StatsClient,
+  state: RpcStateProvider,
+}
+
+impl RpcHealthSource {
+  #[must_use]
+  pub fn new(rpc: Arc<RpcClient>) -> RpcHealthSource {
+    RpcHealthSource {
+      stats: StatsClient::new(rpc.clone()),
+      state: RpcStateProvider::new(rpc),
+    }
+  }
+}
+
+#[async_trait]
+impl HealthSource for RpcHealthSource {
+  async fn health(&self) -> Result<HealthSnapshot> {
+    let (state, pool) =
+      tokio::try_join!(self.state.fetch_state(), self.stats.earn_pool_stats())?;
+    Ok(HealthSnapshot::collect(&state, &pool))
+  }
+}
+
+/// Replays a [`HealthSnapshot`] stored as JSON, re-read on every poll.
+#[derive(Debug, Clone)]
+pub struct SnapshotSource {
+  path: PathBuf,
+}
+
+impl SnapshotSource {
+  #[must_use]
+  pub fn new(path: impl Into<PathBuf>) -> SnapshotSource {
+    SnapshotSource { path: path.into() }
+  }
+}
+
+#[async_trait]
+impl HealthSource for SnapshotSource {
+  async fn health(&self) -> Result<HealthSnapshot> {
+    let file = BufReader::new(File::open(&self.path)?);
+    Ok(serde_json::from_reader(file)?)
+  }
+}
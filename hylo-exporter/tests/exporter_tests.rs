@@ -0,0 +1,187 @@
+//! Offline exporter tests against a stored health snapshot.
+//!
+//! `tests/data/health-snapshot.json` has the cbBTC pair under the default
+//! CR floor and two epochs behind on harvest, and the HYPE pair paused
+//! with a ten minute old oracle and little market cap headroom.
+//!
+//! `HealthSnapshot::collect` is checked against the hylo-quotes protocol
+//! state snapshot.
+
+use std::fs::File;
+
+use anchor_lang::solana_program::clock::Clock;
+use anyhow::Result;
+use fix::prelude::*;
+use hylo_exporter::config::AlertThresholds;
+use hylo_exporter::health::{HealthSnapshot, LST_PAIR};
+use hylo_exporter::refresh;
+use hylo_exporter::source::{HealthSource, SnapshotSource};
+use hylo_quotes::protocol_state::{ProtocolAccounts, ProtocolState};
+use hylo_stats::epochs_per_year::EpochsPerYear;
+use hylo_stats::types::{ApyBand, EarnPoolStats, RealizedHarvest};
+use tokio::sync::RwLock;
+
+fn fixture() -> SnapshotSource {
+  SnapshotSource::new(format!(
+    "{}/tests/data/health-snapshot.json",
+    env!("CARGO_MANIFEST_DIR")
+  ))
+}
+
+async fn scrape(
+  source: &impl HealthSource,
+  thresholds: &AlertThresholds,
+) -> String {
+  let metrics = RwLock::new(String::new());
+  let _ = refresh(source, thresholds, &metrics).await;
+  metrics.into_inner()
+}
+
+/// Sample lines with value `1` whose name and labels start with `prefix`.
+fn firing<'a>(exposition: &'a str, prefix: &str) -> Vec<&'a str> {
+  exposition
+    .lines()
+    .filter(|line| line.starts_with(prefix) && line.ends_with(" 1"))
+    .collect()
+}
+
+#[tokio::test]
+async fn snapshot_renders_gauges() -> Result<()> {
+  let out = scrape(&fixture(), &AlertThresholds::default()).await;
+  for line in [
+    "hylo_up 1",
+    "hylo_epoch 850",
+    "hylo_collateral_ratio{pair=\"LST\"} 1.62",
+    "hylo_pair_paused{pair=\"HYPE\"} 1",
+    "hylo_levercoin_market_cap_headroom_usd{pair=\"CBBTC\"} 250000",
+    "hylo_stablecoin_mint_headroom{pair=\"LST\"} 8400000",
+    "hylo_earn_pool_limiter_headroom{limiter=\"deposit\"} 2500000",
+    "hylo_earn_pool_limiter_headroom{limiter=\"withdrawal\"} 480000",
+    "hylo_oracle_age_seconds{feed=\"HYPE\"} 600",
+    "hylo_oracle_age_seconds{feed=\"USDC\"} 10",
+    "hylo_shyusd_apy{basis=\"projected\"} 0.1187",
+    "hylo_harvest_stale{stream=\"LST\"} 0",
+    "hylo_harvest_lag_epochs{stream=\"CBBTC\"} 2",
+  ] {
+    assert!(out.lines().any(|l| l == line), "missing {line}");
+  }
+  // One active mode per pair
+  assert_eq!(
+    firing(&out, "hylo_rebalance_mode"),
+    [
+      "hylo_rebalance_mode{pair=\"LST\",mode=\"Neutral\"} 1",
+      "hylo_rebalance_mode{pair=\"CBBTC\",mode=\"SellZone1\"} 1",
+      "hylo_rebalance_mode{pair=\"HYPE\",mode=\"BuyZone2\"} 1",
+    ]
+  );
+  // The LST pair has no market cap limiter
+  assert!(!out.contains("headroom_usd{pair=\"LST\"}"));
+  Ok(())
+}
+
+#[tokio::test]
+async fn thresholds_drive_alerts() {
+  let out = scrape(&fixture(), &AlertThresholds::default()).await;
+  assert_eq!(
+    firing(&out, "hylo_alert"),
+    [
+      "hylo_alert{alert=\"low_collateral_ratio\",subject=\"CBBTC\"} 1",
+      "hylo_alert{alert=\"low_market_cap_headroom\",subject=\"HYPE\"} 1",
+      "hylo_alert{alert=\"stale_oracle\",subject=\"HYPE\"} 1",
+      "hylo_alert{alert=\"harvest_lag\",subject=\"CBBTC\"} 1",
+    ]
+  );
+
+  let relaxed = AlertThresholds {
+    min_collateral_ratio: 1.2,
+    max_oracle_age_secs: 900,
+    max_harvest_lag_epochs: 2,
+    min_market_cap_headroom_usd: 1_000.0,
+  };
+  let out = scrape(&fixture(), &relaxed).await;
+  assert!(firing(&out, "hylo_alert").is_empty());
+}
+
+#[tokio::test]
+async fn failed_poll_marks_down() {
+  let missing = SnapshotSource::new("tests/data/no-such-snapshot.json");
+  let metrics = RwLock::new(String::from("hylo_up 1\n"));
+  let result = refresh(&missing, &AlertThresholds::default(), &metrics).await;
+  assert!(result.is_err());
+  let out = metrics.into_inner();
+  assert!(out.contains("hylo_up 0\n"));
+  assert!(!out.contains("hylo_collateral_ratio"));
+}
+
+fn protocol_state() -> Result<ProtocolState<Clock>> {
+  let path = format!(
+    "{}/../hylo-quotes/tests/data/protocol-state-1018-114971.json",
+    env!("CARGO_MANIFEST_DIR")
+  );
+  let accounts: ProtocolAccounts = serde_json::from_reader(File::open(path)?)?;
+  // The snapshot's USDC pair predates `usdc_pair.redeem_fee`
+  ProtocolState::from_accounts_without_usdc(&accounts)
+}
+
+fn earn_pool_stats() -> EarnPoolStats {
+  let band = ApyBand {
+    low: 0.09,
+    high: 0.13,
+  };
+  EarnPoolStats {
+    nav: UFix64::new(1_050_000),
+    pool_balance: UFix64::new(2_000_000_000_000),
+    shyusd_supply: UFix64::new(1_904_761_904_761),
+    current_epoch: 1018,
+    epochs_per_year: EpochsPerYear::fixed(182.5),
+    lst_harvest: RealizedHarvest {
+      epoch: 1017,
+      hyusd_to_pool: UFix64::new(1_200_000_000),
+      is_stale: true,
+    },
+    exo_stats: Vec::new(),
+    last_epoch_yield_rate: UFix64::new(600_000),
+    naive_apy: 0.11,
+    naive_apy_band: band,
+    projected_lst_inflow: UFix64::new(1_200_000_000),
+    projected_exo_inflow: UFix64::new(0),
+    outstanding_drawdown: UFix64::new(0),
+    projected_epoch_rate: UFix64::new(600_000),
+    projected_apy: 0.11,
+    projected_apy_band: band,
+  }
+}
+
+#[test]
+fn collect_from_protocol_state() -> Result<()> {
+  let state = protocol_state()?;
+  let health = HealthSnapshot::collect(&state, &earn_pool_stats());
+  assert_eq!(health.epoch, 1018);
+  assert_eq!(health.protocol_paused, state.protocol_paused);
+  assert!((health.earn_pool.nav - 1.05).abs() < f64::EPSILON);
+  assert!((health.earn_pool.pool_balance - 2_000_000.0).abs() < f64::EPSILON);
+  // Limiter headroom comes from the state's earn pool, not the stats
+  assert!(health.earn_pool.deposit_headroom.is_some());
+  assert!(health.earn_pool.withdrawal_headroom.is_some());
+
+  // LST first, then each exo pair loaded in the state
+  let (lst, exo) = health.pairs.split_first().expect("LST pair");
+  assert_eq!(lst.pair, LST_PAIR);
+  assert!(lst.collateral_ratio > 1.0);
+  assert!(lst.stablecoin_nav.is_some());
+  assert!(lst.levercoin_nav.is_some());
+  assert_eq!(lst.market_cap_headroom_usd, None);
+  // The snapshot's LST CR sits under the mint threshold
+  assert_eq!(lst.stablecoin_mint_headroom, Some(0.0));
+  let labels = exo
+    .iter()
+    .map(|pair| pair.pair.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(labels, ["CBBTC", "HYPE"]);
+
+  // No exo stats, so only the LST harvest stream
+  assert_eq!(health.harvests.len(), 1);
+  assert_eq!(health.harvests[0].stream, LST_PAIR);
+  assert!(health.harvests[0].is_stale);
+  Ok(())
+}
@@ -0,0 +1,102 @@
+//! Anchor event CPI decoding.
+//!
+//! Programs emitting with `emit_cpi!` invoke themselves with
+//! [`EVENT_IX_TAG_LE`], the event discriminator and the Borsh-encoded
+//! event. Transaction metadata lists these self-invocations among the inner
+//! instructions of the instruction that emitted them.
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Event;
+
+/// Event carried by one event CPI instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventCpi<'a> {
+  program_id: Pubkey,
+  discriminator: &'a [u8],
+  body: &'a [u8],
+}
+
+impl<'a> EventCpi<'a> {
+  /// Splits the data of an instruction invoking `program_id`. `None` when
+  /// the data lacks the event CPI tag or a full discriminator.
+  #[must_use]
+  pub fn parse(program_id: Pubkey, data: &'a [u8]) -> Option<EventCpi<'a>> {
+    let (discriminator, body) =
+      data.strip_prefix(EVENT_IX_TAG_LE)?.split_at_checked(8)?;
+    Some(EventCpi {
+      program_id,
+      discriminator,
+      body,
+    })
+  }
+
+  /// Program that emitted the event.
+  #[must_use]
+  pub fn program_id(&self) -> Pubkey {
+    self.program_id
+  }
+
+  /// Decodes the event as `E` if `program` emitted it under `E`'s
+  /// discriminator; `None` for any other event.
+  ///
+  /// # Errors
+  /// * Matching discriminator with malformed event data
+  pub fn decode<E: Event>(
+    &self,
+    program: &Pubkey,
+  ) -> std::io::Result<Option<E>> {
+    if self.program_id != *program || self.discriminator != E::DISCRIMINATOR {
+      return Ok(None);
+    }
+    let mut body = self.body;
+    E::deserialize(&mut body).map(Some)
+  }
+}
+
+/// Instruction data of the event CPI emitting `event`.
+#[must_use]
+pub fn event_cpi_data<E: Event>(event: &E) -> Vec<u8> {
+  [EVENT_IX_TAG_LE, &event.data()].concat()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::earn_pool::events::{UserDepositEvent, UserWithdrawEvent};
+  use crate::earn_pool::types::UFixValue64;
+  use crate::{earn_pool, exchange};
+
+  fn deposit() -> UserDepositEvent {
+    let value = |bits| UFixValue64 { bits, exp: -6 };
+    UserDepositEvent {
+      stablecoin_deposited: value(1_000_000),
+      lp_token_nav: value(1_000_000),
+      lp_token_minted: value(1_000_000),
+    }
+  }
+
+  #[test]
+  fn decodes_only_tagged_events_of_the_program() -> std::io::Result<()> {
+    let data = event_cpi_data(&deposit());
+    let event = EventCpi::parse(earn_pool::ID, &data).expect("event CPI");
+    assert_eq!(
+      event
+        .decode::<UserDepositEvent>(&earn_pool::ID)?
+        .map(|e| e.lp_token_minted.bits),
+      Some(1_000_000)
+    );
+    assert!(event.decode::<UserWithdrawEvent>(&earn_pool::ID)?.is_none());
+    assert!(event.decode::<UserDepositEvent>(&exchange::ID)?.is_none());
+    Ok(())
+  }
+
+  #[test]
+  fn untagged_data_is_not_an_event() {
+    let data = event_cpi_data(&deposit());
+    assert_eq!(EventCpi::parse(earn_pool::ID, &data[..12]), None);
+    let mut untagged = data;
+    untagged[0] ^= 1;
+    assert_eq!(EventCpi::parse(earn_pool::ID, &untagged), None);
+  }
+}
@@ -17,6 +17,7 @@ mod codegen {
   anchor_lang::declare_program!(hylo_router_shadow);
 }
 
+pub mod events;
 pub mod pda;
 pub mod tokens;
 pub mod type_bridge;
@@ -1,33 +1,24 @@
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 use anchor_lang::prelude::Pubkey;
-use anchor_spl::token::{Mint, TokenAccount};
 use anyhow::{anyhow, Context, Result};
-use fix::prelude::UFix64;
-use hylo_core::idl::earn_pool::accounts::PoolConfig;
-use hylo_core::idl::exchange::accounts::{Hylo, LstHeader, UsdcPair};
 use hylo_core::idl::tokens::{
   StakePool, TokenMint, CBBTC, HYLOSOL, HYPE, HYUSD, JITOSOL, SHYUSD, USDC,
   XBTC, XHYPE, XSOL,
 };
 use hylo_core::idl::{earn_pool, exchange, pda};
-use hylo_core::lst::stake_pool::SplStakePool;
-use hylo_core::pyth::{query_pyth_oracle, OracleConfig, PythOracle, SOL_USD};
-use hylo_core::virtual_stablecoin::VirtualStablecoin;
+use hylo_core::pyth::PythOracle;
 use hylo_jupiter_amm_interface::{
   AccountMap, Amm, AmmContext, ClockRef, KeyedAccount, Quote, QuoteParams,
   SwapAndAccountMetas, SwapParams,
 };
-use hylo_quotes::protocol_state::{
-  build_exo_pair_state, stablecoin_oracle_valid, ProtocolState,
-  UsdcExchangeState,
-};
-use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use hylo_quotes::protocol_state::ProtocolState;
 
 use crate::account_metas;
-use crate::util::{
-  account_map_get, keyed_account, quote, validate_swap_params,
-};
+use crate::state_cache::StateCache;
+use crate::sub_state::{accounts_to_update, SubState};
+use crate::util::{quote, validate_swap_params};
 
 /// Bidirectional single-pair Jupiter AMM client.
 pub struct HyloJupiterPair<IN, OUT>
@@ -36,39 +27,33 @@ where
   OUT: TokenMint,
 {
   clock: ClockRef,
-  state: Option<ProtocolState<ClockRef>>,
+  cache: Arc<StateCache>,
+  state: Option<Arc<ProtocolState<ClockRef>>>,
   _phantom: PhantomData<(IN, OUT)>,
 }
 
-/// Builds the USDC exchange state from a Jupiter account snapshot.
-fn usdc_state(
-  clock: &ClockRef,
-  usdc_pair: &UsdcPair,
-  usdc_usd: &PriceUpdateV2,
-  usdc_vault: &TokenAccount,
-) -> Result<UsdcExchangeState> {
-  let usdc_oracle_config = OracleConfig::new(
-    usdc_pair.oracle_interval_secs,
-    usdc_pair.oracle_conf_tolerance.try_into()?,
-  );
-  let usdc_oracle = query_pyth_oracle(clock, usdc_usd, usdc_oracle_config)?;
-  let virtual_stablecoin: VirtualStablecoin =
-    usdc_pair.virtual_stablecoin.into();
-  Ok(UsdcExchangeState {
-    mint_fee: usdc_pair.mint_fee.try_into()?,
-    redeem_fee: usdc_pair.redeem_fee.try_into()?,
-    paused: usdc_pair.paused,
-    vault_balance: UFix64::new(usdc_vault.amount),
-    virtual_stablecoin_supply: virtual_stablecoin.supply()?,
-    usdc_usd_spot: usdc_oracle.spot,
-    par_tolerance: usdc_pair.par_tolerance.into(),
-  })
+impl<IN: TokenMint, OUT: TokenMint> HyloJupiterPair<IN, OUT> {
+  /// Pair reading its state through `cache`. Pairs updated from the same
+  /// account maps share one cache to build each slot's state once.
+  #[must_use]
+  pub fn with_cache(
+    amm_context: &AmmContext,
+    cache: Arc<StateCache>,
+  ) -> HyloJupiterPair<IN, OUT> {
+    HyloJupiterPair {
+      clock: amm_context.clock_ref.clone(),
+      cache,
+      state: None,
+      _phantom: PhantomData,
+    }
+  }
 }
 
 impl<IN: TokenMint, OUT: TokenMint> Clone for HyloJupiterPair<IN, OUT> {
   fn clone(&self) -> Self {
     Self {
       clock: self.clock.clone(),
+      cache: self.cache.clone(),
       state: self.state.clone(),
       _phantom: PhantomData,
     }
@@ -81,6 +66,9 @@ pub trait PairConfig<IN: TokenMint, OUT: TokenMint> {
   fn label() -> &'static str;
   fn key() -> Pubkey;
 
+  /// Optional protocol sub-states read by this pair's routes.
+  fn sub_states() -> &'static [SubState];
+
   /// Generate a quote for the given pair.
   ///
   /// # Errors
@@ -126,6 +114,9 @@ impl PairConfig<JITOSOL, HYUSD> for HyloJupiterPair<JITOSOL, HYUSD> {
   fn key() -> Pubkey {
     pda::HYLO
   }
+  fn sub_states() -> &'static [SubState] {
+    &[SubState::JitosolCollateral]
+  }
 
   fn quote(
     state: &ProtocolState<ClockRef>,
@@ -163,6 +154,9 @@ impl PairConfig<HYLOSOL, HYUSD> for HyloJupiterPair<HYLOSOL, HYUSD> {
   fn key() -> Pubkey {
     pda::HYLO
   }
+  fn sub_states() -> &'static [SubState] {
+    &[SubState::HylosolCollateral]
+  }
 
   fn quote(
     state: &ProtocolState<ClockRef>,
@@ -200,6 +194,9 @@ impl PairConfig<JITOSOL, XSOL> for HyloJupiterPair<JITOSOL, XSOL> {
   fn key() -> Pubkey {
     pda::HYLO
   }
+  fn sub_states() -> &'static [SubState] {
+    &[SubState::JitosolCollateral]
+  }
 
   fn quote(
     state: &ProtocolState<ClockRef>,
@@ -237,6 +234,9 @@ impl PairConfig<HYLOSOL, XSOL> for HyloJupiterPair<HYLOSOL, XSOL> {
   fn key() -> Pubkey {
     pda::HYLO
   }
+  fn sub_states() -> &'static [SubState] {
+    &[SubState::HylosolCollateral]
+  }
 
   fn quote(
     state: &ProtocolState<ClockRef>,
@@ -274,6 +274,9 @@ impl PairConfig<HYUSD, XSOL> for HyloJupiterPair<HYUSD, XSOL> {
   fn key() -> Pubkey {
     pda::HYLO
   }
+  fn sub_states() -> &'static [SubState] {
+    &[]
+  }
 
   fn quote(
     state: &ProtocolState<ClockRef>,
@@ -311,6 +314,9 @@ impl PairConfig<HYUSD, SHYUSD> for HyloJupiterPair<HYUSD, SHYUSD> {
   fn key() -> Pubkey {
     pda::POOL_CONFIG
   }
+  fn sub_states() -> &'static [SubState] {
+    &[SubState::EarnPool]
+  }
 
   fn quote(
     state: &ProtocolState<ClockRef>,
@@ -346,6 +352,9 @@ impl PairConfig<JITOSOL, HYLOSOL> for HyloJupiterPair<JITOSOL, HYLOSOL> {
   fn key() -> Pubkey {
     pda::HYLO
   }
+  fn sub_states() -> &'static [SubState] {
+    &[SubState::JitosolCollateral, SubState::HylosolCollateral]
+  }
 
   fn quote(
     state: &ProtocolState<ClockRef>,
@@ -387,6 +396,14 @@ impl PairConfig<JITOSOL, USDC> for HyloJupiterPair<JITOSOL, USDC> {
   fn key() -> Pubkey {
     pda::HYLO
   }
+  fn sub_states() -> &'static [SubState] {
+    &[
+      SubState::JitosolCollateral,
+      SubState::JitosolStakePool,
+      SubState::UsdcPair,
+      SubState::EarnPool,
+    ]
+  }
 
   fn quote(
     state: &ProtocolState<ClockRef>,
@@ -428,6 +445,14 @@ impl PairConfig<HYLOSOL, USDC> for HyloJupiterPair<HYLOSOL, USDC> {
   fn key() -> Pubkey {
     pda::HYLO
   }
+  fn sub_states() -> &'static [SubState] {
+    &[
+      SubState::HylosolCollateral,
+      SubState::HylosolStakePool,
+      SubState::UsdcPair,
+      SubState::EarnPool,
+    ]
+  }
 
   fn quote(
     state: &ProtocolState<ClockRef>,
@@ -469,6 +494,9 @@ impl PairConfig<USDC, HYUSD> for HyloJupiterPair<USDC, HYUSD> {
   fn key() -> Pubkey {
     pda::HYLO
   }
+  fn sub_states() -> &'static [SubState] {
+    &[SubState::UsdcPair]
+  }
 
   fn quote(
     state: &ProtocolState<ClockRef>,
@@ -498,7 +526,7 @@ impl PairConfig<USDC, HYUSD> for HyloJupiterPair<USDC, HYUSD> {
 
 /// [`PairConfig`] impls for an exo collateral and its levercoin.
 macro_rules! exo_pair_configs {
-  ($exo:ident, $lever:ident) => {
+  ($exo:ident, $lever:ident, $pair:ident) => {
     impl PairConfig<$exo, USDC> for HyloJupiterPair<$exo, USDC> {
       fn program_id() -> Pubkey {
         exchange::ID
@@ -509,6 +537,9 @@ macro_rules! exo_pair_configs {
       fn key() -> Pubkey {
         pda::HYLO
       }
+      fn sub_states() -> &'static [SubState] {
+        &[SubState::$pair, SubState::UsdcPair, SubState::EarnPool]
+      }
 
       fn quote(
         state: &ProtocolState<ClockRef>,
@@ -550,6 +581,9 @@ macro_rules! exo_pair_configs {
       fn key() -> Pubkey {
         pda::HYLO
       }
+      fn sub_states() -> &'static [SubState] {
+        &[SubState::$pair]
+      }
 
       fn quote(
         state: &ProtocolState<ClockRef>,
@@ -593,6 +627,9 @@ macro_rules! exo_pair_configs {
       fn key() -> Pubkey {
         pda::HYLO
       }
+      fn sub_states() -> &'static [SubState] {
+        &[SubState::$pair]
+      }
 
       fn quote(
         state: &ProtocolState<ClockRef>,
@@ -636,6 +673,9 @@ macro_rules! exo_pair_configs {
       fn key() -> Pubkey {
         pda::HYLO
       }
+      fn sub_states() -> &'static [SubState] {
+        &[SubState::$pair]
+      }
 
       fn quote(
         state: &ProtocolState<ClockRef>,
@@ -673,8 +713,8 @@ macro_rules! exo_pair_configs {
   };
 }
 
-exo_pair_configs!(CBBTC, XBTC);
-exo_pair_configs!(HYPE, XHYPE);
+exo_pair_configs!(CBBTC, XBTC, CbbtcPair);
+exo_pair_configs!(HYPE, XHYPE, HypePair);
 
 impl<IN, OUT> Amm for HyloJupiterPair<IN, OUT>
 where
@@ -689,11 +729,10 @@ where
   where
     Self: Sized,
   {
-    Ok(HyloJupiterPair {
-      clock: amm_context.clock_ref.clone(),
-      state: None,
-      _phantom: PhantomData,
-    })
+    Ok(HyloJupiterPair::with_cache(
+      amm_context,
+      Arc::new(StateCache::new()),
+    ))
   }
 
   fn label(&self) -> String {
@@ -713,118 +752,20 @@ where
   }
 
   fn get_accounts_to_update(&self) -> Vec<Pubkey> {
-    vec![
-      pda::HYLO,
-      HYUSD::MINT,
-      XSOL::MINT,
-      pda::lst_header(JITOSOL::MINT),
-      pda::lst_header(HYLOSOL::MINT),
-      JITOSOL::POOL_STATE,
-      HYLOSOL::POOL_STATE,
-      SOL_USD.address,
-      SHYUSD::MINT,
-      pda::HYUSD_POOL,
-      pda::POOL_CONFIG,
-      pda::exo_pair(CBBTC::MINT),
-      pda::exo_vault(CBBTC::MINT),
-      pda::exo_levercoin_mint(CBBTC::MINT),
-      CBBTC::FEED.address,
-      pda::exo_pair(HYPE::MINT),
-      pda::exo_vault(HYPE::MINT),
-      pda::exo_levercoin_mint(HYPE::MINT),
-      HYPE::FEED.address,
-      pda::USDC_PAIR,
-      pda::USDC_USD_PYTH_FEED,
-      pda::lst_vault(JITOSOL::MINT),
-      pda::lst_vault(HYLOSOL::MINT),
-      pda::usdc_vault(USDC::MINT),
-    ]
+    accounts_to_update(<Self as PairConfig<IN, OUT>>::sub_states())
   }
 
   fn update(&mut self, account_map: &AccountMap) -> Result<()> {
-    // Core protocol state
-    let hylo: Hylo = account_map_get(account_map, &pda::HYLO)?;
-    let hyusd_mint: Mint = account_map_get(account_map, &HYUSD::MINT)?;
-    let xsol_mint: Mint = account_map_get(account_map, &XSOL::MINT)?;
-    let jitosol_header: LstHeader =
-      account_map_get(account_map, &pda::lst_header(JITOSOL::MINT))?;
-    let hylosol_header: LstHeader =
-      account_map_get(account_map, &pda::lst_header(HYLOSOL::MINT))?;
-    let sol_usd: PriceUpdateV2 =
-      account_map_get(account_map, &SOL_USD.address)?;
-
-    // Earn pool
-    let shyusd_mint: Mint = account_map_get(account_map, &SHYUSD::MINT)?;
-    let hyusd_pool: TokenAccount =
-      account_map_get(account_map, &pda::HYUSD_POOL)?;
-    let pool_config: PoolConfig =
-      account_map_get(account_map, &pda::POOL_CONFIG)?;
-
-    // Exo pairs
-    let cbbtc_pair = build_exo_pair_state::<CBBTC, ClockRef>(
-      self.clock.clone(),
-      keyed_account(account_map, &pda::exo_pair(CBBTC::MINT))?,
-      keyed_account(account_map, &pda::exo_vault(CBBTC::MINT))?,
-      keyed_account(account_map, &pda::exo_levercoin_mint(CBBTC::MINT))?,
-      keyed_account(account_map, &CBBTC::FEED.address)?,
-    )?;
-    let hype_pair = build_exo_pair_state::<HYPE, ClockRef>(
-      self.clock.clone(),
-      keyed_account(account_map, &pda::exo_pair(HYPE::MINT))?,
-      keyed_account(account_map, &pda::exo_vault(HYPE::MINT))?,
-      keyed_account(account_map, &pda::exo_levercoin_mint(HYPE::MINT))?,
-      keyed_account(account_map, &HYPE::FEED.address)?,
-    )?;
-
-    let usdc_pair: UsdcPair = account_map_get(account_map, &pda::USDC_PAIR)?;
-    let jitosol_vault: TokenAccount =
-      account_map_get(account_map, &pda::lst_vault(JITOSOL::MINT))?;
-    let hylosol_vault: TokenAccount =
-      account_map_get(account_map, &pda::lst_vault(HYLOSOL::MINT))?;
-    let usdc_vault: TokenAccount =
-      account_map_get(account_map, &pda::usdc_vault(USDC::MINT))?;
-    let usdc_usd: PriceUpdateV2 =
-      account_map_get(account_map, &pda::USDC_USD_PYTH_FEED)?;
-
-    let usdc_exchange_state =
-      usdc_state(&self.clock, &usdc_pair, &usdc_usd, &usdc_vault)?;
-
-    // Stake pools
-    let jitosol_pool_state = keyed_account(account_map, &JITOSOL::POOL_STATE)?;
-    let jitosol_stake_pool =
-      SplStakePool::from_bytes(&jitosol_pool_state.data)?;
-    let hylosol_pool_state = keyed_account(account_map, &HYLOSOL::POOL_STATE)?;
-    let hylosol_stake_pool =
-      SplStakePool::from_bytes(&hylosol_pool_state.data)?;
-
-    let sol_stablecoin_oracle_valid =
-      stablecoin_oracle_valid(&self.clock, &sol_usd, hylo.oracle_interval_secs);
-    self.state = Some(ProtocolState::build(
-      self.clock.clone(),
-      &hylo,
-      jitosol_header,
-      hylosol_header,
-      hyusd_mint,
-      xsol_mint,
-      shyusd_mint,
-      pool_config,
-      hyusd_pool,
-      &sol_usd,
-      cbbtc_pair,
-      hype_pair,
-      usdc_exchange_state,
-      jitosol_stake_pool,
-      hylosol_stake_pool,
-      UFix64::new(jitosol_vault.amount),
-      UFix64::new(hylosol_vault.amount),
-      sol_stablecoin_oracle_valid,
+    self.state = Some(self.cache.state(
+      &self.clock,
+      account_map,
+      <Self as PairConfig<IN, OUT>>::sub_states(),
     )?);
-
     Ok(())
   }
 
   fn quote(&self, params: &QuoteParams) -> Result<Quote> {
-    let state = self.state.as_ref().context("`state` not set")?;
+    let state = self.state.as_deref().context("`state` not set")?;
     <Self as PairConfig<IN, OUT>>::quote(
       state,
       params.amount,
@@ -1,5 +1,8 @@
 pub mod account_metas;
 pub mod jupiter;
+pub mod state_cache;
+pub mod sub_state;
 pub mod util;
 
 pub use jupiter::{HyloJupiterPair, PairConfig};
+pub use state_cache::StateCache;
@@ -0,0 +1,450 @@
+//! Protocol state shared across Jupiter pairs.
+//!
+//! Jupiter updates every pair against the same account map. Built per pair,
+//! each update deserializes the exchange core and validates the SOL/USD
+//! oracle again. [`StateCache`] builds one [`ProtocolState`] per slot and
+//! set of accounts read, loads sub-states onto it as pairs ask for them,
+//! and hands every pair holding the cache the same [`Arc`]. Pass one cache
+//! to each pair of a group with [`HyloJupiterPair::with_cache`].
+//!
+//! [`HyloJupiterPair::with_cache`]: crate::jupiter::HyloJupiterPair::with_cache
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anchor_lang::prelude::Pubkey;
+
+use anyhow::{anyhow, Result};
+use hylo_core::solana_clock::SolanaClock;
+use hylo_jupiter_amm_interface::{AccountMap, ClockRef};
+use hylo_quotes::protocol_state::ProtocolState;
+
+use crate::sub_state::{accounts_to_update, build_state, SubState};
+
+/// Work done by a [`StateCache`] since it was created.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+  /// States built from scratch, one per slot and account contents seen
+  pub builds: usize,
+  /// Cached states extended with sub-states they lacked
+  pub extensions: usize,
+  /// Lookups served without loading anything
+  pub hits: usize,
+}
+
+/// Slot and the keys of the accounts a [`ProtocolState`] reads from the
+/// account map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct StateKey {
+  slot: u64,
+  accounts: Vec<Pubkey>,
+}
+
+impl StateKey {
+  /// Key for `slot` and the core and sub-state accounts present in
+  /// `account_map`.
+  fn new(slot: u64, account_map: &AccountMap) -> StateKey {
+    let mut accounts = accounts_to_update(&SubState::ALL);
+    accounts.retain(|key| account_map.contains_key(key));
+    StateKey { slot, accounts }
+  }
+}
+
+/// Value cached for one key, with the sub-states loaded into it.
+struct Entry<T> {
+  key: StateKey,
+  loaded: Vec<SubState>,
+  value: Arc<T>,
+}
+
+/// Cache of one value per [`StateKey`], independent of what the value is.
+struct SlotCache<T> {
+  entry: Mutex<Option<Entry<T>>>,
+  builds: AtomicUsize,
+  extensions: AtomicUsize,
+  hits: AtomicUsize,
+}
+
+impl<T> SlotCache<T> {
+  fn new() -> SlotCache<T> {
+    SlotCache {
+      entry: Mutex::new(None),
+      builds: AtomicUsize::new(0),
+      extensions: AtomicUsize::new(0),
+      hits: AtomicUsize::new(0),
+    }
+  }
+
+  /// Value for `key` holding at least `needed`.
+  ///
+  /// `build` receives the cached value to extend, or `None` for a new
+  /// key, with the sub-states to add. It returns the new value and every
+  /// sub-state it now holds beyond the base it was given.
+  fn get_or_build(
+    &self,
+    key: StateKey,
+    needed: &[SubState],
+    build: impl FnOnce(Option<&T>, &[SubState]) -> Result<(T, Vec<SubState>)>,
+  ) -> Result<Arc<T>> {
+    let mut guard = self
+      .entry
+      .lock()
+      .map_err(|_| anyhow!("Protocol state cache poisoned"))?;
+    if let Some(entry) = guard.as_mut().filter(|entry| entry.key == key) {
+      let missing = needed
+        .iter()
+        .filter(|sub_state| !entry.loaded.contains(sub_state))
+        .copied()
+        .collect::<Vec<_>>();
+      if missing.is_empty() {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+      } else {
+        let (value, added) = build(Some(entry.value.as_ref()), &missing)?;
+        self.extensions.fetch_add(1, Ordering::Relaxed);
+        entry.value = Arc::new(value);
+        entry.loaded.extend(added);
+      }
+      return Ok(entry.value.clone());
+    }
+    let (value, loaded) = build(None, needed)?;
+    self.builds.fetch_add(1, Ordering::Relaxed);
+    let value = Arc::new(value);
+    *guard = Some(Entry {
+      key,
+      loaded,
+      value: value.clone(),
+    });
+    Ok(value)
+  }
+
+  fn stats(&self) -> CacheStats {
+    CacheStats {
+      builds: self.builds.load(Ordering::Relaxed),
+      extensions: self.extensions.load(Ordering::Relaxed),
+      hits: self.hits.load(Ordering::Relaxed),
+    }
+  }
+}
+
+/// One [`ProtocolState`] per slot, shared by every pair holding the cache.
+///
+/// Entries are keyed by slot and the accounts the state reads: Jupiter
+/// updates every pair of a slot from one account map, so a map holding
+/// different accounts rebuilds the state rather than extending one read
+/// from another. A new key builds the core, the sub-states the asking pair
+/// needs, and every other sub-state whose accounts are already in the map,
+/// so pairs updated after it in the same slot usually find everything
+/// loaded.
+/// Optional sub-states that fail to load are left out and retried, with
+/// errors, by the first pair that needs them.
+pub struct StateCache(SlotCache<ProtocolState<ClockRef>>);
+
+impl Default for StateCache {
+  fn default() -> Self {
+    StateCache::new()
+  }
+}
+
+impl StateCache {
+  /// Empty cache, shared by every pair it is passed to.
+  #[must_use]
+  pub fn new() -> StateCache {
+    StateCache(SlotCache::new())
+  }
+
+  /// State for the clock's current slot and `account_map` holding at
+  /// least `sub_states`.
+  ///
+  /// # Errors
+  /// * A core or required sub-state account is missing or malformed
+  /// * Oracle or context validation
+  pub fn state(
+    &self,
+    clock: &ClockRef,
+    account_map: &AccountMap,
+    sub_states: &[SubState],
+  ) -> Result<Arc<ProtocolState<ClockRef>>> {
+    self.0.get_or_build(
+      StateKey::new(clock.slot(), account_map),
+      sub_states,
+      |cached, required| {
+        let base = match cached {
+          Some(state) => state.clone(),
+          None => build_state(clock, account_map, &[])?,
+        };
+        let state = required.iter().try_fold(base, |state, sub_state| {
+          sub_state.load(state, clock, account_map)
+        })?;
+        let mut loaded = required.to_vec();
+        let state = if cached.is_none() {
+          opportunistic(state, clock, account_map, &mut loaded)
+        } else {
+          state
+        };
+        Ok((state, loaded))
+      },
+    )
+  }
+
+  /// Builds, extensions and hits so far.
+  #[must_use]
+  pub fn stats(&self) -> CacheStats {
+    self.0.stats()
+  }
+}
+
+/// Loads every sub-state not yet in `loaded` whose accounts are all in
+/// `account_map`, skipping any that fail.
+fn opportunistic(
+  state: ProtocolState<ClockRef>,
+  clock: &ClockRef,
+  account_map: &AccountMap,
+  loaded: &mut Vec<SubState>,
+) -> ProtocolState<ClockRef> {
+  SubState::ALL.iter().fold(state, |state, sub_state| {
+    let present = sub_state
+      .accounts()
+      .iter()
+      .all(|key| account_map.contains_key(key));
+    if loaded.contains(sub_state) || !present {
+      state
+    } else if let Ok(extended) =
+      sub_state.load(state.clone(), clock, account_map)
+    {
+      loaded.push(*sub_state);
+      extended
+    } else {
+      state
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs::File;
+
+  use anyhow::ensure;
+  use hylo_core::idl::tokens::{
+    TokenMint, CBBTC, HYUSD, JITOSOL, SHYUSD, XBTC, XSOL,
+  };
+  use hylo_jupiter_amm_interface::{Amm, AmmContext, QuoteParams, SwapMode};
+  use hylo_quotes::prelude::ProtocolAccounts;
+
+  use super::*;
+  use crate::jupiter::{HyloJupiterPair, PairConfig};
+  use crate::sub_state::CORE_ACCOUNTS;
+  use crate::util::{snapshot_account_map, snapshot_amm_context};
+
+  fn key(slot: u64) -> StateKey {
+    StateKey {
+      slot,
+      accounts: CORE_ACCOUNTS.to_vec(),
+    }
+  }
+
+  /// Builder recording what it was asked to load, as the cached value.
+  /// Fails if asked to extend a value with nothing.
+  fn record(
+    cached: Option<&Vec<SubState>>,
+    required: &[SubState],
+  ) -> Result<(Vec<SubState>, Vec<SubState>)> {
+    ensure!(cached.is_none() || !required.is_empty(), "empty extension");
+    let mut value = cached.cloned().unwrap_or_default();
+    value.extend_from_slice(required);
+    Ok((value, required.to_vec()))
+  }
+
+  #[test]
+  fn pairs_in_one_slot_share_one_build() -> Result<()> {
+    let cache = SlotCache::new();
+    let needed = [SubState::JitosolCollateral];
+    let first = cache.get_or_build(key(7), &needed, record)?;
+    let rest = (0..9)
+      .map(|_| cache.get_or_build(key(7), &needed, record))
+      .collect::<Result<Vec<_>>>()?;
+    assert!(rest.iter().all(|value| Arc::ptr_eq(value, &first)));
+    assert_eq!(
+      cache.stats(),
+      CacheStats {
+        builds: 1,
+        extensions: 0,
+        hits: 9,
+      }
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn new_slot_rebuilds() -> Result<()> {
+    let cache = SlotCache::new();
+    let first = cache.get_or_build(key(7), &[SubState::EarnPool], record)?;
+    let second = cache.get_or_build(key(8), &[SubState::EarnPool], record)?;
+    assert!(!Arc::ptr_eq(&first, &second));
+    assert_eq!(cache.stats().builds, 2);
+    Ok(())
+  }
+
+  #[test]
+  fn missing_sub_states_extend_cached_value() -> Result<()> {
+    let cache = SlotCache::new();
+    cache.get_or_build(key(7), &[SubState::JitosolCollateral], record)?;
+    let extended = cache.get_or_build(
+      key(7),
+      &[SubState::JitosolCollateral, SubState::UsdcPair],
+      record,
+    )?;
+    assert_eq!(
+      *extended,
+      vec![SubState::JitosolCollateral, SubState::UsdcPair]
+    );
+    let again = cache.get_or_build(key(7), &[SubState::UsdcPair], record)?;
+    assert!(Arc::ptr_eq(&extended, &again));
+    assert_eq!(
+      cache.stats(),
+      CacheStats {
+        builds: 1,
+        extensions: 1,
+        hits: 1,
+      }
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn other_accounts_rebuild_within_slot() -> Result<()> {
+    let cache = SlotCache::new();
+    cache.get_or_build(key(7), &[SubState::JitosolCollateral], record)?;
+    let changed = StateKey {
+      slot: 7,
+      accounts: accounts_to_update(&[SubState::UsdcPair]),
+    };
+    let rebuilt = cache.get_or_build(changed, &[SubState::UsdcPair], record)?;
+    assert_eq!(*rebuilt, vec![SubState::UsdcPair]);
+    assert_eq!(cache.stats().builds, 2);
+    assert_eq!(cache.stats().extensions, 0);
+    Ok(())
+  }
+
+  #[test]
+  fn failed_build_not_cached() -> Result<()> {
+    let cache = SlotCache::<Vec<SubState>>::new();
+    let failed =
+      cache.get_or_build(key(7), &[], |_, _| Err(anyhow!("account missing")));
+    assert!(failed.is_err());
+    cache.get_or_build(key(7), &[], record)?;
+    assert_eq!(cache.stats().builds, 1);
+    Ok(())
+  }
+
+  /// Clock and account map recorded in the hylo-quotes snapshot.
+  fn snapshot() -> Result<(AmmContext, AccountMap)> {
+    let path = format!(
+      "{}/../hylo-quotes/tests/data/protocol-state-1018-114971.json",
+      env!("CARGO_MANIFEST_DIR")
+    );
+    let accounts: ProtocolAccounts =
+      serde_json::from_reader(File::open(path)?)?;
+    Ok((
+      snapshot_amm_context(&accounts)?,
+      snapshot_account_map(&accounts),
+    ))
+  }
+
+  /// Quotes `IN -> OUT` from a pair on `shared` and from one on its own
+  /// cache, asserting they agree.
+  fn assert_same_quote<IN, OUT>(
+    amm_context: &AmmContext,
+    account_map: &AccountMap,
+    shared: &Arc<StateCache>,
+    amount: u64,
+  ) -> Result<()>
+  where
+    IN: TokenMint + 'static,
+    OUT: TokenMint + 'static,
+    HyloJupiterPair<IN, OUT>: PairConfig<IN, OUT> + Amm,
+  {
+    let mut cached =
+      HyloJupiterPair::<IN, OUT>::with_cache(amm_context, shared.clone());
+    let mut own = HyloJupiterPair::<IN, OUT>::with_cache(
+      amm_context,
+      Arc::new(StateCache::new()),
+    );
+    cached.update(account_map)?;
+    own.update(account_map)?;
+    let params = QuoteParams {
+      amount,
+      input_mint: IN::MINT,
+      output_mint: OUT::MINT,
+      swap_mode: SwapMode::ExactIn,
+    };
+    let (cached, own) = (cached.quote(&params)?, own.quote(&params)?);
+    assert_eq!(cached.in_amount, own.in_amount);
+    assert_eq!(cached.out_amount, own.out_amount);
+    assert_eq!(cached.fee_amount, own.fee_amount);
+    assert_eq!(cached.fee_pct, own.fee_pct);
+    Ok(())
+  }
+
+  #[test]
+  fn shared_state_quotes_match_independent() -> Result<()> {
+    let (amm_context, account_map) = snapshot()?;
+    let shared = Arc::new(StateCache::new());
+    assert_same_quote::<JITOSOL, XSOL>(
+      &amm_context,
+      &account_map,
+      &shared,
+      1_000_000_000,
+    )?;
+    assert_same_quote::<HYUSD, XSOL>(
+      &amm_context,
+      &account_map,
+      &shared,
+      1_000_000,
+    )?;
+    assert_same_quote::<HYUSD, SHYUSD>(
+      &amm_context,
+      &account_map,
+      &shared,
+      1_000_000,
+    )?;
+    assert_same_quote::<CBBTC, XBTC>(
+      &amm_context,
+      &account_map,
+      &shared,
+      100_000,
+    )?;
+    assert_eq!(shared.stats().builds, 1);
+    assert_eq!(shared.stats().extensions, 0);
+    Ok(())
+  }
+
+  #[test]
+  fn state_key_tracks_accounts_read() -> Result<()> {
+    let (_, mut account_map) = snapshot()?;
+    let before = StateKey::new(7, &account_map);
+    assert_eq!(before.accounts, accounts_to_update(&SubState::ALL));
+    let hylo = account_map
+      .get_mut(&CORE_ACCOUNTS[0])
+      .ok_or(anyhow!("Hylo account missing"))?;
+    hylo.lamports += 1;
+    assert_eq!(before, StateKey::new(7, &account_map));
+    account_map.remove(&CORE_ACCOUNTS[1]);
+    assert_ne!(before, StateKey::new(7, &account_map));
+    Ok(())
+  }
+
+  #[test]
+  fn removed_account_rebuilds_state() -> Result<()> {
+    let (amm_context, mut account_map) = snapshot()?;
+    let cache = StateCache::new();
+    let clock = &amm_context.clock_ref;
+    let first = cache.state(clock, &account_map, &[])?;
+    assert!(first.exo_pair::<CBBTC>().is_ok());
+    account_map.remove(&SubState::CbbtcPair.accounts()[0]);
+    let second = cache.state(clock, &account_map, &[])?;
+    assert!(!Arc::ptr_eq(&first, &second));
+    assert!(second.exo_pair::<CBBTC>().is_err());
+    assert_eq!(cache.stats().builds, 2);
+    Ok(())
+  }
+}
@@ -0,0 +1,296 @@
+//! Protocol sub-states a Jupiter pair loads from its account map.
+//!
+//! Every pair reads the LST exchange core (Hylo, xSOL mint, SOL/USD feed).
+//! Everything else is declared per pair through [`PairConfig::sub_states`],
+//! so an update only needs, and can only fail on, the accounts its routes
+//! read.
+//!
+//! [`PairConfig::sub_states`]: crate::jupiter::PairConfig::sub_states
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token::{Mint, TokenAccount};
+use anyhow::Result;
+use fix::prelude::{FixExt, UFix64};
+use hylo_core::idl::earn_pool::accounts::PoolConfig;
+use hylo_core::idl::exchange::accounts::{Hylo, LstHeader, UsdcPair};
+use hylo_core::idl::pda;
+use hylo_core::idl::tokens::{
+  Exo, StakePool, TokenMint, CBBTC, HYLOSOL, HYPE, JITOSOL, SHYUSD, USDC, XSOL,
+};
+use hylo_core::lst::stake_pool::SplStakePool;
+use hylo_core::pyth::{query_pyth_oracle, OracleConfig, PythOracle, SOL_USD};
+use hylo_core::virtual_stablecoin::VirtualStablecoin;
+use hylo_jupiter_amm_interface::{AccountMap, ClockRef};
+use hylo_quotes::protocol_state::{
+  build_exo_pair_state, EarnPoolState, ProtocolState, UsdcExchangeState,
+};
+use hylo_quotes::LST;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::util::{account_map_get, keyed_account};
+
+/// Accounts behind the LST exchange core, read by every pair.
+pub const CORE_ACCOUNTS: [Pubkey; 3] = [pda::HYLO, XSOL::MINT, SOL_USD.address];
+
+/// Optional [`ProtocolState`] component read by a pair's routes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SubState {
+  /// `JitoSOL` header and collateral vault
+  JitosolCollateral,
+  /// `hyloSOL` header and collateral vault
+  HylosolCollateral,
+  /// `JitoSOL` SPL stake pool, priced by the rebalance routes
+  JitosolStakePool,
+  /// `hyloSOL` SPL stake pool, priced by the rebalance routes
+  HylosolStakePool,
+  /// cbBTC exo pair
+  CbbtcPair,
+  /// HYPE exo pair
+  HypePair,
+  /// USDC pair, vault and USDC/USD feed
+  UsdcPair,
+  /// Earn pool config, HYUSD pool and SHYUSD mint
+  EarnPool,
+}
+
+impl SubState {
+  /// Every sub-state, in load order.
+  pub const ALL: [SubState; 8] = [
+    SubState::JitosolCollateral,
+    SubState::HylosolCollateral,
+    SubState::JitosolStakePool,
+    SubState::HylosolStakePool,
+    SubState::CbbtcPair,
+    SubState::HypePair,
+    SubState::UsdcPair,
+    SubState::EarnPool,
+  ];
+
+  /// Accounts this sub-state is built from.
+  #[must_use]
+  pub fn accounts(self) -> Vec<Pubkey> {
+    match self {
+      SubState::JitosolCollateral => lst_accounts::<JITOSOL>(),
+      SubState::HylosolCollateral => lst_accounts::<HYLOSOL>(),
+      SubState::JitosolStakePool => vec![JITOSOL::POOL_STATE],
+      SubState::HylosolStakePool => vec![HYLOSOL::POOL_STATE],
+      SubState::CbbtcPair => exo_accounts::<CBBTC>(),
+      SubState::HypePair => exo_accounts::<HYPE>(),
+      SubState::UsdcPair => vec![
+        pda::USDC_PAIR,
+        pda::usdc_vault(USDC::MINT),
+        pda::USDC_USD_PYTH_FEED,
+      ],
+      SubState::EarnPool => {
+        vec![pda::POOL_CONFIG, pda::HYUSD_POOL, SHYUSD::MINT]
+      }
+    }
+  }
+
+  /// Loads this sub-state from `account_map` into `state`.
+  ///
+  /// # Errors
+  /// * Missing account or deserialization failure
+  /// * Oracle or context validation
+  pub fn load(
+    self,
+    state: ProtocolState<ClockRef>,
+    clock: &ClockRef,
+    account_map: &AccountMap,
+  ) -> Result<ProtocolState<ClockRef>> {
+    match self {
+      SubState::JitosolCollateral => load_lst::<JITOSOL>(state, account_map),
+      SubState::HylosolCollateral => load_lst::<HYLOSOL>(state, account_map),
+      SubState::JitosolStakePool => {
+        load_stake_pool::<JITOSOL>(state, account_map)
+      }
+      SubState::HylosolStakePool => {
+        load_stake_pool::<HYLOSOL>(state, account_map)
+      }
+      SubState::CbbtcPair => load_exo::<CBBTC>(state, clock, account_map),
+      SubState::HypePair => load_exo::<HYPE>(state, clock, account_map),
+      SubState::UsdcPair => {
+        Ok(state.with_usdc(usdc_state(clock, account_map)?))
+      }
+      SubState::EarnPool => Ok(state.with_earn_pool(EarnPoolState {
+        shyusd_mint: account_map_get::<Mint>(account_map, &SHYUSD::MINT)?,
+        pool_config: account_map_get::<PoolConfig>(
+          account_map,
+          &pda::POOL_CONFIG,
+        )?,
+        hyusd_pool: account_map_get::<TokenAccount>(
+          account_map,
+          &pda::HYUSD_POOL,
+        )?,
+      })),
+    }
+  }
+}
+
+fn lst_accounts<L: LST>() -> Vec<Pubkey> {
+  vec![pda::lst_header(L::MINT), pda::lst_vault(L::MINT)]
+}
+
+fn exo_accounts<E: Exo + PythOracle>() -> Vec<Pubkey> {
+  vec![
+    pda::exo_pair(E::MINT),
+    pda::exo_vault(E::MINT),
+    pda::exo_levercoin_mint(E::MINT),
+    E::FEED.address,
+  ]
+}
+
+fn load_lst<L: LST>(
+  state: ProtocolState<ClockRef>,
+  account_map: &AccountMap,
+) -> Result<ProtocolState<ClockRef>> {
+  let header: LstHeader =
+    account_map_get(account_map, &pda::lst_header(L::MINT))?;
+  let vault: TokenAccount =
+    account_map_get(account_map, &pda::lst_vault(L::MINT))?;
+  Ok(state.with_lst::<L>(header, UFix64::new(vault.amount))?)
+}
+
+fn load_stake_pool<L: LST>(
+  state: ProtocolState<ClockRef>,
+  account_map: &AccountMap,
+) -> Result<ProtocolState<ClockRef>> {
+  let pool_state = keyed_account(account_map, &L::POOL_STATE)?;
+  let stake_pool = SplStakePool::from_bytes(&pool_state.data)?;
+  Ok(state.with_stake_pool::<L>(stake_pool)?)
+}
+
+fn load_exo<E: Exo + PythOracle>(
+  state: ProtocolState<ClockRef>,
+  clock: &ClockRef,
+  account_map: &AccountMap,
+) -> Result<ProtocolState<ClockRef>>
+where
+  UFix64<E::Exp>: FixExt,
+{
+  let pair = build_exo_pair_state::<E, ClockRef>(
+    clock.clone(),
+    keyed_account(account_map, &pda::exo_pair(E::MINT))?,
+    keyed_account(account_map, &pda::exo_vault(E::MINT))?,
+    keyed_account(account_map, &pda::exo_levercoin_mint(E::MINT))?,
+    keyed_account(account_map, &E::FEED.address)?,
+  )?;
+  Ok(state.with_exo_pair::<E>(pair)?)
+}
+
+/// Builds the USDC exchange state from a Jupiter account snapshot.
+fn usdc_state(
+  clock: &ClockRef,
+  account_map: &AccountMap,
+) -> Result<UsdcExchangeState> {
+  let usdc_pair: UsdcPair = account_map_get(account_map, &pda::USDC_PAIR)?;
+  let usdc_usd: PriceUpdateV2 =
+    account_map_get(account_map, &pda::USDC_USD_PYTH_FEED)?;
+  let usdc_vault: TokenAccount =
+    account_map_get(account_map, &pda::usdc_vault(USDC::MINT))?;
+  let usdc_oracle_config = OracleConfig::new(
+    usdc_pair.oracle_interval_secs,
+    usdc_pair.oracle_conf_tolerance.try_into()?,
+  );
+  let usdc_oracle = query_pyth_oracle(clock, &usdc_usd, usdc_oracle_config)?;
+  let virtual_stablecoin: VirtualStablecoin =
+    usdc_pair.virtual_stablecoin.into();
+  Ok(UsdcExchangeState {
+    mint_fee: usdc_pair.mint_fee.try_into()?,
+    redeem_fee: usdc_pair.redeem_fee.try_into()?,
+    paused: usdc_pair.paused,
+    vault_balance: UFix64::new(usdc_vault.amount),
+    virtual_stablecoin_supply: virtual_stablecoin.supply()?,
+    usdc_usd_spot: usdc_oracle.spot,
+    par_tolerance: usdc_pair.par_tolerance.into(),
+    oracle_publish_time: usdc_usd.price_message.publish_time,
+    oracle_posted_slot: usdc_usd.posted_slot,
+    oracle_interval_secs: usdc_pair.oracle_interval_secs,
+  })
+}
+
+/// Every account a pair reading `sub_states` needs, core first.
+#[must_use]
+pub fn accounts_to_update(sub_states: &[SubState]) -> Vec<Pubkey> {
+  let mut accounts = CORE_ACCOUNTS.to_vec();
+  sub_states.iter().for_each(|sub_state| {
+    sub_state.accounts().into_iter().for_each(|key| {
+      if !accounts.contains(&key) {
+        accounts.push(key);
+      }
+    });
+  });
+  accounts
+}
+
+/// Builds a [`ProtocolState`] holding the core and exactly `sub_states`.
+///
+/// # Errors
+/// * A core or declared account is missing or malformed
+/// * Oracle or context validation
+pub fn build_state(
+  clock: &ClockRef,
+  account_map: &AccountMap,
+  sub_states: &[SubState],
+) -> Result<ProtocolState<ClockRef>> {
+  let hylo: Hylo = account_map_get(account_map, &pda::HYLO)?;
+  let xsol_mint: Mint = account_map_get(account_map, &XSOL::MINT)?;
+  let sol_usd: PriceUpdateV2 = account_map_get(account_map, &SOL_USD.address)?;
+  let core = ProtocolState::core(clock.clone(), &hylo, xsol_mint, &sol_usd)?;
+  sub_states.iter().try_fold(core, |state, sub_state| {
+    sub_state.load(state, clock, account_map)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use hylo_core::idl::tokens::{HYUSD, SHYUSD};
+
+  use super::*;
+  use crate::jupiter::{HyloJupiterPair, PairConfig};
+
+  fn accounts<IN: TokenMint, OUT: TokenMint>() -> Vec<Pubkey>
+  where
+    HyloJupiterPair<IN, OUT>: PairConfig<IN, OUT>,
+  {
+    accounts_to_update(
+      <HyloJupiterPair<IN, OUT> as PairConfig<IN, OUT>>::sub_states(),
+    )
+  }
+
+  #[test]
+  fn earn_pool_pair_skips_collateral() {
+    let keys = accounts::<HYUSD, SHYUSD>();
+    assert!(keys.contains(&pda::POOL_CONFIG));
+    assert!(!keys.contains(&pda::lst_header(JITOSOL::MINT)));
+    assert!(!keys.contains(&pda::exo_pair(CBBTC::MINT)));
+    assert!(!keys.contains(&pda::USDC_PAIR));
+  }
+
+  #[test]
+  fn swap_pair_reads_core_only() {
+    assert_eq!(accounts::<HYUSD, XSOL>(), CORE_ACCOUNTS.to_vec());
+  }
+
+  #[test]
+  fn rebalance_pair_reads_pool_and_usdc() {
+    let keys = accounts::<JITOSOL, USDC>();
+    assert!(keys.contains(&JITOSOL::POOL_STATE));
+    assert!(keys.contains(&pda::USDC_PAIR));
+    assert!(keys.contains(&pda::HYUSD_POOL));
+    assert!(!keys.contains(&HYLOSOL::POOL_STATE));
+  }
+
+  #[test]
+  fn accounts_deduplicated() {
+    let keys = accounts_to_update(&[
+      SubState::JitosolCollateral,
+      SubState::JitosolCollateral,
+      SubState::EarnPool,
+    ]);
+    let mut unique = keys.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(keys.len(), unique.len());
+  }
+}
@@ -9,7 +9,7 @@ use hylo_core::idl::tokens::TokenMint;
 use hylo_jupiter_amm_interface::{
   AccountMap, AmmContext, ClockRef, Quote, SwapMode, SwapParams,
 };
-use hylo_quotes::protocol_state::ProtocolState;
+use hylo_quotes::protocol_state::{ProtocolAccounts, ProtocolState};
 use hylo_quotes::token_operation::{
   OperationOutput, TokenOperation, TokenOperationExt,
 };
@@ -136,6 +136,26 @@ pub async fn load_amm_context(client: &RpcClient) -> Result<AmmContext> {
   Ok(AmmContext { clock_ref })
 }
 
+/// Builds an account map from a recorded [`ProtocolAccounts`] snapshot.
+#[must_use]
+pub fn snapshot_account_map(accounts: &ProtocolAccounts) -> AccountMap {
+  accounts
+    .keyed_accounts()
+    .into_iter()
+    .map(|(key, account)| (key, account.clone()))
+    .collect()
+}
+
+/// Loads the clock recorded in a [`ProtocolAccounts`] snapshot.
+///
+/// # Errors
+/// * Deserialization fails
+pub fn snapshot_amm_context(accounts: &ProtocolAccounts) -> Result<AmmContext> {
+  let clock: Clock = bincode::deserialize(&accounts.clock.data)?;
+  let clock_ref = ClockRef::from(clock);
+  Ok(AmmContext { clock_ref })
+}
+
 /// Validates Jupiter swap parameters for Hylo compatibility.
 ///
 /// # Errors
@@ -0,0 +1,209 @@
+//! Regenerates `src/compute_units/table.rs` from mainnet simulations.
+//!
+//! Simulates every runtime pair at several input sizes from the reference
+//! wallet and records the per-pair distribution of `units_consumed`, along
+//! with the LST registry size at calibration. Pairs the wallet cannot fund
+//! record no samples and fall back to the default budget; each failed
+//! simulation is reported on stderr.
+//!
+//! ```bash
+//! RPC_URL=<mainnet> cargo run -p hylo-quotes --example \
+//!   calibrate_compute_units -- hylo-quotes/src/compute_units/table.rs
+//! ```
+
+use std::fmt::Write;
+use std::process::Command;
+use std::str::FromStr;
+
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::Cluster;
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Context, Result};
+use hylo_clients::prelude::{ProgramClient, RouterClient};
+use hylo_clients::util::{
+  deserialize_lookup_table, lst_registry_size, LST_REGISTRY_LOOKUP_TABLE,
+  REFERENCE_WALLET,
+};
+use hylo_idl::tokens::{
+  TokenMint, CBBTC, HYLOSOL, HYPE, HYUSD, JITOSOL, ONYC, PST, SHYUSD, USDC,
+  WETH, XBTC, XETH, XHYPE, XONYC, XPST, XSOL, XZEC, ZEC,
+};
+use hylo_quotes::compute_units::ComputeUnitEstimate;
+use hylo_quotes::prelude::{
+  ComputeUnitStrategy, RuntimePair, RuntimeQuoteStrategy, SimulationStrategy,
+  RUNTIME_PAIRS,
+};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+
+const SLIPPAGE_BPS: u64 = 50;
+
+/// Input sizes as percentages of one whole input token.
+const INPUT_PERCENTS: [u64; 7] = [1, 2, 5, 10, 20, 50, 100];
+
+/// Token type names as written in the generated table.
+const TOKENS: &[(Pubkey, &str)] = &[
+  (CBBTC::MINT, "CBBTC"),
+  (HYLOSOL::MINT, "HYLOSOL"),
+  (HYPE::MINT, "HYPE"),
+  (HYUSD::MINT, "HYUSD"),
+  (JITOSOL::MINT, "JITOSOL"),
+  (ONYC::MINT, "ONYC"),
+  (PST::MINT, "PST"),
+  (SHYUSD::MINT, "SHYUSD"),
+  (USDC::MINT, "USDC"),
+  (WETH::MINT, "WETH"),
+  (XBTC::MINT, "XBTC"),
+  (XETH::MINT, "XETH"),
+  (XHYPE::MINT, "XHYPE"),
+  (XONYC::MINT, "XONYC"),
+  (XPST::MINT, "XPST"),
+  (XSOL::MINT, "XSOL"),
+  (XZEC::MINT, "XZEC"),
+  (ZEC::MINT, "ZEC"),
+];
+
+fn token_name(mint: Pubkey) -> Result<&'static str> {
+  TOKENS
+    .iter()
+    .find(|(token, _)| *token == mint)
+    .map(|(_, name)| *name)
+    .ok_or_else(|| anyhow!("No token type for mint {mint}"))
+}
+
+/// Rust source for `src/compute_units/table.rs`.
+fn render_table(
+  estimates: &[(&RuntimePair, Option<ComputeUnitEstimate>)],
+  lst_registry_size: usize,
+  epoch: u64,
+  slot_index: u64,
+) -> Result<String> {
+  let names = TOKENS
+    .iter()
+    .map(|(_, name)| *name)
+    .collect::<Vec<_>>()
+    .join(", ");
+  let mut out = String::new();
+  writeln!(
+    out,
+    "//! Generated by the `calibrate_compute_units` example; do not edit by \
+     hand.\n//!\n//! Calibrated at epoch {epoch}, slot index {slot_index}. \
+     Pairs without\n//! samples fall back to \
+     `DEFAULT_CUS_WITH_BUFFER`.\n\nuse hylo_idl::tokens::{{TokenMint, \
+     {names}}};\n\nuse super::{{ComputeUnitEstimate, PairEstimate}};\nuse \
+     crate::Operation;\n\n/// LST registry size during calibration.\npub \
+     const CALIBRATED_LST_REGISTRY_SIZE: usize = {lst_registry_size};\n\n/// \
+     Simulated compute unit distribution per runtime pair.\npub const \
+     COMPUTE_UNIT_TABLE: &[PairEstimate] = &["
+  )?;
+  for (pair, estimate) in estimates {
+    writeln!(
+      out,
+      "  // {}\n  PairEstimate {{\n    input_mint: {}::MINT,\n    \
+       output_mint: {}::MINT,\n    operation: Operation::{:?},",
+      pair.description,
+      token_name(pair.input_mint)?,
+      token_name(pair.output_mint)?,
+      pair.operation,
+    )?;
+    match estimate {
+      Some(e) => writeln!(
+        out,
+        "    estimate: ComputeUnitEstimate {{\n      samples: {},\n      \
+         p50: {},\n      p90: {},\n      p99: {},\n      max: {},\n    }},",
+        e.samples, e.p50, e.p90, e.p99, e.max
+      )?,
+      None => {
+        writeln!(out, "    estimate: ComputeUnitEstimate::UNCALIBRATED,")?;
+      }
+    }
+    writeln!(out, "  }},")?;
+  }
+  writeln!(out, "];")?;
+  Ok(out)
+}
+
+/// Simulated `units_consumed` for `pair` at each of [`INPUT_PERCENTS`],
+/// and the number of inputs whose simulation failed.
+async fn sample_pair(
+  strategy: &SimulationStrategy,
+  pair: &RuntimePair,
+) -> (Vec<u64>, usize) {
+  let one_token = 10u64.pow(u32::from(pair.input_exp.unsigned_abs()));
+  let mut samples = Vec::with_capacity(INPUT_PERCENTS.len());
+  let mut failed = 0;
+  for percent in INPUT_PERCENTS {
+    let quote = strategy
+      .runtime_quote(
+        pair.input_mint,
+        pair.output_mint,
+        one_token * percent / 100,
+        REFERENCE_WALLET,
+        SLIPPAGE_BPS,
+      )
+      .await;
+    match quote {
+      Ok(quote)
+        if matches!(
+          quote.compute_unit_strategy,
+          ComputeUnitStrategy::Simulated
+        ) =>
+      {
+        samples.push(quote.compute_units);
+      }
+      Ok(_) => {
+        failed += 1;
+        eprintln!("{} at {percent}%: not simulated", pair.description);
+      }
+      Err(err) => {
+        failed += 1;
+        eprintln!("{} at {percent}%: {err:#}", pair.description);
+      }
+    }
+  }
+  (samples, failed)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+  let table_path = std::env::args()
+    .nth(1)
+    .context("Usage: calibrate_compute_units <path/to/table.rs>")?;
+  let rpc_url = std::env::var("RPC_URL").context("RPC_URL is not set")?;
+  let rpc_client = RpcClient::new_with_commitment(
+    rpc_url.clone(),
+    CommitmentConfig::confirmed(),
+  );
+  let cluster =
+    Cluster::from_str(&rpc_url).context("Failed to parse RPC_URL")?;
+  let router_client =
+    RouterClient::new_random_keypair(cluster, CommitmentConfig::confirmed())?;
+  let strategy = SimulationStrategy::new(router_client);
+
+  let registry_account =
+    rpc_client.get_account(&LST_REGISTRY_LOOKUP_TABLE).await?;
+  let registry =
+    deserialize_lookup_table(&LST_REGISTRY_LOOKUP_TABLE, &registry_account)?;
+  let registry_size = lst_registry_size(&registry);
+  let epoch = rpc_client.get_epoch_info().await?;
+
+  let mut estimates = Vec::with_capacity(RUNTIME_PAIRS.len());
+  let mut failed = 0;
+  for pair in RUNTIME_PAIRS {
+    let (samples, pair_failed) = sample_pair(&strategy, pair).await;
+    println!("{}: {samples:?}, {pair_failed} failed", pair.description);
+    failed += pair_failed;
+    estimates.push((pair, ComputeUnitEstimate::from_samples(&samples)));
+  }
+  println!("{failed} simulations failed");
+
+  let table =
+    render_table(&estimates, registry_size, epoch.epoch, epoch.slot_index)?;
+  std::fs::write(&table_path, table)?;
+  let status = Command::new("rustfmt")
+    .args(["--edition", "2021", &table_path])
+    .status()?;
+  if !status.success() {
+    return Err(anyhow!("rustfmt failed on {table_path}"));
+  }
+  Ok(())
+}
@@ -0,0 +1,260 @@
+//! Quote strategies composing [`ProtocolStateStrategy`] with
+//! [`SimulationStrategy`].
+//!
+//! - [`FallbackStrategy`] quotes from state and simulates only when that
+//!   fails.
+//! - [`VerifyingStrategy`] runs both and rejects the quote when their
+//!   outputs disagree beyond a tolerance.
+//! - [`HybridStrategy`] keeps the state-based amounts and takes simulated
+//!   compute units, keeping the state estimate when simulation fails.
+
+mod router;
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use fix::prelude::{CheckedSub, FixExt, MulDiv, UFix64, N4};
+use fix::typenum::Integer;
+use hylo_core::solana_clock::SolanaClock;
+
+use crate::protocol_state::StateProvider;
+use crate::runtime_quote_strategy::RuntimeQuoteStrategy;
+use crate::{
+  ComputeUnitStrategy, ExecutableQuote, ProtocolStateStrategy,
+  SimulationStrategy,
+};
+
+/// Quotes from state, falling back to simulation on error.
+pub struct FallbackStrategy<S> {
+  pub state: ProtocolStateStrategy<S>,
+  pub simulation: SimulationStrategy,
+}
+
+impl<S> FallbackStrategy<S> {
+  #[must_use]
+  pub fn new(
+    state: ProtocolStateStrategy<S>,
+    simulation: SimulationStrategy,
+  ) -> Self {
+    Self { state, simulation }
+  }
+}
+
+/// Quotes from state and simulation together, returning the state quote
+/// only if the simulated output is within `tolerance` of it.
+pub struct VerifyingStrategy<S> {
+  pub state: ProtocolStateStrategy<S>,
+  pub simulation: SimulationStrategy,
+  /// Largest accepted difference relative to the larger output
+  pub tolerance: UFix64<N4>,
+}
+
+impl<S> VerifyingStrategy<S> {
+  #[must_use]
+  pub fn new(
+    state: ProtocolStateStrategy<S>,
+    simulation: SimulationStrategy,
+    tolerance: UFix64<N4>,
+  ) -> Self {
+    Self {
+      state,
+      simulation,
+      tolerance,
+    }
+  }
+}
+
+/// State-based amounts and instructions with simulated compute units.
+/// A failed simulation leaves the state quote as is.
+pub struct HybridStrategy<S> {
+  pub state: ProtocolStateStrategy<S>,
+  pub simulation: SimulationStrategy,
+}
+
+impl<S> HybridStrategy<S> {
+  #[must_use]
+  pub fn new(
+    state: ProtocolStateStrategy<S>,
+    simulation: SimulationStrategy,
+  ) -> Self {
+    Self { state, simulation }
+  }
+}
+
+#[async_trait]
+impl<S: StateProvider<C> + Sync, C: SolanaClock> RuntimeQuoteStrategy<C>
+  for FallbackStrategy<S>
+{
+}
+
+#[async_trait]
+impl<S: StateProvider<C> + Sync, C: SolanaClock> RuntimeQuoteStrategy<C>
+  for VerifyingStrategy<S>
+{
+}
+
+#[async_trait]
+impl<S: StateProvider<C> + Sync, C: SolanaClock> RuntimeQuoteStrategy<C>
+  for HybridStrategy<S>
+{
+}
+
+/// Whether `a` and `b` differ by at most `tolerance` of the larger.
+fn within_tolerance<Exp: Integer>(
+  a: UFix64<Exp>,
+  b: UFix64<Exp>,
+  tolerance: UFix64<N4>,
+) -> Option<bool> {
+  let (low, high) = if a <= b { (a, b) } else { (b, a) };
+  let diff = high.checked_sub(&low)?;
+  let bound = high.mul_div_floor(tolerance, UFix64::<N4>::one())?;
+  Some(diff <= bound)
+}
+
+/// Re-expresses a simulated quote's fee at the state quote's precision;
+/// the USDC mint event reports its fee at `N9` where state uses `N6`.
+///
+/// # Errors
+/// * Fee overflows the target precision
+fn convert_fee<In, Out, From, To>(
+  quote: ExecutableQuote<In, Out, From>,
+) -> Result<ExecutableQuote<In, Out, To>>
+where
+  In: Integer,
+  Out: Integer,
+  From: Integer,
+  To: Integer,
+  UFix64<From>: FixExt,
+  UFix64<To>: FixExt,
+{
+  let fee_amount = quote
+    .fee_amount
+    .checked_convert::<To>()
+    .context("simulated fee precision")?;
+  Ok(ExecutableQuote {
+    amount_in: quote.amount_in,
+    amount_out: quote.amount_out,
+    min_amount_out: quote.min_amount_out,
+    compute_units: quote.compute_units,
+    compute_unit_strategy: quote.compute_unit_strategy,
+    fee_amount,
+    fee_mint: quote.fee_mint,
+    instructions: quote.instructions,
+    address_lookup_tables: quote.address_lookup_tables,
+    expiry: quote.expiry,
+  })
+}
+
+/// Passes `state` through if `simulated` agrees on output.
+///
+/// # Errors
+/// * Outputs differ beyond `tolerance`
+fn verify<In: Integer, Out: Integer, Fee: Integer>(
+  state: ExecutableQuote<In, Out, Fee>,
+  simulated: &ExecutableQuote<In, Out, Fee>,
+  tolerance: UFix64<N4>,
+) -> Result<ExecutableQuote<In, Out, Fee>> {
+  match within_tolerance(state.amount_out, simulated.amount_out, tolerance) {
+    Some(true) => Ok(state),
+    Some(false) => Err(anyhow!(
+      "state amount_out {} and simulated amount_out {} differ beyond \
+       tolerance",
+      state.amount_out.bits,
+      simulated.amount_out.bits
+    )),
+    None => Err(anyhow!("amount_out tolerance arithmetic")),
+  }
+}
+
+/// `state` with compute units from `simulated`, when simulation succeeded
+/// and measured them.
+fn hybrid<In: Integer, Out: Integer, Fee: Integer>(
+  state: ExecutableQuote<In, Out, Fee>,
+  simulated: Option<&ExecutableQuote<In, Out, Fee>>,
+) -> ExecutableQuote<In, Out, Fee> {
+  match simulated {
+    Some(ExecutableQuote {
+      compute_units,
+      compute_unit_strategy: ComputeUnitStrategy::Simulated,
+      ..
+    }) => ExecutableQuote {
+      compute_units: *compute_units,
+      compute_unit_strategy: ComputeUnitStrategy::Simulated,
+      ..state
+    },
+    _ => state,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Pubkey;
+  use fix::prelude::{N6, N9};
+
+  use super::*;
+  use crate::quote_expiry::QuoteExpiry;
+  use crate::DEFAULT_CUS_WITH_BUFFER;
+
+  const HALF_PERCENT: UFix64<N4> = UFix64::constant(50);
+
+  fn quote(
+    amount_out: u64,
+    compute_units: u64,
+    compute_unit_strategy: ComputeUnitStrategy,
+  ) -> ExecutableQuote<N9, N6, N9> {
+    ExecutableQuote {
+      amount_in: UFix64::new(1_000_000_000),
+      amount_out: UFix64::new(amount_out),
+      min_amount_out: UFix64::new(amount_out),
+      compute_units,
+      compute_unit_strategy,
+      fee_amount: UFix64::zero(),
+      fee_mint: Pubkey::default(),
+      instructions: Vec::new(),
+      address_lookup_tables: Vec::new(),
+      expiry: QuoteExpiry::default(),
+    }
+  }
+
+  #[test]
+  fn tolerance_relative_to_larger_output() {
+    let out = |bits| UFix64::<N6>::new(bits);
+    assert_eq!(
+      within_tolerance(out(1_000_000), out(995_000), HALF_PERCENT),
+      Some(true)
+    );
+    assert_eq!(
+      within_tolerance(out(994_999), out(1_000_000), HALF_PERCENT),
+      Some(false)
+    );
+  }
+
+  #[test]
+  fn verify_rejects_divergent_simulation() {
+    let estimated = ComputeUnitStrategy::Estimated;
+    let state = quote(150_000_000, DEFAULT_CUS_WITH_BUFFER, estimated);
+    let far = quote(140_000_000, 80_000, ComputeUnitStrategy::Simulated);
+    let near = quote(149_900_000, 80_000, ComputeUnitStrategy::Simulated);
+    assert!(verify(state.clone(), &far, HALF_PERCENT).is_err());
+    assert!(verify(state, &near, HALF_PERCENT)
+      .is_ok_and(|quote| quote.amount_out == UFix64::new(150_000_000)));
+  }
+
+  #[test]
+  fn hybrid_takes_only_simulated_units() {
+    let estimated = ComputeUnitStrategy::Estimated;
+    let state = quote(150_000_000, DEFAULT_CUS_WITH_BUFFER, estimated);
+    let simulated = quote(149_000_000, 82_000, ComputeUnitStrategy::Simulated);
+    let merged = hybrid(state.clone(), Some(&simulated));
+    assert_eq!(merged.amount_out, state.amount_out);
+    assert_eq!(merged.compute_units, 82_000);
+    assert!(matches!(
+      merged.compute_unit_strategy,
+      ComputeUnitStrategy::Simulated
+    ));
+    let unmeasured = quote(149_000_000, 0, ComputeUnitStrategy::Estimated);
+    let kept = hybrid(state.clone(), Some(&unmeasured));
+    assert_eq!(kept.compute_units, DEFAULT_CUS_WITH_BUFFER);
+    let failed = hybrid(state, None);
+    assert_eq!(failed.compute_units, DEFAULT_CUS_WITH_BUFFER);
+  }
+}
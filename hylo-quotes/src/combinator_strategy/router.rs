@@ -0,0 +1,189 @@
+//! `QuoteStrategy` impls for the strategy combinators.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use fix::prelude::*;
+use futures::future::join;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_idl::tokens::{
+  CBBTC, HYLOSOL, HYPE, HYUSD, JITOSOL, ONYC, PST, SHYUSD, USDC, WETH, XBTC,
+  XETH, XHYPE, XONYC, XPST, XSOL, XZEC, ZEC,
+};
+use hylo_idl::with_exo_pairs;
+
+use crate::combinator_strategy::{
+  convert_fee, hybrid, verify, FallbackStrategy, HybridStrategy,
+  VerifyingStrategy,
+};
+use crate::protocol_state::StateProvider;
+use crate::{ExecutableQuote, QuoteStrategy};
+
+macro_rules! combinator_quote {
+  ($in:ty, $out:ty, $fee_exp:ty, $quote_ty:ty) => {
+    #[async_trait]
+    impl<S: StateProvider<C>, C: SolanaClock> QuoteStrategy<$in, $out, C>
+      for FallbackStrategy<S>
+    {
+      type FeeExp = $fee_exp;
+
+      async fn get_quote(
+        &self,
+        amount_in: u64,
+        user: Pubkey,
+        slippage_tolerance: u64,
+      ) -> Result<$quote_ty> {
+        let state_error = match QuoteStrategy::<$in, $out, C>::get_quote(
+          &self.state,
+          amount_in,
+          user,
+          slippage_tolerance,
+        )
+        .await
+        {
+          Ok(quote) => return Ok(quote),
+          Err(error) => error,
+        };
+        QuoteStrategy::<$in, $out, C>::get_quote(
+          &self.simulation,
+          amount_in,
+          user,
+          slippage_tolerance,
+        )
+        .await
+        .and_then(convert_fee)
+        .with_context(|| format!("state quote failed first: {state_error}"))
+      }
+    }
+
+    #[async_trait]
+    impl<S: StateProvider<C>, C: SolanaClock> QuoteStrategy<$in, $out, C>
+      for VerifyingStrategy<S>
+    {
+      type FeeExp = $fee_exp;
+
+      async fn get_quote(
+        &self,
+        amount_in: u64,
+        user: Pubkey,
+        slippage_tolerance: u64,
+      ) -> Result<$quote_ty> {
+        let (state, simulated) = join(
+          QuoteStrategy::<$in, $out, C>::get_quote(
+            &self.state,
+            amount_in,
+            user,
+            slippage_tolerance,
+          ),
+          QuoteStrategy::<$in, $out, C>::get_quote(
+            &self.simulation,
+            amount_in,
+            user,
+            slippage_tolerance,
+          ),
+        )
+        .await;
+        verify(state?, &convert_fee(simulated?)?, self.tolerance)
+      }
+    }
+
+    #[async_trait]
+    impl<S: StateProvider<C>, C: SolanaClock> QuoteStrategy<$in, $out, C>
+      for HybridStrategy<S>
+    {
+      type FeeExp = $fee_exp;
+
+      async fn get_quote(
+        &self,
+        amount_in: u64,
+        user: Pubkey,
+        slippage_tolerance: u64,
+      ) -> Result<$quote_ty> {
+        let (state, simulated) = join(
+          QuoteStrategy::<$in, $out, C>::get_quote(
+            &self.state,
+            amount_in,
+            user,
+            slippage_tolerance,
+          ),
+          QuoteStrategy::<$in, $out, C>::get_quote(
+            &self.simulation,
+            amount_in,
+            user,
+            slippage_tolerance,
+          ),
+        )
+        .await;
+        Ok(hybrid(
+          state?,
+          simulated.and_then(convert_fee).ok().as_ref(),
+        ))
+      }
+    }
+  };
+}
+
+// `mint_stablecoin_lst`
+combinator_quote!(JITOSOL, HYUSD, N9, ExecutableQuote<N9, N6, N9>);
+combinator_quote!(HYLOSOL, HYUSD, N9, ExecutableQuote<N9, N6, N9>);
+
+// `redeem_stablecoin_lst`
+combinator_quote!(HYUSD, JITOSOL, N9, ExecutableQuote<N6, N9, N9>);
+combinator_quote!(HYUSD, HYLOSOL, N9, ExecutableQuote<N6, N9, N9>);
+
+// `mint_levercoin_lst`
+combinator_quote!(JITOSOL, XSOL, N9, ExecutableQuote<N9, N6, N9>);
+combinator_quote!(HYLOSOL, XSOL, N9, ExecutableQuote<N9, N6, N9>);
+
+// `redeem_levercoin_lst`
+combinator_quote!(XSOL, JITOSOL, N9, ExecutableQuote<N6, N9, N9>);
+combinator_quote!(XSOL, HYLOSOL, N9, ExecutableQuote<N6, N9, N9>);
+
+// `convert_stable_to_lever_lst`
+combinator_quote!(HYUSD, XSOL, N6, ExecutableQuote<N6, N6, N6>);
+
+// `convert_lever_to_stable_lst`
+combinator_quote!(XSOL, HYUSD, N6, ExecutableQuote<N6, N6, N6>);
+
+// `swap_lst_to_lst`
+combinator_quote!(JITOSOL, HYLOSOL, N9, ExecutableQuote<N9, N9, N9>);
+combinator_quote!(HYLOSOL, JITOSOL, N9, ExecutableQuote<N9, N9, N9>);
+
+// `mint_stablecoin_usdc`
+combinator_quote!(USDC, HYUSD, N6, ExecutableQuote<N6, N6, N6>);
+
+// `redeem_stablecoin_usdc`
+combinator_quote!(HYUSD, USDC, N6, ExecutableQuote<N6, N6, N6>);
+
+// `swap_lst_to_usdc`
+combinator_quote!(JITOSOL, USDC, N9, ExecutableQuote<N9, N6, N9>);
+combinator_quote!(HYLOSOL, USDC, N9, ExecutableQuote<N9, N6, N9>);
+
+// `swap_usdc_to_lst`
+combinator_quote!(USDC, JITOSOL, N6, ExecutableQuote<N6, N9, N6>);
+combinator_quote!(USDC, HYLOSOL, N6, ExecutableQuote<N6, N9, N6>);
+
+// `swap_exo_to_usdc`
+
+// `user_deposit`
+combinator_quote!(HYUSD, SHYUSD, N6, ExecutableQuote<N6, N6, N6>);
+
+// `user_withdraw`
+combinator_quote!(SHYUSD, HYUSD, N6, ExecutableQuote<N6, N6, N6>);
+
+macro_rules! exo_combinator_quotes {
+  ($(($exo:ident, $lever:ident, $exp:ty)),+ $(,)?) => {
+    $(
+      combinator_quote!($exo, HYUSD, N9, ExecutableQuote<$exp, N6, N9>);
+      combinator_quote!(HYUSD, $exo, N9, ExecutableQuote<N6, $exp, N9>);
+      combinator_quote!($exo, $lever, N9, ExecutableQuote<$exp, N6, N9>);
+      combinator_quote!($lever, $exo, N9, ExecutableQuote<N6, $exp, N9>);
+      combinator_quote!(HYUSD, $lever, N6, ExecutableQuote<N6, N6, N6>);
+      combinator_quote!($lever, HYUSD, N6, ExecutableQuote<N6, N6, N6>);
+      combinator_quote!($exo, USDC, $exp, ExecutableQuote<$exp, N6, $exp>);
+      combinator_quote!(USDC, $exo, N6, ExecutableQuote<N6, $exp, N6>);
+    )+
+  };
+}
+
+with_exo_pairs!(exo_combinator_quotes);
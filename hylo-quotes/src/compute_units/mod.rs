@@ -0,0 +1,265 @@
+//! Per-pair compute unit budgets.
+//!
+//! [`COMPUTE_UNIT_TABLE`] holds percentiles of simulated `units_consumed`
+//! for each runtime pair, regenerated by the `calibrate_compute_units`
+//! example. State quotes budget a pair at its p99 plus [`CU_BUFFER_BPS`];
+//! pairs without samples keep [`DEFAULT_CUS_WITH_BUFFER`].
+//!
+//! LST routes load the LST registry lookup table, whose size was
+//! [`CALIBRATED_LST_REGISTRY_SIZE`] at calibration. When the registry has
+//! grown since, [`pair_budget`] scales those routes' samples by the ratio
+//! of current to calibrated size.
+//!
+//! ```bash
+//! RPC_URL=<mainnet> cargo run -p hylo-quotes --example \
+//!   calibrate_compute_units -- hylo-quotes/src/compute_units/table.rs
+//! ```
+
+mod table;
+
+use anchor_lang::prelude::Pubkey;
+
+pub use self::table::{CALIBRATED_LST_REGISTRY_SIZE, COMPUTE_UNIT_TABLE};
+use crate::{Operation, DEFAULT_CUS_WITH_BUFFER};
+
+/// Headroom added over the p99 sample, in basis points.
+pub const CU_BUFFER_BPS: u64 = 1_000;
+
+/// Simulated compute unit distribution for one pair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComputeUnitEstimate {
+  /// Simulations behind the percentiles, zero when uncalibrated
+  pub samples: u32,
+  pub p50: u64,
+  pub p90: u64,
+  pub p99: u64,
+  pub max: u64,
+}
+
+impl ComputeUnitEstimate {
+  /// Placeholder for pairs never simulated.
+  pub const UNCALIBRATED: ComputeUnitEstimate = ComputeUnitEstimate {
+    samples: 0,
+    p50: 0,
+    p90: 0,
+    p99: 0,
+    max: 0,
+  };
+
+  /// Nearest-rank percentiles over `samples`, `None` when empty.
+  #[must_use]
+  pub fn from_samples(samples: &[u64]) -> Option<ComputeUnitEstimate> {
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    let max = *sorted.last()?;
+    Some(ComputeUnitEstimate {
+      samples: u32::try_from(sorted.len()).unwrap_or(u32::MAX),
+      p50: nearest_rank(&sorted, 50),
+      p90: nearest_rank(&sorted, 90),
+      p99: nearest_rank(&sorted, 99),
+      max,
+    })
+  }
+
+  /// Compute unit limit to request: p99 plus [`CU_BUFFER_BPS`], or
+  /// [`DEFAULT_CUS_WITH_BUFFER`] when uncalibrated.
+  #[must_use]
+  pub fn budget(&self) -> u64 {
+    if self.samples == 0 {
+      DEFAULT_CUS_WITH_BUFFER
+    } else {
+      self
+        .p99
+        .saturating_mul(10_000 + CU_BUFFER_BPS)
+        .div_ceil(10_000)
+    }
+  }
+
+  /// Scales every percentile by `current / calibrated`, rounding up.
+  fn scaled(&self, current: usize, calibrated: usize) -> ComputeUnitEstimate {
+    let scale = |units: u64| {
+      u64::try_from(
+        (u128::from(units) * current as u128).div_ceil(calibrated as u128),
+      )
+      .unwrap_or(u64::MAX)
+    };
+    ComputeUnitEstimate {
+      samples: self.samples,
+      p50: scale(self.p50),
+      p90: scale(self.p90),
+      p99: scale(self.p99),
+      max: scale(self.max),
+    }
+  }
+}
+
+/// Smallest sample with at least `pct` percent of `sorted` at or below it.
+fn nearest_rank(sorted: &[u64], pct: usize) -> u64 {
+  let rank = (sorted.len() * pct).div_ceil(100).max(1);
+  sorted[rank - 1]
+}
+
+/// Calibrated compute units for one runtime pair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PairEstimate {
+  pub input_mint: Pubkey,
+  pub output_mint: Pubkey,
+  pub operation: Operation,
+  pub estimate: ComputeUnitEstimate,
+}
+
+impl PairEstimate {
+  /// Estimate adjusted for an LST registry of `lst_registry_size` entries,
+  /// against a table calibrated at `calibrated_size`.
+  ///
+  /// Registry cost is taken as linear in its size, so scaling the whole
+  /// sample overstates it: the result bounds the true cost from above.
+  /// Routes that skip the registry, unknown sizes and registries no larger
+  /// than at calibration keep the calibrated estimate.
+  fn adjusted(
+    &self,
+    lst_registry_size: Option<usize>,
+    calibrated_size: usize,
+  ) -> ComputeUnitEstimate {
+    match lst_registry_size {
+      Some(current)
+        if self.operation.reads_lst_registry()
+          && calibrated_size > 0
+          && current > calibrated_size =>
+      {
+        self.estimate.scaled(current, calibrated_size)
+      }
+      _ => self.estimate,
+    }
+  }
+}
+
+/// Table row for a mint pair, if calibrated or listed.
+fn pair_row(
+  input_mint: Pubkey,
+  output_mint: Pubkey,
+) -> Option<&'static PairEstimate> {
+  COMPUTE_UNIT_TABLE
+    .iter()
+    .find(|row| row.input_mint == input_mint && row.output_mint == output_mint)
+}
+
+/// Calibrated distribution for a mint pair.
+#[must_use]
+pub fn estimate(
+  input_mint: Pubkey,
+  output_mint: Pubkey,
+) -> ComputeUnitEstimate {
+  pair_row(input_mint, output_mint)
+    .map_or(ComputeUnitEstimate::UNCALIBRATED, |row| row.estimate)
+}
+
+/// Compute unit budget for a mint pair, scaled for LST registry growth
+/// when `lst_registry_size` is known.
+#[must_use]
+pub fn pair_budget(
+  input_mint: Pubkey,
+  output_mint: Pubkey,
+  lst_registry_size: Option<usize>,
+) -> u64 {
+  pair_row(input_mint, output_mint).map_or(DEFAULT_CUS_WITH_BUFFER, |row| {
+    row
+      .adjusted(lst_registry_size, CALIBRATED_LST_REGISTRY_SIZE)
+      .budget()
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::runtime_quote_strategy::RUNTIME_PAIRS;
+
+  #[test]
+  fn nearest_rank_percentiles() {
+    let samples = (1..=100).rev().map(|n| n * 1_000).collect::<Vec<u64>>();
+    let estimate = ComputeUnitEstimate::from_samples(&samples);
+    assert_eq!(
+      estimate,
+      Some(ComputeUnitEstimate {
+        samples: 100,
+        p50: 50_000,
+        p90: 90_000,
+        p99: 99_000,
+        max: 100_000,
+      })
+    );
+    assert_eq!(ComputeUnitEstimate::from_samples(&[]), None);
+  }
+
+  #[test]
+  fn budget_buffers_p99() {
+    let estimate = ComputeUnitEstimate::from_samples(&[74_001, 81_000, 97_000])
+      .expect("non-empty samples");
+    assert_eq!(estimate.p99, 97_000);
+    assert_eq!(estimate.budget(), 106_700);
+  }
+
+  #[test]
+  fn uncalibrated_falls_back_to_default() {
+    assert_eq!(
+      ComputeUnitEstimate::UNCALIBRATED.budget(),
+      DEFAULT_CUS_WITH_BUFFER
+    );
+    assert_eq!(
+      pair_budget(Pubkey::default(), Pubkey::default(), Some(64)),
+      DEFAULT_CUS_WITH_BUFFER
+    );
+  }
+
+  fn row(operation: Operation, p99: u64) -> PairEstimate {
+    PairEstimate {
+      input_mint: Pubkey::default(),
+      output_mint: Pubkey::default(),
+      operation,
+      estimate: ComputeUnitEstimate {
+        samples: 7,
+        p50: p99 / 2,
+        p90: p99 - 1_000,
+        p99,
+        max: p99 + 1_000,
+      },
+    }
+  }
+
+  #[test]
+  fn registry_growth_scales_lst_routes() {
+    let lst = row(Operation::MintStablecoinLst, 90_001);
+    let scaled = lst.adjusted(Some(12), 8);
+    assert_eq!(scaled.p99, 135_002);
+    assert_eq!(scaled.p50, 67_500);
+    assert_eq!(scaled.max, 136_502);
+    assert_eq!(scaled.samples, 7);
+  }
+
+  #[test]
+  fn registry_scaling_skips_non_lst_routes() {
+    let usdc = row(Operation::MintStablecoinUsdc, 90_000);
+    assert_eq!(usdc.adjusted(Some(12), 8), usdc.estimate);
+    let exo = row(Operation::SwapExoToUsdc, 90_000);
+    assert_eq!(exo.adjusted(Some(12), 8), exo.estimate);
+  }
+
+  #[test]
+  fn registry_scaling_never_shrinks() {
+    let lst = row(Operation::SwapLstToLst, 90_000);
+    assert_eq!(lst.adjusted(Some(4), 8), lst.estimate);
+    assert_eq!(lst.adjusted(Some(8), 8), lst.estimate);
+    assert_eq!(lst.adjusted(None, 8), lst.estimate);
+    assert_eq!(lst.adjusted(Some(12), 0), lst.estimate);
+  }
+
+  #[test]
+  fn table_covers_runtime_pairs() {
+    assert_eq!(COMPUTE_UNIT_TABLE.len(), RUNTIME_PAIRS.len());
+    for (row, pair) in COMPUTE_UNIT_TABLE.iter().zip(RUNTIME_PAIRS) {
+      assert_eq!(row.input_mint, pair.input_mint);
+      assert_eq!(row.output_mint, pair.output_mint);
+      assert_eq!(row.operation, pair.operation);
+    }
+  }
+}
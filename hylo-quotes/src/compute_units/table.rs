@@ -0,0 +1,495 @@
+//! Generated by the `calibrate_compute_units` example; do not edit by hand.
+//!
+//! Uncalibrated: every pair falls back to `DEFAULT_CUS_WITH_BUFFER` until
+//! the example runs against mainnet.
+
+use hylo_idl::tokens::{
+  TokenMint, CBBTC, HYLOSOL, HYPE, HYUSD, JITOSOL, ONYC, PST, SHYUSD, USDC,
+  WETH, XBTC, XETH, XHYPE, XONYC, XPST, XSOL, XZEC, ZEC,
+};
+
+use super::{ComputeUnitEstimate, PairEstimate};
+use crate::Operation;
+
+/// LST registry size during calibration.
+pub const CALIBRATED_LST_REGISTRY_SIZE: usize = 0;
+
+/// Simulated compute unit distribution per runtime pair.
+pub const COMPUTE_UNIT_TABLE: &[PairEstimate] = &[
+  // Mint hyUSD with JitoSOL
+  PairEstimate {
+    input_mint: JITOSOL::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::MintStablecoinLst,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem hyUSD for JitoSOL
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: JITOSOL::MINT,
+    operation: Operation::RedeemStablecoinLst,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint hyUSD with hyloSOL
+  PairEstimate {
+    input_mint: HYLOSOL::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::MintStablecoinLst,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem hyUSD for hyloSOL
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: HYLOSOL::MINT,
+    operation: Operation::RedeemStablecoinLst,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint xSOL with JitoSOL
+  PairEstimate {
+    input_mint: JITOSOL::MINT,
+    output_mint: XSOL::MINT,
+    operation: Operation::MintLevercoinLst,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem xSOL for JitoSOL
+  PairEstimate {
+    input_mint: XSOL::MINT,
+    output_mint: JITOSOL::MINT,
+    operation: Operation::RedeemLevercoinLst,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint xSOL with hyloSOL
+  PairEstimate {
+    input_mint: HYLOSOL::MINT,
+    output_mint: XSOL::MINT,
+    operation: Operation::MintLevercoinLst,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem xSOL for hyloSOL
+  PairEstimate {
+    input_mint: XSOL::MINT,
+    output_mint: HYLOSOL::MINT,
+    operation: Operation::RedeemLevercoinLst,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Convert hyUSD to xSOL
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: XSOL::MINT,
+    operation: Operation::ConvertStableToLeverLst,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Convert xSOL to hyUSD
+  PairEstimate {
+    input_mint: XSOL::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::ConvertLeverToStableLst,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap JitoSOL to hyloSOL
+  PairEstimate {
+    input_mint: JITOSOL::MINT,
+    output_mint: HYLOSOL::MINT,
+    operation: Operation::SwapLstToLst,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap hyloSOL to JitoSOL
+  PairEstimate {
+    input_mint: HYLOSOL::MINT,
+    output_mint: JITOSOL::MINT,
+    operation: Operation::SwapLstToLst,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap JitoSOL for USDC
+  PairEstimate {
+    input_mint: JITOSOL::MINT,
+    output_mint: USDC::MINT,
+    operation: Operation::SwapLstToUsdc,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap hyloSOL for USDC
+  PairEstimate {
+    input_mint: HYLOSOL::MINT,
+    output_mint: USDC::MINT,
+    operation: Operation::SwapLstToUsdc,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap USDC for JitoSOL
+  PairEstimate {
+    input_mint: USDC::MINT,
+    output_mint: JITOSOL::MINT,
+    operation: Operation::SwapUsdcToLst,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap USDC for hyloSOL
+  PairEstimate {
+    input_mint: USDC::MINT,
+    output_mint: HYLOSOL::MINT,
+    operation: Operation::SwapUsdcToLst,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap cbBTC for USDC
+  PairEstimate {
+    input_mint: CBBTC::MINT,
+    output_mint: USDC::MINT,
+    operation: Operation::SwapExoToUsdc,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap USDC for cbBTC
+  PairEstimate {
+    input_mint: USDC::MINT,
+    output_mint: CBBTC::MINT,
+    operation: Operation::SwapUsdcToExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Deposit hyUSD to Earn Pool
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: SHYUSD::MINT,
+    operation: Operation::DepositToEarnPool,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Withdraw hyUSD from Earn Pool
+  PairEstimate {
+    input_mint: SHYUSD::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::WithdrawFromEarnPool,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint hyUSD with USDC
+  PairEstimate {
+    input_mint: USDC::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::MintStablecoinUsdc,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem hyUSD for USDC
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: USDC::MINT,
+    operation: Operation::RedeemStablecoinUsdc,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint hyUSD with cbBTC
+  PairEstimate {
+    input_mint: CBBTC::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::MintStablecoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem hyUSD for cbBTC
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: CBBTC::MINT,
+    operation: Operation::RedeemStablecoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint xBTC with cbBTC
+  PairEstimate {
+    input_mint: CBBTC::MINT,
+    output_mint: XBTC::MINT,
+    operation: Operation::MintLevercoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem xBTC for cbBTC
+  PairEstimate {
+    input_mint: XBTC::MINT,
+    output_mint: CBBTC::MINT,
+    operation: Operation::RedeemLevercoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Convert hyUSD to xBTC
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: XBTC::MINT,
+    operation: Operation::ConvertStableToLeverExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Convert xBTC to hyUSD
+  PairEstimate {
+    input_mint: XBTC::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::ConvertLeverToStableExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint hyUSD with HYPE
+  PairEstimate {
+    input_mint: HYPE::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::MintStablecoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem hyUSD for HYPE
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: HYPE::MINT,
+    operation: Operation::RedeemStablecoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint xHYPE with HYPE
+  PairEstimate {
+    input_mint: HYPE::MINT,
+    output_mint: XHYPE::MINT,
+    operation: Operation::MintLevercoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem xHYPE for HYPE
+  PairEstimate {
+    input_mint: XHYPE::MINT,
+    output_mint: HYPE::MINT,
+    operation: Operation::RedeemLevercoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Convert hyUSD to xHYPE
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: XHYPE::MINT,
+    operation: Operation::ConvertStableToLeverExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Convert xHYPE to hyUSD
+  PairEstimate {
+    input_mint: XHYPE::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::ConvertLeverToStableExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap HYPE for USDC
+  PairEstimate {
+    input_mint: HYPE::MINT,
+    output_mint: USDC::MINT,
+    operation: Operation::SwapExoToUsdc,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap USDC for HYPE
+  PairEstimate {
+    input_mint: USDC::MINT,
+    output_mint: HYPE::MINT,
+    operation: Operation::SwapUsdcToExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint hyUSD with ZEC
+  PairEstimate {
+    input_mint: ZEC::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::MintStablecoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem hyUSD for ZEC
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: ZEC::MINT,
+    operation: Operation::RedeemStablecoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint xZEC with ZEC
+  PairEstimate {
+    input_mint: ZEC::MINT,
+    output_mint: XZEC::MINT,
+    operation: Operation::MintLevercoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem xZEC for ZEC
+  PairEstimate {
+    input_mint: XZEC::MINT,
+    output_mint: ZEC::MINT,
+    operation: Operation::RedeemLevercoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Convert hyUSD to xZEC
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: XZEC::MINT,
+    operation: Operation::ConvertStableToLeverExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Convert xZEC to hyUSD
+  PairEstimate {
+    input_mint: XZEC::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::ConvertLeverToStableExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap ZEC for USDC
+  PairEstimate {
+    input_mint: ZEC::MINT,
+    output_mint: USDC::MINT,
+    operation: Operation::SwapExoToUsdc,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap USDC for ZEC
+  PairEstimate {
+    input_mint: USDC::MINT,
+    output_mint: ZEC::MINT,
+    operation: Operation::SwapUsdcToExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint hyUSD with PST
+  PairEstimate {
+    input_mint: PST::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::MintStablecoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem hyUSD for PST
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: PST::MINT,
+    operation: Operation::RedeemStablecoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint xPST with PST
+  PairEstimate {
+    input_mint: PST::MINT,
+    output_mint: XPST::MINT,
+    operation: Operation::MintLevercoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem xPST for PST
+  PairEstimate {
+    input_mint: XPST::MINT,
+    output_mint: PST::MINT,
+    operation: Operation::RedeemLevercoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Convert hyUSD to xPST
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: XPST::MINT,
+    operation: Operation::ConvertStableToLeverExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Convert xPST to hyUSD
+  PairEstimate {
+    input_mint: XPST::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::ConvertLeverToStableExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap PST for USDC
+  PairEstimate {
+    input_mint: PST::MINT,
+    output_mint: USDC::MINT,
+    operation: Operation::SwapExoToUsdc,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap USDC for PST
+  PairEstimate {
+    input_mint: USDC::MINT,
+    output_mint: PST::MINT,
+    operation: Operation::SwapUsdcToExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint hyUSD with ONYC
+  PairEstimate {
+    input_mint: ONYC::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::MintStablecoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem hyUSD for ONYC
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: ONYC::MINT,
+    operation: Operation::RedeemStablecoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint xONYC with ONYC
+  PairEstimate {
+    input_mint: ONYC::MINT,
+    output_mint: XONYC::MINT,
+    operation: Operation::MintLevercoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem xONYC for ONYC
+  PairEstimate {
+    input_mint: XONYC::MINT,
+    output_mint: ONYC::MINT,
+    operation: Operation::RedeemLevercoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Convert hyUSD to xONYC
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: XONYC::MINT,
+    operation: Operation::ConvertStableToLeverExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Convert xONYC to hyUSD
+  PairEstimate {
+    input_mint: XONYC::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::ConvertLeverToStableExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap ONYC for USDC
+  PairEstimate {
+    input_mint: ONYC::MINT,
+    output_mint: USDC::MINT,
+    operation: Operation::SwapExoToUsdc,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap USDC for ONYC
+  PairEstimate {
+    input_mint: USDC::MINT,
+    output_mint: ONYC::MINT,
+    operation: Operation::SwapUsdcToExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint hyUSD with WETH
+  PairEstimate {
+    input_mint: WETH::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::MintStablecoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem hyUSD for WETH
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: WETH::MINT,
+    operation: Operation::RedeemStablecoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Mint xETH with WETH
+  PairEstimate {
+    input_mint: WETH::MINT,
+    output_mint: XETH::MINT,
+    operation: Operation::MintLevercoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Redeem xETH for WETH
+  PairEstimate {
+    input_mint: XETH::MINT,
+    output_mint: WETH::MINT,
+    operation: Operation::RedeemLevercoinExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Convert hyUSD to xETH
+  PairEstimate {
+    input_mint: HYUSD::MINT,
+    output_mint: XETH::MINT,
+    operation: Operation::ConvertStableToLeverExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Convert xETH to hyUSD
+  PairEstimate {
+    input_mint: XETH::MINT,
+    output_mint: HYUSD::MINT,
+    operation: Operation::ConvertLeverToStableExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap WETH for USDC
+  PairEstimate {
+    input_mint: WETH::MINT,
+    output_mint: USDC::MINT,
+    operation: Operation::SwapExoToUsdc,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+  // Swap USDC for WETH
+  PairEstimate {
+    input_mint: USDC::MINT,
+    output_mint: WETH::MINT,
+    operation: Operation::SwapUsdcToExo,
+    estimate: ComputeUnitEstimate::UNCALIBRATED,
+  },
+];
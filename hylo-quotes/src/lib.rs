@@ -15,6 +15,11 @@
 //!   Slower but validates that transactions would actually succeed (e.g.,
 //!   checks wallet balances).
 //!
+//! `FallbackStrategy`, `VerifyingStrategy` and `HybridStrategy` combine the
+//! two: simulate only when the state quote fails, reject state quotes the
+//! simulation disagrees with, or keep state amounts with simulated compute
+//! units.
+//!
 //! # Examples
 //!
 //! ## Using `ProtocolStateStrategy`
@@ -100,47 +105,67 @@ use fix::prelude::{UFix64, UFixValue64};
 use fix::typenum::Integer;
 use hylo_idl::tokens::{CBBTC, HYLOSOL, HYPE, JITOSOL, ONYC, PST, WETH, ZEC};
 use hylo_idl::with_exo_pairs;
+use quote_expiry::QuoteExpiry;
 
+mod combinator_strategy;
+pub mod compute_units;
+pub mod portfolio;
 pub mod prelude;
+pub mod price_breakdown;
 pub mod protocol_state;
 mod protocol_state_strategy;
+mod quote_batch;
+pub mod quote_expiry;
 mod quote_metadata;
 mod quote_strategy;
+pub mod rebalance_arbitrage;
+pub mod route_status;
 mod runtime_quote_strategy;
 pub mod simulated_operation;
 mod simulation_strategy;
+pub mod slippage_recommendation;
 pub mod token_operation;
+pub mod treasury;
 
+pub use combinator_strategy::{
+  FallbackStrategy, HybridStrategy, VerifyingStrategy,
+};
 pub use hylo_clients::util::LST;
 pub use protocol_state_strategy::ProtocolStateStrategy;
+pub use quote_batch::{QuoteBatch, QuoteRequest};
 pub use quote_metadata::{Operation, QuoteMetadata};
-pub use quote_strategy::QuoteStrategy;
-pub use runtime_quote_strategy::RuntimeQuoteStrategy;
+pub use quote_strategy::{QuoteStrategy, StateQuoteStrategy};
+pub use runtime_quote_strategy::{
+  pair_operation, RuntimePair, RuntimeQuoteStrategy, RuntimeStateQuoteStrategy,
+  RUNTIME_PAIRS,
+};
 pub use simulated_operation::ComputeUnitInfo;
 pub use simulation_strategy::SimulationStrategy;
 
-/// Default buffered compute units for all exchange operations.
+/// Default buffered compute units for exchange operations.
 ///
 /// This is a buffered estimate (higher than measured values ~74k-97k CU) that
-/// provides a safe default for all current quote operations. Measured values
-/// came from calibration tool, but this value includes additional buffer for
-/// safety across all operation types.
-///
-/// In the future, this could be replaced with per-instruction defaults based
-/// on more comprehensive statistical analysis.
+/// provides a safe default for all current quote operations. State quotes use
+/// per-pair budgets from [`compute_units`] and fall back to this value
+/// for pairs without calibration samples.
 pub const DEFAULT_CUS_WITH_BUFFER: u64 = 100_000;
 
 /// Typed executable quote with amounts, instructions, and compute units.
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub struct ExecutableQuote<In: Integer, Out: Integer, Fee: Integer> {
   pub amount_in: UFix64<In>,
   pub amount_out: UFix64<Out>,
+  /// Least output the instructions accept under the slippage tolerance
+  pub min_amount_out: UFix64<Out>,
   pub compute_units: u64,
   pub compute_unit_strategy: ComputeUnitStrategy,
   pub fee_amount: UFix64<Fee>,
   pub fee_mint: Pubkey,
   pub instructions: Vec<Instruction>,
   pub address_lookup_tables: Vec<Pubkey>,
+  /// When the state inputs behind the quote lapse
+  pub expiry: QuoteExpiry,
 }
 
 /// Executable quote with runtime exponent information.
@@ -148,12 +173,14 @@ pub struct ExecutableQuote<In: Integer, Out: Integer, Fee: Integer> {
 pub struct ExecutableQuoteValue {
   pub amount_in: UFixValue64,
   pub amount_out: UFixValue64,
+  pub min_amount_out: UFixValue64,
   pub compute_units: u64,
   pub compute_unit_strategy: ComputeUnitStrategy,
   pub fee_amount: UFixValue64,
   pub fee_mint: Pubkey,
   pub instructions: Vec<Instruction>,
   pub address_lookup_tables: Vec<Pubkey>,
+  pub expiry: QuoteExpiry,
 }
 
 impl<In: Integer, Out: Integer, Fee: Integer>
@@ -163,12 +190,14 @@ impl<In: Integer, Out: Integer, Fee: Integer>
     ExecutableQuoteValue {
       amount_in: quote.amount_in.into(),
       amount_out: quote.amount_out.into(),
+      min_amount_out: quote.min_amount_out.into(),
       compute_units: quote.compute_units,
       compute_unit_strategy: quote.compute_unit_strategy,
       fee_amount: quote.fee_amount.into(),
       fee_mint: quote.fee_mint,
       instructions: quote.instructions,
       address_lookup_tables: quote.address_lookup_tables,
+      expiry: quote.expiry,
     }
   }
 }
@@ -0,0 +1,478 @@
+//! Wallet valuation across Hylo tokens.
+//!
+//! Each balance is marked in USD and hyUSD at current NAVs: hyUSD at
+//! `stablecoin_nav`, sHYUSD at `lp_token_nav`, levercoins at their redeem
+//! NAV, LSTs at their epoch SOL price and exo collateral at oracle spot.
+//!
+//! Exit value runs the redeem [`TokenOperation`] for the whole balance, so
+//! it is net of fees and an `Err` while the route is gated. hyUSD and xSOL
+//! exit to jitoSOL, sHYUSD to hyUSD and exo levercoins to their
+//! collateral. LSTs and exo collateral exit as themselves at mark.
+
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::TokenAccount;
+use anyhow::{anyhow, Result};
+use fix::prelude::*;
+use fix::typenum::Integer;
+use hylo_core::error::CoreError;
+use hylo_core::exchange_context::ExchangeContext;
+use hylo_core::lst::sol_price::LstSolPrice;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_idl::pda;
+use hylo_idl::tokens::{
+  Exo, TokenMint, CBBTC, HYLOSOL, HYPE, HYUSD, JITOSOL, ONYC, PST, SHYUSD,
+  WETH, XBTC, XETH, XHYPE, XONYC, XPST, XSOL, XZEC, ZEC,
+};
+use hylo_idl::with_exo_pairs;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+
+use crate::protocol_state::ProtocolState;
+use crate::token_operation::{TokenOperation, TokenOperationExt};
+use crate::LST;
+
+/// Raw token balances of one wallet, by mint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WalletBalances {
+  balances: BTreeMap<Pubkey, u64>,
+}
+
+impl WalletBalances {
+  /// Sets the balance of `mint` in base units, replacing any earlier one.
+  #[must_use]
+  pub fn with_balance(mut self, mint: Pubkey, amount: u64) -> WalletBalances {
+    self.balances.insert(mint, amount);
+    self
+  }
+
+  /// Balance of `mint` in base units, zero if unset.
+  #[must_use]
+  pub fn get(&self, mint: &Pubkey) -> u64 {
+    self.balances.get(mint).copied().unwrap_or_default()
+  }
+
+  /// Reads the wallet's associated token account for every mint in
+  /// [`PORTFOLIO_MINTS`]. Missing accounts read as zero.
+  ///
+  /// # Errors
+  /// * RPC failure
+  /// * Token account deserialization
+  pub async fn fetch(
+    rpc_client: &RpcClient,
+    wallet: Pubkey,
+  ) -> Result<WalletBalances> {
+    let pubkeys = PORTFOLIO_MINTS
+      .iter()
+      .map(|mint| pda::ata(wallet, *mint))
+      .collect::<Vec<_>>();
+    let data = rpc_client
+      .get_multiple_accounts(&pubkeys)
+      .await
+      .map_err(|e| anyhow!("Failed to fetch token accounts from RPC: {e}"))?;
+    PORTFOLIO_MINTS.iter().zip(data).try_fold(
+      WalletBalances::default(),
+      |balances, (mint, account)| match account {
+        Some(account) => {
+          let token =
+            TokenAccount::try_deserialize(&mut account.data.as_slice())?;
+          Ok(balances.with_balance(*mint, token.amount))
+        }
+        None => Ok(balances),
+      },
+    )
+  }
+}
+
+/// Redemption of a full balance through its exit route.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionExit {
+  pub out_mint: Pubkey,
+  pub out_amount: UFixValue64,
+  pub fee_mint: Pubkey,
+  pub fee_amount: UFixValue64,
+  /// Output marked as a held balance of `out_mint`
+  pub value_usd: UFix64<N9>,
+  pub value_hyusd: UFix64<N6>,
+}
+
+/// One marked wallet balance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Holding {
+  pub mint: Pubkey,
+  pub balance: UFixValue64,
+  /// USD per whole token the balance is marked at
+  pub price_usd: UFix64<N9>,
+  pub value_usd: UFix64<N9>,
+  pub value_hyusd: UFix64<N6>,
+  /// Levercoin mint NAV, what adding to the position costs; `price_usd`
+  /// is the redeem NAV. `None` for other tokens or when unavailable.
+  pub mint_nav: Option<UFix64<N9>>,
+  /// Exit of the whole balance; `Err` while the route is gated
+  pub exit: Result<PositionExit, CoreError>,
+}
+
+/// Wallet valuation at one protocol state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Portfolio {
+  /// Marked nonzero balances, in [`PORTFOLIO_MINTS`] order
+  pub holdings: Vec<Holding>,
+  /// Nonzero balances that could not be marked, e.g. an LST whose
+  /// epoch price is outdated
+  pub unpriced: Vec<(Pubkey, CoreError)>,
+  pub value_usd: UFix64<N9>,
+  pub value_hyusd: UFix64<N6>,
+  /// Sum over holdings with an open exit route
+  pub exit_value_usd: UFix64<N9>,
+  pub exit_value_hyusd: UFix64<N6>,
+}
+
+/// USD value of `amount` at `price` (USD per whole token).
+pub(crate) fn usd_value<Exp: Integer>(
+  amount: UFix64<Exp>,
+  price: UFix64<N9>,
+) -> Result<UFix64<N9>, CoreError>
+where
+  UFix64<Exp>: FixExt,
+{
+  amount
+    .checked_convert::<N9>()
+    .and_then(|amount| amount.mul_div_floor(price, UFix64::<N9>::one()))
+    .ok_or(CoreError::TokenAmountPrecision)
+}
+
+/// hyUSD worth `usd` at `stablecoin_nav`.
+fn hyusd_value(
+  usd: UFix64<N9>,
+  stablecoin_nav: UFix64<N9>,
+) -> Result<UFix64<N6>, CoreError> {
+  (stablecoin_nav > UFix64::zero())
+    .then(|| usd.mul_div_floor(UFix64::one(), stablecoin_nav))
+    .flatten()
+    .and_then(UFix64::checked_convert::<N6>)
+    .ok_or(CoreError::TokenAmountPrecision)
+}
+
+/// Prices shared by every holding in one valuation.
+struct Marks<'a, C: SolanaClock> {
+  state: &'a ProtocolState<C>,
+  stablecoin_nav: UFix64<N9>,
+}
+
+impl<C: SolanaClock> Marks<'_, C> {
+  fn holding<Exp: Integer>(
+    &self,
+    mint: Pubkey,
+    balance: UFix64<Exp>,
+    price_usd: UFix64<N9>,
+    mint_nav: Option<UFix64<N9>>,
+    exit: Result<PositionExit, CoreError>,
+  ) -> Result<Holding, CoreError>
+  where
+    UFix64<Exp>: FixExt,
+  {
+    let value_usd = usd_value(balance, price_usd)?;
+    Ok(Holding {
+      mint,
+      balance: balance.into(),
+      price_usd,
+      value_usd,
+      value_hyusd: hyusd_value(value_usd, self.stablecoin_nav)?,
+      mint_nav,
+      exit,
+    })
+  }
+
+  /// Runs `IN -> OUT` for `balance`, marking the output at `out_price`.
+  fn exit<IN: TokenMint, OUT: TokenMint>(
+    &self,
+    balance: UFix64<IN::Exp>,
+    out_price: UFix64<N9>,
+  ) -> Result<PositionExit, CoreError>
+  where
+    ProtocolState<C>: TokenOperation<IN, OUT>,
+    UFix64<OUT::Exp>: FixExt,
+  {
+    let out = self.state.output::<IN, OUT>(balance)?;
+    let value_usd = usd_value(out.out_amount, out_price)?;
+    Ok(PositionExit {
+      out_mint: OUT::MINT,
+      out_amount: out.out_amount.into(),
+      fee_mint: out.fee_mint,
+      fee_amount: out.fee_amount.into(),
+      value_usd,
+      value_hyusd: hyusd_value(value_usd, self.stablecoin_nav)?,
+    })
+  }
+
+  /// Collateral exits as itself, at mark and without fee.
+  fn hold<T: TokenMint>(
+    &self,
+    balance: UFix64<T::Exp>,
+    price: UFix64<N9>,
+  ) -> Result<PositionExit, CoreError>
+  where
+    UFix64<T::Exp>: FixExt,
+  {
+    let value_usd = usd_value(balance, price)?;
+    Ok(PositionExit {
+      out_mint: T::MINT,
+      out_amount: balance.into(),
+      fee_mint: T::MINT,
+      fee_amount: UFix64::<T::Exp>::zero().into(),
+      value_usd,
+      value_hyusd: hyusd_value(value_usd, self.stablecoin_nav)?,
+    })
+  }
+
+  fn hyusd(&self, balance: UFix64<N6>) -> Result<Holding, CoreError> {
+    let exit = self
+      .state
+      .lst_usd_price::<JITOSOL>()
+      .and_then(|price| self.exit::<HYUSD, JITOSOL>(balance, price));
+    self.holding(HYUSD::MINT, balance, self.stablecoin_nav, None, exit)
+  }
+
+  fn shyusd(&self, balance: UFix64<N6>) -> Result<Holding, CoreError> {
+    let price = self
+      .state
+      .shyusd_nav()?
+      .mul_div_floor(self.stablecoin_nav, UFix64::one())
+      .ok_or(CoreError::TokenAmountPrecision)?;
+    let exit = self.exit::<SHYUSD, HYUSD>(balance, self.stablecoin_nav);
+    self.holding(SHYUSD::MINT, balance, price, None, exit)
+  }
+
+  fn xsol(&self, balance: UFix64<N6>) -> Result<Holding, CoreError> {
+    let context = &self.state.exchange_context;
+    let exit = self
+      .state
+      .lst_usd_price::<JITOSOL>()
+      .and_then(|price| self.exit::<XSOL, JITOSOL>(balance, price));
+    self.holding(
+      XSOL::MINT,
+      balance,
+      context.levercoin_redeem_nav()?,
+      context.levercoin_mint_nav().ok(),
+      exit,
+    )
+  }
+
+  fn lst<L: LST>(&self, balance: UFix64<N9>) -> Result<Holding, CoreError> {
+    let price = self.state.lst_usd_price::<L>()?;
+    let exit = self.hold::<L>(balance, price);
+    self.holding(L::MINT, balance, price, None, exit)
+  }
+
+  fn exo_levercoin<E: Exo, X: TokenMint>(
+    &self,
+    balance: UFix64<X::Exp>,
+  ) -> Result<Holding, CoreError>
+  where
+    ProtocolState<C>: TokenOperation<X, E>,
+    UFix64<E::Exp>: FixExt,
+    UFix64<X::Exp>: FixExt,
+  {
+    let context = &self.state.exo_pair::<E>()?.context;
+    let exit =
+      self.exit::<X, E>(balance, context.collateral_oracle_price().spot);
+    self.holding(
+      X::MINT,
+      balance,
+      context.levercoin_redeem_nav()?,
+      context.levercoin_mint_nav().ok(),
+      exit,
+    )
+  }
+
+  fn exo_collateral<E: Exo>(
+    &self,
+    balance: UFix64<E::Exp>,
+  ) -> Result<Holding, CoreError>
+  where
+    UFix64<E::Exp>: FixExt,
+  {
+    let price = self
+      .state
+      .exo_pair::<E>()?
+      .context
+      .collateral_oracle_price()
+      .spot;
+    let exit = self.hold::<E>(balance, price);
+    self.holding(E::MINT, balance, price, None, exit)
+  }
+}
+
+macro_rules! portfolio_table {
+  ($(($exo:ident, $lever:ident, $exp:ty)),+ $(,)?) => {
+    /// Mints a [`Portfolio`] values, in report order. Balances in exo
+    /// pairs [`ProtocolState`] cannot load are reported unpriced with
+    /// [`CoreError::UnknownExoMint`].
+    pub const PORTFOLIO_MINTS: &[Pubkey] = &[
+      HYUSD::MINT,
+      SHYUSD::MINT,
+      XSOL::MINT,
+      $(<$lever>::MINT,)+
+      JITOSOL::MINT,
+      HYLOSOL::MINT,
+      $(<$exo>::MINT,)+
+    ];
+
+    impl<C: SolanaClock> Marks<'_, C> {
+      /// Marks `amount` base units of `mint`; `None` for mints outside
+      /// [`PORTFOLIO_MINTS`].
+      fn mark(
+        &self,
+        mint: Pubkey,
+        amount: u64,
+      ) -> Option<Result<Holding, CoreError>> {
+        match mint {
+          HYUSD::MINT => Some(self.hyusd(UFix64::new(amount))),
+          SHYUSD::MINT => Some(self.shyusd(UFix64::new(amount))),
+          XSOL::MINT => Some(self.xsol(UFix64::new(amount))),
+          JITOSOL::MINT => Some(self.lst::<JITOSOL>(UFix64::new(amount))),
+          HYLOSOL::MINT => Some(self.lst::<HYLOSOL>(UFix64::new(amount))),
+          $(<$lever>::MINT => Some(
+            self.exo_levercoin::<$exo, $lever>(UFix64::new(amount)),
+          ),)+
+          $(<$exo>::MINT => Some(
+            self.exo_collateral::<$exo>(UFix64::new(amount)),
+          ),)+
+          _ => None,
+        }
+      }
+    }
+  };
+}
+
+with_exo_pairs!(portfolio_table);
+
+impl<C: SolanaClock> ProtocolState<C> {
+  /// LST/USD at the current epoch's SOL price and SOL/USD spot.
+  ///
+  /// # Errors
+  /// * LST header not loaded or its price outdated
+  /// * Arithmetic overflow
+  pub(crate) fn lst_usd_price<L: LST>(&self) -> Result<UFix64<N9>, CoreError> {
+    let context = &self.exchange_context;
+    let lst_price: LstSolPrice = self.lst_header::<L>()?.price_sol.into();
+    lst_price
+      .get_epoch_price(context.clock.epoch())?
+      .mul_div_floor(context.collateral_oracle_price().spot, UFix64::one())
+      .ok_or(CoreError::TokenAmountPrecision)
+  }
+
+  /// Values `balances` in USD and hyUSD, with the exit of each position.
+  /// Zero balances and mints outside [`PORTFOLIO_MINTS`] are skipped.
+  ///
+  /// # Errors
+  /// * Stablecoin NAV
+  /// * Total overflow
+  pub fn portfolio(
+    &self,
+    balances: &WalletBalances,
+  ) -> Result<Portfolio, CoreError> {
+    let marks = Marks {
+      state: self,
+      stablecoin_nav: self.exchange_context.stablecoin_nav()?,
+    };
+    let mut portfolio = Portfolio {
+      holdings: Vec::new(),
+      unpriced: Vec::new(),
+      value_usd: UFix64::zero(),
+      value_hyusd: UFix64::zero(),
+      exit_value_usd: UFix64::zero(),
+      exit_value_hyusd: UFix64::zero(),
+    };
+    let held = PORTFOLIO_MINTS
+      .iter()
+      .map(|mint| (*mint, balances.get(mint)))
+      .filter(|(_, amount)| *amount > 0);
+    for (mint, amount) in held {
+      match marks.mark(mint, amount) {
+        Some(Ok(holding)) => portfolio.add(holding)?,
+        Some(Err(err)) => portfolio.unpriced.push((mint, err)),
+        None => {}
+      }
+    }
+    Ok(portfolio)
+  }
+}
+
+impl Portfolio {
+  fn add(&mut self, holding: Holding) -> Result<(), CoreError> {
+    let overflow = CoreError::TokenAmountPrecision;
+    self.value_usd = self
+      .value_usd
+      .checked_add(&holding.value_usd)
+      .ok_or(overflow)?;
+    self.value_hyusd = self
+      .value_hyusd
+      .checked_add(&holding.value_hyusd)
+      .ok_or(overflow)?;
+    if let Ok(exit) = &holding.exit {
+      self.exit_value_usd = self
+        .exit_value_usd
+        .checked_add(&exit.value_usd)
+        .ok_or(overflow)?;
+      self.exit_value_hyusd = self
+        .exit_value_hyusd
+        .checked_add(&exit.value_hyusd)
+        .ok_or(overflow)?;
+    }
+    self.holdings.push(holding);
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn usd_value_widens_and_floors() -> Result<(), CoreError> {
+    // 0.5 cbBTC (N8) at $100,000
+    let amount = UFix64::<N8>::new(50_000_000);
+    let price = UFix64::<N9>::new(100_000_000_000_000);
+    assert_eq!(usd_value(amount, price)?, UFix64::new(50_000_000_000_000));
+    // One atom of an N9 token at $1.5 rounds down to a whole atom
+    assert_eq!(
+      usd_value(UFix64::<N9>::new(1), UFix64::new(1_500_000_000))?,
+      UFix64::new(1)
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn hyusd_value_scales_by_nav() -> Result<(), CoreError> {
+    let usd = UFix64::<N9>::new(90_000_000_000);
+    assert_eq!(hyusd_value(usd, UFix64::one())?, UFix64::new(90_000_000));
+    // Under depeg a hyUSD is worth $0.90
+    let nav = UFix64::new(900_000_000);
+    assert_eq!(hyusd_value(usd, nav)?, UFix64::new(100_000_000));
+    assert_eq!(
+      hyusd_value(usd, UFix64::zero()),
+      Err(CoreError::TokenAmountPrecision)
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn wallet_balances_default_to_zero() {
+    let balances = WalletBalances::default()
+      .with_balance(HYUSD::MINT, 5)
+      .with_balance(HYUSD::MINT, 7);
+    assert_eq!(balances.get(&HYUSD::MINT), 7);
+    assert_eq!(balances.get(&XSOL::MINT), 0);
+  }
+
+  #[test]
+  fn portfolio_mints_cover_every_exo_pair() {
+    assert_eq!(PORTFOLIO_MINTS.len(), 5 + 2 * 6);
+    assert!(PORTFOLIO_MINTS.contains(&XBTC::MINT));
+    assert!(PORTFOLIO_MINTS.contains(&HYPE::MINT));
+    assert!(PORTFOLIO_MINTS.contains(&XZEC::MINT));
+    assert!(PORTFOLIO_MINTS.contains(&ZEC::MINT));
+  }
+}
@@ -12,24 +12,49 @@ pub use hylo_idl::tokens::{
   WETH, XBTC, XETH, XHYPE, XONYC, XPST, XSOL, XZEC, ZEC,
 };
 
+// Wallet portfolio valuation
+pub use crate::portfolio::{
+  Holding, Portfolio, PositionExit, WalletBalances, PORTFOLIO_MINTS,
+};
+// Quote price decomposition
+pub use crate::price_breakdown::{PriceBreakdown, PriceComponents};
 // Protocol state
 pub use crate::protocol_state::{
   ProtocolAccounts, ProtocolState, RpcStateProvider, StateProvider,
 };
+// Quote expiry and revalidation
+pub use crate::quote_expiry::{
+  revalidate, Deadline, QuoteExpiry, Revalidation,
+};
+// Rebalance arbitrage sizing
+pub use crate::rebalance_arbitrage::RebalanceArbitrage;
+// Route gate and limiter diagnostics
+pub use crate::route_status::{LimitHeadroom, Reopen, RouteStatus};
+// Per-quote slippage recommendation
+pub use crate::slippage_recommendation::{
+  SlippageComponents, SlippageModel, SlippageRecommendation,
+};
 // SimulatedOperation (event extraction)
 pub use crate::simulated_operation::{
   SimulatedOperation, SimulatedOperationExt,
 };
 // TokenOperation (pure math)
 pub use crate::token_operation::{
-  LstSwapOperationOutput, MintOperationOutput, OperationOutput,
-  RedeemOperationOutput, SwapOperationOutput, TokenOperation,
-  TokenOperationExt,
+  FirstClosed, Gate, GateCheck, GateSink, Limiter, LstSwapOperationOutput,
+  MintOperationOutput, OperationOutput, Pair, RedeemOperationOutput,
+  RouteLimit, SwapOperationOutput, TokenOperation, TokenOperationExt,
+};
+// Treasury fee vaults
+pub use crate::treasury::{
+  FeeAccrual, FeeAttribution, FeeSource, FeeVault, FeeVaults, FeeWithdrawal,
+  TreasuryReport, WithdrawalPlan, FEE_MINTS,
 };
 // Strategy implementations
 pub use crate::ProtocolStateStrategy;
+// Strategy combinators
+pub use crate::{FallbackStrategy, HybridStrategy, VerifyingStrategy};
 // Quoting traits
-pub use crate::QuoteStrategy;
+pub use crate::{QuoteStrategy, StateQuoteStrategy};
 // LST marker trait
 pub use crate::LST;
 // Core quote types
@@ -38,4 +63,12 @@ pub use crate::{
   Operation, QuoteMetadata, DEFAULT_CUS_WITH_BUFFER,
 };
 // Quoting strategies
-pub use crate::{RuntimeQuoteStrategy, SimulationStrategy};
+pub use crate::{
+  RuntimeQuoteStrategy, RuntimeStateQuoteStrategy, SimulationStrategy,
+};
+// Runtime pair table
+pub use crate::{pair_operation, RuntimePair, RUNTIME_PAIRS};
+// Compute unit budgets
+pub use crate::compute_units::{pair_budget, ComputeUnitEstimate};
+// Batch quoting
+pub use crate::{QuoteBatch, QuoteRequest};
@@ -0,0 +1,180 @@
+//! What a route's price is made of.
+//!
+//! [`OperationOutput`] carries totals and the fee. [`PriceBreakdown`] adds
+//! the state each route priced against: oracle spot and the confidence
+//! bound the conversion used, LST prices, protocol token NAVs, the
+//! stability mode and the fee tier it sets, and the rebalance curve price.
+
+use fix::prelude::{UFix64, N5, N9};
+use fix::typenum::Integer;
+use hylo_core::rebalance::mode::RebalanceMode;
+
+use crate::token_operation::OperationOutput;
+
+/// State inputs behind a route's conversion, `None` where the route does
+/// not read them.
+///
+/// Levercoin NAVs already include the oracle confidence bound, upper for
+/// mints and lower for redemptions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PriceComponents {
+  /// Collateral/USD oracle spot (SOL/USD on LST routes)
+  pub oracle_spot: Option<UFix64<N9>>,
+  /// Collateral/USD oracle confidence half-width
+  pub oracle_conf: Option<UFix64<N9>>,
+  /// Confidence bound the conversion priced collateral at instead of spot
+  pub oracle_bound: Option<UFix64<N9>>,
+  /// SOL price of the LST going in, from its header
+  pub input_lst_price: Option<UFix64<N9>>,
+  /// SOL price of the LST coming out, from its header
+  pub output_lst_price: Option<UFix64<N9>>,
+  /// Stake pool true price net of the LST rebalance fee, used by the
+  /// rebalance routes in place of the header price
+  pub lst_true_price: Option<UFix64<N9>>,
+  /// USD NAV of the protocol token going in
+  pub input_nav: Option<UFix64<N9>>,
+  /// USD NAV of the protocol token coming out
+  pub output_nav: Option<UFix64<N9>>,
+  /// Stability mode selecting the fee tier
+  pub rebalance_mode: Option<RebalanceMode>,
+  /// Collateral ratio selecting `rebalance_mode`
+  pub collateral_ratio: Option<UFix64<N9>>,
+  /// Collateral/USD price on the rebalance curve at the post-trade CR
+  pub curve_price: Option<UFix64<N9>>,
+  /// Fee rate the route's schedule sets at the current mode or CR, before
+  /// the trade moves either
+  pub fee_tier: Option<UFix64<N5>>,
+  /// Fee rate the schedule sets across the mode boundary nearest the
+  /// collateral ratio, on routes whose fee steps by mode
+  pub boundary_fee_tier: Option<UFix64<N5>>,
+}
+
+/// Price components of one quote with its realized fee and rate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PriceBreakdown {
+  pub components: PriceComponents,
+  /// Fee as a share of the amount it was taken from
+  pub fee_rate: f64,
+  /// Output tokens per input token, after fees
+  pub effective_price: f64,
+}
+
+impl PriceBreakdown {
+  #[must_use]
+  pub fn new<InExp: Integer, OutExp: Integer, FeeExp: Integer>(
+    components: PriceComponents,
+    output: &OperationOutput<InExp, OutExp, FeeExp>,
+  ) -> PriceBreakdown {
+    PriceBreakdown {
+      components,
+      fee_rate: ratio(output.fee_amount.to_f64(), output.fee_base.to_f64()),
+      effective_price: ratio(
+        output.out_amount.to_f64(),
+        output.in_amount.to_f64(),
+      ),
+    }
+  }
+
+  /// Confidence bound relative to spot, `bound / spot - 1`. Negative when
+  /// collateral going in was priced below spot, positive when collateral
+  /// coming out was priced above it.
+  #[must_use]
+  pub fn confidence_spread(&self) -> Option<f64> {
+    let PriceComponents {
+      oracle_spot,
+      oracle_bound,
+      ..
+    } = self.components;
+    relative(oracle_bound?, oracle_spot?)
+  }
+
+  /// Rebalance curve price relative to oracle spot, `curve / spot - 1`.
+  /// Negative is a discount to spot, positive a premium.
+  #[must_use]
+  pub fn curve_premium(&self) -> Option<f64> {
+    let PriceComponents {
+      oracle_spot,
+      curve_price,
+      ..
+    } = self.components;
+    relative(curve_price?, oracle_spot?)
+  }
+
+  /// Adjusted true price relative to the header price of the same LST.
+  #[must_use]
+  pub fn lst_true_price_adjustment(&self) -> Option<f64> {
+    let PriceComponents {
+      input_lst_price,
+      output_lst_price,
+      lst_true_price,
+      ..
+    } = self.components;
+    relative(lst_true_price?, input_lst_price.or(output_lst_price)?)
+  }
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+  if denominator > 0.0 {
+    numerator / denominator
+  } else {
+    0.0
+  }
+}
+
+fn relative(value: UFix64<N9>, base: UFix64<N9>) -> Option<f64> {
+  (base > UFix64::zero()).then(|| value.to_f64() / base.to_f64() - 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Pubkey;
+  use fix::prelude::N6;
+
+  use super::*;
+
+  fn output(
+    in_amount: u64,
+    out_amount: u64,
+    fee_amount: u64,
+  ) -> OperationOutput<N9, N6, N9> {
+    OperationOutput {
+      in_amount: UFix64::new(in_amount),
+      out_amount: UFix64::new(out_amount),
+      fee_amount: UFix64::new(fee_amount),
+      fee_mint: Pubkey::default(),
+      fee_base: UFix64::new(in_amount),
+      marginal_rate: 1.0,
+    }
+  }
+
+  #[test]
+  fn fee_and_effective_price() {
+    let breakdown = PriceBreakdown::new(
+      PriceComponents::default(),
+      &output(2_000_000_000, 300_000_000, 10_000_000),
+    );
+    assert!((breakdown.fee_rate - 0.005).abs() < 1e-12);
+    assert!((breakdown.effective_price - 150.0).abs() < 1e-9);
+    assert_eq!(breakdown.confidence_spread(), None);
+    assert_eq!(breakdown.curve_premium(), None);
+  }
+
+  #[test]
+  fn spreads_relative_to_spot() {
+    let components = PriceComponents {
+      oracle_spot: Some(UFix64::new(200_000_000_000)),
+      oracle_bound: Some(UFix64::new(199_000_000_000)),
+      curve_price: Some(UFix64::new(202_000_000_000)),
+      input_lst_price: Some(UFix64::new(1_250_000_000)),
+      lst_true_price: Some(UFix64::new(1_237_500_000)),
+      ..PriceComponents::default()
+    };
+    let breakdown = PriceBreakdown::new(components, &output(1, 1, 0));
+    let near = |value: Option<f64>, expected: f64| {
+      value.is_some_and(|value| (value - expected).abs() < 1e-12)
+    };
+    assert!(near(breakdown.confidence_spread(), -0.005));
+    assert!(near(breakdown.curve_premium(), 0.01));
+    assert!(near(breakdown.lst_true_price_adjustment(), -0.01));
+  }
+}
@@ -227,6 +227,40 @@ impl ProtocolAccounts {
     })
   }
 
+  /// Accounts paired with their pubkeys, in [`ProtocolAccounts::PUBKEYS`]
+  /// order.
+  #[must_use]
+  pub fn keyed_accounts(&self) -> [(Pubkey, &Account); 25] {
+    let accounts = [
+      &self.hylo,
+      &self.jitosol_header,
+      &self.hylosol_header,
+      &self.hyusd_mint,
+      &self.shyusd_mint,
+      &self.xsol_mint,
+      &self.pool_config,
+      &self.hyusd_pool,
+      &self.sol_usd_pyth,
+      &self.clock,
+      &self.cbbtc_exo_pair,
+      &self.cbbtc_vault,
+      &self.xbtc_mint,
+      &self.btc_usd_pyth,
+      &self.usdc_pair,
+      &self.usdc_usd_pyth,
+      &self.jitosol_pool_state,
+      &self.hylosol_pool_state,
+      &self.jitosol_vault,
+      &self.hylosol_vault,
+      &self.usdc_vault,
+      &self.hype_exo_pair,
+      &self.hype_vault,
+      &self.xhype_mint,
+      &self.hype_usd_pyth,
+    ];
+    std::array::from_fn(|i| (ProtocolAccounts::PUBKEYS[i], accounts[i]))
+  }
+
   /// Validate that pubkeys and accounts match expected protocol accounts
   ///
   /// Validates:
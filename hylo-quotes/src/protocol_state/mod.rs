@@ -6,5 +6,5 @@ pub use accounts::ProtocolAccounts;
 pub use provider::{RpcStateProvider, StateProvider};
 pub use state::{
   build_exo_pair_state, build_lst_exchange_context, stablecoin_oracle_valid,
-  ExoPairState, ProtocolState, UsdcExchangeState,
+  EarnPoolState, ExoPairState, ProtocolState, UsdcExchangeState,
 };
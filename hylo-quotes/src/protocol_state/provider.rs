@@ -11,6 +11,9 @@ use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use fix::prelude::UFix64;
 use fix::util::FixExt;
+use hylo_clients::util::{
+  deserialize_lookup_table, lst_registry_size, LST_REGISTRY_LOOKUP_TABLE,
+};
 use hylo_core::error::CoreError;
 use hylo_core::exchange_context::LstExchangeContext;
 use hylo_core::idl::exchange::accounts::Hylo;
@@ -149,13 +152,24 @@ with_exo_pairs!(exo_pair_dispatch);
 #[async_trait]
 impl StateProvider<Clock> for RpcStateProvider {
   async fn fetch_state(&self) -> Result<ProtocolState<Clock>> {
-    let account_data = self
+    let mut pubkeys = ProtocolAccounts::PUBKEYS.to_vec();
+    pubkeys.push(LST_REGISTRY_LOOKUP_TABLE);
+    let mut account_data = self
       .rpc_client
-      .get_multiple_accounts(&ProtocolAccounts::PUBKEYS)
+      .get_multiple_accounts(&pubkeys)
       .await
       .map_err(|e| anyhow!("Failed to fetch accounts from RPC: {e}"))?;
+    let registry = account_data.pop().flatten();
     let accounts = ProtocolAccounts::from_fetched(&account_data)?;
-    ProtocolState::try_from(&accounts)
+    let state = ProtocolState::try_from(&accounts)?;
+    match registry {
+      Some(registry) => {
+        let table =
+          deserialize_lookup_table(&LST_REGISTRY_LOOKUP_TABLE, &registry)?;
+        Ok(state.with_lst_registry_size(lst_registry_size(&table)))
+      }
+      None => Ok(state),
+    }
   }
 }
 
@@ -204,9 +218,11 @@ mod tests {
     );
 
     // Verify mint accounts have valid data
-    assert!(state.hyusd_mint.decimals > 0);
+    assert!(state.hyusd_mint.is_some_and(|mint| mint.decimals > 0));
     assert!(state.xsol_mint.decimals > 0);
-    assert!(state.shyusd_mint.decimals > 0);
+    assert!(state
+      .earn_pool()
+      .is_ok_and(|pool| pool.shyusd_mint.decimals > 0));
 
     // Verify clock has reasonable values (slot is u64, so just check it's set)
     assert!(state.exchange_context.clock.slot() > 0);
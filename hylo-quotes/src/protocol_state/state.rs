@@ -47,6 +47,12 @@ pub struct UsdcExchangeState {
   pub usdc_usd_spot: UFix64<N9>,
   /// Tolerated distance from par for the USDC pair
   pub par_tolerance: ParTolerance,
+  /// Publish time of the USDC/USD feed
+  pub oracle_publish_time: i64,
+  /// Slot the USDC/USD feed was posted at
+  pub oracle_posted_slot: u64,
+  /// USDC/USD staleness window
+  pub oracle_interval_secs: u64,
 }
 
 /// Whether a collateral feed is valid under the tighter stablecoin
@@ -74,18 +80,20 @@ pub struct ExoPairState<C: SolanaClock> {
   pub borrow_rate_harvest_epoch: u64,
   pub supply_floor: UFix64<N6>,
   pub oracle_publish_time: i64,
+  pub oracle_posted_slot: u64,
   pub oracle_interval_secs: u64,
 }
 
 impl<C: SolanaClock> ExoPairState<C> {
-  /// Assembles pair state from its account and loaded context.
+  /// Assembles pair state from its account, loaded context and collateral
+  /// feed.
   ///
   /// # Errors
   /// * Supply floor conversion
   pub fn new(
     exo_pair: &ExoPair,
     context: ExoExchangeContext<C>,
-    oracle_publish_time: i64,
+    collateral_usd: &PriceUpdateV2,
   ) -> Result<ExoPairState<C>> {
     Ok(ExoPairState {
       context,
@@ -93,7 +101,8 @@ impl<C: SolanaClock> ExoPairState<C> {
       pool_drawdown: exo_pair.pool_drawdown.into(),
       borrow_rate_harvest_epoch: exo_pair.borrow_rate_harvest_cache.epoch,
       supply_floor: exo_pair.virtual_stablecoin_supply_floor.try_into()?,
-      oracle_publish_time,
+      oracle_publish_time: collateral_usd.price_message.publish_time,
+      oracle_posted_slot: collateral_usd.posted_slot,
       oracle_interval_secs: exo_pair.oracle_interval_secs,
     })
   }
@@ -111,7 +120,24 @@ impl<C: SolanaClock> ExoPairState<C> {
   }
 }
 
-/// Complete snapshot of Hylo protocol state
+/// Earn pool accounts read by the sHYUSD routes and rebalance settlement.
+#[derive(Clone)]
+pub struct EarnPoolState {
+  /// SHYUSD mint account
+  pub shyusd_mint: Mint,
+
+  /// Earn pool configuration
+  pub pool_config: PoolConfig,
+
+  /// HYUSD earn pool token account
+  pub hyusd_pool: TokenAccount,
+}
+
+/// Snapshot of Hylo protocol state.
+///
+/// The LST exchange context is always present. Every other sub-state is
+/// optional so a caller can load only what its routes read; routes touching
+/// a missing sub-state fail with [`CoreError::ProtocolAccountNotFound`].
 #[derive(Clone)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct ProtocolState<C: SolanaClock> {
@@ -119,25 +145,19 @@ pub struct ProtocolState<C: SolanaClock> {
   pub exchange_context: LstExchangeContext<C>,
 
   /// `JitoSOL` LST header
-  pub jitosol_header: LstHeader,
+  pub jitosol_header: Option<LstHeader>,
 
   /// `HyloSOL` LST header
-  pub hylosol_header: LstHeader,
+  pub hylosol_header: Option<LstHeader>,
 
   /// HYUSD mint account
-  pub hyusd_mint: Mint,
+  pub hyusd_mint: Option<Mint>,
 
   /// XSOL mint account
   pub xsol_mint: Mint,
 
-  /// SHYUSD mint account
-  pub shyusd_mint: Mint,
-
-  /// Earn pool configuration
-  pub pool_config: PoolConfig,
-
-  /// HYUSD earn pool token account
-  pub hyusd_pool: TokenAccount,
+  /// Earn pool accounts
+  pub earn_pool: Option<EarnPoolState>,
 
   /// Timestamp of when this state was fetched
   pub fetched_at: UnixTimestamp,
@@ -146,19 +166,19 @@ pub struct ProtocolState<C: SolanaClock> {
   pub lst_swap_config: AssetSwapConfig,
 
   /// cbBTC exo pair
-  pub cbbtc_pair: ExoPairState<C>,
+  pub cbbtc_pair: Option<ExoPairState<C>>,
 
   /// HYPE exo pair
-  pub hype_pair: ExoPairState<C>,
+  pub hype_pair: Option<ExoPairState<C>>,
 
   /// USDC exchange state
-  pub usdc_exchange_state: UsdcExchangeState,
+  pub usdc_exchange_state: Option<UsdcExchangeState>,
 
   /// `JitoSOL` SPL stake pool
-  pub jitosol_stake_pool: SplStakePool,
+  pub jitosol_stake_pool: Option<SplStakePool>,
 
   /// `hyloSOL` SPL stake pool
-  pub hylosol_stake_pool: SplStakePool,
+  pub hylosol_stake_pool: Option<SplStakePool>,
 
   /// Protocol-wide pause flag
   pub protocol_paused: bool,
@@ -173,13 +193,61 @@ pub struct ProtocolState<C: SolanaClock> {
   pub yield_harvest_epoch: u64,
 
   /// `JitoSOL` collateral vault balance
-  pub jitosol_vault_balance: UFix64<N9>,
+  pub jitosol_vault_balance: Option<UFix64<N9>>,
 
   /// `hyloSOL` collateral vault balance
-  pub hylosol_vault_balance: UFix64<N9>,
+  pub hylosol_vault_balance: Option<UFix64<N9>>,
 
   /// SOL/USD valid under the stablecoin oracle window
   pub sol_stablecoin_oracle_valid: bool,
+
+  /// Publish time of the SOL/USD feed
+  pub sol_oracle_publish_time: i64,
+
+  /// Slot the SOL/USD feed was posted at
+  pub sol_oracle_posted_slot: u64,
+
+  /// SOL/USD staleness window
+  pub oracle_interval_secs: u64,
+
+  /// Entries in the LST registry lookup table, scaling compute budgets
+  /// of the routes that load it
+  pub lst_registry_size: Option<usize>,
+}
+
+/// Generates [`ProtocolState::with_exo_pair`] and
+/// [`ProtocolState::exo_pair`] from the `(token, field)` list of exo pairs
+/// with a slot in the state.
+macro_rules! exo_pair_slots {
+  ($(($token:ident, $field:ident)),+ $(,)?) => {
+    /// Adds the pair state for a registered exo collateral.
+    ///
+    /// # Errors
+    /// * Collateral has no pair slot in this snapshot
+    pub fn with_exo_pair<E: Exo>(
+      mut self,
+      pair: ExoPairState<C>,
+    ) -> Result<Self, CoreError> {
+      match E::MINT {
+        $($token::MINT => self.$field = Some(pair),)+
+        _ => return Err(CoreError::UnknownExoMint),
+      }
+      Ok(self)
+    }
+
+    /// Selects the pair state for a registered exo collateral.
+    ///
+    /// # Errors
+    /// * Collateral has no pair slot in this snapshot
+    /// * Pair not loaded
+    pub fn exo_pair<E: Exo>(&self) -> Result<&ExoPairState<C>, CoreError> {
+      match E::MINT {
+        $($token::MINT => self.$field.as_ref(),)+
+        _ => return Err(CoreError::UnknownExoMint),
+      }
+      .ok_or(CoreError::ProtocolAccountNotFound)
+    }
+  };
 }
 
 impl<C: SolanaClock> ProtocolState<C> {
@@ -207,88 +275,218 @@ impl<C: SolanaClock> ProtocolState<C> {
     jitosol_vault_balance: UFix64<N9>,
     hylosol_vault_balance: UFix64<N9>,
     sol_stablecoin_oracle_valid: bool,
+  ) -> Result<Self> {
+    let state = Self::core(clock, hylo, xsol_mint, sol_usd)?
+      .with_lst::<JITOSOL>(jitosol_header, jitosol_vault_balance)?
+      .with_lst::<HYLOSOL>(hylosol_header, hylosol_vault_balance)?
+      .with_stake_pool::<JITOSOL>(jitosol_stake_pool)?
+      .with_stake_pool::<HYLOSOL>(hylosol_stake_pool)?
+      .with_exo_pair::<CBBTC>(cbbtc_pair)?
+      .with_exo_pair::<HYPE>(hype_pair)?
+      .with_usdc(usdc_exchange_state)
+      .with_earn_pool(EarnPoolState {
+        shyusd_mint,
+        pool_config,
+        hyusd_pool,
+      })
+      .with_hyusd_mint(hyusd_mint);
+    Ok(Self {
+      sol_stablecoin_oracle_valid,
+      ..state
+    })
+  }
+
+  /// Builds the LST exchange core with every optional sub-state empty.
+  ///
+  /// The SOL stablecoin oracle window is checked against `sol_usd` here.
+  ///
+  /// # Errors
+  /// * Propagates errors from `ExchangeContext::load`.
+  pub fn core(
+    clock: C,
+    hylo: &Hylo,
+    xsol_mint: Mint,
+    sol_usd: &PriceUpdateV2,
   ) -> Result<Self> {
     let fetched_at = clock.unix_timestamp();
+    let sol_stablecoin_oracle_valid =
+      stablecoin_oracle_valid(&clock, sol_usd, hylo.oracle_interval_secs);
     let lst_swap_config = AssetSwapConfig::new(hylo.lst_swap_fee.into())?;
     let exchange_context =
       build_lst_exchange_context(clock, hylo, &xsol_mint, sol_usd)?;
     Ok(Self {
       exchange_context,
-      jitosol_header,
-      hylosol_header,
-      hyusd_mint,
+      jitosol_header: None,
+      hylosol_header: None,
+      hyusd_mint: None,
       xsol_mint,
-      shyusd_mint,
-      pool_config,
-      hyusd_pool,
+      earn_pool: None,
       fetched_at,
       lst_swap_config,
-      cbbtc_pair,
-      hype_pair,
-      usdc_exchange_state,
-      jitosol_stake_pool,
-      hylosol_stake_pool,
+      cbbtc_pair: None,
+      hype_pair: None,
+      usdc_exchange_state: None,
+      jitosol_stake_pool: None,
+      hylosol_stake_pool: None,
       protocol_paused: hylo.protocol_paused,
       lst_pair_paused: hylo.lst_pair_paused,
       pool_drawdown: hylo.pool_drawdown.into(),
       yield_harvest_epoch: hylo.yield_harvest_cache.epoch,
-      jitosol_vault_balance,
-      hylosol_vault_balance,
+      jitosol_vault_balance: None,
+      hylosol_vault_balance: None,
       sol_stablecoin_oracle_valid,
+      sol_oracle_publish_time: sol_usd.price_message.publish_time,
+      sol_oracle_posted_slot: sol_usd.posted_slot,
+      oracle_interval_secs: hylo.oracle_interval_secs,
+      lst_registry_size: None,
     })
   }
 
+  /// Adds an LST's header and collateral vault balance.
+  ///
+  /// # Errors
+  /// * Unknown LST mint
+  pub fn with_lst<L: LST>(
+    self,
+    header: LstHeader,
+    vault_balance: UFix64<N9>,
+  ) -> Result<Self, CoreError> {
+    match L::MINT {
+      JITOSOL::MINT => Ok(Self {
+        jitosol_header: Some(header),
+        jitosol_vault_balance: Some(vault_balance),
+        ..self
+      }),
+      HYLOSOL::MINT => Ok(Self {
+        hylosol_header: Some(header),
+        hylosol_vault_balance: Some(vault_balance),
+        ..self
+      }),
+      _ => Err(CoreError::UnknownLstMint),
+    }
+  }
+
+  /// Adds an LST's SPL stake pool, read by the rebalance routes.
+  ///
+  /// # Errors
+  /// * Unknown LST mint
+  pub fn with_stake_pool<L: LST>(
+    self,
+    stake_pool: SplStakePool,
+  ) -> Result<Self, CoreError> {
+    match L::MINT {
+      JITOSOL::MINT => Ok(Self {
+        jitosol_stake_pool: Some(stake_pool),
+        ..self
+      }),
+      HYLOSOL::MINT => Ok(Self {
+        hylosol_stake_pool: Some(stake_pool),
+        ..self
+      }),
+      _ => Err(CoreError::UnknownLstMint),
+    }
+  }
+
+  exo_pair_slots!((CBBTC, cbbtc_pair), (HYPE, hype_pair));
+
+  /// Adds the USDC exchange state.
+  #[must_use]
+  pub fn with_usdc(self, usdc_exchange_state: UsdcExchangeState) -> Self {
+    Self {
+      usdc_exchange_state: Some(usdc_exchange_state),
+      ..self
+    }
+  }
+
+  /// Adds the LST registry lookup table size.
+  #[must_use]
+  pub fn with_lst_registry_size(self, lst_registry_size: usize) -> Self {
+    Self {
+      lst_registry_size: Some(lst_registry_size),
+      ..self
+    }
+  }
+
+  /// Adds the earn pool accounts.
+  #[must_use]
+  pub fn with_earn_pool(self, earn_pool: EarnPoolState) -> Self {
+    Self {
+      earn_pool: Some(earn_pool),
+      ..self
+    }
+  }
+
+  /// Adds the HYUSD mint account.
+  #[must_use]
+  pub fn with_hyusd_mint(self, hyusd_mint: Mint) -> Self {
+    Self {
+      hyusd_mint: Some(hyusd_mint),
+      ..self
+    }
+  }
+
   /// Selects an [`LstHeader`] field given a token implementing [`LST`].
   ///
   /// # Errors
   /// * LST does not have a corresponding header field in this struct
+  /// * Header not loaded
   pub fn lst_header<L: LST>(&self) -> Result<&LstHeader, CoreError> {
     match L::MINT {
-      JITOSOL::MINT => Ok(&self.jitosol_header),
-      HYLOSOL::MINT => Ok(&self.hylosol_header),
-      _ => Err(CoreError::UnknownLstMint),
+      JITOSOL::MINT => self.jitosol_header.as_ref(),
+      HYLOSOL::MINT => self.hylosol_header.as_ref(),
+      _ => return Err(CoreError::UnknownLstMint),
     }
+    .ok_or(CoreError::ProtocolAccountNotFound)
   }
 
   /// Collateral vault balance for the given LST.
   ///
   /// # Errors
   /// * Unknown LST mint
+  /// * Vault not loaded
   pub fn lst_vault_balance<L: LST>(&self) -> Result<UFix64<N9>, CoreError> {
     match L::MINT {
-      JITOSOL::MINT => Ok(self.jitosol_vault_balance),
-      HYLOSOL::MINT => Ok(self.hylosol_vault_balance),
-      _ => Err(CoreError::UnknownLstMint),
+      JITOSOL::MINT => self.jitosol_vault_balance,
+      HYLOSOL::MINT => self.hylosol_vault_balance,
+      _ => return Err(CoreError::UnknownLstMint),
     }
+    .ok_or(CoreError::ProtocolAccountNotFound)
   }
 
   /// SPL stake pool for the given LST.
   ///
   /// # Errors
   /// * Unknown LST mint
+  /// * Stake pool not loaded
   pub fn stake_pool<L: LST>(&self) -> Result<&SplStakePool, CoreError> {
     match L::MINT {
-      JITOSOL::MINT => Ok(&self.jitosol_stake_pool),
-      HYLOSOL::MINT => Ok(&self.hylosol_stake_pool),
-      _ => Err(CoreError::UnknownLstMint),
+      JITOSOL::MINT => self.jitosol_stake_pool.as_ref(),
+      HYLOSOL::MINT => self.hylosol_stake_pool.as_ref(),
+      _ => return Err(CoreError::UnknownLstMint),
     }
+    .ok_or(CoreError::ProtocolAccountNotFound)
   }
 
-  /// Selects the pair state for a registered exo collateral.
+  /// USDC exchange state.
   ///
   /// # Errors
-  /// * Collateral has no registered pair in this snapshot
-  pub fn exo_pair<E: Exo>(&self) -> Result<&ExoPairState<C>, CoreError> {
-    match E::MINT {
-      CBBTC::MINT => Ok(&self.cbbtc_pair),
-      HYPE::MINT => Ok(&self.hype_pair),
-      _ => Err(CoreError::UnknownExoMint),
-    }
+  /// * USDC pair not loaded
+  pub fn usdc_exchange_state(&self) -> Result<&UsdcExchangeState, CoreError> {
+    self
+      .usdc_exchange_state
+      .as_ref()
+      .ok_or(CoreError::ProtocolAccountNotFound)
   }
 
-  #[must_use]
-  pub fn usdc_exchange_state(&self) -> &UsdcExchangeState {
-    &self.usdc_exchange_state
+  /// Earn pool accounts.
+  ///
+  /// # Errors
+  /// * Earn pool not loaded
+  pub fn earn_pool(&self) -> Result<&EarnPoolState, CoreError> {
+    self
+      .earn_pool
+      .as_ref()
+      .ok_or(CoreError::ProtocolAccountNotFound)
   }
 }
 
@@ -357,7 +555,6 @@ where
     .checked_convert::<N9>()
     .ok_or_else(|| anyhow!("exo vault amount overflows N9"))?;
 
-  let oracle_publish_time = collateral_usd.price_message.publish_time;
   let context = ExoExchangeContext::load(
     clock,
     total_collateral,
@@ -372,7 +569,7 @@ where
     exo_pair.levercoin_market_cap_limit.try_into()?,
   )
   .context("ExoExchangeContext::load")?;
-  ExoPairState::new(&exo_pair, context, oracle_publish_time)
+  ExoPairState::new(&exo_pair, context, &collateral_usd)
 }
 
 /// Builds USDC exchange state from protocol accounts.
@@ -408,17 +605,22 @@ fn build_usdc_exchange_state(
     virtual_stablecoin_supply: virtual_stablecoin.supply()?,
     usdc_usd_spot: usdc_oracle.spot,
     par_tolerance: usdc_pair.par_tolerance.into(),
+    oracle_publish_time: usdc_usd.price_message.publish_time,
+    oracle_posted_slot: usdc_usd.posted_slot,
+    oracle_interval_secs: usdc_pair.oracle_interval_secs,
   })
 }
 
-impl TryFrom<&ProtocolAccounts> for ProtocolState<Clock> {
-  type Error = anyhow::Error;
-
-  /// Build `ProtocolState` from protocol accounts
+impl ProtocolState<Clock> {
+  /// Builds state from every account in `accounts` except the USDC pair,
+  /// which is left unloaded. Snapshots whose USDC pair predates its current
+  /// layout still load this way.
   ///
   /// # Errors
-  /// Returns error if any account fails deserialization.
-  fn try_from(accounts: &ProtocolAccounts) -> Result<Self> {
+  /// * Any other account fails deserialization or context load
+  pub fn from_accounts_without_usdc(
+    accounts: &ProtocolAccounts,
+  ) -> Result<ProtocolState<Clock>> {
     let hylo = Hylo::try_deserialize(&mut accounts.hylo.data.as_slice())?;
 
     let jitosol_header =
@@ -464,7 +666,6 @@ impl TryFrom<&ProtocolAccounts> for ProtocolState<Clock> {
       &accounts.xhype_mint,
       &accounts.hype_usd_pyth,
     )?;
-    let usdc_exchange_state = build_usdc_exchange_state(&clock, accounts)?;
 
     let jitosol_stake_pool =
       SplStakePool::from_bytes(&accounts.jitosol_pool_state.data)?;
@@ -477,28 +678,36 @@ impl TryFrom<&ProtocolAccounts> for ProtocolState<Clock> {
     let hylosol_vault = TokenAccount::try_deserialize(
       &mut accounts.hylosol_vault.data.as_slice(),
     )?;
-    let sol_stablecoin_oracle_valid =
-      stablecoin_oracle_valid(&clock, &sol_usd, hylo.oracle_interval_secs);
-
-    Self::build(
-      clock,
-      &hylo,
-      jitosol_header,
-      hylosol_header,
-      hyusd_mint,
-      xsol_mint,
-      shyusd_mint,
-      pool_config,
-      hyusd_pool,
-      &sol_usd,
-      cbbtc_pair,
-      hype_pair,
-      usdc_exchange_state,
-      jitosol_stake_pool,
-      hylosol_stake_pool,
-      UFix64::new(jitosol_vault.amount),
-      UFix64::new(hylosol_vault.amount),
-      sol_stablecoin_oracle_valid,
+
+    Ok(
+      Self::core(clock, &hylo, xsol_mint, &sol_usd)?
+        .with_lst::<JITOSOL>(jitosol_header, UFix64::new(jitosol_vault.amount))?
+        .with_lst::<HYLOSOL>(hylosol_header, UFix64::new(hylosol_vault.amount))?
+        .with_stake_pool::<JITOSOL>(jitosol_stake_pool)?
+        .with_stake_pool::<HYLOSOL>(hylosol_stake_pool)?
+        .with_exo_pair::<CBBTC>(cbbtc_pair)?
+        .with_exo_pair::<HYPE>(hype_pair)?
+        .with_earn_pool(EarnPoolState {
+          shyusd_mint,
+          pool_config,
+          hyusd_pool,
+        })
+        .with_hyusd_mint(hyusd_mint),
     )
   }
 }
+
+impl TryFrom<&ProtocolAccounts> for ProtocolState<Clock> {
+  type Error = anyhow::Error;
+
+  /// Build `ProtocolState` from protocol accounts
+  ///
+  /// # Errors
+  /// Returns error if any account fails deserialization.
+  fn try_from(accounts: &ProtocolAccounts) -> Result<Self> {
+    let state = ProtocolState::from_accounts_without_usdc(accounts)?;
+    let usdc_exchange_state =
+      build_usdc_exchange_state(&state.exchange_context.clock, accounts)?;
+    Ok(state.with_usdc(usdc_exchange_state))
+  }
+}
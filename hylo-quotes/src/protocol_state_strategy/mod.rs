@@ -5,11 +5,16 @@
 
 mod router;
 
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
 use async_trait::async_trait;
 use hylo_core::solana_clock::SolanaClock;
 
 use crate::protocol_state::StateProvider;
-use crate::runtime_quote_strategy::RuntimeQuoteStrategy;
+use crate::quote_batch::{QuoteBatch, QuoteRequest};
+use crate::runtime_quote_strategy::{
+  RuntimeQuoteStrategy, RuntimeStateQuoteStrategy,
+};
 
 pub struct ProtocolStateStrategy<S> {
   pub state_provider: S,
@@ -20,6 +25,31 @@ impl<S> ProtocolStateStrategy<S> {
   pub fn new(state_provider: S) -> Self {
     Self { state_provider }
   }
+
+  /// Quotes every request against a single state fetch.
+  ///
+  /// # Errors
+  /// * State fetch; per-request failures are returned in the batch
+  pub async fn quote_batch<C>(
+    &self,
+    requests: &[QuoteRequest],
+    user: Pubkey,
+    slippage_tolerance: u64,
+  ) -> Result<QuoteBatch>
+  where
+    C: SolanaClock,
+    S: StateProvider<C>,
+    Self: RuntimeStateQuoteStrategy<C>,
+  {
+    let state = self.state_provider.fetch_state().await?;
+    Ok(QuoteBatch::from_state(
+      self,
+      &state,
+      requests,
+      user,
+      slippage_tolerance,
+    ))
+  }
 }
 
 #[async_trait]
@@ -27,3 +57,8 @@ impl<S: StateProvider<C> + Sync, C: SolanaClock> RuntimeQuoteStrategy<C>
   for ProtocolStateStrategy<S>
 {
 }
+
+impl<S: StateProvider<C>, C: SolanaClock> RuntimeStateQuoteStrategy<C>
+  for ProtocolStateStrategy<S>
+{
+}
@@ -10,17 +10,17 @@ use hylo_clients::router_client::{
 use hylo_core::slippage_config::SlippageConfig;
 use hylo_core::solana_clock::SolanaClock;
 use hylo_idl::tokens::{
-  CBBTC, HYLOSOL, HYPE, HYUSD, JITOSOL, ONYC, PST, SHYUSD, USDC, WETH, XBTC,
-  XETH, XHYPE, XONYC, XPST, XSOL, XZEC, ZEC,
+  TokenMint, CBBTC, HYLOSOL, HYPE, HYUSD, JITOSOL, ONYC, PST, SHYUSD, USDC,
+  WETH, XBTC, XETH, XHYPE, XONYC, XPST, XSOL, XZEC, ZEC,
 };
 use hylo_idl::with_exo_pairs;
 
-use crate::protocol_state::StateProvider;
+use crate::compute_units::pair_budget;
+use crate::protocol_state::{ProtocolState, StateProvider};
 use crate::protocol_state_strategy::ProtocolStateStrategy;
-use crate::token_operation::TokenOperationExt;
-use crate::{
-  ComputeUnitStrategy, ExecutableQuote, QuoteStrategy, DEFAULT_CUS_WITH_BUFFER,
-};
+use crate::quote_strategy::StateQuoteStrategy;
+use crate::token_operation::{TokenOperation, TokenOperationExt};
+use crate::{ComputeUnitStrategy, ExecutableQuote, QuoteStrategy};
 
 macro_rules! state_quote {
   ($in:ty, $out:ty, $fee_exp:ty, $quote_ty:ty) => {
@@ -37,14 +37,37 @@ macro_rules! state_quote {
         slippage_tolerance: u64,
       ) -> Result<$quote_ty> {
         let state = self.state_provider.fetch_state().await?;
+        StateQuoteStrategy::<$in, $out, C>::quote_from_state(
+          self,
+          &state,
+          amount_in,
+          user,
+          slippage_tolerance,
+        )
+      }
+    }
+
+    impl<S: StateProvider<C>, C: SolanaClock> StateQuoteStrategy<$in, $out, C>
+      for ProtocolStateStrategy<S>
+    {
+      fn quote_from_state(
+        &self,
+        state: &ProtocolState<C>,
+        amount_in: u64,
+        user: Pubkey,
+        slippage_tolerance: u64,
+      ) -> Result<$quote_ty> {
         let op = state.output::<$in, $out>(UFix64::new(amount_in))?;
+        let expiry = TokenOperation::<$in, $out>::expiry(state)?;
+        let slippage_config = SlippageConfig::new(
+          op.out_amount,
+          UFix64::<N4>::new(slippage_tolerance),
+        );
+        let min_amount_out = slippage_config.min_token_out()?;
         let args = RouterArgs {
           amount: amount_in,
           user,
-          slippage_config: Some(SlippageConfig::new(
-            op.out_amount,
-            UFix64::<N4>::new(slippage_tolerance),
-          )),
+          slippage_config: Some(slippage_config),
         };
         let instructions = RouterClient::build_instructions::<$in, $out>(args)?;
         let address_lookup_tables =
@@ -52,12 +75,18 @@ macro_rules! state_quote {
         Ok(ExecutableQuote {
           amount_in: op.in_amount,
           amount_out: op.out_amount,
-          compute_units: DEFAULT_CUS_WITH_BUFFER,
+          min_amount_out,
+          compute_units: pair_budget(
+            <$in>::MINT,
+            <$out>::MINT,
+            state.lst_registry_size,
+          ),
           compute_unit_strategy: ComputeUnitStrategy::Estimated,
           fee_amount: op.fee_amount,
           fee_mint: op.fee_mint,
           instructions,
           address_lookup_tables,
+          expiry,
         })
       }
     }
@@ -0,0 +1,90 @@
+//! Many quotes against one protocol state fetch.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use hylo_core::solana_clock::SolanaClock;
+
+use crate::protocol_state::ProtocolState;
+use crate::runtime_quote_strategy::RuntimeStateQuoteStrategy;
+use crate::ExecutableQuoteValue;
+
+/// One pair and size to quote in a batch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuoteRequest {
+  pub input_mint: Pubkey,
+  pub output_mint: Pubkey,
+  pub amount_in: u64,
+}
+
+impl QuoteRequest {
+  #[must_use]
+  pub fn new(
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount_in: u64,
+  ) -> QuoteRequest {
+    QuoteRequest {
+      input_mint,
+      output_mint,
+      amount_in,
+    }
+  }
+}
+
+/// Quotes computed from one protocol state.
+///
+/// `results` lines up with the requests; a failed request does not fail
+/// the batch.
+#[derive(Debug)]
+pub struct QuoteBatch {
+  /// Slot of the state every quote was computed from
+  pub slot: u64,
+  /// Unix timestamp of the state every quote was computed from
+  pub timestamp: i64,
+  /// Quote or error per request, in request order
+  pub results: Vec<Result<ExecutableQuoteValue>>,
+}
+
+impl QuoteBatch {
+  /// Quotes every request against `state` through `strategy`.
+  #[must_use]
+  pub fn from_state<C, Q>(
+    strategy: &Q,
+    state: &ProtocolState<C>,
+    requests: &[QuoteRequest],
+    user: Pubkey,
+    slippage_tolerance: u64,
+  ) -> QuoteBatch
+  where
+    C: SolanaClock,
+    Q: RuntimeStateQuoteStrategy<C>,
+  {
+    let results = requests
+      .iter()
+      .map(|request| {
+        strategy.runtime_quote_from_state(
+          state,
+          request.input_mint,
+          request.output_mint,
+          request.amount_in,
+          user,
+          slippage_tolerance,
+        )
+      })
+      .collect();
+    QuoteBatch {
+      slot: state.exchange_context.clock.slot(),
+      timestamp: state.fetched_at,
+      results,
+    }
+  }
+
+  /// Quotes that succeeded, with their request index.
+  pub fn quotes(&self) -> impl Iterator<Item = (usize, &ExecutableQuoteValue)> {
+    self
+      .results
+      .iter()
+      .enumerate()
+      .filter_map(|(i, result)| result.as_ref().ok().map(|quote| (i, quote)))
+  }
+}
@@ -0,0 +1,305 @@
+//! How long a state-built quote stays executable.
+//!
+//! Collateral oracles go stale after their configured interval, a quarter
+//! of it for stablecoin mints and redemptions, measured both from the
+//! feed's publish time and from the slot it was posted at. LST epoch prices
+//! and harvest gates lapse at epoch rollover. [`QuoteExpiry`] is the earliest
+//! [`Deadline`] a route reads; [`revalidate`] re-runs a quote against
+//! fresher state.
+
+use anchor_lang::prelude::EpochSchedule;
+use anchor_lang::solana_program::clock::DEFAULT_MS_PER_SLOT;
+use fix::prelude::UFix64;
+use fix::typenum::Integer;
+use hylo_core::error::CoreError;
+use hylo_core::pyth::{slot_interval, ORACLE_DIVISOR};
+use hylo_core::solana_clock::SolanaClock;
+use hylo_idl::tokens::TokenMint;
+
+use crate::protocol_state::ProtocolState;
+use crate::token_operation::TokenOperation;
+use crate::ExecutableQuote;
+
+/// Point at which one input a route reads stops being valid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Deadline {
+  /// Collateral oracle leaves its staleness window at `expires_at` or
+  /// `slot`, whichever comes first
+  Oracle { expires_at: i64, slot: u64 },
+  /// Collateral oracle leaves the stablecoin window at `expires_at` or
+  /// `slot`, whichever comes first
+  StablecoinOracle { expires_at: i64, slot: u64 },
+  /// Epoch rolls over at `slot`, roughly at `estimated_at`
+  EpochRollover { slot: u64, estimated_at: i64 },
+}
+
+impl Deadline {
+  /// First second and slot a feed published at `publish_time` and posted
+  /// at `posted_slot` fails `validate_publish_time` or
+  /// `validate_posted_slot` under `interval_secs`.
+  #[must_use]
+  pub fn oracle(
+    publish_time: i64,
+    posted_slot: u64,
+    interval_secs: u64,
+  ) -> Deadline {
+    Deadline::Oracle {
+      expires_at: window_end(publish_time, interval_secs),
+      slot: slot_window_end(posted_slot, interval_secs),
+    }
+  }
+
+  /// Same as [`Deadline::oracle`] under the tighter stablecoin window.
+  #[must_use]
+  pub fn stablecoin_oracle(
+    publish_time: i64,
+    posted_slot: u64,
+    interval_secs: u64,
+  ) -> Deadline {
+    let interval_secs = interval_secs.div_ceil(ORACLE_DIVISOR);
+    Deadline::StablecoinOracle {
+      expires_at: window_end(publish_time, interval_secs),
+      slot: slot_window_end(posted_slot, interval_secs),
+    }
+  }
+
+  /// Start of the next epoch, see [`next_epoch_start`].
+  #[must_use]
+  pub fn epoch_rollover<C: SolanaClock>(clock: &C) -> Deadline {
+    let (slot, estimated_at) = next_epoch_start(clock);
+    Deadline::EpochRollover { slot, estimated_at }
+  }
+
+  /// Unix time of the deadline, estimated for epoch rollover.
+  #[must_use]
+  pub fn unix_timestamp(&self) -> i64 {
+    match *self {
+      Deadline::Oracle { expires_at, .. }
+      | Deadline::StablecoinOracle { expires_at, .. } => expires_at,
+      Deadline::EpochRollover { estimated_at, .. } => estimated_at,
+    }
+  }
+
+  /// First slot past the deadline.
+  #[must_use]
+  pub fn slot(&self) -> u64 {
+    match *self {
+      Deadline::Oracle { slot, .. }
+      | Deadline::StablecoinOracle { slot, .. }
+      | Deadline::EpochRollover { slot, .. } => slot,
+    }
+  }
+}
+
+/// First slot of the epoch after `clock`'s under mainnet's epoch schedule,
+/// which has no warmup, and its start estimated at the default slot target.
+#[must_use]
+pub fn next_epoch_start<C: SolanaClock>(clock: &C) -> (u64, i64) {
+  let schedule = EpochSchedule::without_warmup();
+  let epoch_secs = schedule
+    .get_slots_in_epoch(clock.epoch())
+    .saturating_mul(DEFAULT_MS_PER_SLOT)
+    / 1_000;
+  (
+    schedule.get_first_slot_in_epoch(clock.epoch().saturating_add(1)),
+    clock
+      .epoch_start_timestamp()
+      .saturating_add_unsigned(epoch_secs),
+  )
+}
+
+fn window_end(publish_time: i64, interval_secs: u64) -> i64 {
+  publish_time
+    .saturating_add_unsigned(interval_secs)
+    .saturating_add(1)
+}
+
+fn slot_window_end(posted_slot: u64, interval_secs: u64) -> u64 {
+  slot_interval(interval_secs)
+    .map_or(posted_slot, |slots| posted_slot.saturating_add(slots))
+    .saturating_add(1)
+}
+
+/// Earliest time and slot at which any input behind a quote lapses.
+///
+/// `None` fields mean no input is bound that way; a default expiry never
+/// lapses, as for quotes not built from [`ProtocolState`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QuoteExpiry {
+  /// Earliest lapse in unix seconds
+  pub unix_timestamp: Option<i64>,
+  /// Earliest lapse in slots
+  pub slot: Option<u64>,
+  /// Deadline setting `unix_timestamp`
+  pub binding: Option<Deadline>,
+}
+
+impl QuoteExpiry {
+  #[must_use]
+  pub fn earliest(deadlines: &[Deadline]) -> QuoteExpiry {
+    let binding = deadlines
+      .iter()
+      .copied()
+      .min_by_key(Deadline::unix_timestamp);
+    QuoteExpiry {
+      unix_timestamp: binding.as_ref().map(Deadline::unix_timestamp),
+      slot: deadlines.iter().map(Deadline::slot).min(),
+      binding,
+    }
+  }
+
+  /// Whether `clock` has reached either bound.
+  #[must_use]
+  pub fn is_expired<C: SolanaClock>(&self, clock: &C) -> bool {
+    self
+      .unix_timestamp
+      .is_some_and(|at| clock.unix_timestamp() >= at)
+      || self.slot.is_some_and(|slot| clock.slot() >= slot)
+  }
+
+  /// Seconds from `now` until the time bound, zero once passed.
+  #[must_use]
+  pub fn secs_remaining(&self, now: i64) -> Option<i64> {
+    self.unix_timestamp.map(|at| at.saturating_sub(now).max(0))
+  }
+}
+
+/// Outcome of re-running a quote against newer state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Revalidation<Exp: Integer> {
+  /// Output still clears the quote's slippage minimum
+  Executable {
+    amount_out: UFix64<Exp>,
+    expiry: QuoteExpiry,
+  },
+  /// Output fell below the slippage minimum
+  SlippageExceeded { amount_out: UFix64<Exp> },
+  /// Quote's input left the route's `[min_input, max_input]` range
+  InputOutOfRange,
+  /// Route closed or its math fails in the new state
+  Rejected(CoreError),
+}
+
+impl<Exp: Integer> Revalidation<Exp> {
+  #[must_use]
+  pub fn is_executable(&self) -> bool {
+    matches!(self, Revalidation::Executable { .. })
+  }
+}
+
+/// Re-runs `quote` for `IN -> OUT` against `state`, checking its input
+/// against the route's range and its output against the quote's slippage
+/// minimum. An executable result carries the expiry of the new state, the
+/// next time to check.
+pub fn revalidate<IN, OUT, C, Fee>(
+  quote: &ExecutableQuote<IN::Exp, OUT::Exp, Fee>,
+  state: &ProtocolState<C>,
+) -> Revalidation<OUT::Exp>
+where
+  IN: TokenMint,
+  OUT: TokenMint,
+  C: SolanaClock,
+  Fee: Integer,
+  ProtocolState<C>: TokenOperation<IN, OUT>,
+{
+  match rerun::<IN, OUT, C>(quote.amount_in, state) {
+    Ok(None) => Revalidation::InputOutOfRange,
+    Ok(Some((amount_out, _))) if amount_out < quote.min_amount_out => {
+      Revalidation::SlippageExceeded { amount_out }
+    }
+    Ok(Some((amount_out, expiry))) => {
+      Revalidation::Executable { amount_out, expiry }
+    }
+    Err(error) => Revalidation::Rejected(error),
+  }
+}
+
+/// Output and expiry for `amount_in` in `state`, `None` when the input
+/// falls outside the route's range.
+fn rerun<IN, OUT, C>(
+  amount_in: UFix64<IN::Exp>,
+  state: &ProtocolState<C>,
+) -> Result<Option<(UFix64<OUT::Exp>, QuoteExpiry)>, CoreError>
+where
+  IN: TokenMint,
+  OUT: TokenMint,
+  C: SolanaClock,
+  ProtocolState<C>: TokenOperation<IN, OUT>,
+{
+  TokenOperation::<IN, OUT>::preconditions(state)?;
+  let min_input = TokenOperation::<IN, OUT>::min_input_ungated(state)?;
+  let max_input = TokenOperation::<IN, OUT>::max_input_ungated(state)?;
+  if !(min_input..=max_input).contains(&amount_in) {
+    return Ok(None);
+  }
+  let output =
+    TokenOperation::<IN, OUT>::compute_output_ungated(state, amount_in)?;
+  Ok(Some((
+    output.out_amount,
+    TokenOperation::<IN, OUT>::expiry(state)?,
+  )))
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Clock;
+
+  use super::*;
+
+  const PUBLISHED: i64 = 1_700_000_000;
+  const POSTED: u64 = 345_000_000;
+
+  #[test]
+  fn stablecoin_window_binds_before_oracle() {
+    let expiry = QuoteExpiry::earliest(&[
+      Deadline::oracle(PUBLISHED, POSTED, 60),
+      Deadline::stablecoin_oracle(PUBLISHED, POSTED, 60),
+    ]);
+    assert_eq!(expiry.unix_timestamp, Some(PUBLISHED + 16));
+    assert_eq!(
+      expiry.binding,
+      Some(Deadline::StablecoinOracle {
+        expires_at: PUBLISHED + 16,
+        slot: POSTED + 76,
+      })
+    );
+    assert_eq!(expiry.slot, Some(POSTED + 76));
+  }
+
+  #[test]
+  fn posted_slot_binds_before_rollover() {
+    let clock = Clock {
+      slot: 345_700_000,
+      epoch: 800,
+      epoch_start_timestamp: PUBLISHED,
+      unix_timestamp: PUBLISHED + 40,
+      ..Clock::default()
+    };
+    let rollover = Deadline::epoch_rollover(&clock);
+    assert_eq!(rollover.slot(), 801 * 432_000);
+    assert_eq!(rollover.unix_timestamp(), PUBLISHED + 172_800);
+    let expiry = QuoteExpiry::earliest(&[
+      Deadline::oracle(PUBLISHED + 30, clock.slot, 20),
+      rollover,
+    ]);
+    assert_eq!(expiry.slot, Some(clock.slot + 101));
+    assert_eq!(expiry.unix_timestamp, Some(PUBLISHED + 51));
+    assert!(!expiry.is_expired(&clock));
+    assert_eq!(expiry.secs_remaining(clock.unix_timestamp), Some(11));
+    let slot_lapsed = Clock {
+      slot: clock.slot + 101,
+      ..clock
+    };
+    assert!(expiry.is_expired(&slot_lapsed));
+  }
+
+  #[test]
+  fn default_never_expires() {
+    let clock = Clock {
+      slot: u64::MAX,
+      unix_timestamp: i64::MAX,
+      ..Clock::default()
+    };
+    assert!(!QuoteExpiry::default().is_expired(&clock));
+  }
+}
@@ -53,6 +53,22 @@ impl Operation {
       Operation::SwapUsdcToExo => "swap_usdc_to_exo",
     }
   }
+
+  /// Whether the operation's transactions load the LST registry lookup
+  /// table.
+  #[must_use]
+  pub const fn reads_lst_registry(&self) -> bool {
+    matches!(
+      self,
+      Operation::MintStablecoinLst
+        | Operation::RedeemStablecoinLst
+        | Operation::MintLevercoinLst
+        | Operation::RedeemLevercoinLst
+        | Operation::SwapLstToLst
+        | Operation::SwapLstToUsdc
+        | Operation::SwapUsdcToLst
+    )
+  }
 }
 
 impl AsRef<str> for Operation {
@@ -5,6 +5,7 @@ use fix::typenum::Integer;
 use hylo_core::solana_clock::SolanaClock;
 use hylo_idl::tokens::TokenMint;
 
+use crate::protocol_state::ProtocolState;
 use crate::ExecutableQuote;
 
 /// Trait for strategies that compute quotes for token pair operations.
@@ -23,3 +24,20 @@ pub trait QuoteStrategy<IN: TokenMint, OUT: TokenMint, C: SolanaClock> {
     slippage_tolerance: u64,
   ) -> Result<ExecutableQuote<IN::Exp, OUT::Exp, Self::FeeExp>>;
 }
+
+/// Strategies that can quote from an already-fetched [`ProtocolState`].
+pub trait StateQuoteStrategy<IN: TokenMint, OUT: TokenMint, C: SolanaClock>:
+  QuoteStrategy<IN, OUT, C>
+{
+  /// Compute a quote against `state` without fetching.
+  ///
+  /// # Errors
+  /// Returns error if quote computation fails.
+  fn quote_from_state(
+    &self,
+    state: &ProtocolState<C>,
+    amount_in: u64,
+    user: Pubkey,
+    slippage_tolerance: u64,
+  ) -> Result<ExecutableQuote<IN::Exp, OUT::Exp, Self::FeeExp>>;
+}
@@ -0,0 +1,316 @@
+//! Rebalance auction sizing against an external reference price.
+//!
+//! The rebalance curves price collateral off the post-trade CR, so the
+//! curve premium or discount shrinks as a swap grows. Profit against a
+//! fixed reference price is therefore single-peaked in the input size, and
+//! the optimum is found by ternary search over the route's executable
+//! input range `[min_input, max_input]`.
+
+use fix::prelude::*;
+use fix::typenum::Integer;
+use hylo_core::error::CoreError;
+use hylo_core::lst::sol_price::LstSolPrice;
+use hylo_core::rebalance::pnl::RebalancePnl;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_idl::tokens::{Exo, TokenMint, USDC};
+
+use crate::protocol_state::ProtocolState;
+use crate::token_operation::{OperationOutput, TokenOperation};
+use crate::LST;
+
+/// Most profitable rebalance swap on one route.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RebalanceArbitrage<InExp: Integer, OutExp: Integer> {
+  pub amount_in: UFix64<InExp>,
+  pub amount_out: UFix64<OutExp>,
+  /// Swapper profit in USDC, valuing collateral at the reference price.
+  pub expected_profit: UFix64<N6>,
+  /// Protocol-side result at oracle spot, settled against the earn pool.
+  pub protocol_pnl: RebalancePnl,
+}
+
+/// USDC value of collateral at `price` (USD per whole token).
+fn usdc_value<Exp: Integer>(
+  amount: UFix64<Exp>,
+  price: UFix64<N9>,
+  round_up: bool,
+) -> Result<UFix64<N6>, CoreError>
+where
+  UFix64<Exp>: FixExt,
+{
+  let value = amount
+    .checked_convert::<N9>()
+    .and_then(|amount| {
+      if round_up {
+        amount.mul_div_ceil(price, UFix64::<N9>::one())
+      } else {
+        amount.mul_div_floor(price, UFix64::<N9>::one())
+      }
+    })
+    .ok_or(CoreError::TokenAmountPrecision)?;
+  if round_up {
+    value.checked_convert_ceil::<N6>()
+  } else {
+    value.checked_convert::<N6>()
+  }
+  .ok_or(CoreError::TokenAmountPrecision)
+}
+
+/// Signed USDC delta `gain - cost` in atoms.
+fn signed_profit(gain: UFix64<N6>, cost: UFix64<N6>) -> i128 {
+  i128::from(gain.bits) - i128::from(cost.bits)
+}
+
+/// Ternary search for the input maximizing `eval` over `[lo, hi]`.
+///
+/// `eval` returns `None` for sizes the route rejects, which rank below any
+/// executable size.
+fn maximize<T>(
+  mut lo: u64,
+  mut hi: u64,
+  eval: impl Fn(u64) -> Option<(i128, T)>,
+) -> Option<(u64, i128, T)> {
+  let score = |x: u64| eval(x).map(|(profit, _)| profit);
+  while hi.saturating_sub(lo) >= 3 {
+    let third = (hi - lo) / 3;
+    let (m1, m2) = (lo + third, hi - third);
+    if score(m1) < score(m2) {
+      lo = m1 + 1;
+    } else {
+      hi = m2;
+    }
+  }
+  (lo..=hi)
+    .filter_map(|x| eval(x).map(|(profit, out)| (x, profit, out)))
+    .max_by_key(|(_, profit, _)| *profit)
+}
+
+/// Output of route `IN -> OUT` on operation provider `S`.
+type RouteOutput<S, IN, OUT> = OperationOutput<
+  <IN as TokenMint>::Exp,
+  <OUT as TokenMint>::Exp,
+  <S as TokenOperation<IN, OUT>>::FeeExp,
+>;
+
+impl<C: SolanaClock> ProtocolState<C> {
+  /// Searches an open route for its most profitable size.
+  fn best_rebalance_swap<IN: TokenMint, OUT: TokenMint>(
+    &self,
+    profit: impl Fn(&RouteOutput<Self, IN, OUT>) -> Result<i128, CoreError>,
+  ) -> Result<Option<(RouteOutput<Self, IN, OUT>, UFix64<N6>)>, CoreError>
+  where
+    Self: TokenOperation<IN, OUT>,
+  {
+    TokenOperation::<IN, OUT>::preconditions(self)?;
+    let lo = TokenOperation::<IN, OUT>::min_input_ungated(self)?.bits;
+    let hi = TokenOperation::<IN, OUT>::max_input_ungated(self)?.bits;
+    let best = maximize(lo, hi, |x| {
+      let output = TokenOperation::<IN, OUT>::compute_output_ungated(
+        self,
+        UFix64::new(x),
+      )
+      .ok()?;
+      profit(&output).ok().map(|p| (p, output))
+    });
+    Ok(best.and_then(|(_, profit, output)| {
+      u64::try_from(profit)
+        .ok()
+        .filter(|p| *p > 0)
+        .map(|p| (output, UFix64::new(p)))
+    }))
+  }
+
+  /// Best `swap_lst_to_usdc` size when the protocol's buy-side premium
+  /// beats the reference LST/USD price.
+  ///
+  /// Returns `None` if no executable size is profitable.
+  ///
+  /// # Errors
+  /// * Route gated (paused, buy curve inactive, drawdown outstanding)
+  /// * Underlying arithmetic
+  pub fn rebalance_arbitrage_lst_to_usdc<L: LST>(
+    &self,
+    reference_price: UFix64<N9>,
+  ) -> Result<Option<RebalanceArbitrage<N9, N6>>, CoreError>
+  where
+    Self: TokenOperation<L, USDC>,
+  {
+    let best = self.best_rebalance_swap::<L, USDC>(|out| {
+      let cost = usdc_value(out.in_amount, reference_price, true)?;
+      Ok(signed_profit(out.out_amount, cost))
+    })?;
+    best
+      .map(|(out, expected_profit)| {
+        let lst_price: LstSolPrice = self.lst_header::<L>()?.price_sol.into();
+        let protocol_pnl = self.exchange_context.rebalance_pnl_buy_side(
+          &lst_price,
+          out.in_amount,
+          out.out_amount,
+        )?;
+        Ok(RebalanceArbitrage {
+          amount_in: out.in_amount,
+          amount_out: out.out_amount,
+          expected_profit,
+          protocol_pnl,
+        })
+      })
+      .transpose()
+  }
+
+  /// Best `swap_usdc_to_lst` size when the protocol's sell-side discount
+  /// undercuts the reference LST/USD price.
+  ///
+  /// Returns `None` if no executable size is profitable.
+  ///
+  /// # Errors
+  /// * Route gated (paused, sell curve inactive, drawdown outstanding)
+  /// * Underlying arithmetic
+  pub fn rebalance_arbitrage_usdc_to_lst<L: LST>(
+    &self,
+    reference_price: UFix64<N9>,
+  ) -> Result<Option<RebalanceArbitrage<N6, N9>>, CoreError>
+  where
+    Self: TokenOperation<USDC, L>,
+  {
+    let best = self.best_rebalance_swap::<USDC, L>(|out| {
+      let gain = usdc_value(out.out_amount, reference_price, false)?;
+      Ok(signed_profit(gain, out.in_amount))
+    })?;
+    best
+      .map(|(out, expected_profit)| {
+        let lst_price: LstSolPrice = self.lst_header::<L>()?.price_sol.into();
+        let protocol_pnl = self.exchange_context.rebalance_pnl_sell_side(
+          &lst_price,
+          out.out_amount,
+          out.in_amount,
+        )?;
+        Ok(RebalanceArbitrage {
+          amount_in: out.in_amount,
+          amount_out: out.out_amount,
+          expected_profit,
+          protocol_pnl,
+        })
+      })
+      .transpose()
+  }
+
+  /// Best `swap_exo_to_usdc` size when the protocol's buy-side premium
+  /// beats the reference collateral/USD price.
+  ///
+  /// Returns `None` if no executable size is profitable.
+  ///
+  /// # Errors
+  /// * Route gated (paused, buy curve inactive, drawdown outstanding)
+  /// * Underlying arithmetic
+  pub fn rebalance_arbitrage_exo_to_usdc<E: Exo>(
+    &self,
+    reference_price: UFix64<N9>,
+  ) -> Result<Option<RebalanceArbitrage<E::Exp, N6>>, CoreError>
+  where
+    Self: TokenOperation<E, USDC>,
+    UFix64<E::Exp>: FixExt,
+  {
+    let best = self.best_rebalance_swap::<E, USDC>(|out| {
+      let cost = usdc_value(out.in_amount, reference_price, true)?;
+      Ok(signed_profit(out.out_amount, cost))
+    })?;
+    best
+      .map(|(out, expected_profit)| {
+        let normalized = out
+          .in_amount
+          .checked_convert::<N9>()
+          .ok_or(CoreError::TokenAmountPrecision)?;
+        let protocol_pnl = self
+          .exo_pair::<E>()?
+          .context
+          .rebalance_pnl_buy_side(normalized, out.out_amount)?;
+        Ok(RebalanceArbitrage {
+          amount_in: out.in_amount,
+          amount_out: out.out_amount,
+          expected_profit,
+          protocol_pnl,
+        })
+      })
+      .transpose()
+  }
+
+  /// Best `swap_usdc_to_exo` size when the protocol's sell-side discount
+  /// undercuts the reference collateral/USD price.
+  ///
+  /// Returns `None` if no executable size is profitable.
+  ///
+  /// # Errors
+  /// * Route gated (paused, sell curve inactive, drawdown outstanding)
+  /// * Underlying arithmetic
+  pub fn rebalance_arbitrage_usdc_to_exo<E: Exo>(
+    &self,
+    reference_price: UFix64<N9>,
+  ) -> Result<Option<RebalanceArbitrage<N6, E::Exp>>, CoreError>
+  where
+    Self: TokenOperation<USDC, E>,
+    UFix64<E::Exp>: FixExt,
+  {
+    let best = self.best_rebalance_swap::<USDC, E>(|out| {
+      let gain = usdc_value(out.out_amount, reference_price, false)?;
+      Ok(signed_profit(gain, out.in_amount))
+    })?;
+    best
+      .map(|(out, expected_profit)| {
+        let normalized = out
+          .out_amount
+          .checked_convert::<N9>()
+          .ok_or(CoreError::TokenAmountPrecision)?;
+        let protocol_pnl = self
+          .exo_pair::<E>()?
+          .context
+          .rebalance_pnl_sell_side(normalized, out.in_amount)?;
+        Ok(RebalanceArbitrage {
+          amount_in: out.in_amount,
+          amount_out: out.out_amount,
+          expected_profit,
+          protocol_pnl,
+        })
+      })
+      .transpose()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Concave profit peaking at `peak`, mimicking a shrinking premium.
+  fn concave(peak: u64) -> impl Fn(u64) -> Option<(i128, ())> {
+    move |x| {
+      let d = i128::from(x) - i128::from(peak);
+      Some((1_000_000 - d * d, ()))
+    }
+  }
+
+  #[test]
+  fn finds_interior_peak() {
+    let best = maximize(0, 1_000_000, concave(123_457));
+    assert_eq!(best.map(|(x, _, ())| x), Some(123_457));
+  }
+
+  #[test]
+  fn peak_at_range_edges() {
+    assert_eq!(maximize(10, 500, concave(0)).map(|(x, ..)| x), Some(10));
+    assert_eq!(maximize(10, 500, concave(9_000)).map(|(x, ..)| x), Some(500));
+  }
+
+  #[test]
+  fn rejected_sizes_rank_last() {
+    let capped = |x: u64| (x <= 700).then_some((i128::from(x), ()));
+    assert_eq!(maximize(0, 10_000, capped).map(|(x, ..)| x), Some(700));
+  }
+
+  #[test]
+  fn usdc_value_rounds_by_side() -> Result<(), CoreError> {
+    let amount = UFix64::<N9>::new(1);
+    let price = UFix64::<N9>::new(150_000_000_000);
+    assert_eq!(usdc_value(amount, price, false)?, UFix64::zero());
+    assert_eq!(usdc_value(amount, price, true)?, UFix64::new(1));
+    Ok(())
+  }
+}
@@ -0,0 +1,254 @@
+//! Why a route is closed or capped, and when it may reopen.
+//!
+//! [`TokenOperation::preconditions`] stops at the first failing gate.
+//! [`RouteStatus`] evaluates all of them, reports headroom under each
+//! limiter capping an open route, and hints at what reopens it.
+
+use fix::prelude::{UFix64, UFixValue64};
+use fix::typenum::Integer;
+use hylo_core::error::CoreError;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_idl::tokens::TokenMint;
+
+use crate::protocol_state::ProtocolState;
+use crate::quote_expiry::next_epoch_start;
+use crate::token_operation::{Gate, GateCheck, Limiter, TokenOperation};
+
+/// What has to happen for a closed gate or exhausted limiter to reopen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reopen {
+  /// Epoch rollover, at roughly `estimated_at` (unix seconds)
+  NextEpoch { epoch: u64, estimated_at: i64 },
+  /// Harvest crank for the current epoch
+  Harvest,
+  /// Next oracle price update within tolerance, expected within one
+  /// oracle interval of the state, at roughly `estimated_at` (unix seconds)
+  OracleUpdate { estimated_at: i64 },
+  /// Admin unpause
+  Governance,
+  /// Stability pool drawdown repayment
+  DrawdownRepayment,
+  /// Collateral ratio, supply or peg moving back in range
+  MarketConditions,
+}
+
+/// Headroom under one limiter, in input-mint units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LimitHeadroom {
+  pub limiter: Limiter,
+  pub headroom: UFixValue64,
+}
+
+/// Every gate and limiter on a route in one state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouteStatus {
+  pub gates: Vec<GateCheck>,
+  /// Headroom under each limiter, empty while a gate is closed
+  pub limits: Vec<LimitHeadroom>,
+  /// Executable input ceiling, `None` while the route is closed
+  pub max_input: Option<UFixValue64>,
+  /// Reopen hints for each closed gate and exhausted limiter
+  pub reopen: Vec<Reopen>,
+}
+
+impl RouteStatus {
+  /// Evaluates the `IN -> OUT` route against `state`.
+  ///
+  /// Limiters are only read once every gate is open; a closed gate can
+  /// leave them undefined, as the levercoin fees are in depeg.
+  ///
+  /// # Errors
+  /// * Sub-state the route reads is missing
+  /// * Limiter arithmetic on an open route
+  pub fn evaluate<IN, OUT, C>(
+    state: &ProtocolState<C>,
+  ) -> Result<RouteStatus, CoreError>
+  where
+    IN: TokenMint,
+    OUT: TokenMint,
+    C: SolanaClock,
+    ProtocolState<C>: TokenOperation<IN, OUT>,
+  {
+    let gates = TokenOperation::<IN, OUT>::gates(state)?;
+    let open = gates.iter().all(GateCheck::is_open);
+    let limits = if open {
+      TokenOperation::<IN, OUT>::limits(state)?
+        .into_iter()
+        .map(|limit| headroom(limit.limiter, limit.headroom))
+        .collect::<Vec<_>>()
+    } else {
+      Vec::new()
+    };
+    let max_input = open
+      .then(|| TokenOperation::<IN, OUT>::max_input_ungated(state))
+      .transpose()?
+      .map(UFixValue64::from);
+    let clock = &state.exchange_context.clock;
+    let mut reopen = Vec::new();
+    let hints = gates
+      .iter()
+      .filter(|check| !check.is_open())
+      .map(|check| gate_reopen(check.gate, state))
+      .chain(
+        limits
+          .iter()
+          .filter(|limit| limit.headroom.bits == 0)
+          .map(|limit| limiter_reopen(limit.limiter, clock)),
+      );
+    hints.for_each(|hint| {
+      if !reopen.contains(&hint) {
+        reopen.push(hint);
+      }
+    });
+    Ok(RouteStatus {
+      gates,
+      limits,
+      max_input,
+      reopen,
+    })
+  }
+
+  /// Whether every gate is open.
+  #[must_use]
+  pub fn is_open(&self) -> bool {
+    self.gates.iter().all(GateCheck::is_open)
+  }
+
+  /// Closed gates, in evaluation order.
+  pub fn closed(&self) -> impl Iterator<Item = &GateCheck> {
+    self.gates.iter().filter(|check| !check.is_open())
+  }
+
+  /// Limiter with the least headroom, the one that binds first.
+  #[must_use]
+  pub fn binding(&self) -> Option<&LimitHeadroom> {
+    self.limits.iter().min_by_key(|limit| limit.headroom.bits)
+  }
+}
+
+fn headroom<Exp: Integer>(
+  limiter: Limiter,
+  headroom: UFix64<Exp>,
+) -> LimitHeadroom {
+  LimitHeadroom {
+    limiter,
+    headroom: headroom.into(),
+  }
+}
+
+fn gate_reopen<C: SolanaClock>(gate: Gate, state: &ProtocolState<C>) -> Reopen {
+  match gate {
+    Gate::ProtocolActive | Gate::PairActive(_) => Reopen::Governance,
+    Gate::YieldHarvested | Gate::BorrowRateHarvested => Reopen::Harvest,
+    Gate::OracleFresh => Reopen::OracleUpdate {
+      estimated_at: state
+        .fetched_at
+        .saturating_add_unsigned(state.oracle_interval_secs),
+    },
+    Gate::DrawdownRepaid => Reopen::DrawdownRepayment,
+    Gate::ModeAllows | Gate::UsdcAtPar | Gate::EarnPoolLiquid => {
+      Reopen::MarketConditions
+    }
+  }
+}
+
+fn limiter_reopen<C: SolanaClock>(limiter: Limiter, clock: &C) -> Reopen {
+  match limiter {
+    Limiter::WithdrawalLimiter => next_epoch(clock),
+    Limiter::DepositLimiter
+    | Limiter::LevercoinMarketCap
+    | Limiter::MaxMintable
+    | Limiter::SupplyFloor
+    | Limiter::StabilityProjection
+    | Limiter::ShareSupply => Reopen::MarketConditions,
+  }
+}
+
+/// Next epoch and its estimated start, see [`next_epoch_start`].
+pub fn next_epoch<C: SolanaClock>(clock: &C) -> Reopen {
+  let (_, estimated_at) = next_epoch_start(clock);
+  Reopen::NextEpoch {
+    epoch: clock.epoch() + 1,
+    estimated_at,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Clock;
+  use fix::prelude::N6;
+
+  use super::*;
+  use crate::token_operation::Pair;
+
+  fn limit(limiter: Limiter, bits: u64) -> LimitHeadroom {
+    headroom(limiter, UFix64::<N6>::new(bits))
+  }
+
+  #[test]
+  fn binding_is_least_headroom() {
+    let status = RouteStatus {
+      gates: vec![GateCheck::new(
+        Gate::ProtocolActive,
+        true,
+        CoreError::ProtocolPaused,
+      )],
+      limits: vec![
+        limit(Limiter::LevercoinMarketCap, 500),
+        limit(Limiter::SupplyFloor, 200),
+        limit(Limiter::StabilityProjection, 900),
+      ],
+      max_input: Some(UFix64::<N6>::new(200).into()),
+      reopen: Vec::new(),
+    };
+    assert!(status.is_open());
+    assert_eq!(
+      status.binding().map(|limit| limit.limiter),
+      Some(Limiter::SupplyFloor)
+    );
+  }
+
+  #[test]
+  fn closed_gates_kept_in_order() {
+    let status = RouteStatus {
+      gates: vec![
+        GateCheck::new(Gate::ProtocolActive, true, CoreError::ProtocolPaused),
+        GateCheck::new(
+          Gate::PairActive(Pair::Lst),
+          false,
+          CoreError::PairPaused,
+        ),
+        GateCheck::new(
+          Gate::YieldHarvested,
+          false,
+          CoreError::YieldHarvestNotRun,
+        ),
+      ],
+      limits: Vec::new(),
+      max_input: None,
+      reopen: Vec::new(),
+    };
+    let closed = status.closed().map(|check| check.gate).collect::<Vec<_>>();
+    assert!(!status.is_open());
+    assert_eq!(
+      closed,
+      vec![Gate::PairActive(Pair::Lst), Gate::YieldHarvested]
+    );
+  }
+
+  #[test]
+  fn exhausted_withdrawals_reopen_next_epoch() {
+    let clock = Clock {
+      epoch: 800,
+      epoch_start_timestamp: 1_700_000_000,
+      ..Clock::default()
+    };
+    assert_eq!(
+      limiter_reopen(Limiter::WithdrawalLimiter, &clock),
+      Reopen::NextEpoch {
+        epoch: 801,
+        estimated_at: 1_700_172_800,
+      }
+    );
+  }
+}
@@ -1,18 +1,49 @@
 use anchor_lang::prelude::Pubkey;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use fix::prelude::UFix64;
+use fix::typenum::Integer;
 use hylo_core::solana_clock::SolanaClock;
 use hylo_idl::tokens::{
   TokenMint, CBBTC, HYLOSOL, HYPE, HYUSD, JITOSOL, ONYC, PST, SHYUSD, USDC,
   WETH, XBTC, XETH, XHYPE, XONYC, XPST, XSOL, XZEC, ZEC,
 };
 
+use crate::price_breakdown::PriceBreakdown;
 use crate::protocol_state::ProtocolState;
 use crate::quote_metadata::{Operation, QuoteMetadata};
-use crate::quote_strategy::QuoteStrategy;
+use crate::quote_strategy::{QuoteStrategy, StateQuoteStrategy};
+use crate::route_status::RouteStatus;
+use crate::slippage_recommendation::{SlippageModel, SlippageRecommendation};
 use crate::token_operation::TokenOperation;
 use crate::ExecutableQuoteValue;
 
+/// Mint pair with its operation and input precision.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RuntimePair {
+  pub input_mint: Pubkey,
+  pub output_mint: Pubkey,
+  pub operation: Operation,
+  /// Decimal exponent of the input mint
+  pub input_exp: i8,
+  /// Human-readable route description
+  pub description: &'static str,
+}
+
+/// Operation behind a runtime pair, if supported.
+#[must_use]
+pub fn pair_operation(
+  input_mint: Pubkey,
+  output_mint: Pubkey,
+) -> Option<Operation> {
+  RUNTIME_PAIRS
+    .iter()
+    .find(|pair| {
+      pair.input_mint == input_mint && pair.output_mint == output_mint
+    })
+    .map(|pair| pair.operation)
+}
+
 macro_rules! runtime_quote_strategies {
     ($(($in:ty, $out:ty, $op:expr, $desc:expr)),* $(,)?) => {
       /// Runtime dispatch trait bridging untyped `Pubkey` pair to typed `QuoteStrategy`.
@@ -59,6 +90,47 @@ macro_rules! runtime_quote_strategies {
         }
       }
 
+      /// Runtime dispatch of [`StateQuoteStrategy`] over one fetched state.
+      pub trait RuntimeStateQuoteStrategy<C: SolanaClock>: $( StateQuoteStrategy<$in, $out, C> + )* {
+        /// Quotes the mint pair against `state` without fetching.
+        ///
+        /// # Errors
+        /// * Unsupported pair
+        /// * Quote computation
+        fn runtime_quote_from_state(
+          &self,
+          state: &ProtocolState<C>,
+          input_mint: Pubkey,
+          output_mint: Pubkey,
+          amount_in: u64,
+          user: Pubkey,
+          slippage_tolerance: u64,
+        ) -> Result<ExecutableQuoteValue> {
+          match (input_mint, output_mint) {
+            $(
+              (<$in>::MINT, <$out>::MINT) => {
+                let quote = StateQuoteStrategy::<$in, $out, C>::quote_from_state(self, state, amount_in, user, slippage_tolerance)?;
+                Ok(quote.into())
+              },
+            )*
+            _ => Err(anyhow!("Unsupported pair")),
+          }
+        }
+      }
+
+      /// Every runtime-dispatched pair, in dispatch order.
+      pub const RUNTIME_PAIRS: &[RuntimePair] = &[
+        $(
+          RuntimePair {
+            input_mint: <$in>::MINT,
+            output_mint: <$out>::MINT,
+            operation: $op,
+            input_exp: <<$in as TokenMint>::Exp as Integer>::I8,
+            description: $desc,
+          },
+        )*
+      ];
+
       impl<C: SolanaClock> ProtocolState<C> {
         /// Executable input ceiling for the pair in input-mint atoms.
         ///
@@ -97,6 +169,68 @@ macro_rules! runtime_quote_strategies {
             _ => Err(anyhow!("Unsupported pair")),
           }
         }
+
+        /// Price components and realized fee behind the pair's quote for
+        /// `amount_in` input-mint atoms.
+        ///
+        /// # Errors
+        /// * Unsupported pair, route gated in current state or arithmetic
+        pub fn runtime_price_breakdown(
+          &self,
+          input_mint: Pubkey,
+          output_mint: Pubkey,
+          amount_in: u64,
+        ) -> Result<PriceBreakdown> {
+          match (input_mint, output_mint) {
+            $(
+              (<$in>::MINT, <$out>::MINT) => {
+                Ok(TokenOperation::<$in, $out>::price_breakdown(self, UFix64::new(amount_in))?)
+              },
+            )*
+            _ => Err(anyhow!("Unsupported pair")),
+          }
+        }
+
+        /// Every gate, limiter and reopen hint for the pair.
+        ///
+        /// # Errors
+        /// * Unsupported pair or missing sub-state
+        pub fn runtime_route_status(
+          &self,
+          input_mint: Pubkey,
+          output_mint: Pubkey,
+        ) -> Result<RouteStatus> {
+          match (input_mint, output_mint) {
+            $(
+              (<$in>::MINT, <$out>::MINT) => {
+                Ok(RouteStatus::evaluate::<$in, $out, C>(self)?)
+              },
+            )*
+            _ => Err(anyhow!("Unsupported pair")),
+          }
+        }
+
+        /// Slippage tolerance recommended for `amount_in` input-mint atoms
+        /// on the pair, with the terms behind it.
+        ///
+        /// # Errors
+        /// * Unsupported pair, route gated in current state or arithmetic
+        pub fn runtime_recommend_slippage(
+          &self,
+          input_mint: Pubkey,
+          output_mint: Pubkey,
+          amount_in: u64,
+          model: &SlippageModel,
+        ) -> Result<SlippageRecommendation> {
+          match (input_mint, output_mint) {
+            $(
+              (<$in>::MINT, <$out>::MINT) => {
+                Ok(SlippageRecommendation::evaluate::<$in, $out, C>(self, UFix64::new(amount_in), model)?)
+              },
+            )*
+            _ => Err(anyhow!("Unsupported pair")),
+          }
+        }
       }
     };
 }
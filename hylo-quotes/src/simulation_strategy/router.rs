@@ -16,6 +16,7 @@ use hylo_idl::tokens::{
 };
 use hylo_idl::with_exo_pairs;
 
+use crate::quote_expiry::QuoteExpiry;
 use crate::simulated_operation::SimulatedOperationExt;
 use crate::simulation_strategy::SimulationStrategy;
 use crate::{ExecutableQuote, QuoteStrategy};
@@ -28,20 +29,23 @@ fn sim_args(amount_in: u64, user: Pubkey) -> RouterArgs {
   }
 }
 
+/// Router args carrying the slippage bound, with the least output it
+/// accepts.
 fn quote_args<E: Integer>(
   amount_in: u64,
   user: Pubkey,
   out_amount: UFix64<E>,
   slippage_tolerance: u64,
-) -> RouterArgs {
-  RouterArgs {
+) -> Result<(RouterArgs, UFix64<E>)> {
+  let slippage_config =
+    SlippageConfig::new(out_amount, UFix64::<N4>::new(slippage_tolerance));
+  let min_amount_out = slippage_config.min_token_out()?;
+  let args = RouterArgs {
     amount: amount_in,
     user,
-    slippage_config: Some(SlippageConfig::new(
-      out_amount,
-      UFix64::<N4>::new(slippage_tolerance),
-    )),
-  }
+    slippage_config: Some(slippage_config),
+  };
+  Ok((args, min_amount_out))
 }
 
 macro_rules! simulation_quote {
@@ -60,20 +64,22 @@ macro_rules! simulation_quote {
           .router_client
           .simulate_output::<$in, $out>(user, sim_args(amount_in, user))
           .await?;
-        let args =
-          quote_args(amount_in, user, output.out_amount, slippage_tolerance);
+        let (args, min_amount_out) =
+          quote_args(amount_in, user, output.out_amount, slippage_tolerance)?;
         let instructions = RouterClient::build_instructions::<$in, $out>(args)?;
         let address_lookup_tables =
           RouterClient::lookup_tables::<$in, $out>().into();
         Ok(ExecutableQuote {
           amount_in: output.in_amount,
           amount_out: output.out_amount,
+          min_amount_out,
           compute_units: cu_info.compute_units,
           compute_unit_strategy: cu_info.strategy,
           fee_amount: output.fee_amount,
           fee_mint: output.fee_mint,
           instructions,
           address_lookup_tables,
+          expiry: QuoteExpiry::default(),
         })
       }
     }
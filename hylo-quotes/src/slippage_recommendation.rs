@@ -0,0 +1,359 @@
+//! Slippage tolerance sized per quote.
+//!
+//! A fixed tolerance fails quotes in volatile or boundary states and
+//! overpays in calm ones. [`SlippageRecommendation`] adds up what can move
+//! a route's output between quoting and landing:
+//!
+//! - oracle confidence width, a proxy for near-term collateral moves
+//! - time left in the oracle validity window against landing time
+//! - distance from the collateral ratio to the next [`RebalanceMode`]
+//!   boundary, and the fee tier step across it
+//! - the route's marginal rate sensitivity, what a same-size order landing
+//!   first costs
+
+use fix::prelude::{CheckedSub, UFix64, N9};
+use hylo_core::error::CoreError;
+use hylo_core::rebalance::mode::RebalanceMode;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_idl::tokens::TokenMint;
+
+use crate::price_breakdown::{PriceBreakdown, PriceComponents};
+use crate::protocol_state::ProtocolState;
+use crate::quote_expiry::Deadline;
+use crate::token_operation::{atom_rate, TokenOperation};
+
+const BPS: f64 = 10_000.0;
+
+/// Tunables for [`SlippageRecommendation::evaluate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlippageModel {
+  /// Always included, covering rounding between quote and execution
+  pub base_bps: u64,
+  /// Ceiling on the recommended tolerance
+  pub max_bps: u64,
+  /// Expected seconds from quote to the transaction landing
+  pub landing_secs: i64,
+}
+
+impl Default for SlippageModel {
+  fn default() -> Self {
+    SlippageModel {
+      base_bps: 5,
+      max_bps: 300,
+      landing_secs: 4,
+    }
+  }
+}
+
+/// Reasoning behind a recommendation, each term in basis points of output.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SlippageComponents {
+  /// Oracle confidence half-width relative to spot
+  pub oracle_confidence_bps: f64,
+  /// Confidence scaled by the share of the remaining oracle window spent
+  /// landing; a feed near the end of its window is likelier to update
+  pub oracle_window_bps: f64,
+  /// Seconds left before the oracle leaves its validity window
+  pub oracle_secs_remaining: Option<i64>,
+  /// Collateral ratio distance to the nearest mode boundary
+  pub mode_boundary_distance_bps: Option<f64>,
+  /// Fee tier increase across that boundary, budgeted when the price
+  /// terms could carry the collateral ratio over it
+  pub mode_boundary_bps: f64,
+  /// Gap between the average and marginal rate of the order
+  pub rate_sensitivity_bps: f64,
+}
+
+impl SlippageComponents {
+  /// Sum of the terms, excluding the model's base.
+  #[must_use]
+  pub fn total_bps(&self) -> f64 {
+    self.oracle_confidence_bps
+      + self.oracle_window_bps
+      + self.mode_boundary_bps
+      + self.rate_sensitivity_bps
+  }
+}
+
+/// Tolerance to quote with and why.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SlippageRecommendation {
+  pub tolerance_bps: u64,
+  pub components: SlippageComponents,
+  /// Oracle window closes before the expected landing; no tolerance keeps
+  /// the quote executable, requote after the next oracle update
+  pub expires_before_landing: bool,
+}
+
+impl SlippageRecommendation {
+  /// Recommends a tolerance for `amount_in` on the `IN -> OUT` route.
+  ///
+  /// # Errors
+  /// * Route gated in current state or underlying arithmetic
+  pub fn evaluate<IN, OUT, C>(
+    state: &ProtocolState<C>,
+    amount_in: UFix64<IN::Exp>,
+    model: &SlippageModel,
+  ) -> Result<SlippageRecommendation, CoreError>
+  where
+    IN: TokenMint,
+    OUT: TokenMint,
+    C: SolanaClock,
+    ProtocolState<C>: TokenOperation<IN, OUT>,
+  {
+    let output = TokenOperation::<IN, OUT>::compute_output(state, amount_in)?;
+    let price_components =
+      TokenOperation::<IN, OUT>::price_components(state, amount_in)?;
+    let now = state.exchange_context.clock.unix_timestamp();
+    let oracle_secs_remaining = TokenOperation::<IN, OUT>::deadlines(state)?
+      .iter()
+      .filter_map(|deadline| match *deadline {
+        Deadline::Oracle { expires_at, .. }
+        | Deadline::StablecoinOracle { expires_at, .. } => Some(expires_at),
+        Deadline::EpochRollover { .. } => None,
+      })
+      .min()
+      .map(|at| at.saturating_sub(now).max(0));
+    let breakdown = PriceBreakdown::new(price_components, &output);
+    let average_rate =
+      atom_rate::<IN::Exp, OUT::Exp>(breakdown.effective_price);
+    Ok(recommend(
+      model,
+      &price_components,
+      oracle_secs_remaining,
+      rate_gap_bps(average_rate, output.marginal_rate),
+    ))
+  }
+}
+
+/// Assembles the recommendation from route-level inputs.
+fn recommend(
+  model: &SlippageModel,
+  price_components: &PriceComponents,
+  oracle_secs_remaining: Option<i64>,
+  rate_sensitivity_bps: f64,
+) -> SlippageRecommendation {
+  let oracle_confidence_bps =
+    match (price_components.oracle_conf, price_components.oracle_spot) {
+      (Some(conf), Some(spot)) => relative_bps(conf, spot).unwrap_or(0.0),
+      _ => 0.0,
+    };
+  let oracle_window_bps = oracle_secs_remaining.map_or(0.0, |remaining| {
+    oracle_confidence_bps * landing_share(model.landing_secs, remaining)
+  });
+  let price_move_bps = oracle_confidence_bps + oracle_window_bps;
+  let mode_boundary_distance_bps = price_components
+    .rebalance_mode
+    .zip(price_components.collateral_ratio)
+    .and_then(|(mode, cr)| boundary_distance_bps(mode, cr));
+  let mode_boundary_bps = match mode_boundary_distance_bps {
+    Some(distance) if distance <= price_move_bps => {
+      fee_step_bps(price_components)
+    }
+    _ => 0.0,
+  };
+  let components = SlippageComponents {
+    oracle_confidence_bps,
+    oracle_window_bps,
+    oracle_secs_remaining,
+    mode_boundary_distance_bps,
+    mode_boundary_bps,
+    rate_sensitivity_bps,
+  };
+  SlippageRecommendation {
+    tolerance_bps: clamp_bps(model, components.total_bps()),
+    components,
+    expires_before_landing: oracle_secs_remaining
+      .is_some_and(|remaining| remaining <= model.landing_secs),
+  }
+}
+
+/// `value / base` in basis points, `None` for a zero base. Divides raw
+/// bits so exact ratios stay exact.
+#[allow(clippy::cast_precision_loss)]
+fn relative_bps(value: UFix64<N9>, base: UFix64<N9>) -> Option<f64> {
+  (base > UFix64::zero()).then(|| value.bits as f64 * BPS / base.bits as f64)
+}
+
+/// Share of the remaining oracle window a landing transaction uses, capped
+/// at one.
+#[allow(clippy::cast_precision_loss)]
+fn landing_share(landing_secs: i64, remaining_secs: i64) -> f64 {
+  if remaining_secs <= landing_secs {
+    1.0
+  } else {
+    landing_secs.max(0) as f64 / remaining_secs as f64
+  }
+}
+
+/// Nearest interior edge of `mode` to `cr`, with its relative distance in
+/// basis points and whether it is the range's lower edge.
+fn nearest_boundary(
+  mode: RebalanceMode,
+  cr: UFix64<N9>,
+) -> Option<(UFix64<N9>, f64, bool)> {
+  let range = mode.active_range();
+  [(range.start().ok(), true), (range.end().ok(), false)]
+    .into_iter()
+    .filter_map(|(boundary, lower)| boundary.map(|b| (b, lower)))
+    .filter(|(boundary, _)| *boundary > UFix64::zero())
+    .filter_map(|(boundary, lower)| {
+      relative_bps(boundary, cr)
+        .map(|ratio| (boundary, (ratio - BPS).abs(), lower))
+    })
+    .min_by(|(_, a, _), (_, b, _)| a.total_cmp(b))
+}
+
+/// Relative distance from `cr` to the nearest interior edge of `mode`.
+fn boundary_distance_bps(mode: RebalanceMode, cr: UFix64<N9>) -> Option<f64> {
+  nearest_boundary(mode, cr).map(|(_, distance, _)| distance)
+}
+
+/// Mode on the far side of the boundary nearest to `cr`, `None` when
+/// `mode` has no interior edge.
+#[must_use]
+pub(crate) fn mode_across_boundary(
+  mode: RebalanceMode,
+  cr: UFix64<N9>,
+) -> Option<RebalanceMode> {
+  nearest_boundary(mode, cr).and_then(|(boundary, _, lower)| {
+    if lower {
+      boundary
+        .checked_sub(&UFix64::new(1))
+        .map(RebalanceMode::from_cr)
+    } else {
+      Some(RebalanceMode::from_cr(boundary))
+    }
+  })
+}
+
+/// Fee tier increase from the current mode to the one across the nearest
+/// boundary, zero for flat schedules or a cheaper next tier.
+fn fee_step_bps(price_components: &PriceComponents) -> f64 {
+  price_components
+    .fee_tier
+    .zip(price_components.boundary_fee_tier)
+    .map_or(0.0, |(current, next)| {
+      ((next.to_f64() - current.to_f64()) * BPS).max(0.0)
+    })
+}
+
+/// How far the marginal rate falls below the average, in basis points.
+fn rate_gap_bps(average_rate: f64, marginal_rate: f64) -> f64 {
+  if average_rate > 0.0 && marginal_rate.is_finite() {
+    ((1.0 - marginal_rate / average_rate) * BPS).max(0.0)
+  } else {
+    0.0
+  }
+}
+
+/// Base plus `total_bps`, rounded up and capped by the model.
+fn clamp_bps(model: &SlippageModel, total_bps: f64) -> u64 {
+  let max_extra = model.max_bps.saturating_sub(model.base_bps);
+  // Clamped into `[0, max_extra]` before the cast
+  #[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+  )]
+  let extra = if total_bps.is_finite() {
+    total_bps.ceil().clamp(0.0, max_extra as f64) as u64
+  } else {
+    max_extra
+  };
+  model.base_bps.saturating_add(extra).min(model.max_bps)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn near(value: f64, expected: f64) -> bool {
+    (value - expected).abs() < 1e-6
+  }
+
+  /// SOL at $150 with a 15 cent confidence: 10 bps. The fee tier is 20
+  /// bps, 30 bps across the nearest boundary.
+  fn components(cr: u64) -> PriceComponents {
+    PriceComponents {
+      oracle_spot: Some(UFix64::new(150_000_000_000)),
+      oracle_conf: Some(UFix64::new(150_000_000)),
+      rebalance_mode: Some(RebalanceMode::from_cr(UFix64::new(cr))),
+      collateral_ratio: Some(UFix64::new(cr)),
+      fee_tier: Some(UFix64::new(200)),
+      boundary_fee_tier: Some(UFix64::new(300)),
+      ..PriceComponents::default()
+    }
+  }
+
+  #[test]
+  fn calm_state_adds_confidence_and_window() {
+    let model = SlippageModel::default();
+    let rec = recommend(&model, &components(1_500_000_000), Some(40), 0.0);
+    assert!(near(rec.components.oracle_confidence_bps, 10.0));
+    assert!(near(rec.components.oracle_window_bps, 1.0));
+    assert!(near(rec.components.mode_boundary_bps, 0.0));
+    assert_eq!(rec.tolerance_bps, 5 + 11);
+    assert!(!rec.expires_before_landing);
+  }
+
+  #[test]
+  fn near_boundary_budgets_fee_step() {
+    let model = SlippageModel::default();
+    // 0.1% above the Neutral floor of 1.35
+    let rec = recommend(&model, &components(1_351_350_000), Some(40), 0.0);
+    assert!(rec
+      .components
+      .mode_boundary_distance_bps
+      .is_some_and(|distance| (distance - 10.0).abs() < 0.01));
+    assert!(near(rec.components.mode_boundary_bps, 10.0));
+    assert_eq!(rec.tolerance_bps, 5 + 21);
+  }
+
+  #[test]
+  fn cheaper_next_tier_budgets_nothing() {
+    let model = SlippageModel::default();
+    let rec = recommend(
+      &model,
+      &PriceComponents {
+        boundary_fee_tier: Some(UFix64::new(100)),
+        ..components(1_351_350_000)
+      },
+      Some(40),
+      0.0,
+    );
+    assert!(near(rec.components.mode_boundary_bps, 0.0));
+    assert_eq!(rec.tolerance_bps, 5 + 11);
+  }
+
+  #[test]
+  fn mode_across_nearest_boundary() {
+    let across = |cr: u64| {
+      let cr = UFix64::new(cr);
+      mode_across_boundary(RebalanceMode::from_cr(cr), cr)
+    };
+    assert_eq!(across(1_351_350_000), Some(RebalanceMode::SellZone1));
+    assert_eq!(across(1_640_000_000), Some(RebalanceMode::BuyZone1));
+    assert_eq!(across(1_200_000_000), Some(RebalanceMode::SellZone2));
+    assert_eq!(across(2_000_000_000), Some(RebalanceMode::BuyZone1));
+  }
+
+  #[test]
+  fn stale_window_flags_and_caps() {
+    let model = SlippageModel {
+      max_bps: 20,
+      ..SlippageModel::default()
+    };
+    let rec = recommend(&model, &components(1_500_000_000), Some(3), 250.0);
+    assert!(rec.expires_before_landing);
+    assert!(near(rec.components.oracle_window_bps, 10.0));
+    assert_eq!(rec.tolerance_bps, 20);
+  }
+
+  #[test]
+  fn rate_gap_only_counts_decline() {
+    assert!(near(rate_gap_bps(2.0, 1.99), 50.0));
+    assert!(near(rate_gap_bps(2.0, 2.1), 0.0));
+    assert!(near(rate_gap_bps(0.0, 1.0), 0.0));
+  }
+}
@@ -13,35 +13,72 @@ use hylo_core::limiter::withdraw::WithdrawalLimiter;
 use hylo_core::solana_clock::SolanaClock;
 use hylo_idl::tokens::{TokenMint, HYUSD, SHYUSD};
 
+use crate::price_breakdown::PriceComponents;
 use crate::protocol_state::ProtocolState;
 use crate::token_operation::{
-  gate, past_zero, OperationOutput, SwapOperationOutput, TokenOperation,
+  gate, past_zero, Gate, GateSink, Limiter, OperationOutput, Pair, RouteLimit,
+  SwapOperationOutput, TokenOperation,
 };
 
+impl<C: SolanaClock> ProtocolState<C> {
+  /// hyUSD backing each sHYUSD share, widened to `N9` for comparison with
+  /// other NAVs.
+  pub(crate) fn shyusd_nav(&self) -> Result<UFix64<N9>, CoreError> {
+    let pool = self.earn_pool()?;
+    lp_token_nav(
+      UFix64::new(pool.hyusd_pool.amount),
+      UFix64::new(pool.shyusd_mint.supply),
+    )?
+    .checked_convert::<N9>()
+    .ok_or(CoreError::TokenAmountPrecision)
+  }
+}
+
 impl<C: SolanaClock> TokenOperation<HYUSD, SHYUSD> for ProtocolState<C> {
   type FeeExp = N6;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    gate(!self.protocol_paused, CoreError::ProtocolPaused)?;
-    gate(!self.pool_config.paused, CoreError::PairPaused)?;
-    gate(
-      self.hyusd_pool.amount > 0 || self.shyusd_mint.supply == 0,
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    sink.check(
+      Gate::ProtocolActive,
+      !self.protocol_paused,
+      CoreError::ProtocolPaused,
+    )?;
+    let pool = self.earn_pool()?;
+    sink.check(
+      Gate::PairActive(Pair::EarnPool),
+      !pool.pool_config.paused,
+      CoreError::PairPaused,
+    )?;
+    sink.check(
+      Gate::EarnPoolLiquid,
+      pool.hyusd_pool.amount > 0 || pool.shyusd_mint.supply == 0,
       CoreError::OperationDisabled,
     )
   }
 
+  fn price_components(
+    &self,
+    _amount_in: UFix64<N6>,
+  ) -> Result<PriceComponents, CoreError> {
+    Ok(PriceComponents {
+      output_nav: Some(self.shyusd_nav()?),
+      ..PriceComponents::default()
+    })
+  }
+
   fn compute_output_ungated(
     &self,
     in_amount: UFix64<N6>,
   ) -> Result<SwapOperationOutput, CoreError> {
+    let pool = self.earn_pool()?;
     let shyusd_nav = lp_token_nav(
-      UFix64::new(self.hyusd_pool.amount),
-      UFix64::new(self.shyusd_mint.supply),
+      UFix64::new(pool.hyusd_pool.amount),
+      UFix64::new(pool.shyusd_mint.supply),
     )?;
     let deposit_limiter: DepositLimiter =
-      self.pool_config.deposit_limiter.into();
+      pool.pool_config.deposit_limiter.into();
     deposit_limiter
-      .validate_deposit(UFix64::new(self.hyusd_pool.amount), in_amount)?;
+      .validate_deposit(UFix64::new(pool.hyusd_pool.amount), in_amount)?;
     let shyusd_out = lp_token_out(in_amount, shyusd_nav)?;
 
     // shyusd_out(x) = x / shyusd_nav
@@ -57,15 +94,24 @@ impl<C: SolanaClock> TokenOperation<HYUSD, SHYUSD> for ProtocolState<C> {
   }
 
   fn max_input_ungated(&self) -> Result<UFix64<N6>, CoreError> {
+    let pool = self.earn_pool()?;
     let deposit_limiter: DepositLimiter =
-      self.pool_config.deposit_limiter.into();
-    deposit_limiter.max_deposit(UFix64::new(self.hyusd_pool.amount))
+      pool.pool_config.deposit_limiter.into();
+    deposit_limiter.max_deposit(UFix64::new(pool.hyusd_pool.amount))
+  }
+
+  fn limits(&self) -> Result<Vec<RouteLimit<N6>>, CoreError> {
+    Ok(vec![RouteLimit::new(
+      Limiter::DepositLimiter,
+      TokenOperation::<HYUSD, SHYUSD>::max_input_ungated(self)?,
+    )])
   }
 
   fn min_input_ungated(&self) -> Result<UFix64<N6>, CoreError> {
+    let pool = self.earn_pool()?;
     let shyusd_nav = lp_token_nav(
-      UFix64::new(self.hyusd_pool.amount),
-      UFix64::new(self.shyusd_mint.supply),
+      UFix64::new(pool.hyusd_pool.amount),
+      UFix64::new(pool.shyusd_mint.supply),
     )?;
     past_zero(max_token_for_lp_deposit(UFix64::zero(), shyusd_nav)?)
   }
@@ -74,21 +120,42 @@ impl<C: SolanaClock> TokenOperation<HYUSD, SHYUSD> for ProtocolState<C> {
 impl<C: SolanaClock> TokenOperation<SHYUSD, HYUSD> for ProtocolState<C> {
   type FeeExp = N6;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    gate(!self.protocol_paused, CoreError::ProtocolPaused)?;
-    gate(!self.pool_config.paused, CoreError::PairPaused)?;
-    gate(
-      self.hyusd_pool.amount > 0,
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    sink.check(
+      Gate::ProtocolActive,
+      !self.protocol_paused,
+      CoreError::ProtocolPaused,
+    )?;
+    let pool = self.earn_pool()?;
+    sink.check(
+      Gate::PairActive(Pair::EarnPool),
+      !pool.pool_config.paused,
+      CoreError::PairPaused,
+    )?;
+    sink.check(
+      Gate::EarnPoolLiquid,
+      pool.hyusd_pool.amount > 0,
       CoreError::InsufficientEarnPoolLiquidity,
     )
   }
 
+  fn price_components(
+    &self,
+    _amount_in: UFix64<N6>,
+  ) -> Result<PriceComponents, CoreError> {
+    Ok(PriceComponents {
+      input_nav: Some(self.shyusd_nav()?),
+      ..PriceComponents::default()
+    })
+  }
+
   fn compute_output_ungated(
     &self,
     in_amount: UFix64<N6>,
   ) -> Result<SwapOperationOutput, CoreError> {
-    let shyusd_supply = UFix64::new(self.shyusd_mint.supply);
-    let hyusd_in_pool = UFix64::new(self.hyusd_pool.amount);
+    let pool = self.earn_pool()?;
+    let shyusd_supply = UFix64::new(pool.shyusd_mint.supply);
+    let hyusd_in_pool = UFix64::new(pool.hyusd_pool.amount);
     gate(
       in_amount <= shyusd_supply,
       CoreError::InsufficientEarnPoolLiquidity,
@@ -96,13 +163,13 @@ impl<C: SolanaClock> TokenOperation<SHYUSD, HYUSD> for ProtocolState<C> {
     let hyusd_to_withdraw =
       amount_token_to_withdraw(in_amount, shyusd_supply, hyusd_in_pool)?;
     let withdrawal_limiter: WithdrawalLimiter =
-      self.pool_config.withdrawal_limiter.into();
+      pool.pool_config.withdrawal_limiter.into();
     withdrawal_limiter.validate_withdrawal(
       hyusd_to_withdraw,
       self.exchange_context.clock.epoch(),
     )?;
     let withdrawal_fee: UFix64<N4> =
-      self.pool_config.withdrawal_fee.try_into()?;
+      pool.pool_config.withdrawal_fee.try_into()?;
     let FeeExtract {
       fees_extracted,
       amount_remaining,
@@ -124,10 +191,11 @@ impl<C: SolanaClock> TokenOperation<SHYUSD, HYUSD> for ProtocolState<C> {
   }
 
   fn max_input_ungated(&self) -> Result<UFix64<N6>, CoreError> {
-    let shyusd_supply = UFix64::new(self.shyusd_mint.supply);
-    let hyusd_in_pool = UFix64::new(self.hyusd_pool.amount);
+    let pool = self.earn_pool()?;
+    let shyusd_supply = UFix64::new(pool.shyusd_mint.supply);
+    let hyusd_in_pool = UFix64::new(pool.hyusd_pool.amount);
     let withdrawal_limiter: WithdrawalLimiter =
-      self.pool_config.withdrawal_limiter.into();
+      pool.pool_config.withdrawal_limiter.into();
     let headroom =
       withdrawal_limiter.max_withdrawal(self.exchange_context.clock.epoch())?;
     let limiter_cap =
@@ -135,15 +203,36 @@ impl<C: SolanaClock> TokenOperation<SHYUSD, HYUSD> for ProtocolState<C> {
     Ok(limiter_cap.min(shyusd_supply))
   }
 
+  fn limits(&self) -> Result<Vec<RouteLimit<N6>>, CoreError> {
+    let pool = self.earn_pool()?;
+    let shyusd_supply = UFix64::new(pool.shyusd_mint.supply);
+    let withdrawal_limiter: WithdrawalLimiter =
+      pool.pool_config.withdrawal_limiter.into();
+    let headroom =
+      withdrawal_limiter.max_withdrawal(self.exchange_context.clock.epoch())?;
+    Ok(vec![
+      RouteLimit::new(
+        Limiter::WithdrawalLimiter,
+        max_lp_token_for_withdrawal(
+          headroom,
+          shyusd_supply,
+          UFix64::new(pool.hyusd_pool.amount),
+        )?,
+      ),
+      RouteLimit::new(Limiter::ShareSupply, shyusd_supply),
+    ])
+  }
+
   fn min_input_ungated(&self) -> Result<UFix64<N6>, CoreError> {
+    let pool = self.earn_pool()?;
     let withdrawal_fee: UFix64<N4> =
-      self.pool_config.withdrawal_fee.try_into()?;
+      pool.pool_config.withdrawal_fee.try_into()?;
     let max_zero_withdrawal =
       FeeExtract::max_input(withdrawal_fee, UFix64::zero())?;
     let max_zero_lp = max_lp_token_for_withdrawal(
       max_zero_withdrawal,
-      UFix64::new(self.shyusd_mint.supply),
-      UFix64::new(self.hyusd_pool.amount),
+      UFix64::new(pool.shyusd_mint.supply),
+      UFix64::new(pool.hyusd_pool.amount),
     )?;
     past_zero(max_zero_lp)
   }
@@ -5,12 +5,15 @@ use hylo_core::calculus::positive_rate;
 use hylo_core::error::CoreError;
 use hylo_core::exchange_context::marginal::SwapMarginals;
 use hylo_core::exchange_context::ExchangeContext;
-use hylo_core::fees::controller::FeeExtract;
+use hylo_core::fees::controller::{FeeController, FeeExtract, LevercoinFees};
+use hylo_core::fees::curve_controller::InterpolatedFeeController;
 use hylo_core::lst::sol_price::LstSolPrice;
 use hylo_core::pyth::PythOracle;
 use hylo_core::rebalance::mode::RebalanceMode;
 use hylo_core::rebalance::pnl::RebalancePnl;
+use hylo_core::rebalance::pricing::RebalancePriceController;
 use hylo_core::solana_clock::SolanaClock;
+use hylo_core::util::max_scaled_input;
 use hylo_core::virtual_stablecoin::{
   max_mintable, validate_burn, SUPPLY_FLOOR,
 };
@@ -20,46 +23,299 @@ use hylo_idl::tokens::{
 };
 use hylo_idl::with_exo_pairs;
 
+use crate::price_breakdown::PriceComponents;
 use crate::protocol_state::ProtocolState;
+use crate::quote_expiry::Deadline;
+use crate::slippage_recommendation::mode_across_boundary;
 use crate::token_operation::{
-  atom_rate, gate, past_zero, LstSwapOperationOutput, MintOperationOutput,
-  OperationOutput, RedeemOperationOutput, SwapOperationOutput, TokenOperation,
+  atom_rate, gate, past_zero, Gate, GateSink, Limiter, LstSwapOperationOutput,
+  MintOperationOutput, OperationOutput, Pair, RedeemOperationOutput,
+  RouteLimit, SwapOperationOutput, TokenOperation,
 };
 use crate::{Local, LocalExo, LST};
 
 impl<C: SolanaClock> ProtocolState<C> {
   /// Pause and harvest gates for LST-pair routes.
-  fn lst_pair_gates(&self) -> Result<(), CoreError> {
-    gate(!self.protocol_paused, CoreError::ProtocolPaused)?;
-    gate(!self.lst_pair_paused, CoreError::PairPaused)?;
-    gate(
+  fn lst_pair_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    sink.check(
+      Gate::ProtocolActive,
+      !self.protocol_paused,
+      CoreError::ProtocolPaused,
+    )?;
+    sink.check(
+      Gate::PairActive(Pair::Lst),
+      !self.lst_pair_paused,
+      CoreError::PairPaused,
+    )?;
+    sink.check(
+      Gate::YieldHarvested,
       self.yield_harvest_epoch == self.exchange_context.clock.epoch(),
       CoreError::YieldHarvestNotRun,
     )
   }
 
   /// Pause, harvest, and oracle-binding gates for an exo pair's routes.
-  fn exo_pair_gates<E: Exo>(&self) -> Result<(), CoreError> {
+  fn exo_pair_gates<E: Exo>(
+    &self,
+    sink: &mut dyn GateSink,
+  ) -> Result<(), CoreError> {
+    sink.check(
+      Gate::ProtocolActive,
+      !self.protocol_paused,
+      CoreError::ProtocolPaused,
+    )?;
     let pair = self.exo_pair::<E>()?;
-    gate(!self.protocol_paused, CoreError::ProtocolPaused)?;
-    gate(!pair.paused, CoreError::PairPaused)?;
-    gate(
+    sink.check(
+      Gate::PairActive(Pair::Exo(E::MINT)),
+      !pair.paused,
+      CoreError::PairPaused,
+    )?;
+    sink.check(
+      Gate::BorrowRateHarvested,
       pair.borrow_rate_harvest_epoch == pair.context.clock.epoch(),
       CoreError::BorrowRateHarvestNotRun,
     )
   }
 
   /// Pause and par gates for routes touching the USDC vault.
-  fn usdc_pair_gates(&self) -> Result<(), CoreError> {
-    let usdc_state = self.usdc_exchange_state();
-    gate(!self.protocol_paused, CoreError::ProtocolPaused)?;
-    gate(!usdc_state.paused, CoreError::PairPaused)?;
-    usdc_state
-      .par_tolerance
-      .validate_spot(usdc_state.usdc_usd_spot)
+  fn usdc_pair_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    sink.check(
+      Gate::ProtocolActive,
+      !self.protocol_paused,
+      CoreError::ProtocolPaused,
+    )?;
+    let usdc_state = self.usdc_exchange_state()?;
+    sink.check(
+      Gate::PairActive(Pair::Usdc),
+      !usdc_state.paused,
+      CoreError::PairPaused,
+    )?;
+    sink.check_result(
+      Gate::UsdcAtPar,
+      usdc_state
+        .par_tolerance
+        .validate_spot(usdc_state.usdc_usd_spot),
+    )
+  }
+}
+
+impl<C: SolanaClock> ProtocolState<C> {
+  /// SOL/USD window and the epoch rollover lapsing LST epoch prices and
+  /// the yield harvest gate.
+  fn lst_pair_deadlines(&self) -> Vec<Deadline> {
+    vec![
+      Deadline::oracle(
+        self.sol_oracle_publish_time,
+        self.sol_oracle_posted_slot,
+        self.oracle_interval_secs,
+      ),
+      Deadline::epoch_rollover(&self.exchange_context.clock),
+    ]
+  }
+
+  /// LST-pair deadlines plus the SOL/USD stablecoin window.
+  fn stablecoin_lst_deadlines(&self) -> Vec<Deadline> {
+    let mut deadlines = self.lst_pair_deadlines();
+    deadlines.push(Deadline::stablecoin_oracle(
+      self.sol_oracle_publish_time,
+      self.sol_oracle_posted_slot,
+      self.oracle_interval_secs,
+    ));
+    deadlines
+  }
+
+  /// Collateral window and the epoch rollover lapsing an exo pair's
+  /// borrow rate harvest gate.
+  fn exo_pair_deadlines<E: Exo>(&self) -> Result<Vec<Deadline>, CoreError> {
+    let pair = self.exo_pair::<E>()?;
+    Ok(vec![
+      Deadline::oracle(
+        pair.oracle_publish_time,
+        pair.oracle_posted_slot,
+        pair.oracle_interval_secs,
+      ),
+      Deadline::epoch_rollover(&pair.context.clock),
+    ])
+  }
+
+  /// Exo pair deadlines plus its collateral stablecoin window.
+  fn stablecoin_exo_deadlines<E: Exo>(
+    &self,
+  ) -> Result<Vec<Deadline>, CoreError> {
+    let pair = self.exo_pair::<E>()?;
+    let mut deadlines = self.exo_pair_deadlines::<E>()?;
+    deadlines.push(Deadline::stablecoin_oracle(
+      pair.oracle_publish_time,
+      pair.oracle_posted_slot,
+      pair.oracle_interval_secs,
+    ));
+    Ok(deadlines)
+  }
+
+  /// USDC/USD window for routes touching the USDC vault.
+  fn usdc_pair_deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    let usdc_state = self.usdc_exchange_state()?;
+    Ok(vec![Deadline::oracle(
+      usdc_state.oracle_publish_time,
+      usdc_state.oracle_posted_slot,
+      usdc_state.oracle_interval_secs,
+    )])
+  }
+
+  /// LST-pair and USDC deadlines behind the LST rebalance routes.
+  fn rebalance_deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    let mut deadlines = self.lst_pair_deadlines();
+    deadlines.extend(self.usdc_pair_deadlines()?);
+    Ok(deadlines)
+  }
+
+  /// Exo pair and USDC deadlines behind an exo/USDC swap.
+  fn exo_usdc_deadlines<E: Exo>(&self) -> Result<Vec<Deadline>, CoreError> {
+    let mut deadlines = self.exo_pair_deadlines::<E>()?;
+    deadlines.extend(self.usdc_pair_deadlines()?);
+    Ok(deadlines)
   }
 }
 
+impl<C: SolanaClock> ProtocolState<C> {
+  /// Oracle, header price and mode behind an LST mint or redemption.
+  /// Collateral going in is priced at the lower confidence bound, coming
+  /// out at the upper.
+  fn lst_price_components<L: LST + Local>(
+    &self,
+    collateral_in: bool,
+  ) -> Result<PriceComponents, CoreError> {
+    let lst_price: LstSolPrice = self.lst_header::<L>()?.price_sol.into();
+    let conversion = self.exchange_context.token_conversion(&lst_price)?;
+    let lst_sol = Some(conversion.lst_sol_price);
+    let (oracle_bound, input_lst_price, output_lst_price) = if collateral_in {
+      (conversion.usd_sol_price.lower, lst_sol, None)
+    } else {
+      (conversion.usd_sol_price.upper, None, lst_sol)
+    };
+    Ok(PriceComponents {
+      oracle_spot: Some(self.exchange_context.collateral_oracle_price().spot),
+      oracle_conf: Some(self.exchange_context.collateral_oracle_price().conf),
+      oracle_bound: Some(oracle_bound),
+      input_lst_price,
+      output_lst_price,
+      rebalance_mode: Some(self.exchange_context.rebalance_mode()),
+      collateral_ratio: Some(self.exchange_context.collateral_ratio()),
+      ..PriceComponents::default()
+    })
+  }
+
+  /// USDC/USD spot and pair fee behind the USDC pair; both directions are
+  /// 1:1 net of the fee.
+  fn usdc_price_components(
+    &self,
+    usdc_in: bool,
+  ) -> Result<PriceComponents, CoreError> {
+    let usdc_state = self.usdc_exchange_state()?;
+    let fee = if usdc_in {
+      usdc_state.mint_fee
+    } else {
+      usdc_state.redeem_fee
+    };
+    Ok(PriceComponents {
+      oracle_spot: Some(usdc_state.usdc_usd_spot),
+      fee_tier: Some(fee.convert()),
+      ..PriceComponents::default()
+    })
+  }
+
+  /// Oracle and mode behind an exo pair mint or redemption.
+  fn exo_price_components<E: Exo + PythOracle>(
+    &self,
+    collateral_in: bool,
+  ) -> Result<PriceComponents, CoreError> {
+    let exo = &self.exo_pair::<E>()?.context;
+    let range = exo.collateral_usd_price();
+    Ok(PriceComponents {
+      oracle_spot: Some(exo.collateral_oracle_price().spot),
+      oracle_conf: Some(exo.collateral_oracle_price().conf),
+      oracle_bound: Some(if collateral_in {
+        range.lower
+      } else {
+        range.upper
+      }),
+      rebalance_mode: Some(exo.rebalance_mode()),
+      collateral_ratio: Some(exo.collateral_ratio()),
+      ..PriceComponents::default()
+    })
+  }
+}
+
+/// NAVs behind a stablecoin/levercoin swap. Stablecoin going in buys
+/// levercoin at its upper NAV, levercoin going in sells at its lower.
+fn swap_price_components(
+  context: &impl ExchangeContext,
+  stable_in: bool,
+) -> Result<PriceComponents, CoreError> {
+  let conversion = context.swap_conversion()?;
+  let (input_nav, output_nav) = if stable_in {
+    (conversion.stablecoin_nav, conversion.levercoin_nav.upper)
+  } else {
+    (conversion.levercoin_nav.lower, conversion.stablecoin_nav)
+  };
+  let tier = if stable_in {
+    LevercoinFees::convert_from_stablecoin_fee
+  } else {
+    LevercoinFees::convert_to_stablecoin_fee
+  };
+  Ok(PriceComponents {
+    oracle_spot: Some(context.collateral_oracle_price().spot),
+    oracle_conf: Some(context.collateral_oracle_price().conf),
+    input_nav: Some(input_nav),
+    output_nav: Some(output_nav),
+    rebalance_mode: Some(context.rebalance_mode()),
+    collateral_ratio: Some(context.collateral_ratio()),
+    fee_tier: levercoin_fee_tier(context, tier),
+    boundary_fee_tier: levercoin_boundary_fee_tier(context, tier),
+    ..PriceComponents::default()
+  })
+}
+
+/// Levercoin fee tier at the current mode, widened to the breakdown's
+/// precision. `None` where the mode has no valid fee.
+fn levercoin_fee_tier(
+  context: &impl ExchangeContext,
+  tier: fn(&LevercoinFees, RebalanceMode) -> Result<UFix64<N4>, CoreError>,
+) -> Option<UFix64<N5>> {
+  tier(context.levercoin_fees(), context.rebalance_mode())
+    .ok()
+    .map(UFix64::convert)
+}
+
+/// Levercoin fee tier in the mode across the boundary nearest the
+/// collateral ratio. `None` where that mode has no valid fee.
+fn levercoin_boundary_fee_tier(
+  context: &impl ExchangeContext,
+  tier: fn(&LevercoinFees, RebalanceMode) -> Result<UFix64<N4>, CoreError>,
+) -> Option<UFix64<N5>> {
+  mode_across_boundary(context.rebalance_mode(), context.collateral_ratio())
+    .and_then(|mode| tier(context.levercoin_fees(), mode).ok())
+    .map(UFix64::convert)
+}
+
+/// Largest USDC input minting at most `headroom` hyUSD after `fee`.
+///
+/// Headroom runs to `u64::MAX`; where grossing it up for the fee
+/// overflows, any representable input fits.
+fn max_usdc_mint_input(
+  fee: UFix64<N4>,
+  headroom: UFix64<N6>,
+) -> Result<UFix64<N6>, CoreError> {
+  let kept = UFix64::one()
+    .checked_sub(&fee)
+    .filter(|kept| *kept != UFix64::zero())
+    .ok_or(CoreError::FeeExtraction)?;
+  Ok(
+    max_scaled_input(headroom, kept, UFix64::one())
+      .unwrap_or(UFix64::new(u64::MAX)),
+  )
+}
+
 /// Largest `N9` amount that truncates to zero in `E`'s precision.
 fn max_zero_exo<E: Exo>() -> Result<UFix64<N9>, CoreError>
 where
@@ -72,14 +328,23 @@ where
 }
 
 impl<C: SolanaClock> ProtocolState<C> {
-  fn mint_stablecoin_lst_preconditions(&self) -> Result<(), CoreError> {
-    self.lst_pair_gates()?;
-    gate(
+  fn mint_stablecoin_lst_gates(
+    &self,
+    sink: &mut dyn GateSink,
+  ) -> Result<(), CoreError> {
+    self.lst_pair_gates(sink)?;
+    sink.check(
+      Gate::OracleFresh,
       self.sol_stablecoin_oracle_valid,
       CoreError::PythOracleOutdated,
     )?;
-    gate(self.pool_drawdown.is_repaid(), CoreError::DrawdownNotRepaid)?;
-    gate(
+    sink.check(
+      Gate::DrawdownRepaid,
+      self.pool_drawdown.is_repaid(),
+      CoreError::DrawdownNotRepaid,
+    )?;
+    sink.check(
+      Gate::ModeAllows,
       self.exchange_context.stablecoin_mint_enabled(),
       CoreError::OperationDisabled,
     )
@@ -131,6 +396,18 @@ impl<C: SolanaClock> ProtocolState<C> {
       .max_lst_for_token(cap, self.exchange_context.stablecoin_nav()?)
   }
 
+  /// Mint headroom, zero once the collateral ratio is under the mint
+  /// threshold and [`Self::mint_stablecoin_lst_max_input`] has none.
+  fn mint_stablecoin_lst_headroom<L: LST + Local>(
+    &self,
+  ) -> Result<UFix64<N9>, CoreError> {
+    if self.exchange_context.stablecoin_mint_enabled() {
+      self.mint_stablecoin_lst_max_input::<L>()
+    } else {
+      Ok(UFix64::zero())
+    }
+  }
+
   fn mint_stablecoin_lst_min_input<L: LST + Local>(
     &self,
   ) -> Result<UFix64<N9>, CoreError> {
@@ -148,9 +425,13 @@ impl<C: SolanaClock> ProtocolState<C> {
     past_zero(FeeExtract::max_input(fee_rate, max_zero_lst)?)
   }
 
-  fn redeem_stablecoin_lst_preconditions(&self) -> Result<(), CoreError> {
-    self.lst_pair_gates()?;
-    gate(
+  fn redeem_stablecoin_lst_gates(
+    &self,
+    sink: &mut dyn GateSink,
+  ) -> Result<(), CoreError> {
+    self.lst_pair_gates(sink)?;
+    sink.check(
+      Gate::OracleFresh,
       self.sol_stablecoin_oracle_valid,
       CoreError::PythOracleOutdated,
     )
@@ -239,10 +520,15 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<L, XSOL>
 {
   type FeeExp = N9;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.lst_pair_gates()?;
-    gate(self.pool_drawdown.is_repaid(), CoreError::DrawdownNotRepaid)?;
-    gate(
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.lst_pair_gates(sink)?;
+    sink.check(
+      Gate::DrawdownRepaid,
+      self.pool_drawdown.is_repaid(),
+      CoreError::DrawdownNotRepaid,
+    )?;
+    sink.check(
+      Gate::ModeAllows,
       self.exchange_context.levercoin_mint_enabled(),
       CoreError::OperationDisabled,
     )
@@ -293,6 +579,28 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<L, XSOL>
     Ok(collateral_cap.min(FeeExtract::max_input(fee_rate, representable)?))
   }
 
+  fn price_components(
+    &self,
+    _amount_in: UFix64<N9>,
+  ) -> Result<PriceComponents, CoreError> {
+    Ok(PriceComponents {
+      output_nav: Some(self.exchange_context.levercoin_mint_nav()?),
+      fee_tier: levercoin_fee_tier(
+        &self.exchange_context,
+        LevercoinFees::mint_fee,
+      ),
+      boundary_fee_tier: levercoin_boundary_fee_tier(
+        &self.exchange_context,
+        LevercoinFees::mint_fee,
+      ),
+      ..self.lst_price_components::<L>(true)?
+    })
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    Ok(self.lst_pair_deadlines())
+  }
+
   fn min_input_ungated(&self) -> Result<UFix64<N9>, CoreError> {
     let lst_price: LstSolPrice = self.lst_header::<L>()?.price_sol.into();
     let max_zero_lst = self
@@ -314,15 +622,42 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<XSOL, L>
 {
   type FeeExp = N9;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.lst_pair_gates()?;
-    gate(self.pool_drawdown.is_repaid(), CoreError::DrawdownNotRepaid)?;
-    gate(
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.lst_pair_gates(sink)?;
+    sink.check(
+      Gate::DrawdownRepaid,
+      self.pool_drawdown.is_repaid(),
+      CoreError::DrawdownNotRepaid,
+    )?;
+    sink.check(
+      Gate::ModeAllows,
       self.exchange_context.rebalance_mode() != RebalanceMode::Depeg,
       CoreError::OperationDisabled,
     )
   }
 
+  fn price_components(
+    &self,
+    _amount_in: UFix64<<XSOL as TokenMint>::Exp>,
+  ) -> Result<PriceComponents, CoreError> {
+    Ok(PriceComponents {
+      input_nav: Some(self.exchange_context.levercoin_redeem_nav()?),
+      fee_tier: levercoin_fee_tier(
+        &self.exchange_context,
+        LevercoinFees::redeem_fee,
+      ),
+      boundary_fee_tier: levercoin_boundary_fee_tier(
+        &self.exchange_context,
+        LevercoinFees::redeem_fee,
+      ),
+      ..self.lst_price_components::<L>(false)?
+    })
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    Ok(self.lst_pair_deadlines())
+  }
+
   fn compute_output_ungated(
     &self,
     in_amount: UFix64<<XSOL as TokenMint>::Exp>,
@@ -399,15 +734,31 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<XSOL, L>
 impl<C: SolanaClock> TokenOperation<HYUSD, XSOL> for ProtocolState<C> {
   type FeeExp = <HYUSD as TokenMint>::Exp;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.lst_pair_gates()?;
-    gate(self.pool_drawdown.is_repaid(), CoreError::DrawdownNotRepaid)?;
-    gate(
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.lst_pair_gates(sink)?;
+    sink.check(
+      Gate::DrawdownRepaid,
+      self.pool_drawdown.is_repaid(),
+      CoreError::DrawdownNotRepaid,
+    )?;
+    sink.check(
+      Gate::ModeAllows,
       self.exchange_context.levercoin_mint_enabled(),
       CoreError::OperationDisabled,
     )
   }
 
+  fn price_components(
+    &self,
+    _amount_in: UFix64<<HYUSD as TokenMint>::Exp>,
+  ) -> Result<PriceComponents, CoreError> {
+    swap_price_components(&self.exchange_context, true)
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    Ok(self.lst_pair_deadlines())
+  }
+
   fn compute_output_ungated(
     &self,
     in_amount: UFix64<<HYUSD as TokenMint>::Exp>,
@@ -449,6 +800,24 @@ impl<C: SolanaClock> TokenOperation<HYUSD, XSOL> for ProtocolState<C> {
     Ok(FeeExtract::max_input(fee_rate, burn_cap)?.min(projectable))
   }
 
+  fn limits(&self) -> Result<Vec<RouteLimit<N6>>, CoreError> {
+    let supply = self.exchange_context.virtual_stablecoin_supply()?;
+    let burn_cap = supply.checked_sub(&SUPPLY_FLOOR).unwrap_or_default();
+    let fee_rate = self
+      .exchange_context
+      .stablecoin_to_levercoin_fee_rate(burn_cap)?;
+    Ok(vec![
+      RouteLimit::new(
+        Limiter::SupplyFloor,
+        FeeExtract::max_input(fee_rate, burn_cap)?,
+      ),
+      RouteLimit::new(
+        Limiter::StabilityProjection,
+        self.exchange_context.max_stablecoin_removal()?,
+      ),
+    ])
+  }
+
   fn min_input_ungated(&self) -> Result<UFix64<N6>, CoreError> {
     let max_zero_hyusd = self
       .exchange_context
@@ -464,15 +833,31 @@ impl<C: SolanaClock> TokenOperation<HYUSD, XSOL> for ProtocolState<C> {
 impl<C: SolanaClock> TokenOperation<XSOL, HYUSD> for ProtocolState<C> {
   type FeeExp = <HYUSD as TokenMint>::Exp;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.lst_pair_gates()?;
-    gate(self.pool_drawdown.is_repaid(), CoreError::DrawdownNotRepaid)?;
-    gate(
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.lst_pair_gates(sink)?;
+    sink.check(
+      Gate::DrawdownRepaid,
+      self.pool_drawdown.is_repaid(),
+      CoreError::DrawdownNotRepaid,
+    )?;
+    sink.check(
+      Gate::ModeAllows,
       self.exchange_context.stablecoin_mint_enabled(),
       CoreError::OperationDisabled,
     )
   }
 
+  fn price_components(
+    &self,
+    _amount_in: UFix64<<XSOL as TokenMint>::Exp>,
+  ) -> Result<PriceComponents, CoreError> {
+    swap_price_components(&self.exchange_context, false)
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    Ok(self.lst_pair_deadlines())
+  }
+
   fn compute_output_ungated(
     &self,
     in_amount: UFix64<<XSOL as TokenMint>::Exp>,
@@ -531,8 +916,27 @@ impl<L1: LST + Local, L2: LST + Local, C: SolanaClock> TokenOperation<L1, L2>
 {
   type FeeExp = N9;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.lst_pair_gates()
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.lst_pair_gates(sink)
+  }
+
+  fn price_components(
+    &self,
+    _amount_in: UFix64<N9>,
+  ) -> Result<PriceComponents, CoreError> {
+    let epoch = self.exchange_context.clock.epoch();
+    let in_price: LstSolPrice = self.lst_header::<L1>()?.price_sol.into();
+    let out_price: LstSolPrice = self.lst_header::<L2>()?.price_sol.into();
+    Ok(PriceComponents {
+      input_lst_price: Some(in_price.get_epoch_price(epoch)?),
+      output_lst_price: Some(out_price.get_epoch_price(epoch)?),
+      fee_tier: Some(self.lst_swap_config.fee.convert()),
+      ..PriceComponents::default()
+    })
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    Ok(self.lst_pair_deadlines())
   }
 
   fn compute_output_ungated(
@@ -601,15 +1005,26 @@ impl<L1: LST + Local, L2: LST + Local, C: SolanaClock> TokenOperation<L1, L2>
 impl<C: SolanaClock> TokenOperation<USDC, HYUSD> for ProtocolState<C> {
   type FeeExp = N6;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.usdc_pair_gates()
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.usdc_pair_gates(sink)
+  }
+
+  fn price_components(
+    &self,
+    _amount_in: UFix64<N6>,
+  ) -> Result<PriceComponents, CoreError> {
+    self.usdc_price_components(true)
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    self.usdc_pair_deadlines()
   }
 
   fn compute_output_ungated(
     &self,
     in_amount: UFix64<N6>,
   ) -> Result<OperationOutput<N6, N6, N6>, CoreError> {
-    let usdc_state = self.usdc_exchange_state();
+    let usdc_state = self.usdc_exchange_state()?;
     let FeeExtract {
       fees_extracted,
       amount_remaining,
@@ -628,13 +1043,20 @@ impl<C: SolanaClock> TokenOperation<USDC, HYUSD> for ProtocolState<C> {
   }
 
   fn max_input_ungated(&self) -> Result<UFix64<N6>, CoreError> {
-    let usdc_state = self.usdc_exchange_state();
+    let usdc_state = self.usdc_exchange_state()?;
     let headroom = max_mintable(usdc_state.virtual_stablecoin_supply)?;
-    FeeExtract::max_input(usdc_state.mint_fee, headroom)
+    max_usdc_mint_input(usdc_state.mint_fee, headroom)
+  }
+
+  fn limits(&self) -> Result<Vec<RouteLimit<N6>>, CoreError> {
+    Ok(vec![RouteLimit::new(
+      Limiter::MaxMintable,
+      TokenOperation::<USDC, HYUSD>::max_input_ungated(self)?,
+    )])
   }
 
   fn min_input_ungated(&self) -> Result<UFix64<N6>, CoreError> {
-    let usdc_state = self.usdc_exchange_state();
+    let usdc_state = self.usdc_exchange_state()?;
     past_zero(FeeExtract::max_input(usdc_state.mint_fee, UFix64::zero())?)
   }
 }
@@ -642,15 +1064,26 @@ impl<C: SolanaClock> TokenOperation<USDC, HYUSD> for ProtocolState<C> {
 impl<C: SolanaClock> TokenOperation<HYUSD, USDC> for ProtocolState<C> {
   type FeeExp = N6;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.usdc_pair_gates()
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.usdc_pair_gates(sink)
+  }
+
+  fn price_components(
+    &self,
+    _amount_in: UFix64<N6>,
+  ) -> Result<PriceComponents, CoreError> {
+    self.usdc_price_components(false)
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    self.usdc_pair_deadlines()
   }
 
   fn compute_output_ungated(
     &self,
     in_amount: UFix64<N6>,
   ) -> Result<OperationOutput<N6, N6, N6>, CoreError> {
-    let usdc_state = self.usdc_exchange_state();
+    let usdc_state = self.usdc_exchange_state()?;
     let FeeExtract {
       fees_extracted,
       amount_remaining,
@@ -677,7 +1110,7 @@ impl<C: SolanaClock> TokenOperation<HYUSD, USDC> for ProtocolState<C> {
   }
 
   fn max_input_ungated(&self) -> Result<UFix64<N6>, CoreError> {
-    let usdc_state = self.usdc_exchange_state();
+    let usdc_state = self.usdc_exchange_state()?;
     let remaining = usdc_state
       .vault_balance
       .min(usdc_state.virtual_stablecoin_supply);
@@ -685,7 +1118,7 @@ impl<C: SolanaClock> TokenOperation<HYUSD, USDC> for ProtocolState<C> {
   }
 
   fn min_input_ungated(&self) -> Result<UFix64<N6>, CoreError> {
-    let usdc_state = self.usdc_exchange_state();
+    let usdc_state = self.usdc_exchange_state()?;
     past_zero(FeeExtract::max_input(
       usdc_state.redeem_fee,
       UFix64::zero(),
@@ -694,17 +1127,24 @@ impl<C: SolanaClock> TokenOperation<HYUSD, USDC> for ProtocolState<C> {
 }
 
 impl<C: SolanaClock> ProtocolState<C> {
-  fn mint_stablecoin_exo_preconditions<E: Exo + PythOracle>(
+  fn mint_stablecoin_exo_gates<E: Exo + PythOracle>(
     &self,
+    sink: &mut dyn GateSink,
   ) -> Result<(), CoreError> {
-    self.exo_pair_gates::<E>()?;
+    self.exo_pair_gates::<E>(sink)?;
     let pair = self.exo_pair::<E>()?;
-    gate(
+    sink.check(
+      Gate::OracleFresh,
       pair.stablecoin_oracle_valid(),
       CoreError::PythOracleOutdated,
     )?;
-    gate(pair.pool_drawdown.is_repaid(), CoreError::DrawdownNotRepaid)?;
-    gate(
+    sink.check(
+      Gate::DrawdownRepaid,
+      pair.pool_drawdown.is_repaid(),
+      CoreError::DrawdownNotRepaid,
+    )?;
+    sink.check(
+      Gate::ModeAllows,
       pair.context.stablecoin_mint_enabled(),
       CoreError::OperationDisabled,
     )
@@ -758,6 +1198,21 @@ impl<C: SolanaClock> ProtocolState<C> {
       .ok_or(CoreError::TokenAmountPrecision)
   }
 
+  /// Mint headroom, zero once the pair's collateral ratio is under its
+  /// mint threshold.
+  fn mint_stablecoin_exo_headroom<E: Exo + PythOracle>(
+    &self,
+  ) -> Result<UFix64<E::Exp>, CoreError>
+  where
+    UFix64<E::Exp>: FixExt,
+  {
+    if self.exo_pair::<E>()?.context.stablecoin_mint_enabled() {
+      self.mint_stablecoin_exo_max_input::<E>()
+    } else {
+      Ok(UFix64::zero())
+    }
+  }
+
   fn mint_stablecoin_exo_min_input<E: Exo + PythOracle>(
     &self,
   ) -> Result<UFix64<E::Exp>, CoreError>
@@ -778,11 +1233,13 @@ impl<C: SolanaClock> ProtocolState<C> {
     )
   }
 
-  fn redeem_stablecoin_exo_preconditions<E: Exo + PythOracle>(
+  fn redeem_stablecoin_exo_gates<E: Exo + PythOracle>(
     &self,
+    sink: &mut dyn GateSink,
   ) -> Result<(), CoreError> {
-    self.exo_pair_gates::<E>()?;
-    gate(
+    self.exo_pair_gates::<E>(sink)?;
+    sink.check(
+      Gate::OracleFresh,
       self.exo_pair::<E>()?.stablecoin_oracle_valid(),
       CoreError::PythOracleOutdated,
     )
@@ -862,13 +1319,19 @@ impl<C: SolanaClock> ProtocolState<C> {
 }
 
 impl<C: SolanaClock> ProtocolState<C> {
-  fn mint_levercoin_exo_preconditions<E: Exo + PythOracle>(
+  fn mint_levercoin_exo_gates<E: Exo + PythOracle>(
     &self,
+    sink: &mut dyn GateSink,
   ) -> Result<(), CoreError> {
-    self.exo_pair_gates::<E>()?;
+    self.exo_pair_gates::<E>(sink)?;
     let pair = self.exo_pair::<E>()?;
-    gate(pair.pool_drawdown.is_repaid(), CoreError::DrawdownNotRepaid)?;
-    gate(
+    sink.check(
+      Gate::DrawdownRepaid,
+      pair.pool_drawdown.is_repaid(),
+      CoreError::DrawdownNotRepaid,
+    )?;
+    sink.check(
+      Gate::ModeAllows,
       pair.context.levercoin_mint_enabled(),
       CoreError::OperationDisabled,
     )
@@ -945,13 +1408,19 @@ impl<C: SolanaClock> ProtocolState<C> {
     )
   }
 
-  fn redeem_levercoin_exo_preconditions<E: Exo + PythOracle>(
+  fn redeem_levercoin_exo_gates<E: Exo + PythOracle>(
     &self,
+    sink: &mut dyn GateSink,
   ) -> Result<(), CoreError> {
-    self.exo_pair_gates::<E>()?;
+    self.exo_pair_gates::<E>(sink)?;
     let pair = self.exo_pair::<E>()?;
-    gate(pair.pool_drawdown.is_repaid(), CoreError::DrawdownNotRepaid)?;
-    gate(
+    sink.check(
+      Gate::DrawdownRepaid,
+      pair.pool_drawdown.is_repaid(),
+      CoreError::DrawdownNotRepaid,
+    )?;
+    sink.check(
+      Gate::ModeAllows,
       pair.context.rebalance_mode() != RebalanceMode::Depeg,
       CoreError::OperationDisabled,
     )
@@ -1025,13 +1494,19 @@ impl<C: SolanaClock> ProtocolState<C> {
 }
 
 impl<C: SolanaClock> ProtocolState<C> {
-  fn convert_stable_to_lever_exo_preconditions<E: Exo + PythOracle>(
+  fn convert_stable_to_lever_exo_gates<E: Exo + PythOracle>(
     &self,
+    sink: &mut dyn GateSink,
   ) -> Result<(), CoreError> {
-    self.exo_pair_gates::<E>()?;
+    self.exo_pair_gates::<E>(sink)?;
     let pair = self.exo_pair::<E>()?;
-    gate(pair.pool_drawdown.is_repaid(), CoreError::DrawdownNotRepaid)?;
-    gate(
+    sink.check(
+      Gate::DrawdownRepaid,
+      pair.pool_drawdown.is_repaid(),
+      CoreError::DrawdownNotRepaid,
+    )?;
+    sink.check(
+      Gate::ModeAllows,
       pair.context.levercoin_mint_enabled(),
       CoreError::OperationDisabled,
     )
@@ -1089,6 +1564,34 @@ impl<C: SolanaClock> ProtocolState<C> {
     Ok(FeeExtract::max_input(fee_rate, remaining)?.min(projectable))
   }
 
+  /// Market cap, supply floor and stability projection headroom for
+  /// hyUSD to exo levercoin conversion, each grossed up for the fee.
+  fn convert_stable_to_lever_exo_limits<E: Exo + PythOracle>(
+    &self,
+  ) -> Result<Vec<RouteLimit<N6>>, CoreError> {
+    let pair = self.exo_pair::<E>()?;
+    let market_cap = pair.context.swap_conversion()?.max_stable_for_lever(
+      pair
+        .context
+        .levercoin_market_cap_limiter()?
+        .max_token_out()?,
+    )?;
+    let supply = pair.context.virtual_stablecoin_supply()?;
+    let burn_cap = supply.checked_sub(&pair.supply_floor).unwrap_or_default();
+    let gross = |cap: UFix64<N6>| -> Result<UFix64<N6>, CoreError> {
+      let fee_rate = pair.context.stablecoin_to_levercoin_fee_rate(cap)?;
+      FeeExtract::max_input(fee_rate, cap)
+    };
+    Ok(vec![
+      RouteLimit::new(Limiter::LevercoinMarketCap, gross(market_cap)?),
+      RouteLimit::new(Limiter::SupplyFloor, gross(burn_cap)?),
+      RouteLimit::new(
+        Limiter::StabilityProjection,
+        pair.context.max_stablecoin_removal()?,
+      ),
+    ])
+  }
+
   fn convert_stable_to_lever_exo_min_input<E: Exo + PythOracle>(
     &self,
   ) -> Result<UFix64<N6>, CoreError> {
@@ -1100,13 +1603,19 @@ impl<C: SolanaClock> ProtocolState<C> {
     past_zero(FeeExtract::max_input(fee_rate, max_zero_hyusd)?)
   }
 
-  fn convert_lever_to_stable_exo_preconditions<E: Exo + PythOracle>(
+  fn convert_lever_to_stable_exo_gates<E: Exo + PythOracle>(
     &self,
+    sink: &mut dyn GateSink,
   ) -> Result<(), CoreError> {
-    self.exo_pair_gates::<E>()?;
+    self.exo_pair_gates::<E>(sink)?;
     let pair = self.exo_pair::<E>()?;
-    gate(pair.pool_drawdown.is_repaid(), CoreError::DrawdownNotRepaid)?;
-    gate(
+    sink.check(
+      Gate::DrawdownRepaid,
+      pair.pool_drawdown.is_repaid(),
+      CoreError::DrawdownNotRepaid,
+    )?;
+    sink.check(
+      Gate::ModeAllows,
       pair.context.stablecoin_mint_enabled(),
       CoreError::OperationDisabled,
     )
@@ -1175,7 +1684,7 @@ impl<C: SolanaClock> ProtocolState<C> {
       }
       RebalancePnl::Loss(loss) => {
         gate(
-          UFix64::new(self.hyusd_pool.amount) >= loss,
+          UFix64::new(self.earn_pool()?.hyusd_pool.amount) >= loss,
           CoreError::InsufficientEarnPoolLiquidity,
         )?;
         validate_burn(context.virtual_stablecoin_supply()?, loss, floor)
@@ -1186,22 +1695,38 @@ impl<C: SolanaClock> ProtocolState<C> {
   }
 
   /// State gates shared by the LST rebalance buy routes.
-  fn rebalance_buy_preconditions(&self) -> Result<(), CoreError> {
-    self.lst_pair_gates()?;
-    self.usdc_pair_gates()?;
-    gate(self.pool_drawdown.is_repaid(), CoreError::DrawdownNotRepaid)?;
-    gate(
+  fn rebalance_buy_gates(
+    &self,
+    sink: &mut dyn GateSink,
+  ) -> Result<(), CoreError> {
+    self.lst_pair_gates(sink)?;
+    self.usdc_pair_gates(sink)?;
+    sink.check(
+      Gate::DrawdownRepaid,
+      self.pool_drawdown.is_repaid(),
+      CoreError::DrawdownNotRepaid,
+    )?;
+    sink.check(
+      Gate::ModeAllows,
       self.exchange_context.rebalance_buy_active(),
       CoreError::OperationDisabled,
     )
   }
 
   /// State gates shared by the LST rebalance sell routes.
-  fn rebalance_sell_preconditions(&self) -> Result<(), CoreError> {
-    self.lst_pair_gates()?;
-    self.usdc_pair_gates()?;
-    gate(self.pool_drawdown.is_repaid(), CoreError::DrawdownNotRepaid)?;
-    gate(
+  fn rebalance_sell_gates(
+    &self,
+    sink: &mut dyn GateSink,
+  ) -> Result<(), CoreError> {
+    self.lst_pair_gates(sink)?;
+    self.usdc_pair_gates(sink)?;
+    sink.check(
+      Gate::DrawdownRepaid,
+      self.pool_drawdown.is_repaid(),
+      CoreError::DrawdownNotRepaid,
+    )?;
+    sink.check(
+      Gate::ModeAllows,
       self.exchange_context.rebalance_sell_active(),
       CoreError::OperationDisabled,
     )
@@ -1229,11 +1754,11 @@ impl<C: SolanaClock> ProtocolState<C> {
       .rebalance_buy_conversion(&adjusted, in_amount)?;
     let out_amount = conversion.lst_to_token(in_amount, UFix64::one())?;
     gate(
-      out_amount <= self.usdc_exchange_state().vault_balance,
+      out_amount <= self.usdc_exchange_state()?.vault_balance,
       CoreError::InsufficientLiquidity,
     )?;
     gate(
-      out_amount <= self.usdc_exchange_state().virtual_stablecoin_supply,
+      out_amount <= self.usdc_exchange_state()?.virtual_stablecoin_supply,
       CoreError::BurnUnderflow,
     )?;
     let pnl = self
@@ -1255,6 +1780,62 @@ impl<C: SolanaClock> ProtocolState<C> {
     })
   }
 
+  /// Adjusted true price the rebalance routes price an LST at.
+  fn rebalance_lst_price<L: LST + Local>(
+    &self,
+  ) -> Result<LstSolPrice, CoreError> {
+    let header = self.lst_header::<L>()?;
+    self
+      .stake_pool::<L>()?
+      .true_price()?
+      .adjust_price(header.rebalance_fee.try_into()?)
+  }
+
+  /// Curve and true price behind selling `in_amount` of LST for USDC.
+  fn rebalance_buy_components<L: LST + Local>(
+    &self,
+    in_amount: UFix64<N9>,
+  ) -> Result<PriceComponents, CoreError> {
+    let epoch = self.exchange_context.clock.epoch();
+    let lst_price: LstSolPrice = self.lst_header::<L>()?.price_sol.into();
+    let conversion = self
+      .exchange_context
+      .rebalance_buy_conversion(&self.rebalance_lst_price::<L>()?, in_amount)?;
+    Ok(PriceComponents {
+      oracle_spot: Some(self.exchange_context.collateral_oracle_price().spot),
+      oracle_conf: Some(self.exchange_context.collateral_oracle_price().conf),
+      input_lst_price: Some(lst_price.get_epoch_price(epoch)?),
+      lst_true_price: Some(conversion.lst_sol_price),
+      rebalance_mode: Some(self.exchange_context.rebalance_mode()),
+      collateral_ratio: Some(self.exchange_context.collateral_ratio()),
+      curve_price: Some(conversion.usd_sol_price.lower),
+      ..PriceComponents::default()
+    })
+  }
+
+  /// Curve and true price behind buying LST with `in_amount` of USDC.
+  fn rebalance_sell_components<L: LST + Local>(
+    &self,
+    in_amount: UFix64<N6>,
+  ) -> Result<PriceComponents, CoreError> {
+    let epoch = self.exchange_context.clock.epoch();
+    let lst_price: LstSolPrice = self.lst_header::<L>()?.price_sol.into();
+    let conversion = self.exchange_context.rebalance_sell_conversion(
+      &self.rebalance_lst_price::<L>()?,
+      in_amount,
+    )?;
+    Ok(PriceComponents {
+      oracle_spot: Some(self.exchange_context.collateral_oracle_price().spot),
+      oracle_conf: Some(self.exchange_context.collateral_oracle_price().conf),
+      output_lst_price: Some(lst_price.get_epoch_price(epoch)?),
+      lst_true_price: Some(conversion.lst_sol_price),
+      rebalance_mode: Some(self.exchange_context.rebalance_mode()),
+      collateral_ratio: Some(self.exchange_context.collateral_ratio()),
+      curve_price: Some(conversion.usd_sol_price.upper),
+      ..PriceComponents::default()
+    })
+  }
+
   /// Input cap is a conservative spot-priced bound on the onchain gates.
   fn rebalance_sell_quote<L: LST + Local>(
     &self,
@@ -1311,7 +1892,7 @@ impl<C: SolanaClock> ProtocolState<C> {
       .exchange_context
       .rebalance_buy_conversion(&adjusted, buy_target)?
       .max_lst_for_token(
-        self.usdc_exchange_state().vault_balance,
+        self.usdc_exchange_state()?.vault_balance,
         UFix64::one(),
       )?;
     Ok(buy_target.min(vault_cap))
@@ -1360,8 +1941,19 @@ impl<C: SolanaClock> ProtocolState<C> {
 impl<C: SolanaClock> TokenOperation<JITOSOL, USDC> for ProtocolState<C> {
   type FeeExp = N9;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.rebalance_buy_preconditions()
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.rebalance_buy_gates(sink)
+  }
+
+  fn price_components(
+    &self,
+    amount_in: UFix64<N9>,
+  ) -> Result<PriceComponents, CoreError> {
+    self.rebalance_buy_components::<JITOSOL>(amount_in)
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    self.rebalance_deadlines()
   }
 
   fn compute_output_ungated(
@@ -1383,8 +1975,19 @@ impl<C: SolanaClock> TokenOperation<JITOSOL, USDC> for ProtocolState<C> {
 impl<C: SolanaClock> TokenOperation<HYLOSOL, USDC> for ProtocolState<C> {
   type FeeExp = N9;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.rebalance_buy_preconditions()
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.rebalance_buy_gates(sink)
+  }
+
+  fn price_components(
+    &self,
+    amount_in: UFix64<N9>,
+  ) -> Result<PriceComponents, CoreError> {
+    self.rebalance_buy_components::<HYLOSOL>(amount_in)
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    self.rebalance_deadlines()
   }
 
   fn compute_output_ungated(
@@ -1406,8 +2009,19 @@ impl<C: SolanaClock> TokenOperation<HYLOSOL, USDC> for ProtocolState<C> {
 impl<C: SolanaClock> TokenOperation<USDC, JITOSOL> for ProtocolState<C> {
   type FeeExp = N6;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.rebalance_sell_preconditions()
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.rebalance_sell_gates(sink)
+  }
+
+  fn price_components(
+    &self,
+    amount_in: UFix64<N6>,
+  ) -> Result<PriceComponents, CoreError> {
+    self.rebalance_sell_components::<JITOSOL>(amount_in)
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    self.rebalance_deadlines()
   }
 
   fn compute_output_ungated(
@@ -1429,8 +2043,19 @@ impl<C: SolanaClock> TokenOperation<USDC, JITOSOL> for ProtocolState<C> {
 impl<C: SolanaClock> TokenOperation<USDC, HYLOSOL> for ProtocolState<C> {
   type FeeExp = N6;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.rebalance_sell_preconditions()
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.rebalance_sell_gates(sink)
+  }
+
+  fn price_components(
+    &self,
+    amount_in: UFix64<N6>,
+  ) -> Result<PriceComponents, CoreError> {
+    self.rebalance_sell_components::<HYLOSOL>(amount_in)
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    self.rebalance_deadlines()
   }
 
   fn compute_output_ungated(
@@ -1451,14 +2076,20 @@ impl<C: SolanaClock> TokenOperation<USDC, HYLOSOL> for ProtocolState<C> {
 
 impl<C: SolanaClock> ProtocolState<C> {
   /// State gates for the exo-to-USDC rebalance buy routes.
-  fn swap_exo_to_usdc_preconditions<E: Exo + PythOracle>(
+  fn swap_exo_to_usdc_gates<E: Exo + PythOracle>(
     &self,
+    sink: &mut dyn GateSink,
   ) -> Result<(), CoreError> {
-    self.exo_pair_gates::<E>()?;
-    self.usdc_pair_gates()?;
+    self.exo_pair_gates::<E>(sink)?;
+    self.usdc_pair_gates(sink)?;
     let pair = self.exo_pair::<E>()?;
-    gate(pair.pool_drawdown.is_repaid(), CoreError::DrawdownNotRepaid)?;
-    gate(
+    sink.check(
+      Gate::DrawdownRepaid,
+      pair.pool_drawdown.is_repaid(),
+      CoreError::DrawdownNotRepaid,
+    )?;
+    sink.check(
+      Gate::ModeAllows,
       pair.context.rebalance_buy_active(),
       CoreError::OperationDisabled,
     )
@@ -1483,11 +2114,11 @@ impl<C: SolanaClock> ProtocolState<C> {
     let out_amount =
       conversion.exo_to_token(normalized, UFix64::<N9>::one())?;
     gate(
-      out_amount <= self.usdc_exchange_state().vault_balance,
+      out_amount <= self.usdc_exchange_state()?.vault_balance,
       CoreError::InsufficientLiquidity,
     )?;
     gate(
-      out_amount <= self.usdc_exchange_state().virtual_stablecoin_supply,
+      out_amount <= self.usdc_exchange_state()?.virtual_stablecoin_supply,
       CoreError::BurnUnderflow,
     )?;
     let pnl = pair
@@ -1517,7 +2148,7 @@ impl<C: SolanaClock> ProtocolState<C> {
     let vault_cap = exo
       .rebalance_buy_conversion(buy_target)?
       .max_exo_for_token(
-        self.usdc_exchange_state().vault_balance,
+        self.usdc_exchange_state()?.vault_balance,
         UFix64::<N9>::one(),
       )?;
     buy_target
@@ -1544,14 +2175,20 @@ impl<C: SolanaClock> ProtocolState<C> {
   }
 
   /// State gates for the USDC-to-exo rebalance sell routes.
-  fn swap_usdc_to_exo_preconditions<E: Exo + PythOracle>(
+  fn swap_usdc_to_exo_gates<E: Exo + PythOracle>(
     &self,
+    sink: &mut dyn GateSink,
   ) -> Result<(), CoreError> {
-    self.exo_pair_gates::<E>()?;
-    self.usdc_pair_gates()?;
+    self.exo_pair_gates::<E>(sink)?;
+    self.usdc_pair_gates(sink)?;
     let pair = self.exo_pair::<E>()?;
-    gate(pair.pool_drawdown.is_repaid(), CoreError::DrawdownNotRepaid)?;
-    gate(
+    sink.check(
+      Gate::DrawdownRepaid,
+      pair.pool_drawdown.is_repaid(),
+      CoreError::DrawdownNotRepaid,
+    )?;
+    sink.check(
+      Gate::ModeAllows,
       pair.context.rebalance_sell_active(),
       CoreError::OperationDisabled,
     )
@@ -1597,6 +2234,55 @@ impl<C: SolanaClock> ProtocolState<C> {
     pair.context.max_rebalance_sell_usdc(pair.supply_floor)
   }
 
+  /// Buy curve price at the CR after the protocol takes `in_amount`.
+  fn swap_exo_to_usdc_components<E: Exo + PythOracle>(
+    &self,
+    in_amount: UFix64<E::Exp>,
+  ) -> Result<PriceComponents, CoreError>
+  where
+    UFix64<E::Exp>: FixExt,
+  {
+    let exo = &self.exo_pair::<E>()?.context;
+    let normalized: UFix64<N9> = in_amount
+      .checked_convert::<N9>()
+      .ok_or(CoreError::TokenAmountPrecision)?;
+    let projected = exo.projected_rebalance_buy_state(normalized)?;
+    Ok(PriceComponents {
+      oracle_spot: Some(exo.collateral_oracle_price().spot),
+      oracle_conf: Some(exo.collateral_oracle_price().conf),
+      rebalance_mode: Some(exo.rebalance_mode()),
+      collateral_ratio: Some(exo.collateral_ratio()),
+      curve_price: Some(
+        exo
+          .rebalance_buy_curve()?
+          .price(projected.collateral_ratio)?,
+      ),
+      ..PriceComponents::default()
+    })
+  }
+
+  /// Sell curve price at the CR after the protocol pays out for
+  /// `in_amount` of USDC.
+  fn swap_usdc_to_exo_components<E: Exo + PythOracle>(
+    &self,
+    in_amount: UFix64<N6>,
+  ) -> Result<PriceComponents, CoreError> {
+    let exo = &self.exo_pair::<E>()?.context;
+    let projected = exo.projected_rebalance_sell_state(in_amount)?;
+    Ok(PriceComponents {
+      oracle_spot: Some(exo.collateral_oracle_price().spot),
+      oracle_conf: Some(exo.collateral_oracle_price().conf),
+      rebalance_mode: Some(exo.rebalance_mode()),
+      collateral_ratio: Some(exo.collateral_ratio()),
+      curve_price: Some(
+        exo
+          .rebalance_sell_curve()?
+          .price(projected.collateral_ratio)?,
+      ),
+      ..PriceComponents::default()
+    })
+  }
+
   fn swap_usdc_to_exo_min_input<E: Exo + PythOracle>(
     &self,
   ) -> Result<UFix64<N6>, CoreError>
@@ -1619,8 +2305,27 @@ where
 {
   type FeeExp = N9;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.mint_stablecoin_exo_preconditions::<E>()
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.mint_stablecoin_exo_gates::<E>(sink)
+  }
+
+  fn price_components(
+    &self,
+    _amount_in: UFix64<E::Exp>,
+  ) -> Result<PriceComponents, CoreError> {
+    let exo = &self.exo_pair::<E>()?.context;
+    Ok(PriceComponents {
+      output_nav: Some(exo.stablecoin_nav()?),
+      fee_tier: exo
+        .stablecoin_mint_fees
+        .fee_rate(exo.collateral_ratio())
+        .ok(),
+      ..self.exo_price_components::<E>(true)?
+    })
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    self.stablecoin_exo_deadlines::<E>()
   }
 
   fn compute_output_ungated(
@@ -1634,6 +2339,13 @@ where
     self.mint_stablecoin_exo_max_input::<E>()
   }
 
+  fn limits(&self) -> Result<Vec<RouteLimit<E::Exp>>, CoreError> {
+    Ok(vec![RouteLimit::new(
+      Limiter::MaxMintable,
+      self.mint_stablecoin_exo_headroom::<E>()?,
+    )])
+  }
+
   fn min_input_ungated(&self) -> Result<UFix64<E::Exp>, CoreError> {
     self.mint_stablecoin_exo_min_input::<E>()
   }
@@ -1646,8 +2358,27 @@ where
 {
   type FeeExp = N9;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.redeem_stablecoin_exo_preconditions::<E>()
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.redeem_stablecoin_exo_gates::<E>(sink)
+  }
+
+  fn price_components(
+    &self,
+    _amount_in: UFix64<N6>,
+  ) -> Result<PriceComponents, CoreError> {
+    let exo = &self.exo_pair::<E>()?.context;
+    Ok(PriceComponents {
+      input_nav: Some(exo.stablecoin_nav()?),
+      fee_tier: exo
+        .stablecoin_redeem_fees
+        .fee_rate(exo.collateral_ratio())
+        .ok(),
+      ..self.exo_price_components::<E>(false)?
+    })
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    self.stablecoin_exo_deadlines::<E>()
   }
 
   fn compute_output_ungated(
@@ -1673,8 +2404,19 @@ where
 {
   type FeeExp = E::Exp;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.swap_exo_to_usdc_preconditions::<E>()
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.swap_exo_to_usdc_gates::<E>(sink)
+  }
+
+  fn price_components(
+    &self,
+    amount_in: UFix64<E::Exp>,
+  ) -> Result<PriceComponents, CoreError> {
+    self.swap_exo_to_usdc_components::<E>(amount_in)
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    self.exo_usdc_deadlines::<E>()
   }
 
   fn compute_output_ungated(
@@ -1700,8 +2442,19 @@ where
 {
   type FeeExp = N6;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.swap_usdc_to_exo_preconditions::<E>()
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.swap_usdc_to_exo_gates::<E>(sink)
+  }
+
+  fn price_components(
+    &self,
+    amount_in: UFix64<N6>,
+  ) -> Result<PriceComponents, CoreError> {
+    self.swap_usdc_to_exo_components::<E>(amount_in)
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    self.exo_usdc_deadlines::<E>()
   }
 
   fn compute_output_ungated(
@@ -1723,8 +2476,27 @@ where
 impl<C: SolanaClock> TokenOperation<JITOSOL, HYUSD> for ProtocolState<C> {
   type FeeExp = N9;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.mint_stablecoin_lst_preconditions()
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.mint_stablecoin_lst_gates(sink)
+  }
+
+  fn price_components(
+    &self,
+    _amount_in: UFix64<N9>,
+  ) -> Result<PriceComponents, CoreError> {
+    Ok(PriceComponents {
+      output_nav: Some(self.exchange_context.stablecoin_nav()?),
+      fee_tier: self
+        .exchange_context
+        .stablecoin_mint_fees
+        .fee_rate(self.exchange_context.collateral_ratio())
+        .ok(),
+      ..self.lst_price_components::<JITOSOL>(true)?
+    })
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    Ok(self.stablecoin_lst_deadlines())
   }
 
   fn compute_output_ungated(
@@ -1738,6 +2510,13 @@ impl<C: SolanaClock> TokenOperation<JITOSOL, HYUSD> for ProtocolState<C> {
     self.mint_stablecoin_lst_max_input::<JITOSOL>()
   }
 
+  fn limits(&self) -> Result<Vec<RouteLimit<N9>>, CoreError> {
+    Ok(vec![RouteLimit::new(
+      Limiter::MaxMintable,
+      self.mint_stablecoin_lst_headroom::<JITOSOL>()?,
+    )])
+  }
+
   fn min_input_ungated(&self) -> Result<UFix64<N9>, CoreError> {
     self.mint_stablecoin_lst_min_input::<JITOSOL>()
   }
@@ -1746,8 +2525,27 @@ impl<C: SolanaClock> TokenOperation<JITOSOL, HYUSD> for ProtocolState<C> {
 impl<C: SolanaClock> TokenOperation<HYLOSOL, HYUSD> for ProtocolState<C> {
   type FeeExp = N9;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.mint_stablecoin_lst_preconditions()
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.mint_stablecoin_lst_gates(sink)
+  }
+
+  fn price_components(
+    &self,
+    _amount_in: UFix64<N9>,
+  ) -> Result<PriceComponents, CoreError> {
+    Ok(PriceComponents {
+      output_nav: Some(self.exchange_context.stablecoin_nav()?),
+      fee_tier: self
+        .exchange_context
+        .stablecoin_mint_fees
+        .fee_rate(self.exchange_context.collateral_ratio())
+        .ok(),
+      ..self.lst_price_components::<HYLOSOL>(true)?
+    })
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    Ok(self.stablecoin_lst_deadlines())
   }
 
   fn compute_output_ungated(
@@ -1761,6 +2559,13 @@ impl<C: SolanaClock> TokenOperation<HYLOSOL, HYUSD> for ProtocolState<C> {
     self.mint_stablecoin_lst_max_input::<HYLOSOL>()
   }
 
+  fn limits(&self) -> Result<Vec<RouteLimit<N9>>, CoreError> {
+    Ok(vec![RouteLimit::new(
+      Limiter::MaxMintable,
+      self.mint_stablecoin_lst_headroom::<HYLOSOL>()?,
+    )])
+  }
+
   fn min_input_ungated(&self) -> Result<UFix64<N9>, CoreError> {
     self.mint_stablecoin_lst_min_input::<HYLOSOL>()
   }
@@ -1769,8 +2574,27 @@ impl<C: SolanaClock> TokenOperation<HYLOSOL, HYUSD> for ProtocolState<C> {
 impl<C: SolanaClock> TokenOperation<HYUSD, JITOSOL> for ProtocolState<C> {
   type FeeExp = N9;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.redeem_stablecoin_lst_preconditions()
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.redeem_stablecoin_lst_gates(sink)
+  }
+
+  fn price_components(
+    &self,
+    _amount_in: UFix64<N6>,
+  ) -> Result<PriceComponents, CoreError> {
+    Ok(PriceComponents {
+      input_nav: Some(self.exchange_context.stablecoin_nav()?),
+      fee_tier: self
+        .exchange_context
+        .stablecoin_redeem_fees
+        .fee_rate(self.exchange_context.collateral_ratio())
+        .ok(),
+      ..self.lst_price_components::<JITOSOL>(false)?
+    })
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    Ok(self.stablecoin_lst_deadlines())
   }
 
   fn compute_output_ungated(
@@ -1792,8 +2616,27 @@ impl<C: SolanaClock> TokenOperation<HYUSD, JITOSOL> for ProtocolState<C> {
 impl<C: SolanaClock> TokenOperation<HYUSD, HYLOSOL> for ProtocolState<C> {
   type FeeExp = N9;
 
-  fn preconditions(&self) -> Result<(), CoreError> {
-    self.redeem_stablecoin_lst_preconditions()
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    self.redeem_stablecoin_lst_gates(sink)
+  }
+
+  fn price_components(
+    &self,
+    _amount_in: UFix64<N6>,
+  ) -> Result<PriceComponents, CoreError> {
+    Ok(PriceComponents {
+      input_nav: Some(self.exchange_context.stablecoin_nav()?),
+      fee_tier: self
+        .exchange_context
+        .stablecoin_redeem_fees
+        .fee_rate(self.exchange_context.collateral_ratio())
+        .ok(),
+      ..self.lst_price_components::<HYLOSOL>(false)?
+    })
+  }
+
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    Ok(self.stablecoin_lst_deadlines())
   }
 
   fn compute_output_ungated(
@@ -1817,8 +2660,31 @@ macro_rules! exo_levercoin_ops {
     $(impl<C: SolanaClock> TokenOperation<$exo, $lever> for ProtocolState<C> {
       type FeeExp = N9;
 
-      fn preconditions(&self) -> Result<(), CoreError> {
-        self.mint_levercoin_exo_preconditions::<$exo>()
+      fn check_gates(
+        &self,
+        sink: &mut dyn GateSink,
+      ) -> Result<(), CoreError> {
+        self.mint_levercoin_exo_gates::<$exo>(sink)
+      }
+
+      fn price_components(
+        &self,
+        _amount_in: UFix64<$exp>,
+      ) -> Result<PriceComponents, CoreError> {
+        let exo = &self.exo_pair::<$exo>()?.context;
+        Ok(PriceComponents {
+          output_nav: Some(exo.levercoin_mint_nav()?),
+          fee_tier: levercoin_fee_tier(exo, LevercoinFees::mint_fee),
+          boundary_fee_tier: levercoin_boundary_fee_tier(
+            exo,
+            LevercoinFees::mint_fee,
+          ),
+          ..self.exo_price_components::<$exo>(true)?
+        })
+      }
+
+      fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+        self.exo_pair_deadlines::<$exo>()
       }
 
       fn compute_output_ungated(
@@ -1832,6 +2698,13 @@ macro_rules! exo_levercoin_ops {
         self.mint_levercoin_exo_max_input::<$exo>()
       }
 
+      fn limits(&self) -> Result<Vec<RouteLimit<$exp>>, CoreError> {
+        Ok(vec![RouteLimit::new(
+          Limiter::LevercoinMarketCap,
+          self.mint_levercoin_exo_max_input::<$exo>()?,
+        )])
+      }
+
       fn min_input_ungated(&self) -> Result<UFix64<$exp>, CoreError> {
         self.mint_levercoin_exo_min_input::<$exo>()
       }
@@ -1840,8 +2713,31 @@ macro_rules! exo_levercoin_ops {
     impl<C: SolanaClock> TokenOperation<$lever, $exo> for ProtocolState<C> {
       type FeeExp = N9;
 
-      fn preconditions(&self) -> Result<(), CoreError> {
-        self.redeem_levercoin_exo_preconditions::<$exo>()
+      fn check_gates(
+        &self,
+        sink: &mut dyn GateSink,
+      ) -> Result<(), CoreError> {
+        self.redeem_levercoin_exo_gates::<$exo>(sink)
+      }
+
+      fn price_components(
+        &self,
+        _amount_in: UFix64<N6>,
+      ) -> Result<PriceComponents, CoreError> {
+        let exo = &self.exo_pair::<$exo>()?.context;
+        Ok(PriceComponents {
+          input_nav: Some(exo.levercoin_redeem_nav()?),
+          fee_tier: levercoin_fee_tier(exo, LevercoinFees::redeem_fee),
+          boundary_fee_tier: levercoin_boundary_fee_tier(
+            exo,
+            LevercoinFees::redeem_fee,
+          ),
+          ..self.exo_price_components::<$exo>(false)?
+        })
+      }
+
+      fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+        self.exo_pair_deadlines::<$exo>()
       }
 
       fn compute_output_ungated(
@@ -1863,8 +2759,22 @@ macro_rules! exo_levercoin_ops {
     impl<C: SolanaClock> TokenOperation<HYUSD, $lever> for ProtocolState<C> {
       type FeeExp = N6;
 
-      fn preconditions(&self) -> Result<(), CoreError> {
-        self.convert_stable_to_lever_exo_preconditions::<$exo>()
+      fn check_gates(
+        &self,
+        sink: &mut dyn GateSink,
+      ) -> Result<(), CoreError> {
+        self.convert_stable_to_lever_exo_gates::<$exo>(sink)
+      }
+
+      fn price_components(
+        &self,
+        _amount_in: UFix64<N6>,
+      ) -> Result<PriceComponents, CoreError> {
+        swap_price_components(&self.exo_pair::<$exo>()?.context, true)
+      }
+
+      fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+        self.exo_pair_deadlines::<$exo>()
       }
 
       fn compute_output_ungated(
@@ -1878,6 +2788,10 @@ macro_rules! exo_levercoin_ops {
         self.convert_stable_to_lever_exo_max_input::<$exo>()
       }
 
+      fn limits(&self) -> Result<Vec<RouteLimit<N6>>, CoreError> {
+        self.convert_stable_to_lever_exo_limits::<$exo>()
+      }
+
       fn min_input_ungated(&self) -> Result<UFix64<N6>, CoreError> {
         self.convert_stable_to_lever_exo_min_input::<$exo>()
       }
@@ -1886,8 +2800,22 @@ macro_rules! exo_levercoin_ops {
     impl<C: SolanaClock> TokenOperation<$lever, HYUSD> for ProtocolState<C> {
       type FeeExp = N6;
 
-      fn preconditions(&self) -> Result<(), CoreError> {
-        self.convert_lever_to_stable_exo_preconditions::<$exo>()
+      fn check_gates(
+        &self,
+        sink: &mut dyn GateSink,
+      ) -> Result<(), CoreError> {
+        self.convert_lever_to_stable_exo_gates::<$exo>(sink)
+      }
+
+      fn price_components(
+        &self,
+        _amount_in: UFix64<N6>,
+      ) -> Result<PriceComponents, CoreError> {
+        swap_price_components(&self.exo_pair::<$exo>()?.context, false)
+      }
+
+      fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+        self.exo_pair_deadlines::<$exo>()
       }
 
       fn compute_output_ungated(
@@ -1909,3 +2837,35 @@ macro_rules! exo_levercoin_ops {
 }
 
 with_exo_pairs!(exo_levercoin_ops);
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn usdc_mint_input_grosses_up_headroom() -> Result<(), CoreError> {
+    let fee = UFix64::<N4>::new(20);
+    let max = max_usdc_mint_input(fee, UFix64::new(998_000))?;
+    assert_eq!(max, UFix64::new(1_000_001));
+    assert!(
+      FeeExtract::new(fee, max)?.amount_remaining <= UFix64::new(998_000)
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn usdc_mint_input_saturates_on_overflow() -> Result<(), CoreError> {
+    let fee = UFix64::<N4>::new(20);
+    let max = max_usdc_mint_input(fee, UFix64::new(u64::MAX))?;
+    assert_eq!(max, UFix64::new(u64::MAX));
+    Ok(())
+  }
+
+  #[test]
+  fn usdc_mint_input_rejects_full_fee() {
+    assert_eq!(
+      max_usdc_mint_input(UFix64::one(), UFix64::new(u64::MAX)),
+      Err(CoreError::FeeExtraction)
+    );
+  }
+}
@@ -10,10 +10,166 @@ use hylo_core::calculus::{positive, positive_rate};
 use hylo_core::error::CoreError;
 use hylo_idl::tokens::TokenMint;
 
+use crate::price_breakdown::{PriceBreakdown, PriceComponents};
+use crate::quote_expiry::{Deadline, QuoteExpiry};
+
 fn gate(condition: bool, error: CoreError) -> Result<(), CoreError> {
   condition.then_some(()).ok_or(error)
 }
 
+/// Pair a [`Gate::PairActive`] check reads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Pair {
+  /// SOL LST pair backing hyUSD and xSOL
+  Lst,
+  /// Exo pair, by collateral mint
+  Exo(Pubkey),
+  /// USDC vault pair
+  Usdc,
+  /// Stability pool behind sHYUSD
+  EarnPool,
+}
+
+/// State condition a route must pass before any math runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Gate {
+  /// Protocol-wide pause is off
+  ProtocolActive,
+  /// Named pair is not paused
+  PairActive(Pair),
+  /// LST yield harvested for the current epoch
+  YieldHarvested,
+  /// Exo pair borrow rate harvested for the current epoch
+  BorrowRateHarvested,
+  /// Stablecoin oracle within its staleness and confidence bounds
+  OracleFresh,
+  /// Stability pool drawdown repaid
+  DrawdownRepaid,
+  /// Stability mode permits the operation
+  ModeAllows,
+  /// USDC/USD spot within the par tolerance
+  UsdcAtPar,
+  /// Earn pool holds hyUSD to back its shares
+  EarnPoolLiquid,
+}
+
+/// Outcome of one [`Gate`], with the error it raises when closed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GateCheck {
+  pub gate: Gate,
+  pub error: Option<CoreError>,
+}
+
+impl GateCheck {
+  #[must_use]
+  pub fn new(gate: Gate, condition: bool, error: CoreError) -> GateCheck {
+    GateCheck {
+      gate,
+      error: (!condition).then_some(error),
+    }
+  }
+
+  #[must_use]
+  pub fn from_result(gate: Gate, result: Result<(), CoreError>) -> GateCheck {
+    GateCheck {
+      gate,
+      error: result.err(),
+    }
+  }
+
+  #[must_use]
+  pub fn is_open(&self) -> bool {
+    self.error.is_none()
+  }
+}
+
+/// Receives each gate outcome of a route in evaluation order.
+pub trait GateSink {
+  /// Takes one gate outcome; an error stops evaluation there.
+  ///
+  /// # Errors
+  /// * Sink stops at closed gates and `check` is closed
+  fn record(&mut self, check: GateCheck) -> Result<(), CoreError>;
+
+  /// Records `gate`, closed with `error` unless `condition` holds.
+  ///
+  /// # Errors
+  /// * See [`Self::record`]
+  fn check(
+    &mut self,
+    gate: Gate,
+    condition: bool,
+    error: CoreError,
+  ) -> Result<(), CoreError> {
+    self.record(GateCheck::new(gate, condition, error))
+  }
+
+  /// Records `gate`, closed with `result`'s error if any.
+  ///
+  /// # Errors
+  /// * See [`Self::record`]
+  fn check_result(
+    &mut self,
+    gate: Gate,
+    result: Result<(), CoreError>,
+  ) -> Result<(), CoreError> {
+    self.record(GateCheck::from_result(gate, result))
+  }
+}
+
+/// Collects every gate, open or closed. Routes spanning two pairs
+/// evaluate the protocol gate twice; only the first is kept.
+impl GateSink for Vec<GateCheck> {
+  fn record(&mut self, check: GateCheck) -> Result<(), CoreError> {
+    if !self.iter().any(|seen| seen.gate == check.gate) {
+      self.push(check);
+    }
+    Ok(())
+  }
+}
+
+/// Stops at the first closed gate without collecting anything.
+pub struct FirstClosed;
+
+impl GateSink for FirstClosed {
+  fn record(&mut self, check: GateCheck) -> Result<(), CoreError> {
+    check.error.map_or(Ok(()), Err)
+  }
+}
+
+/// Protocol limit capping a route's input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Limiter {
+  /// Earn pool deposit cap
+  DepositLimiter,
+  /// Earn pool per-epoch withdrawal window
+  WithdrawalLimiter,
+  /// Levercoin market cap ceiling
+  LevercoinMarketCap,
+  /// Stablecoin mintable before collateral ratio hits its floor
+  MaxMintable,
+  /// Stablecoin supply that may be burned above the floor
+  SupplyFloor,
+  /// Stablecoin removable before the stability mode projection trips
+  StabilityProjection,
+  /// Outstanding earn pool shares
+  ShareSupply,
+}
+
+/// Input headroom left under one [`Limiter`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RouteLimit<Exp: Integer> {
+  pub limiter: Limiter,
+  pub headroom: UFix64<Exp>,
+}
+
+impl<Exp: Integer> RouteLimit<Exp> {
+  #[must_use]
+  pub fn new(limiter: Limiter, headroom: UFix64<Exp>) -> RouteLimit<Exp> {
+    RouteLimit { limiter, headroom }
+  }
+}
+
 /// Smallest input past the largest zero-output input.
 fn past_zero<Exp: Integer>(
   zero_ceiling: UFix64<Exp>,
@@ -38,7 +194,9 @@ pub(crate) fn linear_rate<InExp: Integer, OutExp: Integer>(
 
 /// Scales a token-level marginal rate to atoms:
 /// `rate * 10^(out_decimals - in_decimals)`.
-fn atom_rate<InExp: Integer, OutExp: Integer>(token_rate: f64) -> f64 {
+pub(crate) fn atom_rate<InExp: Integer, OutExp: Integer>(
+  token_rate: f64,
+) -> f64 {
   token_rate * 10f64.powi(InExp::to_i32() - OutExp::to_i32())
 }
 
@@ -60,11 +218,42 @@ pub type LstSwapOperationOutput = OperationOutput<N9, N9, N9>;
 pub trait TokenOperation<IN: TokenMint, OUT: TokenMint> {
   type FeeExp: Integer;
 
+  /// Feeds each state-only gate for the route to `sink`, in evaluation
+  /// order. The protocol pause comes first, ahead of any sub-state load.
+  ///
+  /// # Errors
+  /// * `sink` stopped at a closed gate
+  /// * Sub-state the gates read is missing
+  fn check_gates(&self, sink: &mut dyn GateSink) -> Result<(), CoreError>;
+
+  /// Every state-only gate for the route, open or closed, in evaluation
+  /// order.
+  ///
+  /// # Errors
+  /// * Sub-state the gates read is missing
+  fn gates(&self) -> Result<Vec<GateCheck>, CoreError> {
+    let mut checks = Vec::new();
+    self.check_gates(&mut checks)?;
+    Ok(checks)
+  }
+
   /// State-only route gates; an error means the route is closed.
   ///
   /// # Errors
   /// * Route gated in current state (paused, mode-disabled, unharvested)
-  fn preconditions(&self) -> Result<(), CoreError>;
+  fn preconditions(&self) -> Result<(), CoreError> {
+    self.check_gates(&mut FirstClosed)
+  }
+
+  /// Headroom under each named limiter capping the route's input,
+  /// skipping [`Self::preconditions`]. Routes capped only by vault
+  /// balances report none.
+  ///
+  /// # Errors
+  /// * Underlying arithmetic
+  fn limits(&self) -> Result<Vec<RouteLimit<IN::Exp>>, CoreError> {
+    Ok(Vec::new())
+  }
 
   /// Pure math for the operation, skipping [`Self::preconditions`].
   ///
@@ -81,6 +270,27 @@ pub trait TokenOperation<IN: TokenMint, OUT: TokenMint> {
   /// * Underlying arithmetic
   fn max_input_ungated(&self) -> Result<UFix64<IN::Exp>, CoreError>;
 
+  /// State the route prices `amount_in` against, skipping
+  /// [`Self::preconditions`]. Empty for routes that report none.
+  ///
+  /// # Errors
+  /// * Sub-state the route reads is missing or underlying arithmetic
+  fn price_components(
+    &self,
+    _amount_in: UFix64<IN::Exp>,
+  ) -> Result<PriceComponents, CoreError> {
+    Ok(PriceComponents::default())
+  }
+
+  /// Points at which the oracle windows, epoch prices and harvest gates
+  /// the route reads lapse. Empty for routes bound by none.
+  ///
+  /// # Errors
+  /// * Sub-state the route reads is missing
+  fn deadlines(&self) -> Result<Vec<Deadline>, CoreError> {
+    Ok(Vec::new())
+  }
+
   /// Smallest input yielding at least one output atom, skipping
   /// [`Self::preconditions`].
   ///
@@ -110,6 +320,30 @@ pub trait TokenOperation<IN: TokenMint, OUT: TokenMint> {
     self.max_input_ungated()
   }
 
+  /// Price components and realized fee behind the quote for `amount_in`.
+  ///
+  /// # Errors
+  /// * Route gated in current state or underlying arithmetic
+  fn price_breakdown(
+    &self,
+    amount_in: UFix64<IN::Exp>,
+  ) -> Result<PriceBreakdown, CoreError> {
+    let output = self.compute_output(amount_in)?;
+    Ok(PriceBreakdown::new(
+      self.price_components(amount_in)?,
+      &output,
+    ))
+  }
+
+  /// Earliest of [`Self::deadlines`], when a quote from this state
+  /// stops being executable.
+  ///
+  /// # Errors
+  /// * Sub-state the route reads is missing
+  fn expiry(&self) -> Result<QuoteExpiry, CoreError> {
+    Ok(QuoteExpiry::earliest(&self.deadlines()?))
+  }
+
   /// Smallest input the route turns into output in the current state.
   ///
   /// # Errors
@@ -163,3 +397,36 @@ impl<X> TokenOperationExt for X {
     TokenOperation::<IN, OUT>::compute_output(self, amount_in)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn pair_gates(sink: &mut dyn GateSink) -> Result<(), CoreError> {
+    sink.check(Gate::ProtocolActive, true, CoreError::ProtocolPaused)?;
+    sink.check(Gate::PairActive(Pair::Lst), false, CoreError::PairPaused)?;
+    sink.check(Gate::ProtocolActive, true, CoreError::ProtocolPaused)?;
+    sink.check(Gate::PairActive(Pair::Usdc), false, CoreError::PairPaused)
+  }
+
+  #[test]
+  fn collected_gates_skip_repeats() -> Result<(), CoreError> {
+    let mut checks = Vec::new();
+    pair_gates(&mut checks)?;
+    let gates = checks.iter().map(|check| check.gate).collect::<Vec<_>>();
+    assert_eq!(
+      gates,
+      vec![
+        Gate::ProtocolActive,
+        Gate::PairActive(Pair::Lst),
+        Gate::PairActive(Pair::Usdc),
+      ]
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn first_closed_stops_at_first_closed_gate() {
+    assert_eq!(pair_gates(&mut FirstClosed), Err(CoreError::PairPaused));
+  }
+}
@@ -0,0 +1,566 @@
+//! Treasury fee vault reporting.
+//!
+//! Protocol fees accrue in one [`pda::fee_vault`] per fee mint: LSTs and
+//! exo collateral from mint and redeem, hyUSD from conversions, USDC
+//! redemptions and yield harvests, and USDC from USDC mints.
+//! [`ProtocolState::treasury`] values the vault balances at the state's
+//! oracles, [`FeeAccrual`] attributes deposits to the operation that paid
+//! them from decoded exchange events, and [`WithdrawalPlan`] lists the
+//! `withdraw_fees` sweeps to the treasury.
+
+use std::collections::BTreeMap;
+
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::TokenAccount;
+use anyhow::{anyhow, Context, Result};
+use fix::prelude::*;
+use hylo_clients::exchange_client::ExchangeClient;
+use hylo_clients::program_client::VersionedTransactionData;
+use hylo_core::error::CoreError;
+use hylo_core::exchange_context::ExchangeContext;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_idl::events::EventCpi;
+use hylo_idl::exchange::events::{
+  ConvertLeverToStableExoEvent, ConvertLeverToStableLstEvent,
+  ConvertStableToLeverExoEvent, ConvertStableToLeverLstEvent,
+  HarvestBorrowRateEvent, HarvestYieldEvent, MintLevercoinExoEvent,
+  MintLevercoinLstEvent, MintStablecoinExoEvent, MintStablecoinLstEvent,
+  MintStablecoinUsdcEvent, RedeemLevercoinExoEvent, RedeemLevercoinLstEvent,
+  RedeemStablecoinExoEvent, RedeemStablecoinLstEvent,
+  RedeemStablecoinUsdcEvent, SwapLstToLstEvent,
+};
+use hylo_idl::exchange::instruction_builders;
+use hylo_idl::tokens::{
+  TokenMint, CBBTC, HYLOSOL, HYPE, HYUSD, JITOSOL, ONYC, PST, USDC, WETH, ZEC,
+};
+use hylo_idl::with_exo_pairs;
+use hylo_idl::{exchange, pda};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+
+use crate::portfolio::usd_value;
+use crate::protocol_state::ProtocolState;
+
+/// Operation that paid a fee into a vault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FeeSource {
+  MintStablecoin,
+  RedeemStablecoin,
+  MintLevercoin,
+  RedeemLevercoin,
+  ConvertStableToLever,
+  ConvertLeverToStable,
+  SwapLstToLst,
+  HarvestYield,
+  HarvestBorrowRate,
+}
+
+/// One fee deposit decoded from an exchange event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeAccrual {
+  pub source: FeeSource,
+  pub mint: Pubkey,
+  /// Fee widened to `N9` from its mint's precision
+  pub amount: UFix64<N9>,
+}
+
+/// Decodes `$event_cpi` as each exchange event in turn, mapping the first
+/// match to `(FeeSource, fee mint, fee)`; `None` when none match.
+macro_rules! fee_events {
+  (
+    $event_cpi:expr,
+    $($event:ident => |$e:ident| ($source:ident, $mint:expr, $fee:expr)),+
+    $(,)?
+  ) => {
+    'decoded: {
+      $(if let Some($e) = $event_cpi.decode::<$event>(&exchange::ID)? {
+        break 'decoded Some((FeeSource::$source, $mint, $fee));
+      })+
+      None
+    }
+  };
+}
+
+impl FeeAccrual {
+  /// Decodes an exchange event that paid a fee. `None` for other events
+  /// and other programs.
+  ///
+  /// # Errors
+  /// * Matching discriminator with malformed event data
+  pub fn from_event(event: &EventCpi) -> Result<Option<FeeAccrual>> {
+    let Some((source, mint, fee)) = fee_events!(
+      event,
+      MintStablecoinLstEvent => |e|
+        (MintStablecoin, e.lst_mint, e.fees_deposited),
+      RedeemStablecoinLstEvent => |e|
+        (RedeemStablecoin, e.lst_mint, e.fees_deposited),
+      MintLevercoinLstEvent => |e|
+        (MintLevercoin, e.lst_mint, e.fees_deposited),
+      RedeemLevercoinLstEvent => |e|
+        (RedeemLevercoin, e.lst_mint, e.fees_deposited),
+      MintStablecoinExoEvent => |e|
+        (MintStablecoin, e.collateral_mint, e.fees_deposited),
+      RedeemStablecoinExoEvent => |e|
+        (RedeemStablecoin, e.collateral_mint, e.fees_deposited),
+      MintLevercoinExoEvent => |e|
+        (MintLevercoin, e.collateral_mint, e.fees_deposited),
+      RedeemLevercoinExoEvent => |e|
+        (RedeemLevercoin, e.collateral_mint, e.fees_deposited),
+      MintStablecoinUsdcEvent => |e|
+        (MintStablecoin, USDC::MINT, e.usdc_fees),
+      RedeemStablecoinUsdcEvent => |e|
+        (RedeemStablecoin, HYUSD::MINT, e.stablecoin_fees),
+      ConvertStableToLeverLstEvent => |e|
+        (ConvertStableToLever, HYUSD::MINT, e.stablecoin_fees),
+      ConvertStableToLeverExoEvent => |e|
+        (ConvertStableToLever, HYUSD::MINT, e.stablecoin_fees),
+      ConvertLeverToStableLstEvent => |e|
+        (ConvertLeverToStable, HYUSD::MINT, e.stablecoin_minted_fees),
+      ConvertLeverToStableExoEvent => |e|
+        (ConvertLeverToStable, HYUSD::MINT, e.stablecoin_minted_fees),
+      SwapLstToLstEvent => |e|
+        (SwapLstToLst, e.lst_a_mint, e.lst_a_fees_extracted),
+      HarvestYieldEvent => |e|
+        (HarvestYield, HYUSD::MINT, e.fees_extracted),
+      HarvestBorrowRateEvent => |e|
+        (HarvestBorrowRate, HYUSD::MINT, e.fees_extracted),
+    ) else {
+      return Ok(None);
+    };
+    Ok(Some(FeeAccrual {
+      source,
+      mint,
+      amount: widen(fee.into())?,
+    }))
+  }
+}
+
+/// Widens an event fee to `N9`. Fees are reported at their mint's
+/// precision: `N9` for LSTs and HYPE, `N8` for cbBTC, WETH and ZEC, `N6`
+/// for hyUSD and USDC.
+fn widen(fee: UFixValue64) -> Result<UFix64<N9>> {
+  u32::try_from(i32::from(fee.exp) + 9)
+    .ok()
+    .and_then(|shift| 10u64.checked_pow(shift))
+    .and_then(|scale| fee.bits.checked_mul(scale))
+    .map(UFix64::new)
+    .with_context(|| format!("fee at exponent {} does not fit N9", fee.exp))
+}
+
+/// Raw fee vault balances, by fee mint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeeVaults {
+  balances: BTreeMap<Pubkey, u64>,
+}
+
+impl FeeVaults {
+  /// Sets the vault balance of `mint` in base units.
+  #[must_use]
+  pub fn with_balance(mut self, mint: Pubkey, amount: u64) -> FeeVaults {
+    self.balances.insert(mint, amount);
+    self
+  }
+
+  /// Vault balance of `mint` in base units, zero if unset.
+  #[must_use]
+  pub fn get(&self, mint: &Pubkey) -> u64 {
+    self.balances.get(mint).copied().unwrap_or_default()
+  }
+
+  /// Reads the fee vault of every mint in [`FEE_MINTS`]. Vaults not yet
+  /// created read as zero.
+  ///
+  /// # Errors
+  /// * RPC failure
+  /// * Token account deserialization
+  pub async fn fetch(rpc_client: &RpcClient) -> Result<FeeVaults> {
+    let pubkeys = FEE_MINTS
+      .iter()
+      .map(|mint| pda::fee_vault(*mint))
+      .collect::<Vec<_>>();
+    let data = rpc_client
+      .get_multiple_accounts(&pubkeys)
+      .await
+      .map_err(|e| anyhow!("Failed to fetch fee vaults from RPC: {e}"))?;
+    FEE_MINTS.iter().zip(data).try_fold(
+      FeeVaults::default(),
+      |vaults, (mint, account)| match account {
+        Some(account) => {
+          let token =
+            TokenAccount::try_deserialize(&mut account.data.as_slice())?;
+          Ok(vaults.with_balance(*mint, token.amount))
+        }
+        None => Ok(vaults),
+      },
+    )
+  }
+}
+
+/// One fee vault and its value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeVault {
+  pub mint: Pubkey,
+  pub vault: Pubkey,
+  pub balance: UFixValue64,
+  /// `Err` when the state cannot price `mint`, e.g. its pair is not
+  /// loaded or an LST price is outdated
+  pub value_usd: Result<UFix64<N9>, CoreError>,
+}
+
+/// Fees paid by one operation into one vault.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeAttribution {
+  pub source: FeeSource,
+  pub mint: Pubkey,
+  pub amount: UFix64<N9>,
+  /// Accruals summed into `amount`
+  pub count: usize,
+  /// `amount` at the state's current price, not the price when paid
+  pub value_usd: Result<UFix64<N9>, CoreError>,
+}
+
+/// Every fee vault valued at one protocol state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreasuryReport {
+  /// Vaults in [`FEE_MINTS`] order, empty ones included
+  pub vaults: Vec<FeeVault>,
+  /// Sum over priced vaults
+  pub total_usd: UFix64<N9>,
+}
+
+/// One `withdraw_fees` sweep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeWithdrawal {
+  pub mint: Pubkey,
+  pub amount: UFixValue64,
+  pub value_usd: UFix64<N9>,
+}
+
+/// Fee sweeps to a treasury, most valuable first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithdrawalPlan {
+  pub treasury: Pubkey,
+  pub withdrawals: Vec<FeeWithdrawal>,
+  pub total_usd: UFix64<N9>,
+}
+
+impl WithdrawalPlan {
+  /// One `withdraw_fees` instruction per sweep. Each drains its whole
+  /// vault, so fees accrued after planning are swept too.
+  #[must_use]
+  pub fn instructions(&self, payer: Pubkey) -> Vec<Instruction> {
+    self
+      .withdrawals
+      .iter()
+      .map(|withdrawal| {
+        instruction_builders::withdraw_fees(
+          payer,
+          self.treasury,
+          withdrawal.mint,
+        )
+      })
+      .collect()
+  }
+
+  /// The plan as [`ExchangeClient::withdraw_fees`] transactions.
+  ///
+  /// # Errors
+  /// * Failed to build transaction instructions
+  pub fn transactions(
+    &self,
+    client: &ExchangeClient,
+  ) -> Result<Vec<VersionedTransactionData>> {
+    self
+      .withdrawals
+      .iter()
+      .map(|withdrawal| client.withdraw_fees(self.treasury, withdrawal.mint))
+      .collect()
+  }
+}
+
+impl TreasuryReport {
+  /// Sweeps of every nonempty vault worth at least `min_value_usd`.
+  /// Unpriced vaults are left out: their worth cannot be judged against
+  /// the threshold, so a dust balance would be swept like any other.
+  ///
+  /// # Errors
+  /// * Total overflow
+  pub fn withdrawal_plan(
+    &self,
+    treasury: Pubkey,
+    min_value_usd: UFix64<N9>,
+  ) -> Result<WithdrawalPlan, CoreError> {
+    let mut withdrawals = self
+      .vaults
+      .iter()
+      .filter(|vault| vault.balance.bits > 0)
+      .filter_map(|vault| {
+        vault
+          .value_usd
+          .ok()
+          .filter(|value| *value >= min_value_usd)
+          .map(|value_usd| FeeWithdrawal {
+            mint: vault.mint,
+            amount: vault.balance,
+            value_usd,
+          })
+      })
+      .collect::<Vec<_>>();
+    withdrawals
+      .sort_by_key(|withdrawal| std::cmp::Reverse(withdrawal.value_usd));
+    let total_usd = sum_priced(withdrawals.iter().map(|w| Ok(w.value_usd)))?;
+    Ok(WithdrawalPlan {
+      treasury,
+      withdrawals,
+      total_usd,
+    })
+  }
+}
+
+/// Sum of the `Ok` values.
+fn sum_priced(
+  values: impl IntoIterator<Item = Result<UFix64<N9>, CoreError>>,
+) -> Result<UFix64<N9>, CoreError> {
+  values
+    .into_iter()
+    .filter_map(Result::ok)
+    .try_fold(UFix64::zero(), |total, value| total.checked_add(&value))
+    .ok_or(CoreError::TokenAmountPrecision)
+}
+
+macro_rules! fee_vault_table {
+  ($(($exo:ident, $lever:ident, $exp:ty)),+ $(,)?) => {
+    /// Mints with a protocol fee vault, in report order. Vaults of exo
+    /// pairs [`ProtocolState`] cannot load are reported unpriced with
+    /// [`CoreError::UnknownExoMint`].
+    pub const FEE_MINTS: &[Pubkey] = &[
+      JITOSOL::MINT,
+      HYLOSOL::MINT,
+      HYUSD::MINT,
+      USDC::MINT,
+      $(<$exo>::MINT,)+
+    ];
+
+    impl<C: SolanaClock> ProtocolState<C> {
+      /// USD per whole token of a fee mint.
+      ///
+      /// # Errors
+      /// * Mint outside [`FEE_MINTS`]
+      /// * Pricing sub-state not loaded or its price unavailable
+      pub fn fee_mint_price(
+        &self,
+        mint: Pubkey,
+      ) -> Result<UFix64<N9>, CoreError> {
+        match mint {
+          JITOSOL::MINT => self.lst_usd_price::<JITOSOL>(),
+          HYLOSOL::MINT => self.lst_usd_price::<HYLOSOL>(),
+          HYUSD::MINT => self.exchange_context.stablecoin_nav(),
+          USDC::MINT => {
+            self.usdc_exchange_state().map(|usdc| usdc.usdc_usd_spot)
+          }
+          $(<$exo>::MINT => self
+            .exo_pair::<$exo>()
+            .map(|pair| pair.context.collateral_oracle_price().spot),)+
+          _ => Err(CoreError::UnknownExoMint),
+        }
+      }
+
+      /// Vault of `mint` holding `amount` base units, valued.
+      fn fee_vault(&self, mint: Pubkey, amount: u64) -> FeeVault {
+        let (balance, value_usd) = match mint {
+          JITOSOL::MINT => self.fee_balance::<JITOSOL>(amount),
+          HYLOSOL::MINT => self.fee_balance::<HYLOSOL>(amount),
+          HYUSD::MINT => self.fee_balance::<HYUSD>(amount),
+          USDC::MINT => self.fee_balance::<USDC>(amount),
+          $(<$exo>::MINT => self.fee_balance::<$exo>(amount),)+
+          _ => (
+            UFixValue64 { bits: amount, exp: 0 },
+            Err(CoreError::UnknownExoMint),
+          ),
+        };
+        FeeVault {
+          mint,
+          vault: pda::fee_vault(mint),
+          balance,
+          value_usd,
+        }
+      }
+    }
+  };
+}
+
+with_exo_pairs!(fee_vault_table);
+
+impl<C: SolanaClock> ProtocolState<C> {
+  fn fee_balance<T: TokenMint>(
+    &self,
+    amount: u64,
+  ) -> (UFixValue64, Result<UFix64<N9>, CoreError>)
+  where
+    UFix64<T::Exp>: FixExt,
+  {
+    let balance = UFix64::<T::Exp>::new(amount);
+    let value_usd = self
+      .fee_mint_price(T::MINT)
+      .and_then(|price| usd_value(balance, price));
+    (balance.into(), value_usd)
+  }
+
+  /// Values every fee vault in [`FEE_MINTS`].
+  ///
+  /// # Errors
+  /// * Total overflow
+  pub fn treasury(
+    &self,
+    vaults: &FeeVaults,
+  ) -> Result<TreasuryReport, CoreError> {
+    let vaults = FEE_MINTS
+      .iter()
+      .map(|mint| self.fee_vault(*mint, vaults.get(mint)))
+      .collect::<Vec<_>>();
+    let total_usd = sum_priced(vaults.iter().map(|vault| vault.value_usd))?;
+    Ok(TreasuryReport { vaults, total_usd })
+  }
+
+  /// Sums `accruals` by operation and fee mint, valued at current prices.
+  ///
+  /// # Errors
+  /// * Amount overflow
+  pub fn attribute_fees(
+    &self,
+    accruals: impl IntoIterator<Item = FeeAccrual>,
+  ) -> Result<Vec<FeeAttribution>, CoreError> {
+    let mut totals =
+      BTreeMap::<(FeeSource, Pubkey), (UFix64<N9>, usize)>::new();
+    for accrual in accruals {
+      let (amount, count) =
+        totals.entry((accrual.source, accrual.mint)).or_default();
+      *amount = amount
+        .checked_add(&accrual.amount)
+        .ok_or(CoreError::TokenAmountPrecision)?;
+      *count += 1;
+    }
+    Ok(
+      totals
+        .into_iter()
+        .map(|((source, mint), (amount, count))| FeeAttribution {
+          source,
+          mint,
+          amount,
+          count,
+          value_usd: self
+            .fee_mint_price(mint)
+            .and_then(|price| usd_value(amount, price)),
+        })
+        .collect(),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use hylo_idl::events::event_cpi_data;
+  use hylo_idl::exchange::types::{OraclePriceEvent, UFixValue64 as IdlValue};
+
+  use super::*;
+
+  #[test]
+  fn decodes_hyusd_fee_widened() -> Result<()> {
+    let event = RedeemStablecoinUsdcEvent {
+      stablecoin_burned: IdlValue {
+        bits: 99_000_000,
+        exp: -6,
+      },
+      stablecoin_fees: IdlValue {
+        bits: 1_000_000,
+        exp: -6,
+      },
+      usdc_withdrawn: IdlValue {
+        bits: 99_000_000,
+        exp: -6,
+      },
+      virtual_stablecoin_supply: IdlValue { bits: 0, exp: -6 },
+    };
+    let data = event_cpi_data(&event);
+    let from = |program| {
+      FeeAccrual::from_event(
+        &EventCpi::parse(program, &data).expect("event CPI"),
+      )
+    };
+    assert_eq!(
+      from(exchange::ID)?,
+      Some(FeeAccrual {
+        source: FeeSource::RedeemStablecoin,
+        mint: HYUSD::MINT,
+        amount: UFix64::new(1_000_000_000),
+      })
+    );
+    assert_eq!(from(hylo_idl::earn_pool::ID)?, None);
+    Ok(())
+  }
+
+  #[test]
+  fn decodes_cbbtc_fee_at_mint_precision() -> Result<()> {
+    let value = |bits, exp| IdlValue { bits, exp };
+    let event = MintStablecoinExoEvent {
+      collateral_mint: CBBTC::MINT,
+      minted: value(99_000_000_000, -6),
+      nav: value(1_000_000, -6),
+      collateral_usd_price: OraclePriceEvent {
+        spot: value(100_000_000_000_000, -9),
+        conf: value(0, -9),
+      },
+      collateral_deposited: value(99_000_000, -8),
+      fees_deposited: value(1_000_000, -8),
+      virtual_stablecoin_supply: value(0, -6),
+      stablecoin_supply: value(0, -6),
+    };
+    let data = event_cpi_data(&event);
+    let event_cpi = EventCpi::parse(exchange::ID, &data).expect("event CPI");
+    assert_eq!(
+      FeeAccrual::from_event(&event_cpi)?,
+      Some(FeeAccrual {
+        source: FeeSource::MintStablecoin,
+        mint: CBBTC::MINT,
+        amount: UFix64::new(10_000_000),
+      })
+    );
+    Ok(())
+  }
+
+  fn vault(mint: Pubkey, bits: u64, value: Option<u64>) -> FeeVault {
+    FeeVault {
+      mint,
+      vault: pda::fee_vault(mint),
+      balance: UFixValue64 { bits, exp: -6 },
+      value_usd: value
+        .map(UFix64::new)
+        .ok_or(CoreError::ProtocolAccountNotFound),
+    }
+  }
+
+  #[test]
+  fn plan_skips_dust_empty_and_unpriced_vaults() -> Result<(), CoreError> {
+    let report = TreasuryReport {
+      vaults: vec![
+        vault(JITOSOL::MINT, 5, Some(2_000_000_000)),
+        vault(HYUSD::MINT, 10, Some(9_000_000_000)),
+        vault(USDC::MINT, 1, Some(1)),
+        vault(CBBTC::MINT, 3, None),
+        vault(HYPE::MINT, 0, Some(0)),
+      ],
+      total_usd: UFix64::new(11_000_000_001),
+    };
+    let plan = report.withdrawal_plan(Pubkey::default(), UFix64::one())?;
+    let mints = plan
+      .withdrawals
+      .iter()
+      .map(|withdrawal| withdrawal.mint)
+      .collect::<Vec<_>>();
+    assert_eq!(mints, [HYUSD::MINT, JITOSOL::MINT]);
+    assert_eq!(plan.total_usd, UFix64::new(11_000_000_000));
+    assert_eq!(plan.instructions(Pubkey::default()).len(), 2);
+    Ok(())
+  }
+}
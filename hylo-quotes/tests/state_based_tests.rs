@@ -5,14 +5,31 @@
 
 use std::fs::File;
 
+use anchor_client::Cluster;
+use anchor_lang::prelude::Pubkey;
 use anchor_lang::solana_program::clock::Clock;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::Mint;
 use anyhow::Result;
+use async_trait::async_trait;
 use fix::prelude::*;
-use hylo_clients::prelude::CommitmentConfig;
-use hylo_idl::tokens::{HYLOSOL, HYUSD, JITOSOL, SHYUSD, XSOL};
+use hylo_clients::prelude::{CommitmentConfig, ProgramClient, RouterClient};
+use hylo_core::error::CoreError;
+use hylo_core::exchange_context::ExchangeContext;
+use hylo_core::idl::exchange::accounts::Hylo;
+use hylo_core::rebalance::mode::RebalanceMode;
+use hylo_idl::tokens::{
+  TokenMint, CBBTC, HYLOSOL, HYUSD, JITOSOL, SHYUSD, USDC, XSOL, XZEC, ZEC,
+};
 use hylo_quotes::prelude::{
-  ProtocolAccounts, ProtocolState, TokenOperationExt,
+  revalidate, FeeVaults, Gate, HybridStrategy, Pair, ProtocolAccounts,
+  ProtocolState, ProtocolStateStrategy, QuoteRequest, Reopen, Revalidation,
+  RouteStatus, RuntimeQuoteStrategy, SimulationStrategy, SlippageModel,
+  StateProvider, StateQuoteStrategy, TokenOperation, TokenOperationExt,
+  WalletBalances, FEE_MINTS,
 };
+use hylo_quotes::protocol_state::build_lst_exchange_context;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 use serde_json::{from_reader, to_writer};
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
 
@@ -47,14 +64,23 @@ async fn dump_snapshot() -> Result<()> {
   dump_protocol_accounts().await
 }
 
-fn load_state() -> Result<ProtocolState<Clock>> {
+fn load_accounts() -> Result<ProtocolAccounts> {
   let path = format!(
     "{}/tests/data/protocol-state-1018-114971.json",
     env!("CARGO_MANIFEST_DIR")
   );
   let file = File::open(path)?;
-  let accounts = from_reader::<_, ProtocolAccounts>(file)?;
-  ProtocolState::try_from(&accounts)
+  Ok(from_reader::<_, ProtocolAccounts>(file)?)
+}
+
+fn load_state() -> Result<ProtocolState<Clock>> {
+  ProtocolState::try_from(&load_accounts()?)
+}
+
+/// The snapshot's USDC pair predates `usdc_pair.redeem_fee`, so routes that
+/// don't touch USDC load everything else.
+fn load_state_without_usdc() -> Result<ProtocolState<Clock>> {
+  ProtocolState::from_accounts_without_usdc(&load_accounts()?)
 }
 
 #[test]
@@ -106,3 +132,342 @@ fn hyusd_to_shyusd() -> Result<()> {
   assert!(op.out_amount > UFix64::<N6>::new(0));
   Ok(())
 }
+
+#[test]
+fn protocol_pause_precedes_missing_accounts() -> Result<()> {
+  let mut state = load_state_without_usdc()?;
+  state.protocol_paused = true;
+  state.jitosol_header = None;
+  state.earn_pool = None;
+  state.cbbtc_pair = None;
+  state.usdc_exchange_state = None;
+  let paused = Err(CoreError::ProtocolPaused);
+  let lst = UFix64::<N9>::new(1_000_000_000);
+  let stable = UFix64::<N6>::new(1_000_000);
+  assert_eq!(state.output::<JITOSOL, XSOL>(lst).map(|_| ()), paused);
+  assert_eq!(state.output::<HYUSD, SHYUSD>(stable).map(|_| ()), paused);
+  assert_eq!(
+    state
+      .output::<CBBTC, HYUSD>(UFix64::new(100_000))
+      .map(|_| ()),
+    paused
+  );
+  assert_eq!(state.output::<USDC, HYUSD>(stable).map(|_| ()), paused);
+  state.protocol_paused = false;
+  assert_eq!(
+    state.output::<USDC, HYUSD>(stable).map(|_| ()),
+    Err(CoreError::ProtocolAccountNotFound)
+  );
+  Ok(())
+}
+
+/// Route status agrees with the short-circuiting precondition check.
+fn assert_status_matches<IN: TokenMint, OUT: TokenMint>(
+  state: &ProtocolState<Clock>,
+) -> Result<RouteStatus>
+where
+  ProtocolState<Clock>: TokenOperation<IN, OUT>,
+{
+  let status = RouteStatus::evaluate::<IN, OUT, _>(state)?;
+  let first_closed = status.closed().next().and_then(|check| check.error);
+  assert_eq!(
+    TokenOperation::<IN, OUT>::preconditions(state).err(),
+    first_closed
+  );
+  assert_eq!(status.is_open(), first_closed.is_none());
+  assert_eq!(status.max_input.is_some(), status.is_open());
+  Ok(status)
+}
+
+#[test]
+fn route_status_matches_preconditions() -> Result<()> {
+  let state = load_state_without_usdc()?;
+  let status = assert_status_matches::<SHYUSD, HYUSD>(&state)?;
+  assert_eq!(status.limits.len(), 2);
+  let binding = status.binding().map(|limit| limit.headroom.bits);
+  assert_eq!(status.max_input.map(|max| max.bits), binding);
+  assert_status_matches::<JITOSOL, XSOL>(&state)?;
+  let outdated = assert_status_matches::<JITOSOL, HYUSD>(&state)?;
+  let oracle_update = Reopen::OracleUpdate {
+    estimated_at: state.fetched_at + i64::try_from(state.oracle_interval_secs)?,
+  };
+  assert!(outdated.reopen.contains(&oracle_update));
+  Ok(())
+}
+
+#[test]
+#[ignore = "snapshot predates usdc_pair.redeem_fee; re-dump after redeploy"]
+fn usdc_route_status_matches_preconditions() -> Result<()> {
+  let state = load_state()?;
+  assert_status_matches::<USDC, HYUSD>(&state)?;
+  let rebalance = assert_status_matches::<JITOSOL, USDC>(&state)?;
+  let gates = rebalance
+    .gates
+    .iter()
+    .map(|check| check.gate)
+    .collect::<Vec<_>>();
+  assert_eq!(
+    gates
+      .iter()
+      .filter(|&&gate| gate == Gate::ProtocolActive)
+      .count(),
+    1
+  );
+  assert!(gates.contains(&Gate::PairActive(Pair::Lst)));
+  assert!(gates.contains(&Gate::PairActive(Pair::Usdc)));
+  Ok(())
+}
+
+#[test]
+fn depeg_route_status_skips_limits() -> Result<()> {
+  let accounts = load_accounts()?;
+  let mut hylo = Hylo::try_deserialize(&mut accounts.hylo.data.as_slice())?;
+  hylo.total_sol_cache.total_sol.bits /= 100;
+  let xsol_mint =
+    Mint::try_deserialize(&mut accounts.xsol_mint.data.as_slice())?;
+  let sol_usd =
+    PriceUpdateV2::try_deserialize(&mut accounts.sol_usd_pyth.data.as_slice())?;
+  let mut state = load_state_without_usdc()?;
+  state.exchange_context = build_lst_exchange_context(
+    state.exchange_context.clock.clone(),
+    &hylo,
+    &xsol_mint,
+    &sol_usd,
+  )?;
+  assert_eq!(
+    state.exchange_context.rebalance_mode(),
+    RebalanceMode::Depeg
+  );
+
+  // Depeg leaves the levercoin fees, and so the limiters, undefined
+  let status = assert_status_matches::<HYUSD, XSOL>(&state)?;
+  let closed = status.closed().map(|check| check.gate).collect::<Vec<_>>();
+  assert_eq!(closed, [Gate::ModeAllows]);
+  assert!(status.limits.is_empty());
+  assert_eq!(status.reopen, [Reopen::MarketConditions]);
+  Ok(())
+}
+
+#[test]
+fn levercoin_mint_price_breakdown() -> Result<()> {
+  let state = load_state_without_usdc()?;
+  let breakdown =
+    state.runtime_price_breakdown(JITOSOL::MINT, XSOL::MINT, 1_000_000_000)?;
+  let components = breakdown.components;
+  assert!(components.oracle_bound <= components.oracle_spot);
+  assert!(components.input_lst_price.is_some());
+  assert!(components.output_nav.is_some());
+  assert!(breakdown
+    .confidence_spread()
+    .is_some_and(|spread| spread <= 0.0));
+  assert!((0.0..1.0).contains(&breakdown.fee_rate));
+  assert!(components
+    .fee_tier
+    .is_some_and(|tier| (tier.to_f64() - breakdown.fee_rate).abs() < 1e-6));
+  assert!(breakdown.effective_price > 0.0);
+  Ok(())
+}
+
+#[test]
+#[ignore = "snapshot predates usdc_pair.redeem_fee; re-dump after redeploy"]
+fn usdc_mint_price_breakdown() -> Result<()> {
+  let state = load_state()?;
+  let breakdown =
+    state.runtime_price_breakdown(USDC::MINT, HYUSD::MINT, 1_000_000)?;
+  let usdc_state = state.usdc_exchange_state()?;
+  assert_eq!(
+    breakdown.components.oracle_spot,
+    Some(usdc_state.usdc_usd_spot)
+  );
+  assert_eq!(
+    breakdown.components.fee_tier,
+    Some(usdc_state.mint_fee.convert())
+  );
+  Ok(())
+}
+
+#[test]
+fn levercoin_mint_slippage_recommendation() -> Result<()> {
+  let state = load_state_without_usdc()?;
+  let model = SlippageModel::default();
+  let recommendation = state.runtime_recommend_slippage(
+    JITOSOL::MINT,
+    XSOL::MINT,
+    1_000_000_000,
+    &model,
+  )?;
+  let components = recommendation.components;
+  assert!(components.oracle_confidence_bps > 0.0);
+  assert!(components.mode_boundary_distance_bps.is_some());
+  assert!(
+    (model.base_bps..=model.max_bps).contains(&recommendation.tolerance_bps)
+  );
+  Ok(())
+}
+
+#[test]
+fn quote_expiry_and_revalidation() -> Result<()> {
+  let state = load_state_without_usdc()?;
+  let strategy = ProtocolStateStrategy::new(SnapshotProvider);
+  let quote = StateQuoteStrategy::<JITOSOL, XSOL, Clock>::quote_from_state(
+    &strategy,
+    &state,
+    1_000_000_000,
+    Pubkey::new_unique(),
+    50,
+  )?;
+  assert!(quote.min_amount_out <= quote.amount_out);
+  assert!(quote
+    .expiry
+    .unix_timestamp
+    .is_some_and(|at| at > state.fetched_at));
+  assert!(quote.expiry.slot.is_some());
+  assert!(!quote.expiry.is_expired(&state.exchange_context.clock));
+  assert_eq!(
+    revalidate::<JITOSOL, XSOL, _, _>(&quote, &state),
+    Revalidation::Executable {
+      amount_out: quote.amount_out,
+      expiry: quote.expiry,
+    }
+  );
+  let mut oversized = quote.clone();
+  oversized.amount_in = UFix64::new(u64::MAX);
+  assert_eq!(
+    revalidate::<JITOSOL, XSOL, _, _>(&oversized, &state),
+    Revalidation::InputOutOfRange
+  );
+  Ok(())
+}
+
+/// Serves the snapshot state on every fetch.
+struct SnapshotProvider;
+
+#[async_trait]
+impl StateProvider<Clock> for SnapshotProvider {
+  async fn fetch_state(&self) -> Result<ProtocolState<Clock>> {
+    load_state_without_usdc()
+  }
+}
+
+#[tokio::test]
+async fn hybrid_keeps_state_quote_when_simulation_fails() -> Result<()> {
+  // Nothing listens on the discard port, so every simulation errors.
+  let cluster = Cluster::Custom(
+    "http://127.0.0.1:9".to_string(),
+    "ws://127.0.0.1:9".to_string(),
+  );
+  let router_client =
+    RouterClient::new_random_keypair(cluster, CommitmentConfig::confirmed())?;
+  let hybrid = HybridStrategy::new(
+    ProtocolStateStrategy::new(SnapshotProvider),
+    SimulationStrategy::new(router_client),
+  );
+  let state = ProtocolStateStrategy::new(SnapshotProvider);
+  let user = Pubkey::new_unique();
+  let quote = hybrid
+    .runtime_quote(JITOSOL::MINT, XSOL::MINT, 1_000_000_000, user, 50)
+    .await?;
+  let expected = state
+    .runtime_quote(JITOSOL::MINT, XSOL::MINT, 1_000_000_000, user, 50)
+    .await?;
+  assert_eq!(quote.amount_out, expected.amount_out);
+  assert_eq!(quote.compute_units, expected.compute_units);
+  Ok(())
+}
+
+#[test]
+fn portfolio_reports_unloaded_exo_pairs_unpriced() -> Result<()> {
+  let state = load_state_without_usdc()?;
+  let balances = WalletBalances::default()
+    .with_balance(JITOSOL::MINT, 1_000_000_000)
+    .with_balance(XZEC::MINT, 1_000_000)
+    .with_balance(ZEC::MINT, 100_000_000);
+  let portfolio = state.portfolio(&balances)?;
+  let held = portfolio
+    .holdings
+    .iter()
+    .map(|holding| holding.mint)
+    .collect::<Vec<_>>();
+  assert_eq!(held, [JITOSOL::MINT]);
+  assert_eq!(
+    portfolio.unpriced,
+    [
+      (XZEC::MINT, CoreError::UnknownExoMint),
+      (ZEC::MINT, CoreError::UnknownExoMint),
+    ]
+  );
+  Ok(())
+}
+
+#[test]
+fn treasury_values_fee_vaults() -> Result<()> {
+  let state = load_state_without_usdc()?;
+  let vaults = FeeVaults::default()
+    .with_balance(JITOSOL::MINT, 2_000_000_000)
+    .with_balance(HYUSD::MINT, 50_000_000)
+    .with_balance(USDC::MINT, 1_000);
+  let report = state.treasury(&vaults)?;
+  assert_eq!(report.vaults.len(), FEE_MINTS.len());
+  let value = |mint: Pubkey| {
+    report
+      .vaults
+      .iter()
+      .find(|vault| vault.mint == mint)
+      .map(|vault| vault.value_usd)
+  };
+  let jitosol = value(JITOSOL::MINT).transpose()?;
+  let hyusd = value(HYUSD::MINT).transpose()?;
+  assert_eq!(
+    value(USDC::MINT),
+    Some(Err(CoreError::ProtocolAccountNotFound))
+  );
+  assert_eq!(value(ZEC::MINT), Some(Err(CoreError::UnknownExoMint)));
+  assert!(jitosol.is_some_and(|v| v > UFix64::new(100_000_000_000)));
+  assert!(hyusd.is_some_and(|v| v > UFix64::new(40_000_000_000)));
+  let priced = [jitosol, hyusd]
+    .into_iter()
+    .flatten()
+    .try_fold(UFix64::<N9>::zero(), |sum, v| sum.checked_add(&v));
+  assert_eq!(Some(report.total_usd), priced);
+
+  let plan =
+    report.withdrawal_plan(Pubkey::new_unique(), UFix64::new(1_000_000_000))?;
+  let mints = plan.withdrawals.iter().map(|w| w.mint).collect::<Vec<_>>();
+  assert_eq!(mints, [JITOSOL::MINT, HYUSD::MINT]);
+  Ok(())
+}
+
+#[tokio::test]
+#[ignore = "snapshot predates usdc_pair.redeem_fee; re-dump after redeploy"]
+async fn batch_matches_single_quotes() -> Result<()> {
+  let strategy = ProtocolStateStrategy::new(SnapshotProvider);
+  let user = Pubkey::new_unique();
+  let requests = [
+    QuoteRequest::new(JITOSOL::MINT, XSOL::MINT, 1_000_000_000),
+    QuoteRequest::new(JITOSOL::MINT, XSOL::MINT, 10_000_000_000),
+    QuoteRequest::new(HYUSD::MINT, SHYUSD::MINT, 1_000_000),
+    QuoteRequest::new(XSOL::MINT, XSOL::MINT, 1_000_000),
+  ];
+  let batch = strategy.quote_batch(&requests, user, 50).await?;
+  let state = load_state()?;
+  assert_eq!(batch.slot, state.exchange_context.clock.slot);
+  assert_eq!(batch.timestamp, state.fetched_at);
+  assert_eq!(batch.results.len(), requests.len());
+  assert!(batch.results[3].is_err());
+  assert_eq!(batch.quotes().count(), 3);
+  for (i, quote) in batch.quotes() {
+    let request = requests[i];
+    let single = strategy
+      .runtime_quote(
+        request.input_mint,
+        request.output_mint,
+        request.amount_in,
+        user,
+        50,
+      )
+      .await?;
+    assert_eq!(quote.amount_out, single.amount_out);
+    assert_eq!(quote.fee_amount, single.fee_amount);
+  }
+  Ok(())
+}
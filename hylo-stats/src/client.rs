@@ -1,4 +1,4 @@
-//! Read-only fetch layer for earn pool yield statistics.
+//! Read-only fetch layer for earn pool and levercoin statistics.
 
 use std::sync::Arc;
 
@@ -10,29 +10,163 @@ use anchor_lang::AccountDeserialize;
 use anchor_spl::token::{Mint, TokenAccount};
 use anyhow::Result;
 use fix::prelude::*;
-use hylo_core::exchange_context::{ExchangeContext, ExoExchangeContext};
-use hylo_core::idl::exchange::accounts::{ExoPair, Hylo, LstHeader};
+use hylo_core::exchange_context::{
+  ExchangeContext, ExoExchangeContext, LstExchangeContext,
+};
+use hylo_core::idl::earn_pool::accounts::PoolConfig;
+use hylo_core::idl::exchange::accounts::{ExoPair, Hylo, LstHeader, UsdcPair};
 use hylo_core::lst::sol_price::LstSolPrice;
 use hylo_core::lst::stake_pool::SplStakePool;
-use hylo_core::pyth::{query_pyth_oracle, OracleConfig};
+use hylo_core::lst::total_sol_cache::TotalSolCache;
+use hylo_core::pyth::{query_pyth_oracle, OracleConfig, PythOracle};
 use hylo_core::rebalance::pool_drawdown::PoolDrawdown;
-use hylo_core::util::normalize_mint_exp;
-use hylo_idl::pda;
-use hylo_idl::tokens::{StakePool, TokenMint, CBBTC, HYLOSOL, JITOSOL, SHYUSD};
+use hylo_idl::tokens::{
+  Exo, StakePool, TokenMint, CBBTC, HYLOSOL, HYPE, HYUSD, JITOSOL, ONYC, PST,
+  SHYUSD, USDC, WETH, XBTC, XETH, XHYPE, XONYC, XPST, XSOL, XZEC, ZEC,
+};
+use hylo_idl::{pda, with_exo_pairs};
 use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
 
 use crate::earn_pool_stats::compute_stats;
 use crate::earn_pool_yield_math::lst_epoch_growth;
+use crate::epochs_per_year::{
+  estimate, EpochBoundaryCache, EpochsPerYear, EpochsPerYearEstimator,
+};
 use crate::error::StatsError::{
-  AccountCountMismatch, ClockDeserialize, LstVaultValueOverflow,
-  MissingAccounts, NoBlockAtOrAfterSlot, NoPreviousEpoch,
-  NonPositiveEpochDuration, PoolDrawdownOverflow,
+  AccountCountMismatch, ClockDeserialize, ExoVaultOverflow,
+  LstVaultValueOverflow, MissingAccounts, NoBlockAtOrAfterSlot,
+  NoPreviousEpoch, PoolDrawdownOverflow, UnsupportedExoPair,
+};
+use crate::levercoin_stats::{exo_levercoin_stats, lst_levercoin_stats};
+use crate::position::source::PositionSource;
+use crate::position::{PositionEvent, PositionLedger, UserPosition};
+use crate::protocol_overview::{
+  PairOverview, ProtocolOverview, ProtocolOverviewBuilder,
 };
-use crate::types::{EarnPoolStats, ExoSnapshot, LstPosition, StatsInputs};
+use crate::source::StatsSource;
+use crate::types::{
+  EarnPoolStats, ExoSnapshot, LevercoinStats, LstPosition, StatsInputs,
+};
+
+macro_rules! exo_pair_table {
+  ($(($exo:ident, $lever:ident, $exp:ty)),* $(,)?) => {
+    /// Collateral mints of every known exo pair, in
+    /// [`StatsAccounts::KEYS`] order. Pairs not yet deployed are skipped
+    /// when fetched.
+    pub const EXO_PAIR_MINTS: &[Pubkey] = &[$(<$exo>::MINT),*];
+
+    /// Backing array for [`StatsAccounts::KEYS`]: LST, pool and
+    /// sHYUSD accounts, one [`ExoPairAccounts`] block per exo pair, then
+    /// the SOL/USD feed and clock.
+    const STATS_ACCOUNT_KEYS: [Pubkey; StatsAccounts::COUNT] = [
+      pda::HYLO,
+      pda::lst_header(JITOSOL::MINT),
+      pda::lst_header(HYLOSOL::MINT),
+      pda::lst_vault(JITOSOL::MINT),
+      pda::lst_vault(HYLOSOL::MINT),
+      JITOSOL::POOL_STATE,
+      HYLOSOL::POOL_STATE,
+      pda::HYUSD_POOL,
+      SHYUSD::MINT,
+      XSOL::MINT,
+      $(
+        pda::exo_pair(<$exo>::MINT),
+        pda::exo_vault(<$exo>::MINT),
+        pda::exo_levercoin_mint(<$exo>::MINT),
+        <$exo as PythOracle>::FEED.address,
+      )*
+      pda::SOL_USD_PYTH_FEED,
+      sysvar::clock::ID,
+    ];
+
+    /// Snapshots one exo pair's borrow-rate stream, dispatching on its
+    /// collateral mint for the vault precision.
+    fn exo_snapshot(
+      clock: &Clock,
+      pair: &ExoPairAccounts,
+    ) -> Result<ExoSnapshot> {
+      match pair.collateral_mint {
+        $(<$exo>::MINT => typed_exo_snapshot::<$exo>(clock, pair),)*
+        mint => Err(UnsupportedExoPair(mint).into()),
+      }
+    }
+
+    /// Levercoin stats for one exo pair, dispatching on its collateral
+    /// mint for the vault precision and levercoin.
+    fn exo_pair_levercoin_stats(
+      clock: &Clock,
+      pair: &ExoPairAccounts,
+      epochs_per_year: f64,
+    ) -> Result<LevercoinStats> {
+      match pair.collateral_mint {
+        $(<$exo>::MINT => exo_levercoin_stats(
+          &exo_exchange_context::<$exo>(clock, pair)?,
+          <$exo>::MINT,
+          <$lever>::MINT,
+          &pair.exo_pair.borrow_rate_config.into(),
+          epochs_per_year,
+        ),)*
+        mint => Err(UnsupportedExoPair(mint).into()),
+      }
+    }
+
+    /// Overview of one exo pair, dispatching on its collateral mint for
+    /// the vault precision and levercoin.
+    fn exo_pair_overview(
+      clock: &Clock,
+      pair: &ExoPairAccounts,
+    ) -> Result<PairOverview> {
+      match pair.collateral_mint {
+        $(<$exo>::MINT => PairOverview::from_context(
+          &exo_exchange_context::<$exo>(clock, pair)?,
+          Some(<$exo>::MINT),
+          <$lever>::MINT,
+          &pair.exo_pair.pool_drawdown.into(),
+        ),)*
+        mint => Err(UnsupportedExoPair(mint).into()),
+      }
+    }
+  };
+}
 
-/// Seconds in a Julian year.
-const SECONDS_PER_YEAR: f64 = 31_557_600.0;
+with_exo_pairs!(exo_pair_table);
+
+/// Deserialized accounts for one exo pair's borrow-rate stream.
+#[derive(Clone)]
+pub struct ExoPairAccounts {
+  pub collateral_mint: Pubkey,
+  pub exo_pair: ExoPair,
+  pub vault: TokenAccount,
+  pub levercoin_mint: Mint,
+  pub collateral_usd: PriceUpdateV2,
+}
+
+impl ExoPairAccounts {
+  /// Accounts fetched per exo pair: pair, vault, levercoin mint and
+  /// collateral/USD feed.
+  pub const COUNT: usize = 4;
+
+  /// Deserializes one pair's block of [`ExoPairAccounts::COUNT`]
+  /// accounts.
+  ///
+  /// # Errors
+  /// * Deserialization failure
+  fn from_fetched(
+    collateral_mint: Pubkey,
+    accounts: &[Account],
+  ) -> Result<ExoPairAccounts> {
+    Ok(ExoPairAccounts {
+      collateral_mint,
+      exo_pair: ExoPair::try_deserialize(&mut accounts[0].data.as_slice())?,
+      vault: TokenAccount::try_deserialize(&mut accounts[1].data.as_slice())?,
+      levercoin_mint: Mint::try_deserialize(&mut accounts[2].data.as_slice())?,
+      collateral_usd: PriceUpdateV2::try_deserialize(
+        &mut accounts[3].data.as_slice(),
+      )?,
+    })
+  }
+}
 
 /// Deserialized onchain accounts backing one stats fetch, in
 /// [`StatsAccounts::KEYS`] order.
@@ -47,48 +181,46 @@ pub struct StatsAccounts {
   pub hylosol_pool_state: SplStakePool,
   pub hyusd_pool: TokenAccount,
   pub shyusd_mint: Mint,
-  pub exo_pair: ExoPair,
-  pub exo_collateral_mint: Mint,
-  pub exo_vault: TokenAccount,
-  pub exo_levercoin_mint: Mint,
-  pub btc_usd: PriceUpdateV2,
+  pub xsol_mint: Mint,
+  /// One entry per deployed [`EXO_PAIR_MINTS`] pair, in the same order
+  pub exo_pairs: Vec<ExoPairAccounts>,
   pub sol_usd: PriceUpdateV2,
   pub clock: Clock,
 }
 
 impl StatsAccounts {
-  /// Number of accounts fetched for [`EarnPoolStats`].
-  pub const COUNT: usize = 16;
+  /// Accounts ahead of the exo pair blocks.
+  const PREFIX: usize = 10;
+
+  /// Number of accounts fetched for [`EarnPoolStats`] and
+  /// [`LevercoinStats`].
+  pub const COUNT: usize =
+    StatsAccounts::PREFIX + EXO_PAIR_MINTS.len() * ExoPairAccounts::COUNT + 2;
 
-  /// Account keys required for [`EarnPoolStats`], in fetch order —
+  /// Account keys required for [`EarnPoolStats`] and [`LevercoinStats`],
+  /// in fetch order —
   /// the same order [`StatsAccounts::from_fetched`] deserializes.
-  pub const KEYS: [Pubkey; StatsAccounts::COUNT] = [
-    pda::HYLO,
-    pda::lst_header(JITOSOL::MINT),
-    pda::lst_header(HYLOSOL::MINT),
-    pda::lst_vault(JITOSOL::MINT),
-    pda::lst_vault(HYLOSOL::MINT),
-    JITOSOL::POOL_STATE,
-    HYLOSOL::POOL_STATE,
-    pda::HYUSD_POOL,
-    SHYUSD::MINT,
-    pda::exo_pair(CBBTC::MINT),
-    CBBTC::MINT,
-    pda::exo_vault(CBBTC::MINT),
-    pda::exo_levercoin_mint(CBBTC::MINT),
-    pda::BTC_USD_PYTH_FEED,
-    pda::SOL_USD_PYTH_FEED,
-    sysvar::clock::ID,
-  ];
+  pub const KEYS: [Pubkey; StatsAccounts::COUNT] = STATS_ACCOUNT_KEYS;
 
   /// Deserializes a fetched account list, erroring with the keys of
-  /// any missing accounts.
+  /// any missing accounts. Exo pairs whose pair account is absent are
+  /// undeployed and skipped.
   ///
   /// # Errors
   /// * Missing account, count mismatch, or deserialization failure
   pub fn from_fetched(fetched: Vec<Option<Account>>) -> Result<StatsAccounts> {
     StatsAccounts::validate(&fetched)?;
+    let exo_pairs = fetched[StatsAccounts::PREFIX..StatsAccounts::COUNT - 2]
+      .chunks_exact(ExoPairAccounts::COUNT)
+      .zip(EXO_PAIR_MINTS)
+      .filter(|(block, _)| block[0].is_some())
+      .map(|(block, mint)| {
+        let block = block.iter().flatten().cloned().collect::<Vec<_>>();
+        ExoPairAccounts::from_fetched(*mint, &block)
+      })
+      .collect::<Result<Vec<_>>>()?;
     let accounts = fetched.into_iter().flatten().collect::<Vec<Account>>();
+    let suffix = accounts.len() - 2;
     Ok(StatsAccounts {
       hylo: Hylo::try_deserialize(&mut accounts[0].data.as_slice())?,
       jitosol_header: LstHeader::try_deserialize(
@@ -109,35 +241,36 @@ impl StatsAccounts {
         &mut accounts[7].data.as_slice(),
       )?,
       shyusd_mint: Mint::try_deserialize(&mut accounts[8].data.as_slice())?,
-      exo_pair: ExoPair::try_deserialize(&mut accounts[9].data.as_slice())?,
-      exo_collateral_mint: Mint::try_deserialize(
-        &mut accounts[10].data.as_slice(),
-      )?,
-      exo_vault: TokenAccount::try_deserialize(
-        &mut accounts[11].data.as_slice(),
-      )?,
-      exo_levercoin_mint: Mint::try_deserialize(
-        &mut accounts[12].data.as_slice(),
-      )?,
-      btc_usd: PriceUpdateV2::try_deserialize(
-        &mut accounts[13].data.as_slice(),
-      )?,
+      xsol_mint: Mint::try_deserialize(&mut accounts[9].data.as_slice())?,
+      exo_pairs,
       sol_usd: PriceUpdateV2::try_deserialize(
-        &mut accounts[14].data.as_slice(),
+        &mut accounts[suffix].data.as_slice(),
       )?,
-      clock: bincode::deserialize(&accounts[15].data)
+      clock: bincode::deserialize(&accounts[suffix + 1].data)
         .map_err(ClockDeserialize)?,
     })
   }
 
+  /// Whether the account at `index` belongs to an exo pair whose pair
+  /// account was not fetched.
+  fn in_undeployed_pair(fetched: &[Option<Account>], index: usize) -> bool {
+    (StatsAccounts::PREFIX..StatsAccounts::COUNT - 2).contains(&index) && {
+      let block = (index - StatsAccounts::PREFIX) / ExoPairAccounts::COUNT;
+      fetched[StatsAccounts::PREFIX + block * ExoPairAccounts::COUNT].is_none()
+    }
+  }
+
   /// Checks the fetched list has [`StatsAccounts::COUNT`] entries and
-  /// no missing accounts.
+  /// no missing accounts outside undeployed exo pairs.
   fn validate(fetched: &[Option<Account>]) -> Result<()> {
     let missing = StatsAccounts::KEYS
       .iter()
       .zip(fetched)
-      .filter(|(_, account)| account.is_none())
-      .map(|(key, _)| *key)
+      .enumerate()
+      .filter(|(index, (_, account))| {
+        account.is_none() && !StatsAccounts::in_undeployed_pair(fetched, *index)
+      })
+      .map(|(_, (key, _))| *key)
       .collect::<Vec<Pubkey>>();
     if fetched.len() != StatsAccounts::COUNT {
       Err(
@@ -155,116 +288,368 @@ impl StatsAccounts {
   }
 }
 
+/// Deserialized accounts only [`StatsClient::protocol_overview`] reads,
+/// in [`OverviewAccounts::KEYS`] order.
+#[derive(Clone)]
+pub struct OverviewAccounts {
+  pub hyusd_mint: Mint,
+  pub usdc_pair: UsdcPair,
+  pub usdc_vault: TokenAccount,
+  pub usdc_usd: PriceUpdateV2,
+}
+
+impl OverviewAccounts {
+  /// Account keys fetched after [`StatsAccounts::KEYS`] for a
+  /// [`ProtocolOverview`]: hyUSD mint, USDC pair, its vault and the
+  /// USDC/USD feed.
+  pub const KEYS: [Pubkey; 4] = [
+    HYUSD::MINT,
+    pda::USDC_PAIR,
+    pda::usdc_vault(USDC::MINT),
+    pda::USDC_USD_PYTH_FEED,
+  ];
+
+  /// Deserializes accounts fetched for [`OverviewAccounts::KEYS`].
+  ///
+  /// # Errors
+  /// * Missing account, count mismatch, or deserialization failure
+  pub fn from_fetched(
+    fetched: Vec<Option<Account>>,
+  ) -> Result<OverviewAccounts> {
+    let fetched: [Option<Account>; 4] =
+      fetched
+        .try_into()
+        .map_err(|fetched: Vec<_>| AccountCountMismatch {
+          expected: OverviewAccounts::KEYS.len(),
+          actual: fetched.len(),
+        })?;
+    let missing = OverviewAccounts::KEYS
+      .iter()
+      .zip(&fetched)
+      .filter(|(_, account)| account.is_none())
+      .map(|(key, _)| *key)
+      .collect::<Vec<Pubkey>>();
+    let [Some(hyusd_mint), Some(usdc_pair), Some(usdc_vault), Some(usdc_usd)] =
+      fetched
+    else {
+      return Err(MissingAccounts(missing).into());
+    };
+    Ok(OverviewAccounts {
+      hyusd_mint: Mint::try_deserialize(&mut hyusd_mint.data.as_slice())?,
+      usdc_pair: UsdcPair::try_deserialize(&mut usdc_pair.data.as_slice())?,
+      usdc_vault: TokenAccount::try_deserialize(
+        &mut usdc_vault.data.as_slice(),
+      )?,
+      usdc_usd: PriceUpdateV2::try_deserialize(&mut usdc_usd.data.as_slice())?,
+    })
+  }
+}
+
 /// Read-only client for earn pool yield statistics. Needs no keypair
-/// or program client.
+/// or program client; reads through any [`StatsSource`], RPC by
+/// default. Clones share one [`EpochBoundaryCache`].
 #[derive(Clone)]
-pub struct StatsClient {
-  rpc: Arc<RpcClient>,
+pub struct StatsClient<S = Arc<RpcClient>> {
+  source: S,
+  estimator: EpochsPerYearEstimator,
+  boundaries: EpochBoundaryCache,
 }
 
-impl StatsClient {
+impl<S: StatsSource> StatsClient<S> {
   #[must_use]
-  pub fn new(rpc: Arc<RpcClient>) -> StatsClient {
-    StatsClient { rpc }
+  pub fn new(source: S) -> StatsClient<S> {
+    StatsClient {
+      source,
+      estimator: EpochsPerYearEstimator::default(),
+      boundaries: EpochBoundaryCache::default(),
+    }
+  }
+
+  /// Sets how [`StatsClient::estimate_epochs_per_year`] annualizes.
+  #[must_use]
+  pub fn with_estimator(
+    self,
+    estimator: EpochsPerYearEstimator,
+  ) -> StatsClient<S> {
+    StatsClient { estimator, ..self }
+  }
+
+  /// Fetches and deserializes [`StatsAccounts::KEYS`] in one call.
+  ///
+  /// # Errors
+  /// * Source read failure
+  /// * Missing account or deserialization failure
+  pub async fn stats_accounts(&self) -> Result<StatsAccounts> {
+    let fetched = self
+      .source
+      .get_multiple_accounts(&StatsAccounts::KEYS)
+      .await?;
+    StatsAccounts::from_fetched(fetched)
   }
 
   /// Fetches [`EarnPoolStats`] from current onchain state: one
-  /// slot-consistent `get_multiple_accounts` call, plus an
-  /// epoch-schedule fetch and two epoch-boundary block-time lookups to
-  /// measure the last completed epoch's duration.
+  /// slot-consistent `get_multiple_accounts` call, plus the epoch
+  /// boundary block times the estimator needs that aren't cached.
   ///
   /// # Errors
-  /// * RPC fetch, deserialization, or oracle validation failure
+  /// * Source read, deserialization, or oracle validation failure
   /// * Epoch duration measurement failure
   /// * Arithmetic overflow in yield math
   pub async fn earn_pool_stats(&self) -> Result<EarnPoolStats> {
-    let fetched = self.rpc.get_multiple_accounts(&StatsAccounts::KEYS).await?;
-    let accounts = StatsAccounts::from_fetched(fetched)?;
+    let accounts = self.stats_accounts().await?;
     let epochs_per_year =
-      self.measure_epochs_per_year(accounts.clock.epoch).await?;
+      self.estimate_epochs_per_year(accounts.clock.epoch).await?;
     compute_stats(&build_stats_inputs(&accounts, epochs_per_year)?)
   }
 
-  /// Measures the last completed epoch's exact wall-clock duration
-  /// from block times at the epoch boundary slots, returning epochs
-  /// per year.
+  /// Fetches [`LevercoinStats`] for xSOL followed by every deployed exo
+  /// levercoin in [`EXO_PAIR_MINTS`] order, from the same account set
+  /// as [`StatsClient::earn_pool_stats`].
+  ///
+  /// # Errors
+  /// * Source read, deserialization, or oracle validation failure
+  /// * Epoch duration measurement failure
+  /// * Arithmetic overflow in levercoin math
+  pub async fn levercoin_stats(&self) -> Result<Vec<LevercoinStats>> {
+    let accounts = self.stats_accounts().await?;
+    let epochs_per_year =
+      self.estimate_epochs_per_year(accounts.clock.epoch).await?;
+    build_levercoin_stats(&accounts, epochs_per_year.value)
+  }
+
+  /// Fetches a [`ProtocolOverview`] from one slot-consistent
+  /// `get_multiple_accounts` call over [`StatsAccounts::KEYS`] and
+  /// [`OverviewAccounts::KEYS`].
+  ///
+  /// # Errors
+  /// * Source read, deserialization, or oracle validation failure
+  /// * Arithmetic overflow
+  pub async fn protocol_overview(&self) -> Result<ProtocolOverview> {
+    let keys = StatsAccounts::KEYS
+      .iter()
+      .chain(&OverviewAccounts::KEYS)
+      .copied()
+      .collect::<Vec<_>>();
+    let mut fetched = self.source.get_multiple_accounts(&keys).await?;
+    if fetched.len() != keys.len() {
+      return Err(
+        AccountCountMismatch {
+          expected: keys.len(),
+          actual: fetched.len(),
+        }
+        .into(),
+      );
+    }
+    let overview = fetched.split_off(StatsAccounts::COUNT);
+    build_protocol_overview(
+      &StatsAccounts::from_fetched(fetched)?,
+      &OverviewAccounts::from_fetched(overview)?,
+    )
+  }
+
+  /// Fetches `wallet`'s sHYUSD position: its balance and the pool from
+  /// one `get_multiple_accounts` call, its flows from `history`.
   ///
   /// # Errors
-  /// * RPC failure, missing boundary blocks, or non-positive duration
-  #[allow(clippy::cast_precision_loss)]
+  /// * Source or history read failure
+  /// * Missing pool account or deserialization failure
+  /// * Arithmetic overflow replaying or valuing the position
+  pub async fn user_position(
+    &self,
+    wallet: Pubkey,
+    history: &impl PositionSource,
+  ) -> Result<UserPosition> {
+    let fetched = self
+      .source
+      .get_multiple_accounts(&user_position_keys(wallet))
+      .await?;
+    let events = history.position_events(wallet).await?;
+    build_user_position(wallet, fetched, &events)
+  }
+
+  /// Epochs per year from the last completed epoch's wall-clock
+  /// duration alone.
+  ///
+  /// # Errors
+  /// * Source read failure, missing boundary blocks, or non-positive
+  ///   duration
   pub async fn measure_epochs_per_year(
     &self,
     current_epoch: u64,
   ) -> Result<f64> {
-    let prev_epoch = current_epoch.checked_sub(1).ok_or(NoPreviousEpoch)?;
-    let schedule = self.rpc.get_epoch_schedule().await?;
-    let start_prev = schedule.get_first_slot_in_epoch(prev_epoch);
-    let start_curr = schedule.get_first_slot_in_epoch(current_epoch);
-    let t0 = self.block_time_at_or_after(start_prev).await?;
-    let t1 = self.block_time_at_or_after(start_curr).await?;
-    let duration = t1
-      .checked_sub(t0)
-      .filter(|d| *d > 0)
-      .ok_or(NonPositiveEpochDuration)?;
-    Ok(SECONDS_PER_YEAR / duration as f64)
+    let last = self
+      .estimate_with(EpochsPerYearEstimator::LastEpoch, current_epoch)
+      .await?;
+    Ok(last.value)
+  }
+
+  /// Epochs per year under the client's estimator, measured over the
+  /// completed epochs before `current_epoch`. Fewer epochs are used
+  /// when the chain is younger than the estimator's window.
+  ///
+  /// # Errors
+  /// * Source read failure, missing boundary blocks, or non-positive
+  ///   duration
+  pub async fn estimate_epochs_per_year(
+    &self,
+    current_epoch: u64,
+  ) -> Result<EpochsPerYear> {
+    self.estimate_with(self.estimator, current_epoch).await
+  }
+
+  async fn estimate_with(
+    &self,
+    estimator: EpochsPerYearEstimator,
+    current_epoch: u64,
+  ) -> Result<EpochsPerYear> {
+    if estimator.epochs() == 0 {
+      return estimate(estimator, &[]);
+    }
+    let first_epoch = current_epoch.saturating_sub(estimator.epochs());
+    if first_epoch == current_epoch {
+      return Err(NoPreviousEpoch.into());
+    }
+    let mut start_times = Vec::new();
+    for epoch in first_epoch..=current_epoch {
+      start_times.push(self.epoch_start_time(epoch).await?);
+    }
+    let durations = start_times
+      .windows(2)
+      .map(|pair| pair[1] - pair[0])
+      .collect::<Vec<_>>();
+    estimate(estimator, &durations)
+  }
+
+  /// Block time of an epoch's first block, cached once fetched.
+  async fn epoch_start_time(&self, epoch: u64) -> Result<i64> {
+    if let Some(start_time) = self.boundaries.start_time(epoch) {
+      return Ok(start_time);
+    }
+    let schedule = if let Some(schedule) = self.boundaries.schedule() {
+      schedule
+    } else {
+      let schedule = self.source.get_epoch_schedule().await?;
+      self.boundaries.set_schedule(schedule.clone());
+      schedule
+    };
+    let start_time = self
+      .block_time_at_or_after(schedule.get_first_slot_in_epoch(epoch))
+      .await?;
+    self.boundaries.insert_start_time(epoch, start_time);
+    Ok(start_time)
   }
 
   /// Block time of the first block at or after `slot` (epoch boundary
   /// slots can be skipped).
   async fn block_time_at_or_after(&self, slot: u64) -> Result<i64> {
-    let slots = self.rpc.get_blocks_with_limit(slot, 1).await?;
+    let slots = self.source.get_blocks_with_limit(slot, 1).await?;
     let first = slots.first().copied().ok_or(NoBlockAtOrAfterSlot(slot))?;
-    Ok(self.rpc.get_block_time(first).await?)
+    self.source.get_block_time(first).await
   }
 }
 
-/// Values an exo pair's levercoin market cap for the borrow-rate
-/// projection. Mirrors hylo-quotes `build_cbbtc_exchange_context`.
-fn exo_levercoin_market_cap(
+/// Loads an exo pair's exchange context from its fetched accounts.
+/// Mirrors hylo-quotes `build_exo_pair_state`.
+fn exo_exchange_context<E: Exo>(
   clock: &Clock,
-  exo_pair: &ExoPair,
-  collateral_mint: &Mint,
-  exo_vault: &TokenAccount,
-  levercoin_mint: &Mint,
-  collateral_usd: &PriceUpdateV2,
-) -> Result<UFix64<N9>> {
+  pair: &ExoPairAccounts,
+) -> Result<ExoExchangeContext<Clock>>
+where
+  UFix64<E::Exp>: FixExt,
+{
+  let exo_pair = &pair.exo_pair;
   let oracle_config = OracleConfig::new(
     exo_pair.oracle_interval_secs,
     exo_pair.oracle_conf_tolerance.try_into()?,
   );
-  let total_collateral = normalize_mint_exp(collateral_mint, exo_vault.amount)?;
-  let exo_context = ExoExchangeContext::load(
+  let total_collateral = UFix64::<E::Exp>::new(pair.vault.amount)
+    .checked_convert::<N9>()
+    .ok_or(ExoVaultOverflow(E::MINT))?;
+  Ok(ExoExchangeContext::load(
     clock.clone(),
     total_collateral,
     exo_pair.stablecoin_mint_threshold.try_into()?,
     oracle_config,
     exo_pair.levercoin_fees.into(),
-    collateral_usd,
+    &pair.collateral_usd,
     exo_pair.virtual_stablecoin.into(),
-    Some(levercoin_mint),
+    Some(&pair.levercoin_mint),
     exo_pair.sell_curve_config.into(),
     exo_pair.buy_curve_config.into(),
     exo_pair.levercoin_market_cap_limit.try_into()?,
-  )?;
-  let market_cap = exo_context.levercoin_market_cap()?;
-  Ok(market_cap)
+  )?)
+}
+
+/// Loads the LST exchange context backing xSOL.
+/// Mirrors hylo-quotes `build_lst_exchange_context`.
+fn lst_exchange_context(
+  accounts: &StatsAccounts,
+) -> Result<LstExchangeContext<Clock>> {
+  let hylo = &accounts.hylo;
+  let total_sol_cache: TotalSolCache = hylo.total_sol_cache.into();
+  let oracle_config = OracleConfig::new(
+    hylo.oracle_interval_secs,
+    hylo.oracle_conf_tolerance.try_into()?,
+  );
+  Ok(LstExchangeContext::load(
+    accounts.clock.clone(),
+    &total_sol_cache,
+    hylo.stablecoin_mint_threshold.try_into()?,
+    oracle_config,
+    hylo.levercoin_fees.into(),
+    &accounts.sol_usd,
+    hylo.virtual_stablecoin.into(),
+    Some(&accounts.xsol_mint),
+    hylo.lst_sell_curve_config.into(),
+    hylo.lst_buy_curve_config.into(),
+  )?)
 }
 
-/// Sums outstanding pool drawdown across the LST pair and cbBTC exo pair.
+fn typed_exo_snapshot<E: Exo>(
+  clock: &Clock,
+  pair: &ExoPairAccounts,
+) -> Result<ExoSnapshot>
+where
+  UFix64<E::Exp>: FixExt,
+{
+  Ok(ExoSnapshot {
+    collateral_mint: E::MINT,
+    harvest_cache: pair.exo_pair.borrow_rate_harvest_cache.into(),
+    borrow_rate_config: pair.exo_pair.borrow_rate_config.into(),
+    levercoin_market_cap: exo_exchange_context::<E>(clock, pair)?
+      .levercoin_market_cap()?,
+  })
+}
+
+/// Sums outstanding pool drawdown across the LST pair and every deployed
+/// exo pair.
 fn total_outstanding_drawdown(
   hylo: &Hylo,
-  exo_pair: &ExoPair,
+  exo_pairs: &[ExoPairAccounts],
 ) -> Result<UFix64<N6>> {
-  let hylo_drawdown: PoolDrawdown = hylo.pool_drawdown.into();
-  let exo_drawdown: PoolDrawdown = exo_pair.pool_drawdown.into();
-  Ok(
-    hylo_drawdown
-      .outstanding()?
-      .checked_add(&exo_drawdown.outstanding()?)
-      .ok_or(PoolDrawdownOverflow)?,
+  sum_outstanding(
+    std::iter::once(hylo.pool_drawdown.into()).chain(
+      exo_pairs
+        .iter()
+        .map(|pair| pair.exo_pair.pool_drawdown.into()),
+    ),
   )
 }
 
+fn sum_outstanding(
+  drawdowns: impl IntoIterator<Item = PoolDrawdown>,
+) -> Result<UFix64<N6>> {
+  drawdowns
+    .into_iter()
+    .try_fold(UFix64::zero(), |total, drawdown| {
+      Ok(
+        total
+          .checked_add(&drawdown.outstanding()?)
+          .ok_or(PoolDrawdownOverflow)?,
+      )
+    })
+}
+
 fn lst_position(
   header: &LstHeader,
   vault: &TokenAccount,
@@ -290,7 +675,7 @@ fn lst_position(
 /// * Arithmetic overflow
 pub fn build_stats_inputs(
   accounts: &StatsAccounts,
-  epochs_per_year: f64,
+  epochs_per_year: EpochsPerYear,
 ) -> Result<StatsInputs> {
   let oracle_config = OracleConfig::new(
     accounts.hylo.oracle_interval_secs,
@@ -299,16 +684,13 @@ pub fn build_stats_inputs(
   let sol_usd_spot =
     query_pyth_oracle(&accounts.clock, &accounts.sol_usd, oracle_config)?.spot;
 
-  let levercoin_market_cap = exo_levercoin_market_cap(
-    &accounts.clock,
-    &accounts.exo_pair,
-    &accounts.exo_collateral_mint,
-    &accounts.exo_vault,
-    &accounts.exo_levercoin_mint,
-    &accounts.btc_usd,
-  )?;
+  let exo_snapshots = accounts
+    .exo_pairs
+    .iter()
+    .map(|pair| exo_snapshot(&accounts.clock, pair))
+    .collect::<Result<Vec<_>>>()?;
   let outstanding_drawdown =
-    total_outstanding_drawdown(&accounts.hylo, &accounts.exo_pair)?;
+    total_outstanding_drawdown(&accounts.hylo, &accounts.exo_pairs)?;
 
   Ok(StatsInputs {
     current_epoch: accounts.clock.epoch,
@@ -328,29 +710,417 @@ pub fn build_stats_inputs(
         &accounts.hylosol_pool_state,
       )?,
     ],
-    exo_snapshots: vec![ExoSnapshot {
-      collateral_mint: CBBTC::MINT,
-      harvest_cache: accounts.exo_pair.borrow_rate_harvest_cache.into(),
-      borrow_rate_config: accounts.exo_pair.borrow_rate_config.into(),
-      levercoin_market_cap,
-    }],
+    exo_snapshots,
     sol_usd_spot,
     outstanding_drawdown,
     epochs_per_year,
   })
 }
 
+/// Builds [`LevercoinStats`] for xSOL and every deployed exo levercoin
+/// from deserialized accounts.
+///
+/// # Errors
+/// * Context load or oracle validation failure
+/// * Arithmetic overflow
+pub fn build_levercoin_stats(
+  accounts: &StatsAccounts,
+  epochs_per_year: f64,
+) -> Result<Vec<LevercoinStats>> {
+  let xsol = lst_levercoin_stats(&lst_exchange_context(accounts)?)?;
+  std::iter::once(Ok(xsol))
+    .chain(accounts.exo_pairs.iter().map(|pair| {
+      exo_pair_levercoin_stats(&accounts.clock, pair, epochs_per_year)
+    }))
+    .collect()
+}
+
+/// Builds a [`ProtocolOverview`] from deserialized accounts, valuing the
+/// USDC vault at the USDC/USD oracle spot.
+///
+/// # Errors
+/// * Context load or oracle validation failure
+/// * Arithmetic overflow
+pub fn build_protocol_overview(
+  accounts: &StatsAccounts,
+  overview: &OverviewAccounts,
+) -> Result<ProtocolOverview> {
+  let usdc_pair = &overview.usdc_pair;
+  let usdc_oracle_config = OracleConfig::new(
+    usdc_pair.oracle_interval_secs,
+    usdc_pair.oracle_conf_tolerance.try_into()?,
+  );
+  let usdc_usd_spot =
+    query_pyth_oracle(&accounts.clock, &overview.usdc_usd, usdc_oracle_config)?
+      .spot;
+  let lst = PairOverview::from_context(
+    &lst_exchange_context(accounts)?,
+    None,
+    XSOL::MINT,
+    &accounts.hylo.pool_drawdown.into(),
+  )?;
+  let builder = ProtocolOverviewBuilder::new(
+    accounts.clock.epoch,
+    accounts.clock.unix_timestamp,
+    UFix64::new(overview.hyusd_mint.supply),
+  )
+  .with_lst(lst)
+  .with_usdc(
+    UFix64::new(overview.usdc_vault.amount),
+    usdc_usd_spot,
+    &usdc_pair.virtual_stablecoin.into(),
+  )?;
+  accounts
+    .exo_pairs
+    .iter()
+    .try_fold(builder, |builder, pair| -> Result<_> {
+      Ok(builder.with_exo(exo_pair_overview(&accounts.clock, pair)?))
+    })?
+    .build()
+}
+
+/// Accounts read for [`StatsClient::user_position`]: the wallet's
+/// sHYUSD account, hyUSD pool, sHYUSD mint and pool config.
+#[must_use]
+pub const fn user_position_keys(wallet: Pubkey) -> [Pubkey; 4] {
+  [
+    pda::shyusd_ata(wallet),
+    pda::HYUSD_POOL,
+    SHYUSD::MINT,
+    pda::POOL_CONFIG,
+  ]
+}
+
+/// Values a wallet's position from accounts fetched for
+/// [`user_position_keys`] and its replayed `events`. A wallet with no
+/// sHYUSD account holds zero.
+///
+/// # Errors
+/// * Missing pool account, count mismatch, or deserialization failure
+/// * Arithmetic overflow replaying or valuing the position
+pub fn build_user_position(
+  wallet: Pubkey,
+  fetched: Vec<Option<Account>>,
+  events: &[PositionEvent],
+) -> Result<UserPosition> {
+  let keys = user_position_keys(wallet);
+  let fetched: [Option<Account>; 4] =
+    fetched
+      .try_into()
+      .map_err(|fetched: Vec<_>| AccountCountMismatch {
+        expected: keys.len(),
+        actual: fetched.len(),
+      })?;
+  let missing = keys[1..]
+    .iter()
+    .zip(&fetched[1..])
+    .filter(|(_, account)| account.is_none())
+    .map(|(key, _)| *key)
+    .collect::<Vec<Pubkey>>();
+  let [balance, Some(pool), Some(mint), Some(config)] = fetched else {
+    return Err(MissingAccounts(missing).into());
+  };
+  let shares = balance
+    .map(|account| TokenAccount::try_deserialize(&mut account.data.as_slice()))
+    .transpose()?
+    .map_or(UFix64::zero(), |account| UFix64::new(account.amount));
+  let pool = TokenAccount::try_deserialize(&mut pool.data.as_slice())?;
+  let mint = Mint::try_deserialize(&mut mint.data.as_slice())?;
+  let config = PoolConfig::try_deserialize(&mut config.data.as_slice())?;
+  UserPosition::value(
+    wallet,
+    &PositionLedger::replay(events)?,
+    shares,
+    UFix64::new(pool.amount),
+    UFix64::new(mint.supply),
+    config.withdrawal_fee.try_into()?,
+  )
+}
+
 #[cfg(test)]
 mod tests {
+  use std::collections::BTreeMap;
+  use std::str::FromStr;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  use anchor_client::solana_sdk::epoch_schedule::EpochSchedule;
+  use anchor_client::solana_sdk::sysvar;
+  use async_trait::async_trait;
+
   use super::*;
+  use crate::epochs_per_year::SECONDS_PER_YEAR;
+  use crate::error::StatsError;
+  use crate::source::MemorySource;
+
+  /// Counts block time reads through to a [`MemorySource`].
+  struct CountingSource {
+    inner: MemorySource,
+    block_time_reads: AtomicUsize,
+  }
+
+  #[async_trait]
+  impl StatsSource for CountingSource {
+    async fn get_multiple_accounts(
+      &self,
+      keys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>> {
+      self.inner.get_multiple_accounts(keys).await
+    }
+
+    async fn get_epoch_schedule(&self) -> Result<EpochSchedule> {
+      self.inner.get_epoch_schedule().await
+    }
+
+    async fn get_blocks_with_limit(
+      &self,
+      start_slot: u64,
+      limit: usize,
+    ) -> Result<Vec<u64>> {
+      self.inner.get_blocks_with_limit(start_slot, limit).await
+    }
+
+    async fn get_block_time(&self, slot: u64) -> Result<i64> {
+      self.block_time_reads.fetch_add(1, Ordering::SeqCst);
+      self.inner.get_block_time(slot).await
+    }
+  }
 
   #[test]
   fn stats_account_keys_order() {
     assert_eq!(StatsAccounts::KEYS[0], hylo_idl::pda::HYLO);
     assert_eq!(StatsAccounts::KEYS[7], hylo_idl::pda::HYUSD_POOL);
+    assert_eq!(StatsAccounts::KEYS[9], XSOL::MINT);
+    assert!(!StatsAccounts::KEYS.contains(&hylo_idl::pda::USDC_PAIR));
     assert_eq!(
       StatsAccounts::KEYS[StatsAccounts::COUNT - 1],
       anchor_lang::solana_program::sysvar::clock::ID
     );
   }
+
+  #[test]
+  fn stats_account_keys_cover_every_exo_pair() {
+    EXO_PAIR_MINTS.iter().enumerate().for_each(|(i, mint)| {
+      let block = StatsAccounts::PREFIX + i * ExoPairAccounts::COUNT;
+      assert_eq!(StatsAccounts::KEYS[block], hylo_idl::pda::exo_pair(*mint));
+      assert_eq!(
+        StatsAccounts::KEYS[block + 1],
+        hylo_idl::pda::exo_vault(*mint)
+      );
+    });
+    assert_eq!(
+      StatsAccounts::KEYS[StatsAccounts::PREFIX + 3],
+      hylo_idl::pda::BTC_USD_PYTH_FEED
+    );
+    assert_eq!(
+      StatsAccounts::KEYS[StatsAccounts::COUNT - 2],
+      hylo_idl::pda::SOL_USD_PYTH_FEED
+    );
+  }
+
+  /// Every account present except the given indices.
+  fn fetched_without(absent: &[usize]) -> Vec<Option<Account>> {
+    (0..StatsAccounts::COUNT)
+      .map(|i| (!absent.contains(&i)).then(Account::default))
+      .collect()
+  }
+
+  #[test]
+  fn undeployed_exo_pairs_are_optional() -> Result<()> {
+    let last = StatsAccounts::COUNT - 2 - ExoPairAccounts::COUNT;
+    let undeployed = (last..StatsAccounts::COUNT - 2).collect::<Vec<_>>();
+    StatsAccounts::validate(&fetched_without(&undeployed))?;
+    // Pair account alone missing still skips the pair
+    StatsAccounts::validate(&fetched_without(&[last]))
+  }
+
+  #[test]
+  fn deployed_exo_pair_needs_every_account() {
+    let vault = StatsAccounts::PREFIX + 1;
+    let err = StatsAccounts::validate(&fetched_without(&[vault]))
+      .expect_err("vault of a deployed pair is missing");
+    match err.downcast_ref::<StatsError>() {
+      Some(StatsError::MissingAccounts(missing)) => {
+        assert_eq!(missing, &[StatsAccounts::KEYS[vault]]);
+      }
+      other => panic!("unexpected error {other:?}"),
+    }
+  }
+
+  #[test]
+  fn overview_accounts_report_missing_keys() {
+    let err = OverviewAccounts::from_fetched(vec![
+      Some(Account::default()),
+      None,
+      Some(Account::default()),
+      None,
+    ])
+    .err()
+    .expect("USDC pair and feed are missing");
+    match err.downcast_ref::<StatsError>() {
+      Some(StatsError::MissingAccounts(missing)) => {
+        assert_eq!(missing, &[pda::USDC_PAIR, pda::USDC_USD_PYTH_FEED]);
+      }
+      other => panic!("unexpected error {other:?}"),
+    }
+    assert!(OverviewAccounts::from_fetched(Vec::new()).is_err());
+  }
+
+  #[test]
+  fn drawdown_sums_across_pairs() -> Result<()> {
+    let drawdowns = [1_500_000, 0, 250_000, 2_000_000]
+      .into_iter()
+      .map(|bits| {
+        let mut drawdown = PoolDrawdown::default();
+        drawdown.drawdown(UFix64::new(bits))?;
+        Ok(drawdown)
+      })
+      .collect::<Result<Vec<_>>>()?;
+    assert_eq!(sum_outstanding(drawdowns)?, UFix64::new(3_750_000));
+    assert_eq!(sum_outstanding([])?, UFix64::zero());
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn measures_epoch_from_boundary_blocks() -> Result<()> {
+    // Epoch 799's first slot was skipped; its first block is two later
+    let schedule = EpochSchedule::without_warmup();
+    let prev = schedule.get_first_slot_in_epoch(799);
+    let curr = schedule.get_first_slot_in_epoch(800);
+    let client = StatsClient::new(
+      MemorySource::default()
+        .with_epoch_schedule(schedule)
+        .with_block_time(prev + 2, 1_700_000_000)
+        .with_block_time(curr, 1_700_172_800),
+    );
+    let epochs_per_year = client.measure_epochs_per_year(800).await?;
+    assert!((epochs_per_year - 182.625).abs() < 1e-9);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn measure_needs_previous_epoch_and_boundary_blocks() {
+    let client = StatsClient::new(MemorySource::default());
+    assert!(client.measure_epochs_per_year(0).await.is_err());
+    assert!(client.measure_epochs_per_year(800).await.is_err());
+  }
+
+  #[tokio::test]
+  async fn missing_accounts_reported_by_key() {
+    let source =
+      MemorySource::default().with_account(pda::HYLO, Account::default());
+    let err = StatsClient::new(source)
+      .earn_pool_stats()
+      .await
+      .expect_err("only the Hylo account is present");
+    match err.downcast_ref::<StatsError>() {
+      Some(StatsError::MissingAccounts(missing)) => {
+        // Absent exo pairs read as undeployed, not missing
+        assert_eq!(missing.len(), StatsAccounts::PREFIX + 1);
+        assert!(!missing.contains(&pda::HYLO));
+        assert!(!missing.contains(&pda::exo_pair(CBBTC::MINT)));
+        // The USDC pair is only read for the overview
+        assert!(!missing.contains(&pda::USDC_PAIR));
+      }
+      other => panic!("unexpected error {other:?}"),
+    }
+  }
+
+  /// Mainnet accounts the stats fetch reads, copied from the hylo-quotes
+  /// protocol state snapshot, with the five epochs before the snapshot's
+  /// starting two days apart.
+  fn snapshot_source() -> Result<(MemorySource, Clock)> {
+    let path = format!(
+      "{}/tests/data/stats-accounts-1018-114971.json",
+      env!("CARGO_MANIFEST_DIR")
+    );
+    let accounts: BTreeMap<String, Account> =
+      serde_json::from_reader(std::fs::File::open(path)?)?;
+    let accounts = accounts
+      .into_iter()
+      .map(|(key, account)| Ok((Pubkey::from_str(&key)?, account)))
+      .collect::<Result<BTreeMap<_, _>>>()?;
+    let clock: Clock = bincode::deserialize(
+      &accounts
+        .get(&sysvar::clock::ID)
+        .ok_or_else(|| anyhow::anyhow!("Clock missing from fixture"))?
+        .data,
+    )?;
+    let schedule = EpochSchedule::without_warmup();
+    let source = accounts.into_iter().fold(
+      MemorySource::default().with_epoch_schedule(schedule.clone()),
+      |source, (key, account)| source.with_account(key, account),
+    );
+    let source = (clock.epoch - 5..=clock.epoch).zip(0..).fold(
+      source,
+      |source, (epoch, day)| {
+        source.with_block_time(
+          schedule.get_first_slot_in_epoch(epoch),
+          clock.unix_timestamp - 1_000_000 + day * 172_800,
+        )
+      },
+    );
+    Ok((source, clock))
+  }
+
+  #[tokio::test]
+  async fn earn_pool_stats_from_snapshot_accounts() -> Result<()> {
+    let (source, clock) = snapshot_source()?;
+    let client = StatsClient::new(source);
+    let stats = client.earn_pool_stats().await?;
+    assert_eq!(stats.current_epoch, clock.epoch);
+    assert_eq!(stats.epochs_per_year.samples, 5);
+    assert!((stats.epochs_per_year.value - 182.625).abs() < 1e-9);
+
+    // Every exo pair the snapshot holds, in EXO_PAIR_MINTS order
+    let pairs = stats
+      .exo_stats
+      .iter()
+      .map(|exo| exo.collateral_mint)
+      .collect::<Vec<_>>();
+    assert_eq!(pairs, [CBBTC::MINT, HYPE::MINT]);
+
+    let accounts = client.stats_accounts().await?;
+    assert_eq!(stats.pool_balance, UFix64::new(accounts.hyusd_pool.amount));
+    assert_eq!(
+      stats.shyusd_supply,
+      UFix64::new(accounts.shyusd_mint.supply)
+    );
+    assert!(stats.nav >= UFix64::one());
+    assert!(stats.naive_apy.is_finite() && stats.projected_apy.is_finite());
+    assert!(stats.projected_apy_band.low <= stats.projected_apy);
+    assert!(stats.projected_apy <= stats.projected_apy_band.high);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn trailing_estimate_caches_boundary_times() -> Result<()> {
+    // Epochs 795..=800 start two days apart, but 799 ran 2.5 days
+    let schedule = EpochSchedule::without_warmup();
+    let starts = [0, 172_800, 345_600, 518_400, 691_200, 907_200];
+    let inner = (795..=800).zip(starts).fold(
+      MemorySource::default(),
+      |source, (epoch, start)| {
+        source.with_block_time(
+          schedule.get_first_slot_in_epoch(epoch),
+          1_700_000_000 + start,
+        )
+      },
+    );
+    let source = Arc::new(CountingSource {
+      inner,
+      block_time_reads: AtomicUsize::new(0),
+    });
+    let client = StatsClient::new(source.clone());
+
+    let trailing = client.estimate_epochs_per_year(800).await?;
+    assert_eq!(trailing.samples, 5);
+    assert!((trailing.value - SECONDS_PER_YEAR / 181_440.0).abs() < 1e-9);
+    assert!(trailing.low < trailing.value && trailing.value < trailing.high);
+
+    let last = client.measure_epochs_per_year(800).await?;
+    assert!((last - 146.1).abs() < 1e-9);
+    // Both boundaries of the last epoch were already cached
+    assert_eq!(source.block_time_reads.load(Ordering::SeqCst), 6);
+    Ok(())
+  }
 }
@@ -10,12 +10,13 @@ use crate::earn_pool_yield_math::{
   apply_drawdown_offset, epoch_yield_rate, projected_borrow_inflow,
   projected_lst_inflow, EPOCHS_PER_YEAR,
 };
+use crate::epochs_per_year::EpochsPerYear;
 use crate::error::StatsError::{
   ProjectedExoInflowOverflow, ProjectedInflowOverflow,
   ProjectedLstInflowOverflow, RealizedYieldOverflow,
 };
 use crate::types::{
-  EarnPoolStats, ExoSnapshot, ExoStats, LstPosition, RealizedHarvest,
+  ApyBand, EarnPoolStats, ExoSnapshot, ExoStats, LstPosition, RealizedHarvest,
   StatsInputs,
 };
 
@@ -28,6 +29,18 @@ pub fn annualize_with(per_epoch_rate: UFix64<N9>, epochs_per_year: f64) -> f64 {
   (1.0 + rate).powf(epochs_per_year) - 1.0
 }
 
+/// [`annualize_with`] at each end of an [`EpochsPerYear`] band.
+#[must_use]
+pub fn annualize_band(
+  per_epoch_rate: UFix64<N9>,
+  epochs_per_year: &EpochsPerYear,
+) -> ApyBand {
+  ApyBand {
+    low: annualize_with(per_epoch_rate, epochs_per_year.low),
+    high: annualize_with(per_epoch_rate, epochs_per_year.high),
+  }
+}
+
 /// Compounded APY at the protocol's 182-epochs/year convention.
 #[allow(clippy::cast_precision_loss)]
 #[must_use]
@@ -166,12 +179,26 @@ pub fn compute_stats(inputs: &StatsInputs) -> Result<EarnPoolStats> {
     lst_harvest,
     exo_stats,
     last_epoch_yield_rate,
-    naive_apy: annualize_with(last_epoch_yield_rate, inputs.epochs_per_year),
+    naive_apy: annualize_with(
+      last_epoch_yield_rate,
+      inputs.epochs_per_year.value,
+    ),
+    naive_apy_band: annualize_band(
+      last_epoch_yield_rate,
+      &inputs.epochs_per_year,
+    ),
     projected_lst_inflow,
     projected_exo_inflow,
     outstanding_drawdown: inputs.outstanding_drawdown,
     projected_epoch_rate,
-    projected_apy: annualize_with(projected_epoch_rate, inputs.epochs_per_year),
+    projected_apy: annualize_with(
+      projected_epoch_rate,
+      inputs.epochs_per_year.value,
+    ),
+    projected_apy_band: annualize_band(
+      projected_epoch_rate,
+      &inputs.epochs_per_year,
+    ),
   })
 }
 
@@ -227,7 +254,7 @@ mod tests {
       )],
       sol_usd_spot: UFix64::<N9>::new(150_000_000_000),
       outstanding_drawdown: UFix64::zero(),
-      epochs_per_year: 182.0,
+      epochs_per_year: EpochsPerYear::fixed(182.0),
     }
   }
 
@@ -264,14 +291,31 @@ mod tests {
   #[test]
   fn compute_stats_uses_given_basis() -> Result<()> {
     let mut input = inputs();
-    input.epochs_per_year = 100.0;
+    input.epochs_per_year = EpochsPerYear::fixed(100.0);
     let stats = compute_stats(&input)?;
-    assert!((stats.epochs_per_year - 100.0).abs() < f64::EPSILON);
+    assert!((stats.epochs_per_year.value - 100.0).abs() < f64::EPSILON);
     let expected = annualize_with(stats.last_epoch_yield_rate, 100.0);
     assert!((stats.naive_apy - expected).abs() < f64::EPSILON);
     Ok(())
   }
 
+  #[test]
+  fn compute_stats_bands_follow_basis_band() -> Result<()> {
+    let mut input = inputs();
+    input.epochs_per_year = EpochsPerYear {
+      low: 170.0,
+      high: 190.0,
+      ..EpochsPerYear::fixed(182.0)
+    };
+    let stats = compute_stats(&input)?;
+    let naive = stats.naive_apy_band;
+    assert!(naive.low < stats.naive_apy && stats.naive_apy < naive.high);
+    let projected = stats.projected_apy_band;
+    assert!(projected.low < stats.projected_apy);
+    assert!(stats.projected_apy < projected.high);
+    Ok(())
+  }
+
   #[test]
   fn compute_stats_realized_sums_matching_epochs() -> Result<()> {
     let stats = compute_stats(&inputs())?;
@@ -0,0 +1,273 @@
+//! Epochs-per-year estimation from measured epoch durations, with a
+//! confidence band, and a cache of epoch start times so repeated
+//! estimates don't refetch boundary blocks.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use anchor_client::solana_sdk::epoch_schedule::EpochSchedule;
+use anyhow::Result;
+
+use crate::error::StatsError::{
+  InsufficientEpochDurations, InvalidEwmaDecay, NonPositiveEpochDuration,
+};
+
+/// Seconds in a Julian year.
+pub const SECONDS_PER_YEAR: f64 = 31_557_600.0;
+
+/// Two-sided 95% normal quantile for the confidence band.
+const BAND_Z: f64 = 1.96;
+
+/// How epoch durations are combined into an annualization basis.
+///
+/// * `Fixed` — a given basis, no measurement
+/// * `LastEpoch` — the last completed epoch alone
+/// * `TrailingMean` — mean duration of the last `epochs` completed epochs
+/// * `Ewma` — weighted mean of the last `epochs` completed epochs, the
+///   newest at weight 1 and each older one at `decay` times the next;
+///   `decay` must lie in `(0, 1]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EpochsPerYearEstimator {
+  Fixed(f64),
+  LastEpoch,
+  TrailingMean { epochs: u64 },
+  Ewma { epochs: u64, decay: f64 },
+}
+
+impl Default for EpochsPerYearEstimator {
+  fn default() -> EpochsPerYearEstimator {
+    EpochsPerYearEstimator::TrailingMean { epochs: 5 }
+  }
+}
+
+impl EpochsPerYearEstimator {
+  /// Completed epochs this estimator measures.
+  #[must_use]
+  pub fn epochs(&self) -> u64 {
+    match self {
+      EpochsPerYearEstimator::Fixed(_) => 0,
+      EpochsPerYearEstimator::LastEpoch => 1,
+      EpochsPerYearEstimator::TrailingMean { epochs }
+      | EpochsPerYearEstimator::Ewma { epochs, .. } => *epochs,
+    }
+  }
+
+  /// Weight of the duration `age` epochs before the newest.
+  fn weight(&self, age: usize) -> f64 {
+    match self {
+      EpochsPerYearEstimator::Ewma { decay, .. } => {
+        decay.powi(i32::try_from(age).unwrap_or(i32::MAX))
+      }
+      _ => 1.0,
+    }
+  }
+}
+
+/// Annualization basis with its confidence band.
+///
+/// * `value` — [`SECONDS_PER_YEAR`] over the estimated mean duration
+/// * `low`, `high` — basis at the mean duration plus and minus 1.96
+///   standard errors, clamped to the longest and shortest measured
+///   durations; both equal `value` below two samples
+/// * `samples` — epoch durations behind the estimate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EpochsPerYear {
+  pub estimator: EpochsPerYearEstimator,
+  pub value: f64,
+  pub low: f64,
+  pub high: f64,
+  pub samples: usize,
+}
+
+impl EpochsPerYear {
+  /// A fixed basis with no band.
+  #[must_use]
+  pub fn fixed(value: f64) -> EpochsPerYear {
+    EpochsPerYear {
+      estimator: EpochsPerYearEstimator::Fixed(value),
+      value,
+      low: value,
+      high: value,
+      samples: 0,
+    }
+  }
+}
+
+/// Estimates epochs per year from completed epoch `durations` in
+/// seconds, oldest first. Only the newest
+/// [`EpochsPerYearEstimator::epochs`] durations are used.
+///
+/// # Errors
+/// * No durations for a measuring estimator
+/// * Non-positive duration
+/// * EWMA decay outside `(0, 1]`
+#[allow(clippy::cast_precision_loss)]
+pub fn estimate(
+  estimator: EpochsPerYearEstimator,
+  durations: &[i64],
+) -> Result<EpochsPerYear> {
+  if let EpochsPerYearEstimator::Fixed(value) = estimator {
+    return Ok(EpochsPerYear::fixed(value));
+  }
+  if let EpochsPerYearEstimator::Ewma { decay, .. } = estimator {
+    if !(decay > 0.0 && decay <= 1.0) {
+      return Err(InvalidEwmaDecay(decay).into());
+    }
+  }
+  let wanted = usize::try_from(estimator.epochs()).unwrap_or(usize::MAX);
+  let window = &durations[durations.len().saturating_sub(wanted)..];
+  if window.is_empty() {
+    return Err(InsufficientEpochDurations.into());
+  }
+  if window.iter().any(|duration| *duration <= 0) {
+    return Err(NonPositiveEpochDuration.into());
+  }
+  let weighted = window
+    .iter()
+    .rev()
+    .enumerate()
+    .map(|(age, duration)| (estimator.weight(age), *duration as f64))
+    .collect::<Vec<_>>();
+  let total_weight = weighted.iter().map(|(w, _)| w).sum::<f64>();
+  let mean = weighted.iter().map(|(w, d)| w * d).sum::<f64>() / total_weight;
+  let std_error = weighted_std_error(&weighted, mean, total_weight);
+  let (shortest, longest) = weighted
+    .iter()
+    .fold((f64::MAX, f64::MIN), |(lo, hi), (_, d)| {
+      (lo.min(*d), hi.max(*d))
+    });
+  let basis = |duration: f64| SECONDS_PER_YEAR / duration;
+  Ok(EpochsPerYear {
+    estimator,
+    value: basis(mean),
+    low: basis((mean + BAND_Z * std_error).min(longest)),
+    high: basis((mean - BAND_Z * std_error).max(shortest)),
+    samples: window.len(),
+  })
+}
+
+/// Standard error of a weighted mean, using the effective sample size
+/// `(Σw)² / Σw²`. Zero below two effective samples.
+fn weighted_std_error(
+  weighted: &[(f64, f64)],
+  mean: f64,
+  total_weight: f64,
+) -> f64 {
+  let sum_sq_weights = weighted.iter().map(|(w, _)| w * w).sum::<f64>();
+  let effective = total_weight * total_weight / sum_sq_weights;
+  if effective <= 1.0 {
+    0.0
+  } else {
+    let variance = weighted
+      .iter()
+      .map(|(w, d)| w * (d - mean).powi(2))
+      .sum::<f64>()
+      / total_weight
+      * effective
+      / (effective - 1.0);
+    (variance / effective).sqrt()
+  }
+}
+
+/// Epoch schedule and epoch start block times, shared by clones of a
+/// client. A poisoned lock reads as a miss.
+#[derive(Debug, Clone, Default)]
+pub struct EpochBoundaryCache {
+  schedule: Arc<OnceLock<EpochSchedule>>,
+  start_times: Arc<Mutex<BTreeMap<u64, i64>>>,
+}
+
+impl EpochBoundaryCache {
+  #[must_use]
+  pub fn schedule(&self) -> Option<EpochSchedule> {
+    self.schedule.get().cloned()
+  }
+
+  pub fn set_schedule(&self, schedule: EpochSchedule) {
+    let _ = self.schedule.set(schedule);
+  }
+
+  #[must_use]
+  pub fn start_time(&self, epoch: u64) -> Option<i64> {
+    self.start_times.lock().ok()?.get(&epoch).copied()
+  }
+
+  pub fn insert_start_time(&self, epoch: u64, unix_timestamp: i64) {
+    if let Ok(mut start_times) = self.start_times.lock() {
+      start_times.insert(epoch, unix_timestamp);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // Four two-day epochs, then one slowed to 2.5 days
+  const DURATIONS: [i64; 5] = [172_800, 172_800, 172_800, 172_800, 216_000];
+
+  #[test]
+  fn trailing_mean_damps_a_slow_epoch() -> Result<()> {
+    let last = estimate(EpochsPerYearEstimator::LastEpoch, &DURATIONS)?;
+    let mean = estimate(EpochsPerYearEstimator::default(), &DURATIONS)?;
+    let ewma = estimate(
+      EpochsPerYearEstimator::Ewma {
+        epochs: 5,
+        decay: 0.5,
+      },
+      &DURATIONS,
+    )?;
+    assert!((last.value - 146.1).abs() < 1e-9);
+    // Mean duration 181,440s
+    assert!((mean.value - 31_557_600.0 / 181_440.0).abs() < 1e-9);
+    assert!(last.value < ewma.value && ewma.value < mean.value);
+    assert_eq!(mean.samples, 5);
+    Ok(())
+  }
+
+  #[test]
+  fn band_brackets_value_and_collapses_on_one_sample() -> Result<()> {
+    let mean = estimate(EpochsPerYearEstimator::default(), &DURATIONS)?;
+    assert!(mean.low < mean.value && mean.value < mean.high);
+    let last = estimate(EpochsPerYearEstimator::LastEpoch, &DURATIONS)?;
+    assert!((last.high - last.low).abs() < f64::EPSILON);
+    let steady = estimate(EpochsPerYearEstimator::default(), &[172_800; 5])?;
+    assert!((steady.high - steady.low).abs() < 1e-9);
+    Ok(())
+  }
+
+  #[test]
+  fn fixed_needs_no_durations_and_others_do() -> Result<()> {
+    let fixed = estimate(EpochsPerYearEstimator::Fixed(182.0), &[])?;
+    assert_eq!(fixed, EpochsPerYear::fixed(182.0));
+    assert!(estimate(EpochsPerYearEstimator::LastEpoch, &[]).is_err());
+    assert!(estimate(EpochsPerYearEstimator::LastEpoch, &[0]).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn ewma_rejects_decay_outside_unit_interval() -> Result<()> {
+    for decay in [0.0, -0.5, 1.5, f64::NAN] {
+      let ewma = EpochsPerYearEstimator::Ewma { epochs: 5, decay };
+      assert!(estimate(ewma, &DURATIONS).is_err(), "decay {decay}");
+    }
+    let flat = EpochsPerYearEstimator::Ewma {
+      epochs: 5,
+      decay: 1.0,
+    };
+    let mean = estimate(EpochsPerYearEstimator::default(), &DURATIONS)?;
+    assert!((estimate(flat, &DURATIONS)?.value - mean.value).abs() < 1e-9);
+    Ok(())
+  }
+
+  #[test]
+  fn band_stays_within_measured_durations() -> Result<()> {
+    // Two far-apart epochs put 1.96 standard errors past the mean duration
+    let durations = [100_000, 432_000];
+    let mean = estimate(EpochsPerYearEstimator::default(), &durations)?;
+    assert!(mean.low < mean.value && mean.value < mean.high);
+    assert!((mean.high - SECONDS_PER_YEAR / 100_000.0).abs() < 1e-9);
+    assert!((mean.low - SECONDS_PER_YEAR / 432_000.0).abs() < 1e-9);
+    Ok(())
+  }
+}
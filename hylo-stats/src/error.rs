@@ -24,12 +24,40 @@ pub enum StatsError {
   PoolDrawdownOverflow,
   #[error("Overflow computing LST vault SOL value.")]
   LstVaultValueOverflow,
+  #[error("Exo vault balance for {0} overflows N9.")]
+  ExoVaultOverflow(Pubkey),
+  #[error("No exo pair for collateral mint {0}.")]
+  UnsupportedExoPair(Pubkey),
+  #[error("Overflow computing collateral price at Depeg.")]
+  DepegPriceOverflow,
+  #[error("Overflow applying borrow rate to levercoin market cap.")]
+  FundingCostOverflow,
+  #[error("Overflow summing protocol overview totals.")]
+  OverviewOverflow,
+  #[error("Protocol overview missing the {0} pair.")]
+  IncompleteOverview(&'static str),
+  #[error("Overflow summing historical pool flows.")]
+  HistoryFlowOverflow,
+  #[error("Not enough history for a {0}-epoch window.")]
+  InsufficientHistory(u64),
+  #[error("Zero sHYUSD NAV recorded at epoch {0}.")]
+  ZeroHistoryNav(u64),
+  #[error("Overflow replaying earn pool position.")]
+  PositionReplayOverflow,
+  #[error("Arithmetic error valuing earn pool position.")]
+  PositionValueOverflow,
   #[error("No previous epoch to measure.")]
   NoPreviousEpoch,
   #[error("Non-positive epoch duration.")]
   NonPositiveEpochDuration,
+  #[error("No completed epoch durations to estimate from.")]
+  InsufficientEpochDurations,
+  #[error("EWMA decay {0} is outside (0, 1].")]
+  InvalidEwmaDecay(f64),
   #[error("No block found at or after slot {0}.")]
   NoBlockAtOrAfterSlot(u64),
+  #[error("No block time recorded for slot {0}.")]
+  MissingBlockTime(u64),
   #[error("Missing stats accounts: {0:?}")]
   MissingAccounts(Vec<Pubkey>),
   #[error("Expected {expected} stats accounts, got {actual}.")]
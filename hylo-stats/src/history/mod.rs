@@ -0,0 +1,453 @@
+//! Historical sHYUSD yield: per-epoch NAV, harvested hyUSD per stream and
+//! absorbed losses, reconstructed from pool events or stored snapshots.
+//!
+//! - [`EpochRecord`] — one epoch's NAV and pool flows
+//! - [`PoolFlow`] — a NAV observation or flow decoded from a pool event
+//! - [`PoolEvent`] — a [`PoolFlow`] with the transaction that carried it
+//! - [`HistoryBuilder`] — folds flows and snapshots into epoch records
+//! - [`source`] — pluggable transaction sources for pool events
+//! - [`store`] — pluggable persistence for epoch records
+//! - [`trailing`] — trailing realized APY and volatility
+
+pub mod source;
+pub mod store;
+pub mod trailing;
+
+use std::collections::BTreeMap;
+
+use anchor_client::solana_sdk::epoch_schedule::EpochSchedule;
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use fix::prelude::*;
+use hylo_core::earn_pool_math::lp_token_nav;
+use hylo_idl::earn_pool::events::{
+  AbsorbLossEvent, UserDepositEvent, UserWithdrawEvent,
+};
+use hylo_idl::events::EventCpi;
+use hylo_idl::exchange::events::{HarvestBorrowRateEvent, HarvestYieldEvent};
+use hylo_idl::{earn_pool, exchange};
+
+use crate::error::StatsError::HistoryFlowOverflow;
+use crate::types::EarnPoolStats;
+
+/// One epoch of sHYUSD history.
+///
+/// * `nav` — hyUSD per sHYUSD at the last observation in the epoch
+/// * `lst_yield` — hyUSD deposited by the LST staking-yield stream
+/// * `borrow_rate_yield` — hyUSD deposited per exo borrow-rate stream, keyed
+///   by collateral mint
+/// * `loss_absorbed` — hyUSD burned from the pool to cover losses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpochRecord {
+  pub epoch: u64,
+  pub nav: UFix64<N6>,
+  pub lst_yield: UFix64<N6>,
+  pub borrow_rate_yield: BTreeMap<Pubkey, UFix64<N6>>,
+  pub loss_absorbed: UFix64<N6>,
+}
+
+impl EpochRecord {
+  /// Record with no flows, NAV derived from pool balance and supply.
+  ///
+  /// # Errors
+  /// * NAV arithmetic overflow
+  pub fn from_pool(
+    epoch: u64,
+    pool_balance: UFix64<N6>,
+    shyusd_supply: UFix64<N6>,
+  ) -> Result<EpochRecord> {
+    Ok(EpochRecord {
+      nav: lp_token_nav(pool_balance, shyusd_supply)?,
+      ..EpochRecord::empty(epoch)
+    })
+  }
+
+  fn empty(epoch: u64) -> EpochRecord {
+    EpochRecord {
+      epoch,
+      nav: UFix64::zero(),
+      lst_yield: UFix64::zero(),
+      borrow_rate_yield: BTreeMap::new(),
+      loss_absorbed: UFix64::zero(),
+    }
+  }
+
+  /// Snapshot of the current epoch from live stats. Only harvests made in
+  /// the current epoch count; losses are not visible from stats.
+  #[must_use]
+  pub fn from_stats(stats: &EarnPoolStats) -> EpochRecord {
+    let current = |epoch: u64, amount: UFix64<N6>| {
+      if epoch == stats.current_epoch {
+        amount
+      } else {
+        UFix64::zero()
+      }
+    };
+    EpochRecord {
+      epoch: stats.current_epoch,
+      nav: stats.nav,
+      lst_yield: current(
+        stats.lst_harvest.epoch,
+        stats.lst_harvest.hyusd_to_pool,
+      ),
+      borrow_rate_yield: stats
+        .exo_stats
+        .iter()
+        .map(|exo| {
+          let amount = current(exo.harvest.epoch, exo.harvest.hyusd_to_pool);
+          (exo.collateral_mint, amount)
+        })
+        .collect(),
+      loss_absorbed: UFix64::zero(),
+    }
+  }
+
+  /// hyUSD harvested into the pool across all streams.
+  ///
+  /// # Errors
+  /// * Arithmetic overflow
+  pub fn harvested(&self) -> Result<UFix64<N6>> {
+    Ok(
+      self
+        .borrow_rate_yield
+        .values()
+        .try_fold(self.lst_yield, |acc, amount| acc.checked_add(amount))
+        .ok_or(HistoryFlowOverflow)?,
+    )
+  }
+
+  /// Adds `flow` to this record; NAV observations replace the NAV.
+  fn apply(&mut self, flow: &PoolFlow) -> Result<()> {
+    match flow {
+      PoolFlow::Nav(nav) => self.nav = *nav,
+      PoolFlow::LstYield(amount) => {
+        self.lst_yield = add_flow(self.lst_yield, *amount)?;
+      }
+      PoolFlow::BorrowRate {
+        collateral_mint,
+        amount,
+      } => {
+        let entry = self
+          .borrow_rate_yield
+          .entry(*collateral_mint)
+          .or_insert(UFix64::zero());
+        *entry = add_flow(*entry, *amount)?;
+      }
+      PoolFlow::LossAbsorbed(amount) => {
+        self.loss_absorbed = add_flow(self.loss_absorbed, *amount)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+fn add_flow(acc: UFix64<N6>, amount: UFix64<N6>) -> Result<UFix64<N6>> {
+  Ok(acc.checked_add(&amount).ok_or(HistoryFlowOverflow)?)
+}
+
+/// NAV observation or pool flow carried by one protocol event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolFlow {
+  /// `lp_token_nav` quoted by a deposit, or implied by a withdrawal's
+  /// gross hyUSD per sHYUSD burned
+  Nav(UFix64<N6>),
+  /// `token_to_pool` from `harvest_yield`
+  LstYield(UFix64<N6>),
+  /// `stablecoin_to_pool` from `harvest_borrow_rate`
+  BorrowRate {
+    collateral_mint: Pubkey,
+    amount: UFix64<N6>,
+  },
+  /// `amount_stablecoin_burned` from `absorb_loss`
+  LossAbsorbed(UFix64<N6>),
+}
+
+impl PoolFlow {
+  /// # Errors
+  /// * Invalid fixed-point data
+  pub fn from_deposit(event: &UserDepositEvent) -> Result<PoolFlow> {
+    Ok(PoolFlow::Nav(event.lp_token_nav.try_into()?))
+  }
+
+  /// NAV implied by a withdrawal: hyUSD out plus fees per sHYUSD burned.
+  /// `None` when nothing was burned.
+  ///
+  /// # Errors
+  /// * Invalid fixed-point data
+  /// * Arithmetic overflow
+  pub fn from_withdraw(event: &UserWithdrawEvent) -> Result<Option<PoolFlow>> {
+    let burned: UFix64<N6> = event.lp_token_burned.try_into()?;
+    if burned == UFix64::zero() {
+      return Ok(None);
+    }
+    let withdrawn: UFix64<N6> = event.stablecoin_withdrawn.try_into()?;
+    let fees: UFix64<N6> = event.stablecoin_fees.try_into()?;
+    let gross = withdrawn.checked_add(&fees).ok_or(HistoryFlowOverflow)?;
+    Ok(Some(PoolFlow::Nav(lp_token_nav(gross, burned)?)))
+  }
+
+  /// # Errors
+  /// * Invalid fixed-point data
+  pub fn from_harvest_yield(event: &HarvestYieldEvent) -> Result<PoolFlow> {
+    Ok(PoolFlow::LstYield(event.token_to_pool.try_into()?))
+  }
+
+  /// # Errors
+  /// * Invalid fixed-point data
+  pub fn from_harvest_borrow_rate(
+    event: &HarvestBorrowRateEvent,
+  ) -> Result<PoolFlow> {
+    Ok(PoolFlow::BorrowRate {
+      collateral_mint: event.collateral_mint,
+      amount: event.stablecoin_to_pool.try_into()?,
+    })
+  }
+
+  /// # Errors
+  /// * Invalid fixed-point data
+  pub fn from_absorb_loss(event: &AbsorbLossEvent) -> Result<PoolFlow> {
+    Ok(PoolFlow::LossAbsorbed(
+      event.amount_stablecoin_burned.try_into()?,
+    ))
+  }
+
+  /// Decodes an earn pool deposit, withdrawal or loss event, or an exchange
+  /// harvest event. `None` for other events and other programs.
+  ///
+  /// # Errors
+  /// * Matching discriminator with malformed event data
+  pub fn from_event(event: &EventCpi) -> Result<Option<PoolFlow>> {
+    if let Some(deposit) = event.decode::<UserDepositEvent>(&earn_pool::ID)? {
+      return PoolFlow::from_deposit(&deposit).map(Some);
+    }
+    if let Some(withdraw) = event.decode::<UserWithdrawEvent>(&earn_pool::ID)? {
+      return PoolFlow::from_withdraw(&withdraw);
+    }
+    if let Some(loss) = event.decode::<AbsorbLossEvent>(&earn_pool::ID)? {
+      return PoolFlow::from_absorb_loss(&loss).map(Some);
+    }
+    if let Some(harvest) = event.decode::<HarvestYieldEvent>(&exchange::ID)? {
+      return PoolFlow::from_harvest_yield(&harvest).map(Some);
+    }
+    if let Some(harvest) =
+      event.decode::<HarvestBorrowRateEvent>(&exchange::ID)?
+    {
+      return PoolFlow::from_harvest_borrow_rate(&harvest).map(Some);
+    }
+    Ok(None)
+  }
+}
+
+/// A [`PoolFlow`] with the transaction that carried it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolEvent {
+  pub signature: Signature,
+  pub slot: u64,
+  pub flow: PoolFlow,
+}
+
+/// Folds stored snapshots and epoch-tagged flows into [`EpochRecord`]s.
+///
+/// A snapshot is authoritative for its epoch: flows recorded for an epoch
+/// with a snapshot are ignored. Flows in epochs with no NAV observation
+/// carry into the next epoch that has one, so harvests are never lost to a
+/// quiet epoch; flows after the last observation are left out.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryBuilder {
+  snapshots: BTreeMap<u64, EpochRecord>,
+  flows: BTreeMap<u64, Vec<PoolFlow>>,
+}
+
+impl HistoryBuilder {
+  #[must_use]
+  pub fn new() -> HistoryBuilder {
+    HistoryBuilder::default()
+  }
+
+  /// Adds stored records, later snapshots replacing earlier ones.
+  #[must_use]
+  pub fn with_snapshots(
+    mut self,
+    records: impl IntoIterator<Item = EpochRecord>,
+  ) -> HistoryBuilder {
+    self
+      .snapshots
+      .extend(records.into_iter().map(|record| (record.epoch, record)));
+    self
+  }
+
+  /// Adds a flow observed in `epoch`, in transaction order.
+  #[must_use]
+  pub fn with_flow(mut self, epoch: u64, flow: PoolFlow) -> HistoryBuilder {
+    self.flows.entry(epoch).or_default().push(flow);
+    self
+  }
+
+  /// Adds the flows of `events`, oldest first, each in the epoch of its
+  /// slot under `schedule`.
+  #[must_use]
+  pub fn with_events<'a>(
+    self,
+    events: impl IntoIterator<Item = &'a PoolEvent>,
+    schedule: &EpochSchedule,
+  ) -> HistoryBuilder {
+    events.into_iter().fold(self, |builder, event| {
+      builder.with_flow(schedule.get_epoch(event.slot), event.flow)
+    })
+  }
+
+  /// Epoch records in ascending epoch order.
+  ///
+  /// # Errors
+  /// * Arithmetic overflow summing flows
+  pub fn build(self) -> Result<Vec<EpochRecord>> {
+    let mut epochs = self.flows.keys().copied().collect::<Vec<_>>();
+    epochs.extend(self.snapshots.keys());
+    epochs.sort_unstable();
+    epochs.dedup();
+
+    let mut records = Vec::new();
+    let mut pending: Option<EpochRecord> = None;
+    for epoch in epochs {
+      let mut record =
+        pending.take().unwrap_or_else(|| EpochRecord::empty(epoch));
+      record.epoch = epoch;
+      if let Some(snapshot) = self.snapshots.get(&epoch) {
+        record.nav = snapshot.nav;
+        snapshot_flows(snapshot)
+          .iter()
+          .try_for_each(|flow| record.apply(flow))?;
+      } else {
+        record.nav = UFix64::zero();
+        self
+          .flows
+          .get(&epoch)
+          .into_iter()
+          .flatten()
+          .try_for_each(|flow| record.apply(flow))?;
+      }
+      if record.nav > UFix64::zero() {
+        records.push(record);
+      } else {
+        pending = Some(record);
+      }
+    }
+    Ok(records)
+  }
+}
+
+/// A snapshot's flows, for merging with flows carried from quiet epochs.
+fn snapshot_flows(snapshot: &EpochRecord) -> Vec<PoolFlow> {
+  std::iter::once(PoolFlow::LstYield(snapshot.lst_yield))
+    .chain(snapshot.borrow_rate_yield.iter().map(|(mint, amount)| {
+      PoolFlow::BorrowRate {
+        collateral_mint: *mint,
+        amount: *amount,
+      }
+    }))
+    .chain(std::iter::once(PoolFlow::LossAbsorbed(
+      snapshot.loss_absorbed,
+    )))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use hylo_idl::earn_pool::types::UFixValue64;
+  use hylo_idl::events::event_cpi_data;
+
+  use super::*;
+
+  fn hyusd(bits: u64) -> UFix64<N6> {
+    UFix64::new(bits)
+  }
+
+  #[test]
+  fn builder_carries_flows_to_next_nav() -> Result<()> {
+    let mint = Pubkey::new_unique();
+    let records = HistoryBuilder::new()
+      .with_flow(10, PoolFlow::Nav(hyusd(1_000_000)))
+      .with_flow(11, PoolFlow::LstYield(hyusd(5_000_000)))
+      .with_flow(
+        12,
+        PoolFlow::BorrowRate {
+          collateral_mint: mint,
+          amount: hyusd(2_000_000),
+        },
+      )
+      .with_flow(12, PoolFlow::Nav(hyusd(1_001_000)))
+      .with_flow(12, PoolFlow::Nav(hyusd(1_002_000)))
+      .with_flow(13, PoolFlow::LossAbsorbed(hyusd(1_000_000)))
+      .build()?;
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].epoch, 12);
+    assert_eq!(records[1].nav, hyusd(1_002_000));
+    assert_eq!(records[1].lst_yield, hyusd(5_000_000));
+    assert_eq!(records[1].borrow_rate_yield[&mint], hyusd(2_000_000));
+    assert_eq!(records[1].harvested()?, hyusd(7_000_000));
+    Ok(())
+  }
+
+  #[test]
+  fn snapshot_overrides_epoch_flows() -> Result<()> {
+    let snapshot =
+      EpochRecord::from_pool(20, hyusd(1_050_000), hyusd(1_000_000))?;
+    let records = HistoryBuilder::new()
+      .with_snapshots([snapshot.clone()])
+      .with_flow(20, PoolFlow::LstYield(hyusd(9_000_000)))
+      .build()?;
+    assert_eq!(records, vec![snapshot]);
+    Ok(())
+  }
+
+  #[test]
+  fn decodes_pool_events() -> Result<()> {
+    let event = AbsorbLossEvent {
+      requested_loss: UFixValue64 {
+        bits: 3_000_000,
+        exp: -6,
+      },
+      amount_stablecoin_burned: UFixValue64 {
+        bits: 2_500_000,
+        exp: -6,
+      },
+      remaining_pool_balance: UFixValue64 {
+        bits: 1_000_000_000,
+        exp: -6,
+      },
+    };
+    let data = event_cpi_data(&event);
+    let from = |program| {
+      PoolFlow::from_event(&EventCpi::parse(program, &data).expect("event CPI"))
+    };
+    assert_eq!(
+      from(earn_pool::ID)?,
+      Some(PoolFlow::LossAbsorbed(hyusd(2_500_000)))
+    );
+    assert_eq!(from(exchange::ID)?, None);
+    Ok(())
+  }
+
+  #[test]
+  fn withdraw_implies_gross_nav() -> Result<()> {
+    let ufix = |bits| UFixValue64 { bits, exp: -6 };
+    let event = UserWithdrawEvent {
+      lp_token_burned: ufix(10_000_000),
+      stablecoin_withdrawn: ufix(10_489_500),
+      stablecoin_fees: ufix(10_500),
+    };
+    let data = event_cpi_data(&event);
+    let pool_event = EventCpi::parse(earn_pool::ID, &data).expect("event CPI");
+    assert_eq!(
+      PoolFlow::from_event(&pool_event)?,
+      Some(PoolFlow::Nav(hyusd(1_050_000)))
+    );
+    let empty = UserWithdrawEvent {
+      lp_token_burned: ufix(0),
+      ..event
+    };
+    assert_eq!(PoolFlow::from_withdraw(&empty)?, None);
+    Ok(())
+  }
+}
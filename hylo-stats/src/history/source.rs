@@ -0,0 +1,144 @@
+//! Pluggable sources of earn pool events.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use hylo_idl::pda;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+
+use super::{PoolEvent, PoolFlow};
+use crate::transactions::{
+  fetch_instructions, signatures_since, ExecutedInstruction,
+};
+
+/// Transaction history of the earn pool.
+#[async_trait]
+pub trait PoolHistorySource: Send + Sync {
+  /// Pool deposits, withdrawals, harvests and absorbed losses landed at
+  /// or after `since_slot`, oldest first.
+  ///
+  /// # Errors
+  /// * Source read or decode failure
+  async fn pool_events(&self, since_slot: u64) -> Result<Vec<PoolEvent>>;
+}
+
+#[async_trait]
+impl<T: PoolHistorySource + ?Sized> PoolHistorySource for Arc<T> {
+  async fn pool_events(&self, since_slot: u64) -> Result<Vec<PoolEvent>> {
+    (**self).pool_events(since_slot).await
+  }
+}
+
+/// Walks every successful transaction touching the pool's hyUSD account,
+/// which every deposit, withdrawal, harvest and loss moves, and decodes
+/// the earn pool and exchange events it emitted. One `getTransaction` per
+/// signature; persist the built records with a
+/// [`HistoryStore`](super::store::HistoryStore) and walk only new slots.
+#[async_trait]
+impl PoolHistorySource for RpcClient {
+  async fn pool_events(&self, since_slot: u64) -> Result<Vec<PoolEvent>> {
+    let mut events = Vec::new();
+    for (signature, slot) in
+      signatures_since(self, &pda::HYUSD_POOL, since_slot).await?
+    {
+      let instructions = fetch_instructions(self, &signature).await?;
+      for flow in pool_flows(&instructions)? {
+        events.push(PoolEvent {
+          signature,
+          slot,
+          flow,
+        });
+      }
+    }
+    Ok(events)
+  }
+}
+
+/// Pool flows among the event CPIs of a transaction, in order.
+fn pool_flows(instructions: &[ExecutedInstruction]) -> Result<Vec<PoolFlow>> {
+  instructions
+    .iter()
+    .filter_map(ExecutedInstruction::event)
+    .filter_map(|event| PoolFlow::from_event(&event).transpose())
+    .collect()
+}
+
+/// In-process source, for tests and recorded histories.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryPoolHistorySource {
+  events: Vec<PoolEvent>,
+}
+
+impl MemoryPoolHistorySource {
+  /// Appends `event`; add events oldest first.
+  #[must_use]
+  pub fn with_event(mut self, event: PoolEvent) -> MemoryPoolHistorySource {
+    self.events.push(event);
+    self
+  }
+}
+
+#[async_trait]
+impl PoolHistorySource for MemoryPoolHistorySource {
+  async fn pool_events(&self, since_slot: u64) -> Result<Vec<PoolEvent>> {
+    Ok(
+      self
+        .events
+        .iter()
+        .filter(|event| event.slot >= since_slot)
+        .copied()
+        .collect(),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Pubkey;
+  use fix::prelude::*;
+  use hylo_idl::events::event_cpi_data;
+  use hylo_idl::exchange::events::HarvestYieldEvent;
+  use hylo_idl::exchange::types::{OraclePriceEvent, UFixValue64};
+  use hylo_idl::{earn_pool, exchange};
+
+  use super::*;
+
+  fn harvest(program_id: Pubkey) -> ExecutedInstruction {
+    let value = |bits| UFixValue64 { bits, exp: -6 };
+    let event = HarvestYieldEvent {
+      total_sol_harvested: value(6_000_000),
+      fees_extracted: value(1_000_000),
+      token_to_pool: value(5_000_000),
+      pool_drawdown_repaid: value(0),
+      sol_usd_price: OraclePriceEvent {
+        spot: value(150_000_000),
+        conf: value(0),
+      },
+    };
+    ExecutedInstruction {
+      program_id,
+      accounts: Vec::new(),
+      data: event_cpi_data(&event),
+    }
+  }
+
+  #[test]
+  fn flows_come_only_from_the_emitting_program() -> Result<()> {
+    let untagged = ExecutedInstruction {
+      data: harvest(exchange::ID).data[8..].to_vec(),
+      ..harvest(exchange::ID)
+    };
+    let instructions = [
+      harvest(Pubkey::new_unique()),
+      harvest(earn_pool::ID),
+      untagged,
+      harvest(exchange::ID),
+    ];
+    assert_eq!(
+      pool_flows(&instructions)?,
+      [PoolFlow::LstYield(UFix64::new(5_000_000))]
+    );
+    Ok(())
+  }
+}
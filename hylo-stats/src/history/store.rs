@@ -0,0 +1,159 @@
+//! Pluggable persistence for [`EpochRecord`] history.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use fix::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::EpochRecord;
+
+/// Storage backend for epoch records, keyed by epoch.
+pub trait HistoryStore {
+  /// All stored records in ascending epoch order.
+  ///
+  /// # Errors
+  /// * Backend read or decode failure
+  fn load(&self) -> Result<Vec<EpochRecord>>;
+
+  /// Inserts `records`, replacing any stored record for the same epoch.
+  ///
+  /// # Errors
+  /// * Backend write or encode failure
+  fn save(&mut self, records: &[EpochRecord]) -> Result<()>;
+}
+
+/// In-process store, for tests and short-lived analysis.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+  records: BTreeMap<u64, EpochRecord>,
+}
+
+impl HistoryStore for MemoryStore {
+  fn load(&self) -> Result<Vec<EpochRecord>> {
+    Ok(self.records.values().cloned().collect())
+  }
+
+  fn save(&mut self, records: &[EpochRecord]) -> Result<()> {
+    self
+      .records
+      .extend(records.iter().map(|record| (record.epoch, record.clone())));
+    Ok(())
+  }
+}
+
+/// Records as a JSON array in one file. A missing file loads as empty.
+#[derive(Debug, Clone)]
+pub struct JsonFileStore {
+  path: PathBuf,
+}
+
+impl JsonFileStore {
+  #[must_use]
+  pub fn new(path: impl Into<PathBuf>) -> JsonFileStore {
+    JsonFileStore { path: path.into() }
+  }
+}
+
+impl HistoryStore for JsonFileStore {
+  fn load(&self) -> Result<Vec<EpochRecord>> {
+    match File::open(&self.path) {
+      Err(err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+      Err(err) => Err(err.into()),
+      Ok(file) => {
+        let mut stored: Vec<StoredRecord> =
+          serde_json::from_reader(BufReader::new(file))?;
+        stored.sort_by_key(|record| record.epoch);
+        stored.into_iter().map(EpochRecord::try_from).collect()
+      }
+    }
+  }
+
+  fn save(&mut self, records: &[EpochRecord]) -> Result<()> {
+    let mut merged = self
+      .load()?
+      .into_iter()
+      .map(|record| (record.epoch, record))
+      .collect::<BTreeMap<_, _>>();
+    merged.extend(records.iter().map(|record| (record.epoch, record.clone())));
+    let stored = merged.values().map(StoredRecord::from).collect::<Vec<_>>();
+    let file = BufWriter::new(File::create(&self.path)?);
+    serde_json::to_writer_pretty(file, &stored)?;
+    Ok(())
+  }
+}
+
+/// Serialized form of [`EpochRecord`], mints as base58.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredRecord {
+  epoch: u64,
+  nav: UFixValue64,
+  lst_yield: UFixValue64,
+  borrow_rate_yield: BTreeMap<String, UFixValue64>,
+  loss_absorbed: UFixValue64,
+}
+
+impl From<&EpochRecord> for StoredRecord {
+  fn from(record: &EpochRecord) -> StoredRecord {
+    StoredRecord {
+      epoch: record.epoch,
+      nav: record.nav.into(),
+      lst_yield: record.lst_yield.into(),
+      borrow_rate_yield: record
+        .borrow_rate_yield
+        .iter()
+        .map(|(mint, amount)| (mint.to_string(), (*amount).into()))
+        .collect(),
+      loss_absorbed: record.loss_absorbed.into(),
+    }
+  }
+}
+
+impl TryFrom<StoredRecord> for EpochRecord {
+  type Error = anyhow::Error;
+
+  fn try_from(stored: StoredRecord) -> Result<EpochRecord> {
+    Ok(EpochRecord {
+      epoch: stored.epoch,
+      nav: stored.nav.try_into()?,
+      lst_yield: stored.lst_yield.try_into()?,
+      borrow_rate_yield: stored
+        .borrow_rate_yield
+        .into_iter()
+        .map(|(mint, amount)| {
+          Ok((Pubkey::from_str(&mint)?, amount.try_into()?))
+        })
+        .collect::<Result<_>>()?,
+      loss_absorbed: stored.loss_absorbed.try_into()?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn memory_store_upserts_by_epoch() -> Result<()> {
+    let mut store = MemoryStore::default();
+    let first = EpochRecord::from_pool(
+      9,
+      UFix64::new(1_000_000),
+      UFix64::new(1_000_000),
+    )?;
+    let second = EpochRecord::from_pool(
+      9,
+      UFix64::new(1_100_000),
+      UFix64::new(1_000_000),
+    )?;
+    store.save(&[first])?;
+    store.save(std::slice::from_ref(&second))?;
+    assert_eq!(store.load()?, vec![second]);
+    Ok(())
+  }
+}
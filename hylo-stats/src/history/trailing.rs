@@ -0,0 +1,188 @@
+//! Trailing realized APY and volatility over [`EpochRecord`] history.
+
+use anyhow::Result;
+use fix::prelude::*;
+
+use super::EpochRecord;
+use crate::error::StatsError::{
+  HistoryFlowOverflow, InsufficientHistory, ZeroHistoryNav,
+};
+
+/// Trailing windows reported by [`trailing_yields`], in epochs.
+pub const TRAILING_WINDOWS: [u64; 3] = [7, 30, 90];
+
+/// Realized sHYUSD yield over a trailing window.
+///
+/// * `window` — requested window in epochs; `epochs` — epochs actually
+///   spanned, shorter when history starts inside the window
+/// * `apy` — `(nav_last / nav_base)^(epochs_per_year / epochs) - 1`
+/// * `volatility` — annualized standard deviation of per-epoch log NAV
+///   returns, `None` with fewer than two returns in the window
+/// * `harvested`, `loss_absorbed` — hyUSD flows over the window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrailingYield {
+  pub window: u64,
+  pub epochs: u64,
+  pub apy: f64,
+  pub volatility: Option<f64>,
+  pub harvested: UFix64<N6>,
+  pub loss_absorbed: UFix64<N6>,
+}
+
+/// Realized yield over the last `window` epochs of `records`, which must
+/// be sorted by epoch. The base is the latest record at least `window`
+/// epochs before the last, or the first record when history is shorter.
+///
+/// # Errors
+/// * Fewer than two records spanning at least one epoch
+/// * Zero NAV in the window
+/// * Arithmetic overflow summing flows
+#[allow(clippy::cast_precision_loss)]
+pub fn trailing_yield(
+  records: &[EpochRecord],
+  window: u64,
+  epochs_per_year: f64,
+) -> Result<TrailingYield> {
+  let last = records.last().ok_or(InsufficientHistory(window))?;
+  let start = last.epoch.saturating_sub(window);
+  let base_index = records
+    .iter()
+    .rposition(|record| record.epoch <= start)
+    .unwrap_or(0);
+  let span = &records[base_index..];
+  let base = &span[0];
+  let epochs = last.epoch.saturating_sub(base.epoch);
+  if epochs == 0 {
+    return Err(InsufficientHistory(window).into());
+  }
+  let returns = span
+    .windows(2)
+    .map(|pair| epoch_log_return(&pair[0], &pair[1]))
+    .collect::<Result<Vec<f64>>>()?;
+  let total_log_return = log_nav_ratio(base, last)?;
+  let (harvested, loss_absorbed) = span[1..].iter().try_fold(
+    (UFix64::zero(), UFix64::zero()),
+    |(harvested, loss): (UFix64<N6>, UFix64<N6>), record| -> Result<_> {
+      Ok((
+        harvested
+          .checked_add(&record.harvested()?)
+          .ok_or(HistoryFlowOverflow)?,
+        loss
+          .checked_add(&record.loss_absorbed)
+          .ok_or(HistoryFlowOverflow)?,
+      ))
+    },
+  )?;
+  Ok(TrailingYield {
+    window,
+    epochs,
+    apy: (total_log_return * epochs_per_year / epochs as f64).exp_m1(),
+    volatility: sample_std_dev(&returns)
+      .map(|std_dev| std_dev * epochs_per_year.sqrt()),
+    harvested,
+    loss_absorbed,
+  })
+}
+
+/// [`trailing_yield`] for each of [`TRAILING_WINDOWS`].
+///
+/// # Errors
+/// * See [`trailing_yield`]
+pub fn trailing_yields(
+  records: &[EpochRecord],
+  epochs_per_year: f64,
+) -> Result<Vec<TrailingYield>> {
+  TRAILING_WINDOWS
+    .iter()
+    .map(|window| trailing_yield(records, *window, epochs_per_year))
+    .collect()
+}
+
+/// `ln(to.nav / from.nav)`.
+fn log_nav_ratio(from: &EpochRecord, to: &EpochRecord) -> Result<f64> {
+  let nonzero = |record: &EpochRecord| {
+    if record.nav == UFix64::zero() {
+      Err(ZeroHistoryNav(record.epoch))
+    } else {
+      Ok(record.nav.to_f64())
+    }
+  };
+  Ok((nonzero(to)? / nonzero(from)?).ln())
+}
+
+/// Log NAV return between consecutive records, spread evenly over the
+/// epochs between them.
+#[allow(clippy::cast_precision_loss)]
+fn epoch_log_return(from: &EpochRecord, to: &EpochRecord) -> Result<f64> {
+  let gap = to.epoch.saturating_sub(from.epoch).max(1);
+  Ok(log_nav_ratio(from, to)? / gap as f64)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn sample_std_dev(samples: &[f64]) -> Option<f64> {
+  (samples.len() >= 2).then(|| {
+    let n = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / n;
+    let variance = samples
+      .iter()
+      .map(|sample| (sample - mean).powi(2))
+      .sum::<f64>()
+      / (n - 1.0);
+    variance.sqrt()
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::BTreeMap;
+
+  use super::*;
+
+  fn record(epoch: u64, nav_bits: u64, lst_bits: u64) -> EpochRecord {
+    EpochRecord {
+      epoch,
+      nav: UFix64::new(nav_bits),
+      lst_yield: UFix64::new(lst_bits),
+      borrow_rate_yield: BTreeMap::new(),
+      loss_absorbed: UFix64::zero(),
+    }
+  }
+
+  #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+  fn nav_bits(nav: f64) -> u64 {
+    nav.round() as u64
+  }
+
+  #[test]
+  fn constant_growth_annualizes_without_volatility() -> Result<()> {
+    // 0.1% per epoch, compounded
+    let records = (0..=10u8)
+      .map(|i| {
+        let nav = 1_000_000.0 * 1.001f64.powi(i32::from(i));
+        record(100 + u64::from(i), nav_bits(nav), 1_000_000)
+      })
+      .collect::<Vec<_>>();
+    let trailing = trailing_yield(&records, 7, 182.0)?;
+    assert_eq!(trailing.epochs, 7);
+    let expected = 1.001f64.powf(182.0) - 1.0;
+    assert!((trailing.apy - expected).abs() < 1e-2, "{}", trailing.apy);
+    assert!(trailing.volatility.is_some_and(|vol| vol < 1e-2));
+    assert_eq!(trailing.harvested, UFix64::new(7_000_000));
+    Ok(())
+  }
+
+  #[test]
+  fn short_history_uses_first_record() -> Result<()> {
+    let records = [record(5, 1_000_000, 0), record(8, 1_003_000, 0)];
+    let trailing = trailing_yields(&records, 182.0)?;
+    assert!(trailing.iter().all(|t| t.epochs == 3));
+    assert!(trailing.iter().all(|t| t.volatility.is_none()));
+    Ok(())
+  }
+
+  #[test]
+  fn single_record_is_insufficient() {
+    assert!(trailing_yield(&[record(5, 1_000_000, 0)], 7, 182.0).is_err());
+    assert!(trailing_yield(&[], 7, 182.0).is_err());
+  }
+}
@@ -0,0 +1,247 @@
+//! Levercoin statistics for xSOL and the exo levercoins: NAV, market cap,
+//! effective leverage, distance to Depeg, market cap limiter headroom and
+//! the borrow rate exo levercoin holders pay.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use fix::prelude::*;
+use hylo_core::borrow_rate::BorrowRateConfig;
+use hylo_core::exchange_context::{
+  ExchangeContext, ExoExchangeContext, LstExchangeContext,
+};
+use hylo_core::exchange_math::levercoin_market_cap;
+use hylo_core::limiter::levercoin::LevercoinMarketCapLimiter;
+use hylo_core::rebalance::mode::RebalanceMode;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_idl::tokens::{TokenMint, XSOL};
+
+use crate::earn_pool_stats::annualize_with;
+use crate::error::StatsError::{DepegPriceOverflow, FundingCostOverflow};
+use crate::types::{
+  DepegDistance, FundingCost, LevercoinStats, MarketCapHeadroom,
+};
+
+/// `collateral_value / market_cap`, `None` for a zero market cap.
+#[must_use]
+pub fn effective_leverage(
+  collateral_value: UFix64<N9>,
+  market_cap: UFix64<N9>,
+) -> Option<f64> {
+  (market_cap > UFix64::zero())
+    .then(|| collateral_value.to_f64() / market_cap.to_f64())
+}
+
+/// Collateral price at which the collateral ratio falls to the Depeg
+/// boundary. The ratio scales linearly with price at fixed supply.
+///
+/// ```txt
+///                    price * depeg_cr
+/// collateral_price = ----------------
+///                      current_cr
+/// ```
+///
+/// # Errors
+/// * Arithmetic overflow
+pub fn depeg_distance(
+  collateral_ratio: UFix64<N9>,
+  collateral_price: UFix64<N9>,
+) -> Result<DepegDistance> {
+  let depeg_cr = RebalanceMode::Depeg.active_range().end()?;
+  if collateral_ratio <= depeg_cr {
+    Ok(DepegDistance {
+      depeg_cr,
+      collateral_price,
+      price_drop: 0.0,
+    })
+  } else {
+    let depeg_price = collateral_price
+      .mul_div_ceil(depeg_cr, collateral_ratio)
+      .ok_or(DepegPriceOverflow)?;
+    Ok(DepegDistance {
+      depeg_cr,
+      collateral_price: depeg_price,
+      price_drop: 1.0 - depeg_cr.to_f64() / collateral_ratio.to_f64(),
+    })
+  }
+}
+
+/// Room left under the levercoin market cap limit.
+///
+/// # Errors
+/// * Zero levercoin NAV
+pub fn market_cap_headroom(
+  limiter: &LevercoinMarketCapLimiter,
+  market_cap: UFix64<N9>,
+) -> Result<MarketCapHeadroom> {
+  Ok(MarketCapHeadroom {
+    limit: limiter.market_cap_limit,
+    remaining_usd: limiter
+      .market_cap_limit
+      .checked_sub(&market_cap)
+      .unwrap_or_default(),
+    remaining_levercoin: limiter.max_token_out()?,
+  })
+}
+
+/// Borrow rate cost against `market_cap`, annualized at
+/// `epochs_per_year`.
+///
+/// # Errors
+/// * Invalid rate data or arithmetic overflow
+pub fn funding_cost(
+  market_cap: UFix64<N9>,
+  borrow_rate_config: &BorrowRateConfig,
+  epochs_per_year: f64,
+) -> Result<FundingCost> {
+  let per_epoch_rate = borrow_rate_config.rate()?;
+  let per_epoch_usd = borrow_rate_config
+    .apply_borrow_rate(market_cap, UFix64::constant(1))
+    .map_err(|_| FundingCostOverflow)?;
+  Ok(FundingCost {
+    per_epoch_rate,
+    per_epoch_usd,
+    annualized: annualize_with(per_epoch_rate, epochs_per_year),
+  })
+}
+
+/// Statistics shared by every levercoin. NAVs the pair's state leaves
+/// undefined, such as in Depeg, are reported as `None` rather than
+/// failing the pair.
+fn base_stats<X: ExchangeContext>(
+  ctx: &X,
+  clock: &impl SolanaClock,
+  levercoin_mint: Pubkey,
+) -> Result<LevercoinStats> {
+  let levercoin_supply = ctx.levercoin_supply()?;
+  let mint_nav = ctx.levercoin_mint_nav().ok();
+  let market_cap = mint_nav
+    .map(|nav| levercoin_market_cap(levercoin_supply, nav))
+    .transpose()?;
+  let collateral_value = ctx.total_value_locked()?;
+  let collateral_ratio = ctx.collateral_ratio();
+  Ok(LevercoinStats {
+    levercoin_mint,
+    collateral_mint: None,
+    epoch: clock.epoch(),
+    unix_timestamp: clock.unix_timestamp(),
+    mint_nav,
+    redeem_nav: ctx.levercoin_redeem_nav().ok(),
+    levercoin_supply,
+    market_cap,
+    collateral_value,
+    effective_leverage: market_cap
+      .and_then(|market_cap| effective_leverage(collateral_value, market_cap)),
+    collateral_ratio,
+    rebalance_mode: ctx.rebalance_mode(),
+    depeg: depeg_distance(collateral_ratio, ctx.collateral_usd_price().lower)?,
+    market_cap_headroom: None,
+    funding_cost: None,
+  })
+}
+
+/// xSOL statistics from the LST exchange context.
+///
+/// # Errors
+/// * Missing levercoin supply, NAV or arithmetic failure
+pub fn lst_levercoin_stats<C: SolanaClock>(
+  ctx: &LstExchangeContext<C>,
+) -> Result<LevercoinStats> {
+  base_stats(ctx, &ctx.clock, XSOL::MINT)
+}
+
+/// Exo levercoin statistics, including market cap headroom and the
+/// pair's borrow rate cost when the market cap is defined.
+///
+/// # Errors
+/// * Missing levercoin supply, NAV or arithmetic failure
+/// * Invalid borrow rate config
+pub fn exo_levercoin_stats<C: SolanaClock>(
+  ctx: &ExoExchangeContext<C>,
+  collateral_mint: Pubkey,
+  levercoin_mint: Pubkey,
+  borrow_rate_config: &BorrowRateConfig,
+  epochs_per_year: f64,
+) -> Result<LevercoinStats> {
+  let base = base_stats(ctx, &ctx.clock, levercoin_mint)?;
+  let Some(market_cap) = base.market_cap else {
+    return Ok(LevercoinStats {
+      collateral_mint: Some(collateral_mint),
+      ..base
+    });
+  };
+  let limiter = ctx.levercoin_market_cap_limiter()?;
+  Ok(LevercoinStats {
+    collateral_mint: Some(collateral_mint),
+    market_cap_headroom: Some(market_cap_headroom(&limiter, market_cap)?),
+    funding_cost: Some(funding_cost(
+      market_cap,
+      borrow_rate_config,
+      epochs_per_year,
+    )?),
+    ..base
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn leverage_is_collateral_over_market_cap() {
+    let leverage = effective_leverage(
+      UFix64::new(3_000_000_000_000_000),
+      UFix64::new(1_000_000_000_000_000),
+    );
+    assert!(leverage.is_some_and(|l| (l - 3.0).abs() < 1e-9));
+    assert!(effective_leverage(UFix64::one(), UFix64::zero()).is_none());
+  }
+
+  #[test]
+  fn depeg_price_scales_with_ratio() -> Result<()> {
+    // CR 1.5 at $150: Depeg (CR 1.0) at $100, a third lower
+    let distance =
+      depeg_distance(UFix64::new(1_500_000_000), UFix64::new(150_000_000_000))?;
+    assert_eq!(distance.depeg_cr, UFix64::one());
+    assert_eq!(distance.collateral_price, UFix64::new(100_000_000_000));
+    assert!((distance.price_drop - 1.0 / 3.0).abs() < 1e-9);
+
+    let depegged =
+      depeg_distance(UFix64::new(900_000_000), UFix64::new(150_000_000_000))?;
+    assert!(depegged.price_drop.abs() < f64::EPSILON);
+    Ok(())
+  }
+
+  #[test]
+  fn headroom_saturates_over_limit() -> Result<()> {
+    let limiter = LevercoinMarketCapLimiter::new(
+      UFix64::new(1_000_000_000_000_000),
+      UFix64::new(2_000_000_000),
+      UFix64::new(400_000_000_000),
+    );
+    // $800k of a $1M limit at $2 NAV: $200k, 100k levercoin
+    let headroom =
+      market_cap_headroom(&limiter, UFix64::new(800_000_000_000_000))?;
+    assert_eq!(headroom.remaining_usd, UFix64::new(200_000_000_000_000));
+    assert_eq!(headroom.remaining_levercoin, UFix64::new(100_000_000_000));
+
+    let over =
+      market_cap_headroom(&limiter, UFix64::new(1_200_000_000_000_000))?;
+    assert_eq!(over.remaining_usd, UFix64::zero());
+    Ok(())
+  }
+
+  #[test]
+  fn funding_cost_charges_market_cap() -> Result<()> {
+    // 0.038462% per epoch on $1M
+    let config = BorrowRateConfig::new(
+      UFix64::<N9>::new(384_620).into(),
+      UFix64::<N4>::new(500).into(),
+    );
+    let cost =
+      funding_cost(UFix64::new(1_000_000_000_000_000), &config, 182.0)?;
+    assert_eq!(cost.per_epoch_usd, UFix64::new(384_620_000_000));
+    let expected = 1.000_384_62f64.powf(182.0) - 1.0;
+    assert!((cost.annualized - expected).abs() < 1e-9);
+    Ok(())
+  }
+}
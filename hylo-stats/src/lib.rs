@@ -1,16 +1,34 @@
 //! # Hylo Stats
 //!
-//! Offchain yield statistics for the Hylo protocol.
+//! Offchain yield and levercoin statistics for the Hylo protocol.
 //!
-//! - [`client`] — Read-only fetch layer for earn pool stats (no keypair
+//! - [`client`] — Read-only fetch layer for protocol stats (no keypair
 //!   required)
 //! - [`earn_pool_stats`] — Yield statistics computation for sHYUSD
 //! - [`earn_pool_yield_math`] — Pure math for realized and projected earn pool
 //!   yield
+//! - [`epochs_per_year`] — Epochs-per-year estimators with a confidence band
+//!   and cached epoch boundary times
+//! - [`history`] — Per-epoch sHYUSD NAV and flow history with trailing APY
+//!   and volatility
+//! - [`levercoin_stats`] — Levercoin NAV, leverage, Depeg distance, limiter
+//!   headroom and borrow rate cost
+//! - [`position`] — Per-wallet sHYUSD cost basis, value and yield replayed
+//!   from earn pool events
+//! - [`protocol_overview`] — Protocol-wide TVL, hyUSD backing and solvency
+//!   with JSON serialization
+//! - [`source`] — Account and block-time sources: RPC or in-memory fixtures
 //! - [`types`] — Data types for stats inputs and results
 
 pub mod client;
 pub mod earn_pool_stats;
 pub mod earn_pool_yield_math;
+pub mod epochs_per_year;
 pub mod error;
+pub mod history;
+pub mod levercoin_stats;
+pub mod position;
+pub mod protocol_overview;
+pub mod source;
+mod transactions;
 pub mod types;
@@ -0,0 +1,420 @@
+//! Per-wallet sHYUSD position: cost basis, value at NAV, realized and
+//! unrealized yield, replayed from the wallet's earn pool events.
+//!
+//! - [`PositionEvent`] — a deposit or withdrawal decoded from a pool event
+//! - [`PositionLedger`] — average-cost replay of a wallet's events
+//! - [`UserPosition`] — the wallet's balance valued against the ledger
+//! - [`source`] — pluggable transaction sources for a wallet's events
+
+pub mod source;
+
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use fix::prelude::*;
+use hylo_core::earn_pool_math::{lp_token_nav, stablecoin_withdrawal_fee};
+use hylo_idl::earn_pool;
+use hylo_idl::earn_pool::events::{UserDepositEvent, UserWithdrawEvent};
+use hylo_idl::events::EventCpi;
+
+use crate::error::StatsError::{PositionReplayOverflow, PositionValueOverflow};
+
+/// A wallet's deposit into or withdrawal from the earn pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionFlow {
+  /// `UserDepositEvent`: hyUSD in, sHYUSD minted at `lp_token_nav`
+  Deposit {
+    stablecoin_deposited: UFix64<N6>,
+    lp_token_minted: UFix64<N6>,
+    lp_token_nav: UFix64<N6>,
+  },
+  /// `UserWithdrawEvent`: sHYUSD burned, hyUSD out net of fees
+  Withdraw {
+    lp_token_burned: UFix64<N6>,
+    stablecoin_withdrawn: UFix64<N6>,
+    stablecoin_fees: UFix64<N6>,
+  },
+}
+
+impl PositionFlow {
+  /// # Errors
+  /// * Invalid fixed-point data
+  pub fn from_deposit(event: &UserDepositEvent) -> Result<PositionFlow> {
+    Ok(PositionFlow::Deposit {
+      stablecoin_deposited: event.stablecoin_deposited.try_into()?,
+      lp_token_minted: event.lp_token_minted.try_into()?,
+      lp_token_nav: event.lp_token_nav.try_into()?,
+    })
+  }
+
+  /// # Errors
+  /// * Invalid fixed-point data
+  pub fn from_withdraw(event: &UserWithdrawEvent) -> Result<PositionFlow> {
+    Ok(PositionFlow::Withdraw {
+      lp_token_burned: event.lp_token_burned.try_into()?,
+      stablecoin_withdrawn: event.stablecoin_withdrawn.try_into()?,
+      stablecoin_fees: event.stablecoin_fees.try_into()?,
+    })
+  }
+
+  /// Decodes an earn pool deposit or withdrawal event. `None` for other
+  /// events and other programs.
+  ///
+  /// # Errors
+  /// * Matching discriminator with malformed event data
+  pub fn from_event(event: &EventCpi) -> Result<Option<PositionFlow>> {
+    if let Some(deposit) = event.decode::<UserDepositEvent>(&earn_pool::ID)? {
+      return PositionFlow::from_deposit(&deposit).map(Some);
+    }
+    if let Some(withdraw) = event.decode::<UserWithdrawEvent>(&earn_pool::ID)? {
+      return PositionFlow::from_withdraw(&withdraw).map(Some);
+    }
+    Ok(None)
+  }
+}
+
+/// A [`PositionFlow`] with the transaction that carried it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionEvent {
+  pub signature: Signature,
+  pub slot: u64,
+  pub flow: PositionFlow,
+}
+
+/// Running totals from replaying a wallet's flows in order.
+///
+/// Burned sHYUSD leaves at average cost. Burns beyond the replayed
+/// balance (sHYUSD received by transfer) have no known cost, so only the
+/// replayed share of their proceeds counts as realized.
+///
+/// * `shares` — sHYUSD minted less burned
+/// * `cost_basis` — hyUSD cost of `shares`
+/// * `deposited` — hyUSD deposited
+/// * `withdrawn` — hyUSD received, net of fees
+/// * `fees_paid` — hyUSD withdrawal fees
+/// * `realized_proceeds` — gross hyUSD for burned replayed shares
+/// * `realized_cost` — cost basis of burned replayed shares
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PositionLedger {
+  pub shares: UFix64<N6>,
+  pub cost_basis: UFix64<N6>,
+  pub deposited: UFix64<N6>,
+  pub withdrawn: UFix64<N6>,
+  pub fees_paid: UFix64<N6>,
+  pub realized_proceeds: UFix64<N6>,
+  pub realized_cost: UFix64<N6>,
+}
+
+impl PositionLedger {
+  /// Replays `events` in the given order.
+  ///
+  /// # Errors
+  /// * Arithmetic overflow
+  pub fn replay<'a>(
+    events: impl IntoIterator<Item = &'a PositionEvent>,
+  ) -> Result<PositionLedger> {
+    events
+      .into_iter()
+      .try_fold(PositionLedger::default(), |ledger, event| {
+        ledger.apply(&event.flow)
+      })
+  }
+
+  /// Ledger after `flow`.
+  ///
+  /// # Errors
+  /// * Arithmetic overflow
+  pub fn apply(self, flow: &PositionFlow) -> Result<PositionLedger> {
+    match *flow {
+      PositionFlow::Deposit {
+        stablecoin_deposited,
+        lp_token_minted,
+        ..
+      } => Ok(PositionLedger {
+        shares: add(self.shares, lp_token_minted)?,
+        cost_basis: add(self.cost_basis, stablecoin_deposited)?,
+        deposited: add(self.deposited, stablecoin_deposited)?,
+        ..self
+      }),
+      PositionFlow::Withdraw {
+        lp_token_burned,
+        stablecoin_withdrawn,
+        stablecoin_fees,
+      } => {
+        let tracked = lp_token_burned.min(self.shares);
+        let cost = share_of(self.cost_basis, tracked, self.shares)?;
+        let gross = add(stablecoin_withdrawn, stablecoin_fees)?;
+        let proceeds = share_of(gross, tracked, lp_token_burned)?;
+        Ok(PositionLedger {
+          shares: sub(self.shares, tracked)?,
+          cost_basis: sub(self.cost_basis, cost)?,
+          withdrawn: add(self.withdrawn, stablecoin_withdrawn)?,
+          fees_paid: add(self.fees_paid, stablecoin_fees)?,
+          realized_proceeds: add(self.realized_proceeds, proceeds)?,
+          realized_cost: add(self.realized_cost, cost)?,
+          ..self
+        })
+      }
+    }
+  }
+
+  /// Gross proceeds less cost for burned replayed shares.
+  ///
+  /// # Errors
+  /// * Amount out of signed range
+  pub fn realized_yield(&self) -> Result<IFix64<N6>> {
+    signed_diff(self.realized_proceeds, self.realized_cost)
+  }
+}
+
+fn add(acc: UFix64<N6>, amount: UFix64<N6>) -> Result<UFix64<N6>> {
+  Ok(acc.checked_add(&amount).ok_or(PositionReplayOverflow)?)
+}
+
+fn sub(acc: UFix64<N6>, amount: UFix64<N6>) -> Result<UFix64<N6>> {
+  Ok(acc.checked_sub(&amount).ok_or(PositionReplayOverflow)?)
+}
+
+/// `amount * part / whole`, zero for an empty whole.
+fn share_of(
+  amount: UFix64<N6>,
+  part: UFix64<N6>,
+  whole: UFix64<N6>,
+) -> Result<UFix64<N6>> {
+  if whole == UFix64::zero() {
+    Ok(UFix64::zero())
+  } else {
+    Ok(
+      amount
+        .mul_div_floor(part, whole)
+        .ok_or(PositionReplayOverflow)?,
+    )
+  }
+}
+
+/// `amount` as a signed value.
+fn signed(amount: UFix64<N6>) -> Result<IFix64<N6>> {
+  let bits = i64::try_from(amount.bits).map_err(|_| PositionValueOverflow)?;
+  Ok(IFix64::new(bits))
+}
+
+fn signed_diff(gain: UFix64<N6>, cost: UFix64<N6>) -> Result<IFix64<N6>> {
+  Ok(
+    signed(gain)?
+      .checked_sub(&signed(cost)?)
+      .ok_or(PositionValueOverflow)?,
+  )
+}
+
+/// A wallet's sHYUSD position at current pool NAV.
+///
+/// * `shares` — sHYUSD held onchain
+/// * `current_value` — `shares` at `nav`, before withdrawal fees
+/// * `exit_fee` — withdrawal fee on `current_value` at the current rate
+/// * `cost_basis` — ledger cost of the replayed shares still held
+/// * `untracked_shares` — held sHYUSD with no replayed deposit, e.g.
+///   received by transfer; valued but excluded from yield
+/// * `realized_yield` — see [`PositionLedger::realized_yield`]
+/// * `unrealized_yield` — value of replayed shares held less their cost
+/// * `fees_paid` — withdrawal fees paid so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserPosition {
+  pub wallet: Pubkey,
+  pub shares: UFix64<N6>,
+  pub nav: UFix64<N6>,
+  pub current_value: UFix64<N6>,
+  pub exit_fee: UFix64<N6>,
+  pub cost_basis: UFix64<N6>,
+  pub untracked_shares: UFix64<N6>,
+  pub deposited: UFix64<N6>,
+  pub withdrawn: UFix64<N6>,
+  pub realized_yield: IFix64<N6>,
+  pub unrealized_yield: IFix64<N6>,
+  pub fees_paid: UFix64<N6>,
+}
+
+impl UserPosition {
+  /// Values `shares` against `ledger` at the NAV of the given pool.
+  /// Replayed shares the wallet no longer holds are assumed moved out at
+  /// average cost.
+  ///
+  /// # Errors
+  /// * NAV, fee or valuation arithmetic failure
+  pub fn value(
+    wallet: Pubkey,
+    ledger: &PositionLedger,
+    shares: UFix64<N6>,
+    pool_balance: UFix64<N6>,
+    shyusd_supply: UFix64<N6>,
+    withdrawal_fee: UFix64<N4>,
+  ) -> Result<UserPosition> {
+    let nav = lp_token_nav(pool_balance, shyusd_supply)?;
+    let at_nav = |amount: UFix64<N6>| {
+      amount
+        .mul_div_floor(nav, UFix64::one())
+        .ok_or(PositionValueOverflow)
+    };
+    let current_value = at_nav(shares)?;
+    let tracked = shares.min(ledger.shares);
+    let cost_basis = share_of(ledger.cost_basis, tracked, ledger.shares)?;
+    Ok(UserPosition {
+      wallet,
+      shares,
+      nav,
+      current_value,
+      exit_fee: stablecoin_withdrawal_fee(current_value, withdrawal_fee)?
+        .fees_extracted,
+      cost_basis,
+      untracked_shares: shares.saturating_sub(&tracked),
+      deposited: ledger.deposited,
+      withdrawn: ledger.withdrawn,
+      realized_yield: ledger.realized_yield()?,
+      unrealized_yield: signed_diff(at_nav(tracked)?, cost_basis)?,
+      fees_paid: ledger.fees_paid,
+    })
+  }
+
+  /// Realized plus unrealized yield, less fees paid.
+  ///
+  /// # Errors
+  /// * Arithmetic overflow
+  pub fn net_yield(&self) -> Result<IFix64<N6>> {
+    let fees = signed(self.fees_paid)?;
+    Ok(
+      self
+        .realized_yield
+        .checked_add(&self.unrealized_yield)
+        .and_then(|total| total.checked_sub(&fees))
+        .ok_or(PositionValueOverflow)?,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use hylo_idl::earn_pool::types::UFixValue64;
+  use hylo_idl::events::event_cpi_data;
+
+  use super::*;
+
+  fn hyusd(bits: u64) -> UFix64<N6> {
+    UFix64::new(bits)
+  }
+
+  fn event(flow: PositionFlow) -> PositionEvent {
+    PositionEvent {
+      signature: Signature::default(),
+      slot: 0,
+      flow,
+    }
+  }
+
+  fn deposit(hyusd_in: u64, minted: u64) -> PositionEvent {
+    event(PositionFlow::Deposit {
+      stablecoin_deposited: hyusd(hyusd_in),
+      lp_token_minted: hyusd(minted),
+      lp_token_nav: hyusd(hyusd_in * 1_000_000 / minted),
+    })
+  }
+
+  fn withdraw(burned: u64, out: u64, fees: u64) -> PositionEvent {
+    event(PositionFlow::Withdraw {
+      lp_token_burned: hyusd(burned),
+      stablecoin_withdrawn: hyusd(out),
+      stablecoin_fees: hyusd(fees),
+    })
+  }
+
+  #[test]
+  fn withdrawal_realizes_at_average_cost() -> Result<()> {
+    // 3,000 sHYUSD for 3,100 hyUSD, half burned at NAV 1.10 less 0.1%
+    let ledger = PositionLedger::replay(&[
+      deposit(1_000_000_000, 1_000_000_000),
+      deposit(2_100_000_000, 2_000_000_000),
+      withdraw(1_500_000_000, 1_648_350_000, 1_650_000),
+    ])?;
+    assert_eq!(ledger.shares, hyusd(1_500_000_000));
+    assert_eq!(ledger.cost_basis, hyusd(1_550_000_000));
+    assert_eq!(ledger.realized_cost, hyusd(1_550_000_000));
+    assert_eq!(ledger.fees_paid, hyusd(1_650_000));
+    assert_eq!(ledger.realized_yield()?, IFix64::new(100_000_000));
+    Ok(())
+  }
+
+  #[test]
+  fn untracked_shares_carry_no_yield() -> Result<()> {
+    // 100 sHYUSD deposited, 50 more received by transfer, 120 burned
+    let ledger = PositionLedger::replay(&[
+      deposit(100_000_000, 100_000_000),
+      withdraw(120_000_000, 132_000_000, 0),
+    ])?;
+    assert_eq!(ledger.shares, UFix64::zero());
+    assert_eq!(ledger.realized_proceeds, hyusd(110_000_000));
+    assert_eq!(ledger.realized_yield()?, IFix64::new(10_000_000));
+
+    let position = UserPosition::value(
+      Pubkey::new_unique(),
+      &ledger,
+      hyusd(30_000_000),
+      hyusd(1_100_000_000),
+      hyusd(1_000_000_000),
+      UFix64::new(10),
+    )?;
+    assert_eq!(position.untracked_shares, hyusd(30_000_000));
+    assert_eq!(position.current_value, hyusd(33_000_000));
+    assert_eq!(position.exit_fee, hyusd(33_000));
+    assert_eq!(position.unrealized_yield, IFix64::zero());
+    Ok(())
+  }
+
+  #[test]
+  fn loss_shows_as_negative_unrealized_yield() -> Result<()> {
+    let ledger =
+      PositionLedger::replay(&[deposit(1_050_000_000, 1_000_000_000)])?;
+    let position = UserPosition::value(
+      Pubkey::new_unique(),
+      &ledger,
+      hyusd(1_000_000_000),
+      hyusd(1_000_000_000),
+      hyusd(1_000_000_000),
+      UFix64::zero(),
+    )?;
+    assert_eq!(position.unrealized_yield, IFix64::new(-50_000_000));
+    assert_eq!(position.net_yield()?, IFix64::new(-50_000_000));
+    Ok(())
+  }
+
+  #[test]
+  fn decodes_pool_events() -> Result<()> {
+    let event = UserWithdrawEvent {
+      lp_token_burned: UFixValue64 {
+        bits: 10_000_000,
+        exp: -6,
+      },
+      stablecoin_withdrawn: UFixValue64 {
+        bits: 10_989_000,
+        exp: -6,
+      },
+      stablecoin_fees: UFixValue64 {
+        bits: 11_000,
+        exp: -6,
+      },
+    };
+    let data = event_cpi_data(&event);
+    let pool_event = EventCpi::parse(earn_pool::ID, &data).expect("event CPI");
+    assert_eq!(
+      PositionFlow::from_event(&pool_event)?,
+      Some(PositionFlow::Withdraw {
+        lp_token_burned: hyusd(10_000_000),
+        stablecoin_withdrawn: hyusd(10_989_000),
+        stablecoin_fees: hyusd(11_000),
+      })
+    );
+    let other_program = EventCpi::parse(Pubkey::new_unique(), &data);
+    assert_eq!(
+      PositionFlow::from_event(&other_program.expect("event CPI"))?,
+      None
+    );
+    Ok(())
+  }
+}
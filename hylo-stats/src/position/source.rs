@@ -0,0 +1,317 @@
+//! Pluggable sources of a wallet's earn pool deposits and withdrawals.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Discriminator;
+use anyhow::Result;
+use async_trait::async_trait;
+use fix::prelude::*;
+use hylo_idl::earn_pool::client::args::{UserDeposit, UserWithdraw};
+use hylo_idl::{earn_pool, pda};
+use serde::Deserialize;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+
+use super::{PositionEvent, PositionFlow};
+use crate::transactions::{
+  fetch_instructions, signatures_since, ExecutedInstruction,
+};
+
+/// Transaction history for a wallet's earn pool position.
+#[async_trait]
+pub trait PositionSource: Send + Sync {
+  /// Deposits and withdrawals made by `wallet`, oldest first.
+  ///
+  /// # Errors
+  /// * Source read or decode failure
+  async fn position_events(&self, wallet: Pubkey)
+    -> Result<Vec<PositionEvent>>;
+}
+
+#[async_trait]
+impl<T: PositionSource + ?Sized> PositionSource for Arc<T> {
+  async fn position_events(
+    &self,
+    wallet: Pubkey,
+  ) -> Result<Vec<PositionEvent>> {
+    (**self).position_events(wallet).await
+  }
+}
+
+/// Walks every successful transaction touching the wallet's sHYUSD
+/// token account and decodes the pool events emitted by the wallet's own
+/// deposits and withdrawals; see [`wallet_flows`]. One `getTransaction`
+/// per signature; cache results for repeated use.
+#[async_trait]
+impl PositionSource for RpcClient {
+  async fn position_events(
+    &self,
+    wallet: Pubkey,
+  ) -> Result<Vec<PositionEvent>> {
+    let account = pda::shyusd_ata(wallet);
+    let mut events = Vec::new();
+    for (signature, slot) in signatures_since(self, &account, 0).await? {
+      let instructions = fetch_instructions(self, &signature).await?;
+      for flow in wallet_flows(&instructions, &wallet)? {
+        events.push(PositionEvent {
+          signature,
+          slot,
+          flow,
+        });
+      }
+    }
+    Ok(events)
+  }
+}
+
+/// Pool events emitted on `wallet`'s behalf, in order. Pool events carry
+/// no user, so each is attributed to the `user` account of the earn pool
+/// deposit or withdraw instruction that emitted it; events of other
+/// depositors bundled in the same transaction are skipped.
+fn wallet_flows(
+  instructions: &[ExecutedInstruction],
+  wallet: &Pubkey,
+) -> Result<Vec<PositionFlow>> {
+  let mut user = None;
+  let mut flows = Vec::new();
+  for ix in instructions
+    .iter()
+    .filter(|ix| ix.program_id == earn_pool::ID)
+  {
+    if let Some(event) = ix.event() {
+      if user == Some(*wallet) {
+        flows.extend(PositionFlow::from_event(&event)?);
+      }
+    } else {
+      user = match ix.data.get(..8) {
+        Some(d)
+          if d == UserDeposit::DISCRIMINATOR
+            || d == UserWithdraw::DISCRIMINATOR =>
+        {
+          ix.accounts.first().copied()
+        }
+        _ => None,
+      };
+    }
+  }
+  Ok(flows)
+}
+
+/// In-process source, for tests and recorded histories.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryPositionSource {
+  events: HashMap<Pubkey, Vec<PositionEvent>>,
+}
+
+impl MemoryPositionSource {
+  /// Appends `event` to `wallet`'s history; add events oldest first.
+  #[must_use]
+  pub fn with_event(
+    mut self,
+    wallet: Pubkey,
+    event: PositionEvent,
+  ) -> MemoryPositionSource {
+    self.events.entry(wallet).or_default().push(event);
+    self
+  }
+}
+
+#[async_trait]
+impl PositionSource for MemoryPositionSource {
+  async fn position_events(
+    &self,
+    wallet: Pubkey,
+  ) -> Result<Vec<PositionEvent>> {
+    Ok(self.events.get(&wallet).cloned().unwrap_or_default())
+  }
+}
+
+/// Histories as a JSON object of base58 wallet to event array, oldest
+/// first. Wallets not in the file have no events.
+#[derive(Debug, Clone)]
+pub struct JsonFilePositionSource {
+  path: PathBuf,
+}
+
+impl JsonFilePositionSource {
+  #[must_use]
+  pub fn new(path: impl Into<PathBuf>) -> JsonFilePositionSource {
+    JsonFilePositionSource { path: path.into() }
+  }
+}
+
+#[async_trait]
+impl PositionSource for JsonFilePositionSource {
+  async fn position_events(
+    &self,
+    wallet: Pubkey,
+  ) -> Result<Vec<PositionEvent>> {
+    let file = BufReader::new(File::open(&self.path)?);
+    let mut stored: BTreeMap<String, Vec<StoredEvent>> =
+      serde_json::from_reader(file)?;
+    stored
+      .remove(&wallet.to_string())
+      .unwrap_or_default()
+      .into_iter()
+      .map(PositionEvent::try_from)
+      .collect()
+  }
+}
+
+/// Stored form of [`PositionEvent`], signature as base58.
+#[derive(Debug, Deserialize)]
+struct StoredEvent {
+  signature: String,
+  slot: u64,
+  #[serde(flatten)]
+  flow: StoredFlow,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum StoredFlow {
+  Deposit {
+    stablecoin_deposited: UFixValue64,
+    lp_token_minted: UFixValue64,
+    lp_token_nav: UFixValue64,
+  },
+  Withdraw {
+    lp_token_burned: UFixValue64,
+    stablecoin_withdrawn: UFixValue64,
+    stablecoin_fees: UFixValue64,
+  },
+}
+
+impl TryFrom<StoredEvent> for PositionEvent {
+  type Error = anyhow::Error;
+
+  fn try_from(stored: StoredEvent) -> Result<PositionEvent> {
+    let flow = match stored.flow {
+      StoredFlow::Deposit {
+        stablecoin_deposited,
+        lp_token_minted,
+        lp_token_nav,
+      } => PositionFlow::Deposit {
+        stablecoin_deposited: stablecoin_deposited.try_into()?,
+        lp_token_minted: lp_token_minted.try_into()?,
+        lp_token_nav: lp_token_nav.try_into()?,
+      },
+      StoredFlow::Withdraw {
+        lp_token_burned,
+        stablecoin_withdrawn,
+        stablecoin_fees,
+      } => PositionFlow::Withdraw {
+        lp_token_burned: lp_token_burned.try_into()?,
+        stablecoin_withdrawn: stablecoin_withdrawn.try_into()?,
+        stablecoin_fees: stablecoin_fees.try_into()?,
+      },
+    };
+    Ok(PositionEvent {
+      signature: Signature::from_str(&stored.signature)?,
+      slot: stored.slot,
+      flow,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use hylo_idl::earn_pool::events::{UserDepositEvent, UserWithdrawEvent};
+  use hylo_idl::earn_pool::types::UFixValue64 as EventValue;
+  use hylo_idl::events::event_cpi_data;
+
+  use super::*;
+
+  fn value(bits: u64) -> EventValue {
+    EventValue { bits, exp: -6 }
+  }
+
+  fn earn_pool_ix(accounts: Vec<Pubkey>, data: Vec<u8>) -> ExecutedInstruction {
+    ExecutedInstruction {
+      program_id: earn_pool::ID,
+      accounts,
+      data,
+    }
+  }
+
+  /// Earn pool `user_deposit` by `user` and the event it emits.
+  fn deposit(user: Pubkey, bits: u64) -> [ExecutedInstruction; 2] {
+    let event = UserDepositEvent {
+      stablecoin_deposited: value(bits),
+      lp_token_nav: value(1_000_000),
+      lp_token_minted: value(bits),
+    };
+    [
+      earn_pool_ix(vec![user], UserDeposit::DISCRIMINATOR.to_vec()),
+      earn_pool_ix(Vec::new(), event_cpi_data(&event)),
+    ]
+  }
+
+  /// Earn pool `user_withdraw` by `user` and the event it emits.
+  fn withdraw(user: Pubkey, bits: u64) -> [ExecutedInstruction; 2] {
+    let event = UserWithdrawEvent {
+      lp_token_burned: value(bits),
+      stablecoin_withdrawn: value(bits),
+      stablecoin_fees: value(0),
+    };
+    [
+      earn_pool_ix(vec![user], UserWithdraw::DISCRIMINATOR.to_vec()),
+      earn_pool_ix(Vec::new(), event_cpi_data(&event)),
+    ]
+  }
+
+  fn minted(bits: u64) -> PositionFlow {
+    PositionFlow::Deposit {
+      stablecoin_deposited: UFix64::new(bits),
+      lp_token_minted: UFix64::new(bits),
+      lp_token_nav: UFix64::one(),
+    }
+  }
+
+  #[test]
+  fn events_follow_the_emitting_instructions_user() -> Result<()> {
+    let wallet = Pubkey::new_unique();
+    let other = Pubkey::new_unique();
+    // Equal deposits by two users, then a withdrawal by the wallet
+    let instructions = [
+      deposit(other, 3_000_000),
+      deposit(wallet, 3_000_000),
+      withdraw(wallet, 3_000_000),
+    ]
+    .concat();
+    assert_eq!(
+      wallet_flows(&instructions, &wallet)?,
+      [
+        minted(3_000_000),
+        PositionFlow::Withdraw {
+          lp_token_burned: UFix64::new(3_000_000),
+          stablecoin_withdrawn: UFix64::new(3_000_000),
+          stablecoin_fees: UFix64::zero(),
+        },
+      ]
+    );
+    assert_eq!(wallet_flows(&instructions, &other)?, [minted(3_000_000)]);
+    Ok(())
+  }
+
+  #[test]
+  fn events_after_other_pool_instructions_are_unattributed() -> Result<()> {
+    let wallet = Pubkey::new_unique();
+    let [deposit_ix, event_ix] = deposit(wallet, 1_000_000);
+    // Another pool instruction naming the wallet first emits an event
+    let unrelated = earn_pool_ix(vec![wallet], vec![0; 8]);
+    let foreign = ExecutedInstruction {
+      program_id: Pubkey::new_unique(),
+      ..event_ix.clone()
+    };
+    let instructions = [deposit_ix, foreign, unrelated, event_ix];
+    assert!(wallet_flows(&instructions, &wallet)?.is_empty());
+    Ok(())
+  }
+}
@@ -0,0 +1,322 @@
+//! Protocol-wide TVL and solvency overview: collateral value, hyUSD
+//! backing by virtual stablecoin ledger, collateral ratio and rebalance
+//! mode per pair, and outstanding pool drawdowns. Serializes to JSON for
+//! dashboards, fixed-point values as `{ bits, exp }`.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use fix::prelude::*;
+use fix::typenum::Integer;
+use hylo_core::exchange_context::ExchangeContext;
+use hylo_core::rebalance::mode::RebalanceMode;
+use hylo_core::rebalance::pool_drawdown::PoolDrawdown;
+use hylo_core::virtual_stablecoin::VirtualStablecoin;
+use serde::{Serialize, Serializer};
+
+use crate::error::StatsError::{IncompleteOverview, OverviewOverflow};
+
+/// One collateral pair's state.
+///
+/// * `collateral_mint` — `None` for the LST pair
+/// * `tvl` — collateral at the lower-bound oracle price
+/// * `virtual_stablecoin_supply` — hyUSD minted against this pair
+/// * `levercoin_market_cap` — `None` when the levercoin has no NAV, as in
+///   Depeg
+/// * `outstanding_drawdown` — earn pool hyUSD the pair has yet to repay
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PairOverview {
+  #[serde(serialize_with = "opt_pubkey")]
+  pub collateral_mint: Option<Pubkey>,
+  #[serde(serialize_with = "pubkey")]
+  pub levercoin_mint: Pubkey,
+  #[serde(serialize_with = "ufix")]
+  pub total_collateral: UFix64<N9>,
+  #[serde(serialize_with = "ufix")]
+  pub tvl: UFix64<N9>,
+  #[serde(serialize_with = "ufix")]
+  pub virtual_stablecoin_supply: UFix64<N6>,
+  #[serde(serialize_with = "opt_ufix")]
+  pub levercoin_market_cap: Option<UFix64<N9>>,
+  #[serde(serialize_with = "ufix")]
+  pub collateral_ratio: UFix64<N9>,
+  #[serde(serialize_with = "mode")]
+  pub rebalance_mode: RebalanceMode,
+  #[serde(serialize_with = "ufix")]
+  pub outstanding_drawdown: UFix64<N6>,
+}
+
+impl PairOverview {
+  /// Reads a pair's state from its exchange context.
+  ///
+  /// # Errors
+  /// * Collateral valuation or arithmetic failure
+  /// * Invalid drawdown ledger
+  pub fn from_context<X: ExchangeContext>(
+    ctx: &X,
+    collateral_mint: Option<Pubkey>,
+    levercoin_mint: Pubkey,
+    pool_drawdown: &PoolDrawdown,
+  ) -> Result<PairOverview> {
+    Ok(PairOverview {
+      collateral_mint,
+      levercoin_mint,
+      total_collateral: ctx.total_collateral(),
+      tvl: ctx.total_value_locked()?,
+      virtual_stablecoin_supply: ctx.virtual_stablecoin_supply()?,
+      levercoin_market_cap: ctx.levercoin_market_cap().ok(),
+      collateral_ratio: ctx.collateral_ratio(),
+      rebalance_mode: ctx.rebalance_mode(),
+      outstanding_drawdown: pool_drawdown.outstanding()?,
+    })
+  }
+}
+
+/// USDC vault and the hyUSD minted against it.
+///
+/// * `tvl` — vault balance at the USDC/USD oracle spot
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct UsdcOverview {
+  #[serde(serialize_with = "ufix")]
+  pub vault_balance: UFix64<N6>,
+  #[serde(serialize_with = "ufix")]
+  pub usdc_usd_spot: UFix64<N9>,
+  #[serde(serialize_with = "ufix")]
+  pub tvl: UFix64<N9>,
+  #[serde(serialize_with = "ufix")]
+  pub virtual_stablecoin_supply: UFix64<N6>,
+}
+
+/// Protocol-wide state at one slot.
+///
+/// * `hyusd_supply` — hyUSD mint supply
+/// * `virtual_stablecoin_total` — LST, USDC and exo ledgers backing it
+/// * `total_tvl` — LST, exo and USDC collateral in USD
+/// * `total_outstanding_drawdown` — across the LST and exo pairs
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtocolOverview {
+  pub epoch: u64,
+  pub unix_timestamp: i64,
+  #[serde(serialize_with = "ufix")]
+  pub hyusd_supply: UFix64<N6>,
+  #[serde(serialize_with = "ufix")]
+  pub virtual_stablecoin_total: UFix64<N6>,
+  #[serde(serialize_with = "ufix")]
+  pub total_tvl: UFix64<N9>,
+  #[serde(serialize_with = "ufix")]
+  pub total_outstanding_drawdown: UFix64<N6>,
+  pub lst: PairOverview,
+  pub usdc: UsdcOverview,
+  pub exo: Vec<PairOverview>,
+}
+
+impl ProtocolOverview {
+  /// Pretty-printed JSON for dashboards.
+  ///
+  /// # Errors
+  /// * Serialization failure
+  pub fn to_json(&self) -> Result<String> {
+    Ok(serde_json::to_string_pretty(self)?)
+  }
+}
+
+/// Assembles a [`ProtocolOverview`] pair by pair.
+#[derive(Debug, Clone)]
+pub struct ProtocolOverviewBuilder {
+  epoch: u64,
+  unix_timestamp: i64,
+  hyusd_supply: UFix64<N6>,
+  lst: Option<PairOverview>,
+  usdc: Option<UsdcOverview>,
+  exo: Vec<PairOverview>,
+}
+
+impl ProtocolOverviewBuilder {
+  #[must_use]
+  pub fn new(
+    epoch: u64,
+    unix_timestamp: i64,
+    hyusd_supply: UFix64<N6>,
+  ) -> ProtocolOverviewBuilder {
+    ProtocolOverviewBuilder {
+      epoch,
+      unix_timestamp,
+      hyusd_supply,
+      lst: None,
+      usdc: None,
+      exo: Vec::new(),
+    }
+  }
+
+  #[must_use]
+  pub fn with_lst(mut self, lst: PairOverview) -> ProtocolOverviewBuilder {
+    self.lst = Some(lst);
+    self
+  }
+
+  /// # Errors
+  /// * Invalid virtual stablecoin ledger
+  /// * Arithmetic overflow valuing the vault
+  pub fn with_usdc(
+    mut self,
+    vault_balance: UFix64<N6>,
+    usdc_usd_spot: UFix64<N9>,
+    virtual_stablecoin: &VirtualStablecoin,
+  ) -> Result<ProtocolOverviewBuilder> {
+    let tvl = vault_balance
+      .checked_convert::<N9>()
+      .and_then(|balance| balance.mul_div_floor(usdc_usd_spot, UFix64::one()))
+      .ok_or(OverviewOverflow)?;
+    self.usdc = Some(UsdcOverview {
+      vault_balance,
+      usdc_usd_spot,
+      tvl,
+      virtual_stablecoin_supply: virtual_stablecoin.supply()?,
+    });
+    Ok(self)
+  }
+
+  #[must_use]
+  pub fn with_exo(mut self, exo: PairOverview) -> ProtocolOverviewBuilder {
+    self.exo.push(exo);
+    self
+  }
+
+  /// # Errors
+  /// * LST or USDC pair not set
+  /// * Arithmetic overflow summing totals
+  pub fn build(self) -> Result<ProtocolOverview> {
+    let lst = self.lst.ok_or(IncompleteOverview("lst"))?;
+    let usdc = self.usdc.ok_or(IncompleteOverview("usdc"))?;
+    let pairs = || std::iter::once(&lst).chain(&self.exo);
+    let virtual_stablecoin_total = pairs()
+      .try_fold(usdc.virtual_stablecoin_supply, |acc, pair| {
+        acc.checked_add(&pair.virtual_stablecoin_supply)
+      })
+      .ok_or(OverviewOverflow)?;
+    let total_tvl = pairs()
+      .try_fold(usdc.tvl, |acc, pair| acc.checked_add(&pair.tvl))
+      .ok_or(OverviewOverflow)?;
+    let total_outstanding_drawdown = pairs()
+      .try_fold(UFix64::zero(), |acc: UFix64<N6>, pair| {
+        acc.checked_add(&pair.outstanding_drawdown)
+      })
+      .ok_or(OverviewOverflow)?;
+    Ok(ProtocolOverview {
+      epoch: self.epoch,
+      unix_timestamp: self.unix_timestamp,
+      hyusd_supply: self.hyusd_supply,
+      virtual_stablecoin_total,
+      total_tvl,
+      total_outstanding_drawdown,
+      lst,
+      usdc,
+      exo: self.exo,
+    })
+  }
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn ufix<Exp: Integer, S: Serializer>(
+  value: &UFix64<Exp>,
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  UFixValue64::from(*value).serialize(serializer)
+}
+
+#[allow(clippy::ref_option)]
+fn opt_ufix<Exp: Integer, S: Serializer>(
+  value: &Option<UFix64<Exp>>,
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  value.map(UFixValue64::from).serialize(serializer)
+}
+
+fn pubkey<S: Serializer>(
+  key: &Pubkey,
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  serializer.collect_str(key)
+}
+
+#[allow(clippy::ref_option)]
+fn opt_pubkey<S: Serializer>(
+  key: &Option<Pubkey>,
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  match key {
+    Some(key) => serializer.collect_str(key),
+    None => serializer.serialize_none(),
+  }
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn mode<S: Serializer>(
+  mode: &RebalanceMode,
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  serializer.collect_str(mode)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn pair(collateral_mint: Option<Pubkey>, tvl: u64) -> PairOverview {
+    PairOverview {
+      collateral_mint,
+      levercoin_mint: Pubkey::new_unique(),
+      total_collateral: UFix64::new(tvl),
+      tvl: UFix64::new(tvl),
+      virtual_stablecoin_supply: UFix64::new(tvl / 2_000),
+      levercoin_market_cap: Some(UFix64::new(tvl / 2)),
+      collateral_ratio: UFix64::new(2_000_000_000),
+      rebalance_mode: RebalanceMode::BuyZone2,
+      outstanding_drawdown: UFix64::new(1_000_000),
+    }
+  }
+
+  #[test]
+  fn build_sums_every_pair() -> Result<()> {
+    let overview =
+      ProtocolOverviewBuilder::new(800, 1_700_000_000, UFix64::new(0))
+        .with_lst(pair(None, 10_000_000_000_000_000))
+        .with_exo(pair(Some(Pubkey::new_unique()), 2_000_000_000_000_000))
+        .with_usdc(
+          UFix64::new(500_000_000_000),
+          UFix64::new(999_800_000),
+          &VirtualStablecoin::default(),
+        )?
+        .build()?;
+    // $10M LST + $2M exo + 500k USDC at $0.9998
+    assert_eq!(overview.usdc.tvl, UFix64::new(499_900_000_000_000));
+    assert_eq!(overview.total_tvl, UFix64::new(12_499_900_000_000_000));
+    // $5M + $1M ledgers, none against USDC
+    assert_eq!(
+      overview.virtual_stablecoin_total,
+      UFix64::new(6_000_000_000_000)
+    );
+    assert_eq!(overview.total_outstanding_drawdown, UFix64::new(2_000_000));
+    Ok(())
+  }
+
+  #[test]
+  fn build_requires_lst_and_usdc() {
+    let builder = ProtocolOverviewBuilder::new(800, 0, UFix64::zero());
+    assert!(builder.clone().build().is_err());
+    assert!(builder.with_lst(pair(None, 1)).build().is_err());
+  }
+
+  #[test]
+  fn json_encodes_fix_and_mode() -> Result<()> {
+    let json = serde_json::to_value(pair(None, 1_000))?;
+    assert_eq!(json["tvl"], serde_json::json!({ "bits": 1_000, "exp": -9 }));
+    assert_eq!(json["rebalance_mode"], "BuyZone2");
+    assert!(json["collateral_mint"].is_null());
+    let depeg = PairOverview {
+      levercoin_market_cap: None,
+      ..pair(None, 1_000)
+    };
+    assert!(serde_json::to_value(depeg)?["levercoin_market_cap"].is_null());
+    Ok(())
+  }
+}
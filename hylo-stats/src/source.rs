@@ -0,0 +1,203 @@
+//! Account and block-time sources behind [`StatsClient`].
+//!
+//! [`StatsClient`]: crate::client::StatsClient
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use anchor_client::solana_sdk::account::Account;
+use anchor_client::solana_sdk::epoch_schedule::EpochSchedule;
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use async_trait::async_trait;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+
+use crate::error::StatsError::MissingBlockTime;
+
+/// The RPC reads stats need: accounts, the epoch schedule, and block
+/// times at epoch boundaries.
+#[async_trait]
+pub trait StatsSource: Send + Sync {
+  /// Accounts for `keys` in order, `None` where absent.
+  ///
+  /// # Errors
+  /// * Source read failure
+  async fn get_multiple_accounts(
+    &self,
+    keys: &[Pubkey],
+  ) -> Result<Vec<Option<Account>>>;
+
+  /// # Errors
+  /// * Source read failure
+  async fn get_epoch_schedule(&self) -> Result<EpochSchedule>;
+
+  /// Up to `limit` confirmed block slots starting at `start_slot`.
+  ///
+  /// # Errors
+  /// * Source read failure
+  async fn get_blocks_with_limit(
+    &self,
+    start_slot: u64,
+    limit: usize,
+  ) -> Result<Vec<u64>>;
+
+  /// # Errors
+  /// * Source read failure or unknown block
+  async fn get_block_time(&self, slot: u64) -> Result<i64>;
+}
+
+#[async_trait]
+impl<T: StatsSource + ?Sized> StatsSource for Arc<T> {
+  async fn get_multiple_accounts(
+    &self,
+    keys: &[Pubkey],
+  ) -> Result<Vec<Option<Account>>> {
+    (**self).get_multiple_accounts(keys).await
+  }
+
+  async fn get_epoch_schedule(&self) -> Result<EpochSchedule> {
+    (**self).get_epoch_schedule().await
+  }
+
+  async fn get_blocks_with_limit(
+    &self,
+    start_slot: u64,
+    limit: usize,
+  ) -> Result<Vec<u64>> {
+    (**self).get_blocks_with_limit(start_slot, limit).await
+  }
+
+  async fn get_block_time(&self, slot: u64) -> Result<i64> {
+    (**self).get_block_time(slot).await
+  }
+}
+
+#[async_trait]
+impl StatsSource for RpcClient {
+  async fn get_multiple_accounts(
+    &self,
+    keys: &[Pubkey],
+  ) -> Result<Vec<Option<Account>>> {
+    Ok(RpcClient::get_multiple_accounts(self, keys).await?)
+  }
+
+  async fn get_epoch_schedule(&self) -> Result<EpochSchedule> {
+    Ok(RpcClient::get_epoch_schedule(self).await?)
+  }
+
+  async fn get_blocks_with_limit(
+    &self,
+    start_slot: u64,
+    limit: usize,
+  ) -> Result<Vec<u64>> {
+    Ok(RpcClient::get_blocks_with_limit(self, start_slot, limit).await?)
+  }
+
+  async fn get_block_time(&self, slot: u64) -> Result<i64> {
+    Ok(RpcClient::get_block_time(self, slot).await?)
+  }
+}
+
+/// In-memory source for tests and recorded snapshots. Slots without a
+/// block time are treated as skipped.
+#[derive(Debug, Clone)]
+pub struct MemorySource {
+  accounts: HashMap<Pubkey, Account>,
+  epoch_schedule: EpochSchedule,
+  block_times: BTreeMap<u64, i64>,
+}
+
+impl Default for MemorySource {
+  fn default() -> MemorySource {
+    MemorySource {
+      accounts: HashMap::new(),
+      epoch_schedule: EpochSchedule::without_warmup(),
+      block_times: BTreeMap::new(),
+    }
+  }
+}
+
+impl MemorySource {
+  #[must_use]
+  pub fn with_account(mut self, key: Pubkey, account: Account) -> MemorySource {
+    self.accounts.insert(key, account);
+    self
+  }
+
+  /// Adds every present entry of a `get_multiple_accounts` result.
+  #[must_use]
+  pub fn with_fetched(
+    mut self,
+    keys: &[Pubkey],
+    fetched: Vec<Option<Account>>,
+  ) -> MemorySource {
+    self.accounts.extend(
+      keys
+        .iter()
+        .zip(fetched)
+        .filter_map(|(key, account)| Some((*key, account?))),
+    );
+    self
+  }
+
+  #[must_use]
+  pub fn with_epoch_schedule(
+    mut self,
+    epoch_schedule: EpochSchedule,
+  ) -> MemorySource {
+    self.epoch_schedule = epoch_schedule;
+    self
+  }
+
+  #[must_use]
+  pub fn with_block_time(
+    mut self,
+    slot: u64,
+    unix_timestamp: i64,
+  ) -> MemorySource {
+    self.block_times.insert(slot, unix_timestamp);
+    self
+  }
+}
+
+#[async_trait]
+impl StatsSource for MemorySource {
+  async fn get_multiple_accounts(
+    &self,
+    keys: &[Pubkey],
+  ) -> Result<Vec<Option<Account>>> {
+    Ok(
+      keys
+        .iter()
+        .map(|key| self.accounts.get(key).cloned())
+        .collect(),
+    )
+  }
+
+  async fn get_epoch_schedule(&self) -> Result<EpochSchedule> {
+    Ok(self.epoch_schedule.clone())
+  }
+
+  async fn get_blocks_with_limit(
+    &self,
+    start_slot: u64,
+    limit: usize,
+  ) -> Result<Vec<u64>> {
+    Ok(
+      self
+        .block_times
+        .range(start_slot..)
+        .map(|(slot, _)| *slot)
+        .take(limit)
+        .collect(),
+    )
+  }
+
+  async fn get_block_time(&self, slot: u64) -> Result<i64> {
+    self
+      .block_times
+      .get(&slot)
+      .copied()
+      .ok_or_else(|| MissingBlockTime(slot).into())
+  }
+}
@@ -0,0 +1,237 @@
+//! Transaction history of an account over RPC, and the instructions of
+//! each transaction in execution order with program and accounts resolved
+//! against the transaction's account keys.
+
+use std::str::FromStr;
+
+use anchor_client::solana_sdk::bs58;
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Result};
+use hylo_idl::events::EventCpi;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_rpc_client_api::config::RpcTransactionConfig;
+use solana_transaction_status_client_types::option_serializer::OptionSerializer;
+use solana_transaction_status_client_types::{
+  EncodedTransaction, EncodedTransactionWithStatusMeta, UiCompiledInstruction,
+  UiInstruction, UiMessage, UiTransactionEncoding,
+};
+
+/// Signatures requested per `getSignaturesForAddress` page.
+const SIGNATURE_PAGE: usize = 1_000;
+
+/// Successful transactions touching `address` from `since_slot` on, as
+/// `(signature, slot)`, oldest first.
+///
+/// # Errors
+/// * RPC failure or malformed signature
+pub(crate) async fn signatures_since(
+  client: &RpcClient,
+  address: &Pubkey,
+  since_slot: u64,
+) -> Result<Vec<(Signature, u64)>> {
+  let mut signatures = Vec::new();
+  let mut before = None;
+  loop {
+    let page = client
+      .get_signatures_for_address_with_config(
+        address,
+        GetConfirmedSignaturesForAddress2Config {
+          before,
+          limit: Some(SIGNATURE_PAGE),
+          commitment: Some(CommitmentConfig::confirmed()),
+          ..Default::default()
+        },
+      )
+      .await?;
+    let full = page.len() == SIGNATURE_PAGE;
+    for status in page {
+      if status.slot < since_slot {
+        signatures.reverse();
+        return Ok(signatures);
+      }
+      let signature = Signature::from_str(&status.signature)?;
+      before = Some(signature);
+      if status.err.is_none() {
+        signatures.push((signature, status.slot));
+      }
+    }
+    if !full {
+      signatures.reverse();
+      return Ok(signatures);
+    }
+  }
+}
+
+/// Instructions of the confirmed transaction `signature`; see
+/// [`executed_instructions`].
+///
+/// # Errors
+/// * RPC failure or undecodable transaction
+pub(crate) async fn fetch_instructions(
+  client: &RpcClient,
+  signature: &Signature,
+) -> Result<Vec<ExecutedInstruction>> {
+  let tx = client
+    .get_transaction_with_config(
+      signature,
+      RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Json),
+        commitment: Some(CommitmentConfig::confirmed()),
+        max_supported_transaction_version: Some(0),
+      },
+    )
+    .await?;
+  executed_instructions(&tx.transaction)
+}
+
+/// One executed instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ExecutedInstruction {
+  pub program_id: Pubkey,
+  pub accounts: Vec<Pubkey>,
+  pub data: Vec<u8>,
+}
+
+impl ExecutedInstruction {
+  /// Event carried by this instruction, if it is an event CPI.
+  pub fn event(&self) -> Option<EventCpi<'_>> {
+    EventCpi::parse(self.program_id, &self.data)
+  }
+}
+
+/// Each outer instruction of `tx` followed by its inner instructions, in
+/// execution order. Keys loaded from lookup tables follow the static keys,
+/// writable first.
+///
+/// # Errors
+/// * Transaction not JSON encoded with raw instructions
+/// * Malformed key, account index or instruction data
+pub(crate) fn executed_instructions(
+  tx: &EncodedTransactionWithStatusMeta,
+) -> Result<Vec<ExecutedInstruction>> {
+  let EncodedTransaction::Json(ui) = &tx.transaction else {
+    return Err(anyhow!("Transaction is not JSON encoded"));
+  };
+  let UiMessage::Raw(message) = &ui.message else {
+    return Err(anyhow!("Transaction message is parsed, not raw"));
+  };
+  let meta = tx.meta.as_ref();
+  let loaded = meta.and_then(|meta| match &meta.loaded_addresses {
+    OptionSerializer::Some(loaded) => Some(loaded),
+    _ => None,
+  });
+  let keys = message
+    .account_keys
+    .iter()
+    .chain(loaded.iter().flat_map(|loaded| &loaded.writable))
+    .chain(loaded.iter().flat_map(|loaded| &loaded.readonly))
+    .map(|key| Pubkey::from_str(key))
+    .collect::<Result<Vec<_>, _>>()?;
+  let inner = match meta.map(|meta| &meta.inner_instructions) {
+    Some(OptionSerializer::Some(inner)) => inner.as_slice(),
+    _ => &[],
+  };
+
+  let mut executed = Vec::new();
+  for (index, outer) in message.instructions.iter().enumerate() {
+    executed.push(resolve(&keys, outer)?);
+    let group = inner
+      .iter()
+      .filter(|group| usize::from(group.index) == index)
+      .flat_map(|group| &group.instructions);
+    for ix in group {
+      let UiInstruction::Compiled(compiled) = ix else {
+        return Err(anyhow!("Inner instruction is parsed, not compiled"));
+      };
+      executed.push(resolve(&keys, compiled)?);
+    }
+  }
+  Ok(executed)
+}
+
+fn resolve(
+  keys: &[Pubkey],
+  ix: &UiCompiledInstruction,
+) -> Result<ExecutedInstruction> {
+  let key = |index: u8| {
+    keys
+      .get(usize::from(index))
+      .copied()
+      .ok_or_else(|| anyhow!("Account index {index} out of range"))
+  };
+  Ok(ExecutedInstruction {
+    program_id: key(ix.program_id_index)?,
+    accounts: ix
+      .accounts
+      .iter()
+      .map(|index| key(*index))
+      .collect::<Result<_>>()?,
+    data: bs58::decode(&ix.data).into_vec()?,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn inner_instructions_follow_their_outer_instruction() -> Result<()> {
+    let [payer, program, lookup, other] =
+      std::array::from_fn::<_, 4, _>(|_| Pubkey::new_unique());
+    let data = |bytes: &[u8]| bs58::encode(bytes).into_string();
+    let tx: EncodedTransactionWithStatusMeta = serde_json::from_value(json!({
+      "transaction": {
+        "signatures": [],
+        "message": {
+          "header": {
+            "numRequiredSignatures": 1,
+            "numReadonlySignedAccounts": 0,
+            "numReadonlyUnsignedAccounts": 1,
+          },
+          "accountKeys": [payer.to_string(), program.to_string()],
+          "recentBlockhash": "11111111111111111111111111111111",
+          "instructions": [
+            {"programIdIndex": 1, "accounts": [0, 2], "data": data(&[1])},
+            {"programIdIndex": 3, "accounts": [], "data": data(&[2])},
+          ],
+        },
+      },
+      "meta": {
+        "err": null,
+        "status": {"Ok": null},
+        "fee": 5000,
+        "preBalances": [],
+        "postBalances": [],
+        "innerInstructions": [
+          {"index": 1, "instructions": [
+            {"programIdIndex": 1, "accounts": [2], "data": data(&[4])},
+          ]},
+          {"index": 0, "instructions": [
+            {"programIdIndex": 1, "accounts": [], "data": data(&[3])},
+          ]},
+        ],
+        "loadedAddresses": {
+          "writable": [lookup.to_string()],
+          "readonly": [other.to_string()],
+        },
+      },
+    }))?;
+    let executed = executed_instructions(&tx)?;
+    let order = executed
+      .iter()
+      .map(|ix| (ix.program_id, ix.data[0]))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      order,
+      [(program, 1), (program, 3), (other, 2), (program, 4)]
+    );
+    assert_eq!(executed[0].accounts, [payer, lookup]);
+    assert_eq!(executed[3].accounts, [lookup]);
+    Ok(())
+  }
+}
@@ -1,10 +1,13 @@
-//! Data types for earn pool yield statistics.
+//! Data types for earn pool and levercoin statistics.
 
 use anchor_lang::prelude::Pubkey;
 use fix::prelude::*;
 use hylo_core::borrow_rate::BorrowRateConfig;
+use hylo_core::rebalance::mode::RebalanceMode;
 use hylo_core::yields::{HarvestCache, YieldHarvestConfig};
 
+use crate::epochs_per_year::EpochsPerYear;
+
 /// Snapshot of one harvest stream from its onchain [`HarvestCache`]:
 /// the most recent harvest's epoch, the hyUSD it deposited into the
 /// pool, and staleness (no harvest yet for the current epoch).
@@ -45,7 +48,7 @@ pub struct StatsInputs {
   pub exo_snapshots: Vec<ExoSnapshot>,
   pub sol_usd_spot: UFix64<N9>,
   pub outstanding_drawdown: UFix64<N6>,
-  pub epochs_per_year: f64,
+  pub epochs_per_year: EpochsPerYear,
 }
 
 /// Per-stream results for one exo borrow-rate stream, labeled by its
@@ -58,12 +61,21 @@ pub struct ExoStats {
   pub projected_inflow: UFix64<N6>,
 }
 
+/// An APY annualized at the low and high ends of an
+/// [`EpochsPerYear`] band.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ApyBand {
+  pub low: f64,
+  pub high: f64,
+}
+
 /// Earn pool yield statistics for sHYUSD.
 ///
 /// * `nav` — hyUSD per sHYUSD
 /// * `pool_balance` — current hyUSD in the pool, the denominator for both yield
 ///   rates
-/// * `epochs_per_year` — annualization basis used for both APYs
+/// * `epochs_per_year` — annualization basis used for both APYs, with the
+///   estimator behind it and its confidence band
 /// * `lst_harvest` — LST staking-yield stream (`harvest_yield`); `exo_stats` —
 ///   exo borrow-rate streams (`harvest_borrow_rate`), one per pair
 /// * `last_epoch_yield_rate` — sum of the LST stream plus all exo streams at
@@ -71,20 +83,91 @@ pub struct ExoStats {
 ///   last_epoch_yield_rate)^epochs_per_year - 1`
 /// * `projected_epoch_rate` — net projected inflow next epoch (LST + exo, minus
 ///   outstanding drawdown) over the pool; `projected_apy` — its annualization
+/// * `naive_apy_band`, `projected_apy_band` — both APYs across the
+///   `epochs_per_year` band
 #[derive(Debug, Clone)]
 pub struct EarnPoolStats {
   pub nav: UFix64<N6>,
   pub pool_balance: UFix64<N6>,
   pub shyusd_supply: UFix64<N6>,
   pub current_epoch: u64,
-  pub epochs_per_year: f64,
+  pub epochs_per_year: EpochsPerYear,
   pub lst_harvest: RealizedHarvest,
   pub exo_stats: Vec<ExoStats>,
   pub last_epoch_yield_rate: UFix64<N9>,
   pub naive_apy: f64,
+  pub naive_apy_band: ApyBand,
   pub projected_lst_inflow: UFix64<N6>,
   pub projected_exo_inflow: UFix64<N6>,
   pub outstanding_drawdown: UFix64<N6>,
   pub projected_epoch_rate: UFix64<N9>,
   pub projected_apy: f64,
+  pub projected_apy_band: ApyBand,
+}
+
+/// How far collateral can fall before the pair enters
+/// [`RebalanceMode::Depeg`], holding stablecoin supply fixed.
+///
+/// * `depeg_cr` — collateral ratio at which Depeg begins
+/// * `collateral_price` — collateral/USD price (lower bound) at `depeg_cr`
+/// * `price_drop` — fractional fall from the current lower-bound price to
+///   `collateral_price`; zero once in Depeg
+#[derive(Debug, Clone, Copy)]
+pub struct DepegDistance {
+  pub depeg_cr: UFix64<N9>,
+  pub collateral_price: UFix64<N9>,
+  pub price_drop: f64,
+}
+
+/// Remaining room under an exo pair's levercoin market cap limit.
+#[derive(Debug, Clone, Copy)]
+pub struct MarketCapHeadroom {
+  pub limit: UFix64<N9>,
+  pub remaining_usd: UFix64<N9>,
+  pub remaining_levercoin: UFix64<N6>,
+}
+
+/// Borrow rate charged against an exo levercoin's market cap and
+/// harvested into the earn pool.
+///
+/// * `per_epoch_rate` — configured rate per epoch
+/// * `per_epoch_usd` — USD charged next epoch at the current market cap
+/// * `annualized` — `(1 + per_epoch_rate)^epochs_per_year - 1`, the share of
+///   market cap holders give up over a year
+#[derive(Debug, Clone, Copy)]
+pub struct FundingCost {
+  pub per_epoch_rate: UFix64<N9>,
+  pub per_epoch_usd: UFix64<N9>,
+  pub annualized: f64,
+}
+
+/// Levercoin statistics for xSOL or one exo levercoin.
+///
+/// * `mint_nav`, `redeem_nav` — upper and lower NAV bounds from the oracle
+///   confidence interval, `None` where the pair's state leaves them
+///   undefined; `market_cap` — supply at `mint_nav`
+/// * `collateral_value` — collateral at the lower-bound price (TVL)
+/// * `effective_leverage` — `collateral_value / market_cap`, `None` with no
+///   market cap
+/// * `market_cap_headroom`, `funding_cost` — exo pairs with a market cap
+///   only
+///
+/// Snapshots taken each epoch form the levercoin's NAV history.
+#[derive(Debug, Clone, Copy)]
+pub struct LevercoinStats {
+  pub levercoin_mint: Pubkey,
+  pub collateral_mint: Option<Pubkey>,
+  pub epoch: u64,
+  pub unix_timestamp: i64,
+  pub mint_nav: Option<UFix64<N9>>,
+  pub redeem_nav: Option<UFix64<N9>>,
+  pub levercoin_supply: UFix64<N6>,
+  pub market_cap: Option<UFix64<N9>>,
+  pub collateral_value: UFix64<N9>,
+  pub effective_leverage: Option<f64>,
+  pub collateral_ratio: UFix64<N9>,
+  pub rebalance_mode: RebalanceMode,
+  pub depeg: DepegDistance,
+  pub market_cap_headroom: Option<MarketCapHeadroom>,
+  pub funding_cost: Option<FundingCost>,
 }
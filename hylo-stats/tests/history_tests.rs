@@ -0,0 +1,219 @@
+//! Offline tests for sHYUSD yield history over generated pool events.
+//!
+//! [`pool_history`] replays epochs 700..=799 of a 950,000 sHYUSD pool with
+//! steady LST and two borrow-rate streams, a deposit or withdrawal quoting
+//! NAV each epoch except 750 (its flows land in 751) and a 2,000 hyUSD loss
+//! absorbed at 760. The events are read back through a
+//! [`MemoryPoolHistorySource`] from the first slot of epoch 700.
+
+use anchor_client::solana_sdk::epoch_schedule::EpochSchedule;
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Event;
+use anyhow::Result;
+use fix::prelude::*;
+use hylo_core::earn_pool_math::lp_token_nav;
+use hylo_idl::earn_pool;
+use hylo_idl::earn_pool::events::{
+  AbsorbLossEvent, UserDepositEvent, UserWithdrawEvent,
+};
+use hylo_idl::earn_pool::types::UFixValue64;
+use hylo_idl::events::{event_cpi_data, EventCpi};
+use hylo_stats::history::source::{MemoryPoolHistorySource, PoolHistorySource};
+use hylo_stats::history::store::{HistoryStore, JsonFileStore, MemoryStore};
+use hylo_stats::history::trailing::{
+  trailing_yields, TrailingYield, TRAILING_WINDOWS,
+};
+use hylo_stats::history::{EpochRecord, HistoryBuilder, PoolEvent, PoolFlow};
+
+const LST_YIELD: u64 = 500_000_000;
+const BORROW_RATE_YIELDS: [u64; 2] = [100_000_000, 60_000_000];
+
+fn ufix(value: UFix64<N6>) -> UFixValue64 {
+  UFixValue64 {
+    bits: value.bits,
+    exp: -6,
+  }
+}
+
+/// Earn pool event CPI data for `event`, decoded back into a flow.
+fn emit<E: Event>(event: &E) -> Result<PoolFlow> {
+  let data = event_cpi_data(event);
+  let event = EventCpi::parse(earn_pool::ID, &data)
+    .ok_or_else(|| anyhow::anyhow!("not an event CPI"))?;
+  PoolFlow::from_event(&event)?
+    .ok_or_else(|| anyhow::anyhow!("event decoded to no flow"))
+}
+
+/// Earn pool with 1,000,000 hyUSD against 950,000 sHYUSD before epoch 700.
+struct Pool {
+  balance: UFix64<N6>,
+  supply: UFix64<N6>,
+}
+
+impl Pool {
+  fn nav(&self) -> Result<UFix64<N6>> {
+    Ok(lp_token_nav(self.balance, self.supply)?)
+  }
+
+  fn harvest(&mut self, amount: u64) -> UFix64<N6> {
+    let amount = UFix64::new(amount);
+    self.balance += amount;
+    amount
+  }
+
+  fn deposit(&mut self, hyusd: UFix64<N6>) -> Result<PoolFlow> {
+    let nav = self.nav()?;
+    let minted = hyusd
+      .mul_div_floor(UFix64::one(), nav)
+      .ok_or_else(|| anyhow::anyhow!("mint overflow"))?;
+    self.balance += hyusd;
+    self.supply += minted;
+    emit(&UserDepositEvent {
+      stablecoin_deposited: ufix(hyusd),
+      lp_token_nav: ufix(nav),
+      lp_token_minted: ufix(minted),
+    })
+  }
+
+  /// Burns `shares` at NAV, paying a 0.1% fee out of the gross.
+  fn withdraw(&mut self, shares: UFix64<N6>) -> Result<PoolFlow> {
+    let gross = shares
+      .mul_div_floor(self.nav()?, UFix64::one())
+      .ok_or_else(|| anyhow::anyhow!("withdraw overflow"))?;
+    let fees = UFix64::new(gross.bits / 1_000);
+    self.balance -= gross;
+    self.supply -= shares;
+    emit(&UserWithdrawEvent {
+      lp_token_burned: ufix(shares),
+      stablecoin_withdrawn: ufix(gross - fees),
+      stablecoin_fees: ufix(fees),
+    })
+  }
+
+  fn absorb_loss(&mut self, loss: UFix64<N6>) -> Result<PoolFlow> {
+    self.balance -= loss;
+    emit(&AbsorbLossEvent {
+      requested_loss: ufix(loss),
+      amount_stablecoin_burned: ufix(loss),
+      remaining_pool_balance: ufix(self.balance),
+    })
+  }
+}
+
+async fn pool_history() -> Result<Vec<EpochRecord>> {
+  let schedule = EpochSchedule::without_warmup();
+  let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+  let mut pool = Pool {
+    balance: UFix64::new(1_000_000_000_000),
+    supply: UFix64::new(950_000_000_000),
+  };
+  let mut events = Vec::new();
+  let mut land = |epoch, flow| {
+    let slot = schedule.get_first_slot_in_epoch(epoch) + events.len() as u64;
+    events.push(PoolEvent {
+      signature: Signature::default(),
+      slot,
+      flow,
+    });
+  };
+  // Observed before the walked range
+  land(699, PoolFlow::Nav(pool.nav()?));
+  for epoch in 700..=799 {
+    land(epoch, PoolFlow::LstYield(pool.harvest(LST_YIELD)));
+    for (mint, amount) in mints.iter().zip(BORROW_RATE_YIELDS) {
+      land(
+        epoch,
+        PoolFlow::BorrowRate {
+          collateral_mint: *mint,
+          amount: pool.harvest(amount),
+        },
+      );
+    }
+    if epoch == 760 {
+      land(epoch, pool.absorb_loss(UFix64::new(2_000_000_000))?);
+    }
+    if epoch == 750 {
+      continue;
+    }
+    let observation = if epoch % 2 == 0 {
+      pool.deposit(UFix64::new(1_000_000_000))?
+    } else {
+      pool.withdraw(UFix64::new(900_000_000))?
+    };
+    land(epoch, observation);
+  }
+
+  let source = events
+    .into_iter()
+    .fold(MemoryPoolHistorySource::default(), |source, event| {
+      source.with_event(event)
+    });
+  let walked = source
+    .pool_events(schedule.get_first_slot_in_epoch(700))
+    .await?;
+  HistoryBuilder::new()
+    .with_events(&walked, &schedule)
+    .build()
+}
+
+#[tokio::test]
+async fn generated_trailing_yields() -> Result<()> {
+  let records = pool_history().await?;
+  assert_eq!(records.len(), 99);
+  let trailing = trailing_yields(&records, 182.0)?;
+  let windows = trailing.iter().map(|t| t.window).collect::<Vec<_>>();
+  assert_eq!(windows, TRAILING_WINDOWS);
+
+  let [week, month, quarter] = trailing.as_slice() else {
+    panic!("one result per window");
+  };
+  assert_eq!((week.epochs, month.epochs, quarter.epochs), (7, 30, 90));
+  assert!((week.apy - 0.1198).abs() < 1e-3, "7 epoch {}", week.apy);
+  assert!((month.apy - 0.1207).abs() < 1e-3, "30 epoch {}", month.apy);
+  assert!(
+    (quarter.apy - 0.1187).abs() < 1e-3,
+    "90 epoch {}",
+    quarter.apy
+  );
+
+  // 660 hyUSD per epoch across three streams
+  assert_eq!(week.harvested, UFix64::new(4_620_000_000));
+  assert_eq!(quarter.harvested, UFix64::new(59_400_000_000));
+  assert_eq!(week.loss_absorbed, UFix64::zero());
+  assert_eq!(month.loss_absorbed, UFix64::zero());
+  assert_eq!(quarter.loss_absorbed, UFix64::new(2_000_000_000));
+
+  // The loss epoch only shows up in the quarter's dispersion
+  let vol = |t: &TrailingYield| t.volatility.unwrap_or_default();
+  assert!(
+    vol(week) < vol(quarter),
+    "{} vs {}",
+    vol(week),
+    vol(quarter)
+  );
+  Ok(())
+}
+
+#[tokio::test]
+async fn history_round_trips_through_builder_and_store() -> Result<()> {
+  let records = pool_history().await?;
+  let rebuilt = HistoryBuilder::new()
+    .with_snapshots(records.clone())
+    .build()?;
+  assert_eq!(rebuilt, records);
+
+  let mut memory = MemoryStore::default();
+  memory.save(&records)?;
+  assert_eq!(memory.load()?, records);
+
+  let path = std::env::temp_dir()
+    .join(format!("shyusd-history-{}.json", std::process::id()));
+  let mut file = JsonFileStore::new(path.clone());
+  file.save(&records[..50])?;
+  file.save(&records[40..])?;
+  let reloaded = file.load()?;
+  std::fs::remove_file(&path)?;
+  assert_eq!(reloaded, records);
+  Ok(())
+}
@@ -0,0 +1,78 @@
+//! Offline tests for per-wallet sHYUSD positions against a stored fixture.
+//!
+//! `tests/data/shyusd_positions.json` has one wallet depositing 1,000
+//! hyUSD at NAV 1.00 and 2,100 at 1.05, then withdrawing half its
+//! sHYUSD at 1.10 less a 0.1% fee, and a second wallet's deposit.
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use fix::prelude::*;
+use hylo_stats::position::source::{
+  JsonFilePositionSource, MemoryPositionSource, PositionSource,
+};
+use hylo_stats::position::{PositionLedger, UserPosition};
+
+const WALLET: &str = "EqaWcyJEkk5Gdn51r63tWU6Rs3dP4wFA7ieq1BHJuyQv";
+
+fn fixture() -> JsonFilePositionSource {
+  JsonFilePositionSource::new(format!(
+    "{}/tests/data/shyusd_positions.json",
+    env!("CARGO_MANIFEST_DIR")
+  ))
+}
+
+fn hyusd(bits: u64) -> UFix64<N6> {
+  UFix64::new(bits)
+}
+
+#[tokio::test]
+async fn fixture_position_at_current_nav() -> Result<()> {
+  let wallet = Pubkey::from_str(WALLET)?;
+  let events = fixture().position_events(wallet).await?;
+  assert_eq!(events.len(), 3);
+  assert!(events.windows(2).all(|pair| pair[0].slot < pair[1].slot));
+
+  let ledger = PositionLedger::replay(&events)?;
+  assert_eq!(ledger.deposited, hyusd(3_100_000_000));
+  assert_eq!(ledger.withdrawn, hyusd(1_648_350_000));
+
+  // Pool at NAV 1.20 with a 10 bps withdrawal fee
+  let position = UserPosition::value(
+    wallet,
+    &ledger,
+    ledger.shares,
+    hyusd(12_000_000_000),
+    hyusd(10_000_000_000),
+    UFix64::new(10),
+  )?;
+  assert_eq!(position.nav, hyusd(1_200_000));
+  assert_eq!(position.current_value, hyusd(1_800_000_000));
+  assert_eq!(position.cost_basis, hyusd(1_550_000_000));
+  assert_eq!(position.exit_fee, hyusd(1_800_000));
+  assert_eq!(position.realized_yield, IFix64::new(100_000_000));
+  assert_eq!(position.unrealized_yield, IFix64::new(250_000_000));
+  assert_eq!(position.fees_paid, hyusd(1_650_000));
+  assert_eq!(position.net_yield()?, IFix64::new(348_350_000));
+  Ok(())
+}
+
+#[tokio::test]
+async fn sources_key_history_by_wallet() -> Result<()> {
+  let wallet = Pubkey::from_str(WALLET)?;
+  let events = fixture().position_events(wallet).await?;
+  let memory = events
+    .iter()
+    .fold(MemoryPositionSource::default(), |source, event| {
+      source.with_event(wallet, *event)
+    });
+  let shared: Arc<dyn PositionSource> = Arc::new(memory);
+  assert_eq!(shared.position_events(wallet).await?, events);
+
+  let stranger = Pubkey::new_unique();
+  assert!(fixture().position_events(stranger).await?.is_empty());
+  assert!(shared.position_events(stranger).await?.is_empty());
+  Ok(())
+}